@@ -0,0 +1,100 @@
+//! Benchmarks of the BLS hot path of the node: verifying a committed partial signature,
+//! aggregating the partials of a k-of-n group and verifying the aggregated signature.
+//!
+//! Run with `cargo bench --features bench --bench bls`.
+
+use arpa_node::node::algorithm::bls::{BLSCore, SimpleBLSCore};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use threshold_bls::{
+    curve::bn254::{PairingCurve as BN254, Scalar, G2},
+    poly::{Idx, Poly},
+    sig::Share,
+};
+
+const MSG: &[u8] = b"the randomness seed to be signed by the group";
+
+/// (threshold, group size) pairs to aggregate with
+const GROUP_SIZES: [(usize, usize); 3] = [(3, 5), (7, 10), (50, 100)];
+
+struct Group {
+    public_key: G2,
+    partial_public_keys: Vec<G2>,
+    partials: Vec<Vec<u8>>,
+}
+
+fn setup_group(threshold: usize, size: usize) -> Group {
+    let rng = &mut rand::thread_rng();
+    let private = Poly::<Scalar>::new_from(threshold - 1, rng);
+    let public = private.commit::<G2>();
+
+    let shares = (0..size as Idx)
+        .map(|i| Share {
+            index: i,
+            private: private.eval(i).value,
+        })
+        .collect::<Vec<_>>();
+
+    Group {
+        public_key: public.public_key().clone(),
+        partial_public_keys: (0..size as Idx).map(|i| public.eval(i).value).collect(),
+        partials: shares
+            .iter()
+            .map(|share| SimpleBLSCore::<BN254>::partial_sign(share, MSG).unwrap())
+            .collect(),
+    }
+}
+
+fn bench_partial_verify(c: &mut Criterion) {
+    let group = setup_group(3, 5);
+
+    c.bench_function("partial_verify", |b| {
+        b.iter(|| {
+            SimpleBLSCore::<BN254>::partial_verify(
+                black_box(&group.partial_public_keys[0]),
+                black_box(MSG),
+                black_box(&group.partials[0]),
+            )
+            .unwrap()
+        })
+    });
+}
+
+fn bench_aggregate(c: &mut Criterion) {
+    let mut bench_group = c.benchmark_group("aggregate");
+
+    for (threshold, size) in GROUP_SIZES {
+        let group = setup_group(threshold, size);
+        let partials = &group.partials[..threshold];
+
+        bench_group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}-of-{}", threshold, size)),
+            &threshold,
+            |b, &threshold| {
+                b.iter(|| {
+                    SimpleBLSCore::<BN254>::aggregate(threshold, black_box(partials)).unwrap()
+                })
+            },
+        );
+    }
+
+    bench_group.finish();
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let group = setup_group(3, 5);
+    let signature = SimpleBLSCore::<BN254>::aggregate(3, &group.partials).unwrap();
+
+    c.bench_function("verify", |b| {
+        b.iter(|| {
+            SimpleBLSCore::<BN254>::verify(
+                black_box(&group.public_key),
+                black_box(MSG),
+                black_box(&signature),
+            )
+            .unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, bench_partial_verify, bench_aggregate, bench_verify);
+criterion_main!(benches);
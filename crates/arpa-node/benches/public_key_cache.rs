@@ -0,0 +1,45 @@
+//! Compares looking up a partial public key in the `PublicKeyCache`, as the committer does for
+//! every partial signature, against validating and preparing for pairing the key held by the
+//! group cache on every request.
+//!
+//! Run with `cargo bench --features bench --bench public_key_cache`.
+
+use arpa_node_dal::cache::PublicKeyCache;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ethers::types::Address;
+use threshold_bls::{
+    curve::bn254::{PairingCurve as BN254, G2},
+    group::{Element, PairingCurve, Point},
+};
+
+fn bench_public_key_cache(c: &mut Criterion) {
+    let rng = &mut rand::thread_rng();
+    let key = G2::rand(rng);
+    let member = Address::random();
+
+    let mut group = c.benchmark_group("partial_public_key");
+
+    group.bench_function("validate_and_prepare", |b| {
+        b.iter(|| {
+            let key = black_box(&key);
+            assert!(key.is_valid());
+            BN254::prepare_g2(key)
+        })
+    });
+
+    let mut cache = PublicKeyCache::<BN254>::new();
+    cache.get_or_insert(1, member, &key).unwrap();
+
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            cache
+                .get_or_insert(1, black_box(member), black_box(&key))
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_public_key_cache);
+criterion_main!(benches);
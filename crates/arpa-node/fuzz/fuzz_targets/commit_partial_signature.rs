@@ -0,0 +1,58 @@
+//! Feeds arbitrary bytes to the path a committed partial signature takes through the committer,
+//! from parsing the request to verifying the partial against the partial public key of the
+//! member. Malformed input must only ever end up in an error, never in a panic.
+//!
+//! Run with `cargo fuzz run commit_partial_signature` from `crates/arpa-node`.
+
+#![no_main]
+
+use arpa_node::{
+    node::{
+        algorithm::bls::{prepare_negated_generator, BLSCore, SimpleBLSCore},
+        committer::server::parse_commit_partial_signature_request,
+    },
+    rpc_stub::committer::CommitPartialSignatureRequest,
+};
+use arpa_node_dal::cache::PublicKeyCache;
+use libfuzzer_sys::fuzz_target;
+use threshold_bls::{
+    curve::bn254::{PairingCurve as BN254, G2},
+    group::{Element, PairingCurve},
+};
+
+fuzz_target!(|data: &[u8]| {
+    // the first two bytes tell where the address, the message and the partial are split
+    let (split, data) = match data {
+        [a, b, rest @ ..] => ((*a as usize, *b as usize), rest),
+        _ => return,
+    };
+    let address_len = split.0.min(data.len());
+    let (address, data) = data.split_at(address_len);
+    let message_len = split.1.min(data.len());
+    let (message, partial) = data.split_at(message_len);
+
+    let req = CommitPartialSignatureRequest {
+        id_address: String::from_utf8_lossy(address).into_owned(),
+        message: message.to_vec(),
+        partial_signature: partial.to_vec(),
+        ..Default::default()
+    };
+
+    let _ = parse_commit_partial_signature_request::<BN254>(&req);
+
+    // the partial public key of a member comes from the group, the generator stands in for it
+    let partial_public_key = G2::one();
+    let _ = SimpleBLSCore::<BN254>::partial_verify(&partial_public_key, message, partial);
+
+    let prepared_key = BN254::prepare_g2(&partial_public_key);
+    let _ = SimpleBLSCore::<BN254>::batch_partial_verify_with_prepared_keys(
+        &prepare_negated_generator::<BN254>(),
+        &[
+            (&prepared_key, message, partial),
+            (&prepared_key, message, partial),
+        ],
+    );
+
+    // the same bytes as a partial public key, which the committer deserializes from the group
+    let _ = PublicKeyCache::<BN254>::new().get_or_insert(0, partial);
+});
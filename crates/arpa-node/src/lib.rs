@@ -2,21 +2,29 @@
 #![feature(async_fn_in_trait)]
 
 use arpa_node_core::Config;
-use std::{fs::read_to_string, path::PathBuf};
+use std::path::PathBuf;
 
 pub mod node;
 pub mod rpc_stub;
 
-pub fn load_config(config_path: PathBuf) -> Config {
-    let config_str = &read_to_string(config_path).unwrap_or_else(|e| {
-        panic!(
-            "Error loading configuration file: {:?}, please check the configuration!",
-            e
-        )
-    });
+pub fn load_config(config_path: PathBuf, config_overlay_path: Option<PathBuf>) -> Config {
+    let mut config = Config::from_file(config_path)
+        .unwrap_or_else(|e| panic!("{}, please check the configuration!", e));
 
-    let config: Config =
-        serde_yaml::from_str(config_str).expect("Error loading configuration file");
+    if let Some(config_overlay_path) = config_overlay_path {
+        config = Config::read_overlay(config_overlay_path)
+            .and_then(|overlay| Config::merge(config, overlay))
+            .unwrap_or_else(|e| panic!("{}, please check the configuration overlay!", e));
+    }
 
-    config.initialize()
+    let config = config
+        .interpolate_env_vars()
+        .unwrap_or_else(|e| panic!("{}, please check the configuration!", e))
+        .initialize();
+
+    if let Err(e) = config.validate() {
+        panic!("{}, please check the configuration!", e);
+    }
+
+    config
 }
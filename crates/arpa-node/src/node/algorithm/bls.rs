@@ -1,16 +1,17 @@
 use anyhow::Result;
+use arpa_node_core::BLSTaskError;
 use std::marker::PhantomData;
 use threshold_bls::{
-    group::PairingCurve,
+    group::{Element, PairingCurve, Point, Scalar as _},
     poly::Eval,
-    sig::{G2Scheme, Scheme, Share, SignatureScheme, ThresholdScheme},
+    sig::{BLSError, G2Scheme, Scheme, Share, SignatureScheme, ThresholdScheme},
 };
 
-pub(crate) struct SimpleBLSCore<C: PairingCurve> {
+pub struct SimpleBLSCore<C: PairingCurve> {
     c: PhantomData<C>,
 }
 
-pub(crate) trait BLSCore<C: PairingCurve> {
+pub trait BLSCore<C: PairingCurve> {
     /// Partially signs a message with a share of the private key
     fn partial_sign(
         private: &Share<<G2Scheme<C> as Scheme>::Private>,
@@ -24,6 +25,23 @@ pub(crate) trait BLSCore<C: PairingCurve> {
         partial: &[u8],
     ) -> Result<()>;
 
+    /// Verifies partial signatures of possibly different messages in one go, by checking a
+    /// random linear combination of them with a single multi-pairing equation. If the batch
+    /// doesn't hold, every item is verified on its own so that the bad partials can be told
+    /// apart. Returns the result of each item in the order given.
+    fn batch_partial_verify(
+        items: &[(&<G2Scheme<C> as Scheme>::Public, &[u8], &[u8])],
+    ) -> Vec<Result<()>>;
+
+    /// Same as `batch_partial_verify`, but takes partial public keys which have been validated
+    /// and prepared for pairing before, e.g. by a `PublicKeyCache`, so that the G2 side of the
+    /// miller loop is computed once per epoch rather than on every verification.
+    /// `neg_generator` comes from `prepare_negated_generator`.
+    fn batch_partial_verify_with_prepared_keys(
+        neg_generator: &C::G2Prepared,
+        items: &[(&C::G2Prepared, &[u8], &[u8])],
+    ) -> Vec<Result<()>>;
+
     /// Aggregates all partials signature together. Note that this method does
     /// not verify if the partial signatures are correct or not; it only
     /// aggregates them.
@@ -53,11 +71,47 @@ impl<C: PairingCurve + 'static> BLSCore<C> for SimpleBLSCore<C> {
         msg: &[u8],
         partial: &[u8],
     ) -> Result<()> {
-        let partial: Eval<Vec<u8>> = bincode::deserialize(partial)?;
+        if !partial_public_key.is_valid() {
+            return Err(BLSTaskError::InvalidCurvePoint.into());
+        }
+        let (partial, _) = parse_partial::<C>(partial)?;
         G2Scheme::<C>::verify(partial_public_key, msg, &partial.value)?;
         Ok(())
     }
 
+    fn batch_partial_verify(
+        items: &[(&<G2Scheme<C> as Scheme>::Public, &[u8], &[u8])],
+    ) -> Vec<Result<()>> {
+        if items.len() > 1
+            && items.iter().all(|(public, _, _)| public.is_valid())
+            && batch_verify::<C>(items).unwrap_or(false)
+        {
+            return items.iter().map(|_| Ok(())).collect();
+        }
+        items
+            .iter()
+            .map(|(public, msg, partial)| Self::partial_verify(public, msg, partial))
+            .collect()
+    }
+
+    fn batch_partial_verify_with_prepared_keys(
+        neg_generator: &C::G2Prepared,
+        items: &[(&C::G2Prepared, &[u8], &[u8])],
+    ) -> Vec<Result<()>> {
+        if items.len() > 1 && batch_verify_prepared::<C>(neg_generator, items).unwrap_or(false) {
+            return items.iter().map(|_| Ok(())).collect();
+        }
+        items
+            .iter()
+            .map(|item| batch_verify_prepared::<C>(neg_generator, &[*item]))
+            .map(|result| match result {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(BLSError::InvalidSig.into()),
+                Err(e) => Err(e),
+            })
+            .collect()
+    }
+
     fn aggregate(threshold: usize, partials: &[Vec<u8>]) -> Result<Vec<u8>> {
         let signature = G2Scheme::<C>::aggregate(threshold, partials)?;
         Ok(signature)
@@ -77,3 +131,322 @@ impl<C: PairingCurve + 'static> BLSCore<C> for SimpleBLSCore<C> {
         Ok(())
     }
 }
+
+/// Deserializes a partial signature as committed by a peer, along with its signature point
+/// checked to be in the prime-order subgroup. Malformed bytes are `InvalidCurvePoint`.
+pub fn parse_partial<C: PairingCurve>(partial: &[u8]) -> Result<(Eval<Vec<u8>>, C::G1)> {
+    let partial: Eval<Vec<u8>> =
+        bincode::deserialize(partial).map_err(|_| BLSTaskError::InvalidCurvePoint)?;
+    let signature: C::G1 =
+        bincode::deserialize(&partial.value).map_err(|_| BLSTaskError::InvalidCurvePoint)?;
+    if !signature.is_valid() {
+        return Err(BLSTaskError::InvalidCurvePoint.into());
+    }
+    Ok((partial, signature))
+}
+
+/// Returns the generator of G2 negated and prepared for pairing, which is the same for every
+/// verification and so can be computed once for `batch_partial_verify_with_prepared_keys`.
+pub fn prepare_negated_generator<C: PairingCurve>() -> C::G2Prepared {
+    let mut minus_one = C::Scalar::one();
+    minus_one.negate();
+    let mut neg_generator = C::G2::one();
+    neg_generator.mul(&minus_one);
+    C::prepare_g2(&neg_generator)
+}
+
+/// Checks e(sum(r_i * sig_i), -g2) * prod(e(r_i * H(m_i), pk_i)) == 1 with random r_i, in a
+/// single multi-pairing over the prepared keys. A single item is checked without the r_i.
+fn batch_verify_prepared<C: PairingCurve>(
+    neg_generator: &C::G2Prepared,
+    items: &[(&C::G2Prepared, &[u8], &[u8])],
+) -> Result<bool> {
+    let rng = &mut rand::thread_rng();
+    let mut sig_combination = C::G1::zero();
+    let mut hashes = Vec::with_capacity(items.len());
+    for (_, msg, partial) in items {
+        let (_, mut signature) = parse_partial::<C>(partial)?;
+        let mut h = C::G1::new();
+        h.map(msg).map_err(|_| BLSError::HashingError)?;
+        if items.len() > 1 {
+            let r = C::Scalar::rand(rng);
+            signature.mul(&r);
+            h.mul(&r);
+        }
+        sig_combination.add(&signature);
+        hashes.push(h);
+    }
+
+    let mut pairs = Vec::with_capacity(items.len() + 1);
+    pairs.push((&sig_combination, neg_generator));
+    pairs.extend(
+        hashes
+            .iter()
+            .zip(items)
+            .map(|(h, (public, _, _))| (h, *public)),
+    );
+
+    Ok(C::pairings_product_is_one(&pairs))
+}
+
+/// Checks e(sum(r_i * sig_i), g2) == prod(e(r_i * H(m_i), pk_i)) with random r_i,
+/// which holds for all items at once with overwhelming probability only if each of them is valid.
+fn batch_verify<C: PairingCurve>(items: &[(&C::G2, &[u8], &[u8])]) -> Result<bool> {
+    let rng = &mut rand::thread_rng();
+    let mut sig_combination = C::G1::zero();
+    let mut right = C::GT::one();
+    for (public, msg, partial) in items {
+        let (_, mut signature) = parse_partial::<C>(partial)?;
+        let r = C::Scalar::rand(rng);
+
+        signature.mul(&r);
+        sig_combination.add(&signature);
+
+        let mut h = C::G1::new();
+        h.map(msg).map_err(|_| BLSError::HashingError)?;
+        h.mul(&r);
+        right.add(&C::pair(&h, public));
+    }
+    let left = C::pair(&sig_combination, &C::G2::one());
+    Ok(left == right)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{prepare_negated_generator, BLSCore, SimpleBLSCore};
+    use arpa_node_core::BLSTaskError;
+    use proptest::prelude::*;
+    use threshold_bls::{
+        curve::bls12381::{PairingCurve as BLS12381, Scalar, G2},
+        group::PairingCurve as _,
+        poly::{Eval, Idx, Poly},
+        sig::Share,
+    };
+
+    // compressed G1 point with x = 4, which is on the curve but outside the prime-order subgroup
+    const OFF_SUBGROUP_G1: &str = "040000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000";
+
+    fn generate_share() -> (Share<Scalar>, G2) {
+        let rng = &mut rand::thread_rng();
+        let private = Poly::<Scalar>::new_from(1, rng);
+        let public = private.commit::<G2>().eval(1).value;
+        let share = Share {
+            index: 1,
+            private: private.eval(1).value,
+        };
+        (share, public)
+    }
+
+    fn assert_invalid_curve_point(result: anyhow::Result<()>) {
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BLSTaskError>(),
+            Some(&BLSTaskError::InvalidCurvePoint)
+        );
+    }
+
+    #[test]
+    fn test_partial_verify_accepts_valid_partial() {
+        let (share, public) = generate_share();
+        let partial = SimpleBLSCore::<BLS12381>::partial_sign(&share, b"test").unwrap();
+
+        assert!(SimpleBLSCore::<BLS12381>::partial_verify(&public, b"test", &partial).is_ok());
+    }
+
+    #[test]
+    fn test_partial_verify_rejects_off_subgroup_signature() {
+        let (_, public) = generate_share();
+        let partial = bincode::serialize(&Eval {
+            value: hex::decode(OFF_SUBGROUP_G1).unwrap(),
+            index: 1,
+        })
+        .unwrap();
+
+        assert_invalid_curve_point(SimpleBLSCore::<BLS12381>::partial_verify(
+            &public, b"test", &partial,
+        ));
+    }
+
+    #[test]
+    fn test_partial_verify_rejects_malformed_partial() {
+        let (_, public) = generate_share();
+
+        assert_invalid_curve_point(SimpleBLSCore::<BLS12381>::partial_verify(
+            &public, b"test", &[0xff; 7],
+        ));
+    }
+
+    #[test]
+    fn test_batch_partial_verify_accepts_valid_partials() {
+        let shares = (0..4).map(|_| generate_share()).collect::<Vec<_>>();
+        let msgs = (0..4u8).map(|i| vec![i; 32]).collect::<Vec<_>>();
+        let partials = shares
+            .iter()
+            .zip(msgs.iter())
+            .map(|((share, _), msg)| SimpleBLSCore::<BLS12381>::partial_sign(share, msg).unwrap())
+            .collect::<Vec<_>>();
+        let items = shares
+            .iter()
+            .zip(msgs.iter())
+            .zip(partials.iter())
+            .map(|(((_, public), msg), partial)| (public, &msg[..], &partial[..]))
+            .collect::<Vec<_>>();
+
+        let results = SimpleBLSCore::<BLS12381>::batch_partial_verify(&items);
+        assert_eq!(results.len(), 4);
+        assert!(results.iter().all(|r| r.is_ok()));
+    }
+
+    #[test]
+    fn test_batch_partial_verify_isolates_bad_partial() {
+        let shares = (0..4).map(|_| generate_share()).collect::<Vec<_>>();
+        let msgs = (0..4u8).map(|i| vec![i; 32]).collect::<Vec<_>>();
+        let mut partials = shares
+            .iter()
+            .zip(msgs.iter())
+            .map(|((share, _), msg)| SimpleBLSCore::<BLS12381>::partial_sign(share, msg).unwrap())
+            .collect::<Vec<_>>();
+        // signs the message of another item
+        partials[2] = SimpleBLSCore::<BLS12381>::partial_sign(&shares[2].0, &msgs[0]).unwrap();
+        let items = shares
+            .iter()
+            .zip(msgs.iter())
+            .zip(partials.iter())
+            .map(|(((_, public), msg), partial)| (public, &msg[..], &partial[..]))
+            .collect::<Vec<_>>();
+
+        let results = SimpleBLSCore::<BLS12381>::batch_partial_verify(&items);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(results[2].is_err());
+        assert!(results[3].is_ok());
+    }
+
+    #[test]
+    fn test_batch_partial_verify_with_prepared_keys() {
+        let shares = (0..4).map(|_| generate_share()).collect::<Vec<_>>();
+        let msgs = (0..4u8).map(|i| vec![i; 32]).collect::<Vec<_>>();
+        let mut partials = shares
+            .iter()
+            .zip(msgs.iter())
+            .map(|((share, _), msg)| SimpleBLSCore::<BLS12381>::partial_sign(share, msg).unwrap())
+            .collect::<Vec<_>>();
+        let prepared_keys = shares
+            .iter()
+            .map(|(_, public)| BLS12381::prepare_g2(public))
+            .collect::<Vec<_>>();
+        let neg_generator = prepare_negated_generator::<BLS12381>();
+
+        let items = prepared_keys
+            .iter()
+            .zip(msgs.iter())
+            .zip(partials.iter())
+            .map(|((public, msg), partial)| (public, &msg[..], &partial[..]))
+            .collect::<Vec<_>>();
+        let results = SimpleBLSCore::<BLS12381>::batch_partial_verify_with_prepared_keys(
+            &neg_generator,
+            &items,
+        );
+        assert!(results.iter().all(|r| r.is_ok()));
+
+        partials[1] = SimpleBLSCore::<BLS12381>::partial_sign(&shares[1].0, &msgs[3]).unwrap();
+        let items = prepared_keys
+            .iter()
+            .zip(msgs.iter())
+            .zip(partials.iter())
+            .map(|((public, msg), partial)| (public, &msg[..], &partial[..]))
+            .collect::<Vec<_>>();
+        let results = SimpleBLSCore::<BLS12381>::batch_partial_verify_with_prepared_keys(
+            &neg_generator,
+            &items,
+        );
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+        assert!(results[3].is_ok());
+    }
+
+    /// Partials of `msg` signed by all the `n` members of a group with threshold `k`,
+    /// along with the public key of the group.
+    fn sign_by_group(k: usize, n: usize, msg: &[u8]) -> (Vec<Vec<u8>>, G2) {
+        let private = Poly::<Scalar>::new_from(k - 1, &mut rand::thread_rng());
+        let public_key = private.commit::<G2>().public_key().clone();
+
+        let partials = (0..n as Idx)
+            .map(|i| {
+                let share = Share {
+                    index: i,
+                    private: private.eval(i).value,
+                };
+                SimpleBLSCore::<BLS12381>::partial_sign(&share, msg).unwrap()
+            })
+            .collect();
+
+        (partials, public_key)
+    }
+
+    /// Subsets of the indices below `n` of exactly `size` elements.
+    fn subsets(n: usize, size: usize) -> Vec<Vec<usize>> {
+        (0..1u32 << n)
+            .filter(|mask| mask.count_ones() as usize == size)
+            .map(|mask| (0..n).filter(|i| mask & (1 << i) != 0).collect())
+            .collect()
+    }
+
+    fn threshold_and_size() -> impl Strategy<Value = (usize, usize)> {
+        (1..=6usize).prop_flat_map(|n| (1..=n, Just(n)))
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        // any k of the n partials recover the same signature of the group, which verifies
+        #[test]
+        fn test_any_threshold_partials_recover_group_signature(
+            (k, n) in threshold_and_size(),
+            msg in proptest::collection::vec(any::<u8>(), 0..64),
+        ) {
+            let (partials, public_key) = sign_by_group(k, n, &msg);
+
+            let signatures = subsets(n, k)
+                .iter()
+                .map(|subset| {
+                    let subset_partials =
+                        subset.iter().map(|&i| partials[i].clone()).collect::<Vec<_>>();
+                    SimpleBLSCore::<BLS12381>::aggregate(k, &subset_partials).unwrap()
+                })
+                .collect::<Vec<_>>();
+
+            for signature in &signatures {
+                prop_assert!(
+                    SimpleBLSCore::<BLS12381>::verify(&public_key, &msg, signature).is_ok()
+                );
+                prop_assert_eq!(signature, &signatures[0]);
+            }
+        }
+
+        // k - 1 partials are not enough to recover, nor to forge the signature of the group
+        #[test]
+        fn test_fewer_than_threshold_partials_fail(
+            (k, n) in threshold_and_size(),
+            msg in proptest::collection::vec(any::<u8>(), 0..64),
+        ) {
+            let (partials, public_key) = sign_by_group(k, n, &msg);
+
+            for subset in subsets(n, k - 1) {
+                let subset_partials =
+                    subset.iter().map(|&i| partials[i].clone()).collect::<Vec<_>>();
+
+                prop_assert!(SimpleBLSCore::<BLS12381>::aggregate(k, &subset_partials).is_err());
+
+                if k > 1 {
+                    let signature =
+                        SimpleBLSCore::<BLS12381>::aggregate(k - 1, &subset_partials).unwrap();
+                    prop_assert!(
+                        SimpleBLSCore::<BLS12381>::verify(&public_key, &msg, &signature).is_err()
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,16 +1,30 @@
-use crate::node::error::{NodeError, NodeResult};
-use arpa_node_contract_client::coordinator::{CoordinatorTransactions, CoordinatorViews};
+use crate::node::{
+    error::{NodeError, NodeResult},
+    event::dkg_phase::DKGPhase,
+    queue::{event_queue::EventQueue, EventPublisher},
+};
+use arpa_node_contract_client::coordinator::{
+    decode_payloads, CoordinatorPayloadViews, CoordinatorTransactions, CoordinatorViews,
+};
+use arpa_node_contract_client::error::is_response_size_error;
+use arpa_node_core::DKGStatus;
 use async_trait::async_trait;
 use core::fmt::Debug;
 use dkg_core::{
     primitives::{joint_feldman::*, *},
     BoardPublisher, DKGPhase, Phase2Result,
 };
-use log::info;
+use ethers::types::Address;
+use log::{info, warn};
 use rand::RngCore;
 use rustc_hex::ToHex;
-use std::marker::PhantomData;
+use std::{
+    collections::BTreeSet,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
 use threshold_bls::{group::Curve, poly::Idx};
+use tokio::sync::{watch, RwLock};
 
 #[async_trait]
 pub(crate) trait DKGCore<F, R, C> {
@@ -33,18 +47,110 @@ pub(crate) struct AllPhasesDKGCore<
     coordinator_client: P,
     c: PhantomData<C>,
     dkg_wait_for_phase_interval_millis: u64,
+    block_height_receiver: Option<watch::Receiver<usize>>,
+    phase_publisher: DKGPhasePublisher,
 }
 
 impl<P: CoordinatorTransactions + CoordinatorViews + BoardPublisher<C>, C: Curve>
     AllPhasesDKGCore<P, C>
 {
-    pub fn new(coordinator_client: P, dkg_wait_for_phase_interval_millis: u64) -> Self {
+    pub fn new(
+        coordinator_client: P,
+        dkg_wait_for_phase_interval_millis: u64,
+        block_height_receiver: Option<watch::Receiver<usize>>,
+        phase_publisher: DKGPhasePublisher,
+    ) -> Self {
         AllPhasesDKGCore {
             coordinator_client,
             c: PhantomData,
             dkg_wait_for_phase_interval_millis,
+            block_height_receiver,
+            phase_publisher,
         }
     }
+
+    async fn wait_for_phase(&mut self, num: usize) -> NodeResult<()> {
+        match self.block_height_receiver.as_mut() {
+            Some(block_height_receiver) => {
+                wait_for_phase_on_block(
+                    &self.coordinator_client,
+                    num,
+                    block_height_receiver,
+                    self.dkg_wait_for_phase_interval_millis,
+                    &self.phase_publisher,
+                )
+                .await
+            }
+            None => {
+                wait_for_phase(
+                    &self.coordinator_client,
+                    num,
+                    self.dkg_wait_for_phase_interval_millis,
+                    &self.phase_publisher,
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// Publishes a `DKGPhase` event whenever the phase observed from the coordinator
+/// or the DKG status of the grouping changes.
+#[derive(Clone)]
+pub(crate) struct DKGPhasePublisher {
+    group_index: usize,
+    group_epoch: usize,
+    eq: Arc<RwLock<EventQueue>>,
+    state: Arc<Mutex<(Option<i8>, DKGStatus)>>,
+}
+
+impl DKGPhasePublisher {
+    pub fn new(group_index: usize, group_epoch: usize, eq: Arc<RwLock<EventQueue>>) -> Self {
+        DKGPhasePublisher {
+            group_index,
+            group_epoch,
+            eq,
+            state: Arc::new(Mutex::new((None, DKGStatus::InPhase))),
+        }
+    }
+
+    pub async fn update_phase(&self, phase: i8) {
+        self.update(Some(phase), None).await;
+    }
+
+    pub async fn update_status(&self, dkg_status: DKGStatus) {
+        self.update(None, Some(dkg_status)).await;
+    }
+
+    async fn update(&self, phase: Option<i8>, dkg_status: Option<DKGStatus>) {
+        let event = {
+            let mut state = self.state.lock().unwrap();
+
+            let next = (phase.or(state.0), dkg_status.unwrap_or(state.1));
+
+            if next == *state {
+                return;
+            }
+
+            *state = next;
+
+            DKGPhase::new(
+                self.group_index,
+                self.group_epoch,
+                next.0.unwrap_or(0),
+                next.1,
+            )
+        };
+
+        self.publish(event).await;
+    }
+}
+
+#[async_trait]
+impl EventPublisher<DKGPhase> for DKGPhasePublisher {
+    async fn publish(&self, event: DKGPhase) {
+        self.eq.read().await.publish(event).await;
+    }
 }
 
 #[async_trait]
@@ -67,16 +173,11 @@ where
         // TODO error handling and retry
 
         // Wait for Phase 0
-        wait_for_phase(
-            &self.coordinator_client,
-            0,
-            self.dkg_wait_for_phase_interval_millis,
-        )
-        .await?;
+        self.wait_for_phase(0).await?;
 
         // Get the group info
         let group = self.coordinator_client.get_dkg_keys().await?;
-        let participants = self.coordinator_client.get_participants().await?;
+        let participants = fetch_participants(&self.coordinator_client, group.1.len()).await?;
 
         // print some debug info
         info!(
@@ -112,34 +213,24 @@ where
         let phase1 = phase0.run(&mut self.coordinator_client, rng).await?;
 
         // Wait for Phase 1
-        wait_for_phase(
-            &self.coordinator_client,
-            1,
-            self.dkg_wait_for_phase_interval_millis,
-        )
-        .await?;
+        self.wait_for_phase(1).await?;
 
         // Get the shares
-        let shares = self.coordinator_client.get_shares().await?;
+        let shares = self.coordinator_client.get_decoded_shares::<C>().await?;
         info!("Got {} shares...", shares.len());
-        let shares = parse_bundle(&shares)?;
+        let shares = shares.into_iter().flatten().collect::<Vec<_>>();
         info!("Parsed {} shares. Running Phase 1.", shares.len());
 
         // Run Phase 1
         let phase2 = phase1.run(&mut self.coordinator_client, &shares).await?;
 
         // Wait for Phase 2
-        wait_for_phase(
-            &self.coordinator_client,
-            2,
-            self.dkg_wait_for_phase_interval_millis,
-        )
-        .await?;
+        self.wait_for_phase(2).await?;
 
         // Get the responses
-        let responses = self.coordinator_client.get_responses().await?;
+        let responses = self.coordinator_client.get_decoded_responses().await?;
         info!("Got {} responses...", responses.len());
-        let responses = parse_bundle(&responses)?;
+        let responses = responses.into_iter().flatten().collect::<Vec<_>>();
         info!("Parsed {} responses. Running Phase 2.", responses.len());
 
         // Run Phase 2
@@ -149,15 +240,15 @@ where
             Phase2Result::GoToPhase3(phase3) => {
                 info!("There were complaints. Running Phase 3.");
                 // Wait for Phase 3
-                wait_for_phase(
-                    &self.coordinator_client,
-                    3,
-                    self.dkg_wait_for_phase_interval_millis,
-                )
-                .await?;
+                self.wait_for_phase(3).await?;
 
-                let justifications = self.coordinator_client.get_justifications().await?;
-                let justifications = parse_bundle(&justifications)?;
+                let justifications = self
+                    .coordinator_client
+                    .get_decoded_justifications::<C>()
+                    .await?
+                    .into_iter()
+                    .flatten()
+                    .collect::<Vec<_>>();
 
                 // Run Phase 3
                 phase3
@@ -187,12 +278,15 @@ async fn wait_for_phase(
     dkg: &impl CoordinatorViews,
     num: usize,
     dkg_wait_for_phase_interval_millis: u64,
+    phase_publisher: &DKGPhasePublisher,
 ) -> NodeResult<()> {
     info!("Waiting for Phase {} to start", num);
 
     loop {
         let phase = dkg.in_phase().await?;
 
+        phase_publisher.update_phase(phase).await;
+
         if phase == 0 {
             return Err(NodeError::DKGNotStarted);
         }
@@ -215,11 +309,154 @@ async fn wait_for_phase(
     Ok(())
 }
 
-fn parse_bundle<D: serde::de::DeserializeOwned>(bundle: &[Vec<u8>]) -> NodeResult<Vec<D>> {
-    bundle
+async fn wait_for_phase_on_block(
+    dkg: &impl CoordinatorViews,
+    num: usize,
+    block_height_receiver: &mut watch::Receiver<usize>,
+    dkg_wait_for_phase_interval_millis: u64,
+    phase_publisher: &DKGPhasePublisher,
+) -> NodeResult<()> {
+    info!("Waiting for Phase {} to start on new blocks", num);
+
+    let phase_deadlines = dkg.get_phase_deadlines().await?;
+
+    if phase_deadlines.is_empty() {
+        phase_publisher.update_phase(0).await;
+        return Err(NodeError::DKGNotStarted);
+    }
+
+    loop {
+        let block_height = *block_height_receiver.borrow_and_update();
+
+        // the block height is not known until the first NewBlock event arrives
+        if block_height != 0 {
+            let phase = phase_of_block(&phase_deadlines, block_height);
+
+            phase_publisher.update_phase(phase).await;
+
+            if phase == -1 {
+                return Err(NodeError::DKGEnded);
+            }
+            if phase > num as i8 {
+                break;
+            }
+        }
+
+        if block_height_receiver.changed().await.is_err() {
+            // the block subscriber is gone, fall back to polling the coordinator
+            return wait_for_phase(
+                dkg,
+                num,
+                dkg_wait_for_phase_interval_millis,
+                phase_publisher,
+            )
+            .await;
+        }
+    }
+
+    info!("In Phase {}. Moving to the next step.", num);
+
+    Ok(())
+}
+
+/// Mirrors `inPhase()` of the coordinator: a phase lasts until its deadline inclusively
+/// and the DKG has ended once the block height passes the last deadline.
+fn phase_of_block(phase_deadlines: &[usize], block_height: usize) -> i8 {
+    phase_deadlines
+        .iter()
+        .position(|deadline| block_height <= *deadline)
+        .map_or(-1, |index| index as i8 + 1)
+}
+
+/// Number of participants read at once when the list doesn't fit in a single RPC response.
+const PARTICIPANTS_PAGE_SIZE: usize = 32;
+
+/// Fetches the participants in a single call, falling back to reading them page by page
+/// if the provider refuses the response for its size.
+async fn fetch_participants(
+    coordinator_client: &impl CoordinatorViews,
+    group_size: usize,
+) -> NodeResult<Vec<Address>> {
+    match coordinator_client.get_participants().await {
+        Ok(participants) => Ok(participants),
+        Err(e) if is_response_size_error(&e) => {
+            warn!(
+                "The participants don't fit in a single response, reading {} of them by pages: {:?}",
+                group_size, e
+            );
+
+            let mut participants = Vec::with_capacity(group_size);
+            for offset in (0..group_size).step_by(PARTICIPANTS_PAGE_SIZE) {
+                let limit = PARTICIPANTS_PAGE_SIZE.min(group_size - offset);
+                participants.extend(
+                    coordinator_client
+                        .get_participants_paged(offset, limit)
+                        .await?,
+                );
+            }
+
+            Ok(participants)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Fetches what has been published to the coordinator and derives the indices of the dealers
+/// that should be disqualified, see `expected_disqualified_indices`.
+pub(crate) async fn fetch_expected_disqualified_indices<C: Curve>(
+    coordinator_client: &impl CoordinatorViews,
+    group_size: usize,
+) -> NodeResult<BTreeSet<Idx>> {
+    let (_, shares, responses, justifications, _) = coordinator_client.get_dkg_state().await?;
+
+    let shares = decode_payloads::<BundledShares<C>>(&shares);
+    let responses = decode_payloads::<BundledResponses>(&responses)
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let justifications = decode_payloads::<BundledJustification<C>>(&justifications);
+
+    let dealers = shares
+        .iter()
+        .flatten()
+        .map(|s| s.dealer_idx)
+        .collect::<BTreeSet<_>>();
+    let justified_dealers = justifications
         .iter()
-        .filter(|item| !item.is_empty()) // filter out empty items
-        .map(|item| Ok(bincode::deserialize::<D>(item)?))
+        .flatten()
+        .map(|j| j.dealer_idx)
+        .collect::<BTreeSet<_>>();
+
+    Ok(expected_disqualified_indices(
+        group_size,
+        &dealers,
+        &responses,
+        &justified_dealers,
+    ))
+}
+
+/// A dealer is expected to be disqualified if it never published its shares, or if it was
+/// complained about in the responses but never published a justification. Justifications
+/// are not verified here, so an invalid one only shows up in the local DKG output.
+pub(crate) fn expected_disqualified_indices(
+    group_size: usize,
+    dealers: &BTreeSet<Idx>,
+    responses: &[BundledResponses],
+    justified_dealers: &BTreeSet<Idx>,
+) -> BTreeSet<Idx> {
+    let complained_dealers = responses
+        .iter()
+        .flat_map(|r| r.responses.iter())
+        // `Status` is private to dkg-core, a complaint is the status built from `false`
+        .filter(|r| r.status == false.into())
+        .map(|r| r.dealer_idx)
+        .collect::<BTreeSet<_>>();
+
+    (0..group_size as Idx)
+        .filter(|idx| {
+            !dealers.contains(idx)
+                || (complained_dealers.contains(idx) && !justified_dealers.contains(idx))
+        })
         .collect()
 }
 
@@ -244,3 +481,104 @@ struct OutputJson {
     #[serde(rename = "share")]
     share: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{expected_disqualified_indices, fetch_participants, phase_of_block};
+    use arpa_node_contract_client::mock::MockCoordinator;
+    use dkg_core::primitives::{BundledResponses, Response};
+    use ethers::types::Address;
+    use std::collections::BTreeSet;
+
+    fn response(share_idx: u32, complaints: &[u32], dealers: &[u32]) -> BundledResponses {
+        BundledResponses {
+            share_idx,
+            responses: dealers
+                .iter()
+                .map(|dealer_idx| Response {
+                    dealer_idx: *dealer_idx,
+                    status: (!complaints.contains(dealer_idx)).into(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_expected_disqualified_indices() {
+        let dealers = BTreeSet::from([0, 1, 2, 3]);
+        let responses = vec![
+            response(0, &[], &[0, 1, 2, 3]),
+            response(1, &[2, 3], &[0, 1, 2, 3]),
+            response(2, &[], &[0, 1, 2, 3]),
+        ];
+        let justified_dealers = BTreeSet::from([3]);
+
+        // 2 was complained about without a justification and 4 never dealt
+        assert_eq!(
+            expected_disqualified_indices(5, &dealers, &responses, &justified_dealers),
+            BTreeSet::from([2, 4])
+        );
+
+        assert!(
+            expected_disqualified_indices(4, &dealers, &responses[..1], &BTreeSet::new())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_phase_of_block() {
+        let phase_deadlines = [110, 120, 130, 140];
+
+        // each deadline still belongs to its phase, the block after it starts the next one
+        let cases = [
+            (100, 1),
+            (110, 1),
+            (111, 2),
+            (120, 2),
+            (121, 3),
+            (130, 3),
+            (131, 4),
+            (140, 4),
+            (141, -1),
+        ];
+
+        for (block_height, phase) in cases {
+            assert_eq!(
+                phase_of_block(&phase_deadlines, block_height),
+                phase,
+                "block {}",
+                block_height
+            );
+        }
+
+        assert_eq!(phase_of_block(&[], 100), -1);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_participants_by_pages() {
+        let participants = (0..70).map(|_| Address::random()).collect::<Vec<_>>();
+
+        let coordinator = MockCoordinator::new(36, participants.clone());
+        assert_eq!(
+            fetch_participants(&coordinator, participants.len())
+                .await
+                .unwrap(),
+            participants
+        );
+
+        // the full list doesn't fit in a response, while a page does
+        let coordinator = coordinator.with_max_response_participants(50);
+        assert_eq!(
+            fetch_participants(&coordinator, participants.len())
+                .await
+                .unwrap(),
+            participants
+        );
+
+        // a page that doesn't fit either fails the fetch
+        let coordinator = coordinator.with_max_response_participants(10);
+        assert!(fetch_participants(&coordinator, participants.len())
+            .await
+            .is_err());
+    }
+}
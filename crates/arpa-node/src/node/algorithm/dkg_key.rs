@@ -0,0 +1,89 @@
+use arpa_node_core::NodeSigner;
+use hkdf::Hkdf;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use sha2::Sha256;
+use threshold_bls::{
+    group::PairingCurve,
+    sig::{G2Scheme, Scheme},
+};
+
+const DKG_KEY_DOMAIN: &[u8] = b"arpa-node-dkg-keypair-v1";
+
+/// Generates the DKG keypair of the node.
+///
+/// If `derive_from_account` is set and the node holds its key locally, the keypair is derived
+/// from the account key, so that it can be reconstructed from the mnemonic or keystore alone.
+/// Otherwise a fresh random keypair is returned.
+pub fn generate_dkg_keypair<PC: PairingCurve>(
+    signer: &NodeSigner,
+    derive_from_account: bool,
+) -> (PC::Scalar, PC::G2) {
+    match signer {
+        NodeSigner::Local(wallet) | NodeSigner::Legacy(wallet) if derive_from_account => {
+            derive_dkg_keypair::<PC>(&wallet.signer().to_bytes())
+        }
+        _ => G2Scheme::<PC>::keypair(&mut rand::thread_rng()),
+    }
+}
+
+/// Expands the account private key by HKDF-SHA256 into the seed of the rng drawing the DKG key.
+pub fn derive_dkg_keypair<PC: PairingCurve>(account_private_key: &[u8]) -> (PC::Scalar, PC::G2) {
+    let hkdf = Hkdf::<Sha256>::new(None, account_private_key);
+    let mut seed = [0u8; 32];
+    hkdf.expand(DKG_KEY_DOMAIN, &mut seed)
+        .expect("hkdf should not fail");
+
+    G2Scheme::<PC>::keypair(&mut ChaCha20Rng::from_seed(seed))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{derive_dkg_keypair, generate_dkg_keypair};
+    use arpa_node_core::NodeSigner;
+    use ethers::signers::LocalWallet;
+    use threshold_bls::{
+        curve::bn254::PairingCurve as BN254,
+        group::{Element, PairingCurve},
+    };
+
+    #[test]
+    fn test_derive_dkg_keypair() {
+        let account_private_key = [1u8; 32];
+
+        let (private_key, public_key) = derive_dkg_keypair::<BN254>(&account_private_key);
+
+        assert_eq!(
+            derive_dkg_keypair::<BN254>(&account_private_key),
+            (private_key, public_key)
+        );
+
+        let mut expected_public_key = <BN254 as PairingCurve>::G2::one();
+        expected_public_key.mul(&private_key);
+        assert_eq!(public_key, expected_public_key);
+
+        assert_ne!(derive_dkg_keypair::<BN254>(&[2u8; 32]).1, public_key);
+    }
+
+    #[test]
+    fn test_generate_dkg_keypair() {
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let derived = derive_dkg_keypair::<BN254>(&wallet.signer().to_bytes());
+
+        for signer in [
+            NodeSigner::Local(wallet.clone()),
+            NodeSigner::Legacy(wallet.clone()),
+        ] {
+            assert_eq!(generate_dkg_keypair::<BN254>(&signer, true), derived);
+
+            // without derive_from_account a fresh keypair is drawn every time, as it is for the
+            // remote signers whose key never leaves KMS or the device
+            let random = generate_dkg_keypair::<BN254>(&signer, false);
+            assert_ne!(random, derived);
+            assert_ne!(generate_dkg_keypair::<BN254>(&signer, false), random);
+        }
+    }
+}
@@ -1,2 +1,6 @@
 pub mod bls;
 pub mod dkg;
+pub mod dkg_key;
+pub mod randomness;
+#[cfg(feature = "testing")]
+pub mod replay;
@@ -0,0 +1,84 @@
+//! Verifies a fulfilled randomness independently of the node, from the values as they appear
+//! on chain, i.e. the public key of the group, the signed seed and the group signature.
+
+use super::bls::{BLSCore, SimpleBLSCore};
+use crate::node::error::NodeResult;
+use arpa_node_core::derive_randomness;
+use ethers::types::U256;
+use rustc_hex::FromHex;
+use threshold_bls::group::PairingCurve;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RandomnessVerification {
+    /// Whether the signature verifies against the group public key
+    pub valid: bool,
+    /// The randomness the Adapter derives from the signature
+    pub randomness: U256,
+}
+
+/// Verifies the signature on the seed, which is abi.encodePacked(seed, assignment_block_height)
+/// as the Adapter rebuilds it, against the public key of the group. The public key and the
+/// signature are hex encoded with or without the 0x prefix. Malformed hex is an error, while a
+/// well-formed signature which doesn't verify is reported as invalid.
+pub fn verify_randomness<C: PairingCurve + 'static>(
+    group_public_key_hex: &str,
+    seed: &[u8],
+    signature_hex: &str,
+) -> NodeResult<RandomnessVerification> {
+    let public_key_bytes: Vec<u8> = strip_hex_prefix(group_public_key_hex).from_hex()?;
+    let public_key: C::G2 = bincode::deserialize(&public_key_bytes)?;
+
+    let signature: Vec<u8> = strip_hex_prefix(signature_hex).from_hex()?;
+
+    let valid = SimpleBLSCore::<C>::verify(&public_key, seed, &signature).is_ok();
+
+    Ok(RandomnessVerification {
+        valid,
+        randomness: U256::from_big_endian(&derive_randomness(&signature)),
+    })
+}
+
+fn strip_hex_prefix(hex: &str) -> &str {
+    hex.strip_prefix("0x").unwrap_or(hex)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::verify_randomness;
+    use arpa_node_core::encode_seed;
+    use ethers::{types::U256, utils::keccak256};
+    use threshold_bls::{
+        curve::bn254::PairingCurve as BN254,
+        schemes::bn254::G2Scheme,
+        serialize::point_to_hex,
+        sig::{Scheme, SignatureScheme},
+    };
+
+    #[test]
+    fn test_verify_randomness() {
+        let (private, public) = G2Scheme::keypair(&mut rand::thread_rng());
+        let public_key_hex = format!("0x{}", point_to_hex(&public));
+
+        let seed = encode_seed(U256::from(42), 100);
+        let signature = G2Scheme::sign(&private, &seed).unwrap();
+        let signature_hex = hex::encode(&signature);
+
+        let verification =
+            verify_randomness::<BN254>(&public_key_hex, &seed, &signature_hex).unwrap();
+        assert!(verification.valid);
+        assert_eq!(
+            verification.randomness,
+            U256::from_big_endian(&keccak256(&signature))
+        );
+
+        // the same seed assigned at another block is a different message
+        let seed_of_another_block = encode_seed(U256::from(42), 101);
+        let verification =
+            verify_randomness::<BN254>(&public_key_hex, &seed_of_another_block, &signature_hex)
+                .unwrap();
+        assert!(!verification.valid);
+
+        assert!(verify_randomness::<BN254>(&public_key_hex, &seed, "0xzz").is_err());
+        assert!(verify_randomness::<BN254>("0x1234", &seed, &signature_hex).is_err());
+    }
+}
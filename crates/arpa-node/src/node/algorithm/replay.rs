@@ -0,0 +1,223 @@
+//! Replays a single randomness task through the path a fulfillment takes, i.e. the checks of
+//! the committer on each partial signature, the aggregation and the verification of the
+//! aggregated signature, deterministically and without touching the network or the DAL.
+
+use super::bls::{BLSCore, SimpleBLSCore};
+use crate::node::error::NodeError;
+use arpa_node_core::{compute_seed, Group, RandomnessTask};
+use ethers::types::Address;
+use std::collections::BTreeMap;
+use threshold_bls::group::PairingCurve;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplayReport {
+    /// The message derived from the task, which every partial signature should sign
+    pub message: Vec<u8>,
+    /// Partial signatures the committer would reject, along with the reason
+    pub rejected_partials: BTreeMap<Address, String>,
+    /// The aggregated signature, if there are enough accepted partial signatures
+    pub signature: Option<Vec<u8>>,
+    /// Why the signature couldn't be aggregated or verified
+    pub failure: Option<String>,
+}
+
+impl ReplayReport {
+    /// Whether the aggregated signature verifies against the group public key,
+    /// which is what the Adapter checks on fulfillment.
+    pub fn passed(&self) -> bool {
+        self.signature.is_some() && self.failure.is_none()
+    }
+}
+
+/// Replays the task with the partial signatures by member address, against the group
+/// which is expected to be the one the task was assigned to.
+pub fn replay_task<C: PairingCurve + 'static>(
+    task: &RandomnessTask,
+    group: &Group<C>,
+    partials: &BTreeMap<Address, Vec<u8>>,
+) -> ReplayReport {
+    let message = compute_seed(task);
+
+    let mut rejected_partials = BTreeMap::new();
+    let mut accepted_partials = vec![];
+
+    for (id_address, partial) in partials {
+        let partial_public_key = match group.members.get(id_address) {
+            Some(member) => member.partial_public_key.as_ref(),
+            None => {
+                rejected_partials.insert(*id_address, NodeError::MemberNotExisted.to_string());
+                continue;
+            }
+        };
+
+        match partial_public_key {
+            Some(partial_public_key) => {
+                match SimpleBLSCore::<C>::partial_verify(partial_public_key, &message, partial) {
+                    Ok(()) => accepted_partials.push(partial.clone()),
+                    Err(e) => {
+                        rejected_partials.insert(*id_address, e.to_string());
+                    }
+                }
+            }
+            None => {
+                rejected_partials.insert(
+                    *id_address,
+                    "the member has no partial public key".to_string(),
+                );
+            }
+        }
+    }
+
+    let mut report = ReplayReport {
+        message,
+        rejected_partials,
+        signature: None,
+        failure: None,
+    };
+
+    if accepted_partials.len() < group.threshold {
+        report.failure = Some(format!(
+            "{} accepted partial signatures are less than threshold {}",
+            accepted_partials.len(),
+            group.threshold
+        ));
+        return report;
+    }
+
+    let signature = match SimpleBLSCore::<C>::aggregate(group.threshold, &accepted_partials) {
+        Ok(signature) => signature,
+        Err(e) => {
+            report.failure = Some(e.to_string());
+            return report;
+        }
+    };
+
+    report.failure = match &group.public_key {
+        Some(public_key) => SimpleBLSCore::<C>::verify(public_key, &report.message, &signature)
+            .err()
+            .map(|e| e.to_string()),
+        None => Some(NodeError::GroupNotReady.to_string()),
+    };
+    report.signature = Some(signature);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replay_task;
+    use crate::node::algorithm::bls::{BLSCore, SimpleBLSCore};
+    use arpa_node_core::{
+        compute_seed, Group, Member, RandomnessRequestType, RandomnessTask, RandomnessTaskPriority,
+    };
+    use ethers::types::{Address, U256};
+    use std::collections::BTreeMap;
+    use threshold_bls::{
+        curve::bn254::PairingCurve as BN254,
+        group::PairingCurve,
+        poly::{Idx, Poly},
+        sig::Share,
+    };
+
+    fn build_task() -> RandomnessTask {
+        RandomnessTask {
+            request_id: vec![1],
+            subscription_id: 0,
+            group_index: 0,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 100,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    fn build_group(
+        n: usize,
+        t: usize,
+    ) -> (
+        Group<BN254>,
+        Vec<(Address, Share<<BN254 as PairingCurve>::Scalar>)>,
+    ) {
+        let private = Poly::<<BN254 as PairingCurve>::Scalar>::new(t - 1);
+        let public: Poly<<BN254 as PairingCurve>::G2> = private.commit();
+
+        let mut group = Group::new();
+        group.size = n;
+        group.threshold = t;
+        group.state = true;
+        group.public_key = Some(public.public_key().clone());
+
+        let shares = (0..n)
+            .map(|i| {
+                let id_address = Address::from_low_u64_be(i as u64 + 1);
+                group.members.insert(
+                    id_address,
+                    Member {
+                        index: i,
+                        id_address,
+                        rpc_endpoint: None,
+                        partial_public_key: Some(public.eval(i as Idx).value),
+                    },
+                );
+                let eval = private.eval(i as Idx);
+                (
+                    id_address,
+                    Share {
+                        index: eval.index,
+                        private: eval.value,
+                    },
+                )
+            })
+            .collect();
+
+        (group, shares)
+    }
+
+    #[test]
+    fn test_replay_task() {
+        let task = build_task();
+        let (group, shares) = build_group(3, 2);
+
+        let message = compute_seed(&task);
+
+        let mut partials = shares
+            .iter()
+            .map(|(id_address, share)| {
+                (
+                    *id_address,
+                    SimpleBLSCore::<BN254>::partial_sign(share, &message).unwrap(),
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let report = replay_task(&task, &group, &partials);
+        assert!(report.passed());
+        assert!(report.rejected_partials.is_empty());
+        assert_eq!(message, report.message);
+
+        // replaying is deterministic
+        assert_eq!(report, replay_task(&task, &group, &partials));
+
+        // a partial signature on another message and one from a non-member are rejected,
+        // which leaves fewer partial signatures than the threshold
+        let (first, first_share) = &shares[0];
+        partials.insert(
+            *first,
+            SimpleBLSCore::<BN254>::partial_sign(first_share, b"another message").unwrap(),
+        );
+        partials.remove(&shares[1].0);
+        partials.insert(Address::from_low_u64_be(100), vec![0u8; 8]);
+
+        let report = replay_task(&task, &group, &partials);
+        assert!(!report.passed());
+        assert_eq!(2, report.rejected_partials.len());
+        assert!(report.rejected_partials.contains_key(first));
+        assert!(report.signature.is_none());
+    }
+}
@@ -1,4 +1,4 @@
-use super::{CommitterClient, CommitterService, ServiceClient};
+use super::{pool::CommitterChannelPool, CommitterClient, CommitterService, ServiceClient};
 use crate::node::error::{NodeError, NodeResult};
 use crate::rpc_stub::committer::committer_service_client::CommitterServiceClient;
 use crate::rpc_stub::committer::CommitPartialSignatureRequest;
@@ -6,6 +6,8 @@ use arpa_node_core::{address_to_string, jitter, BLSTaskType, ExponentialBackoffR
 use async_trait::async_trait;
 use ethers::types::Address;
 use log::error;
+use std::{sync::Arc, time::Duration};
+use tokio::time::timeout;
 use tokio_retry::{strategy::ExponentialBackoff, RetryIf};
 use tonic::Request;
 
@@ -15,6 +17,7 @@ pub(crate) struct GeneralCommitterClient {
     committer_id_address: Address,
     committer_endpoint: String,
     commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    channel_pool: Arc<CommitterChannelPool>,
 }
 
 impl GeneralCommitterClient {
@@ -23,12 +26,14 @@ impl GeneralCommitterClient {
         committer_id_address: Address,
         committer_endpoint: String,
         commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        channel_pool: Arc<CommitterChannelPool>,
     ) -> Self {
         GeneralCommitterClient {
             id_address,
             committer_id_address,
             committer_endpoint,
             commit_partial_signature_retry_descriptor,
+            channel_pool,
         }
     }
 }
@@ -51,12 +56,14 @@ impl CommitterClient for GeneralCommitterClient {
         committer_id_address: Address,
         committer_endpoint: String,
         commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        channel_pool: Arc<CommitterChannelPool>,
     ) -> Self {
         Self::new(
             id_address,
             committer_id_address,
             committer_endpoint,
             commit_partial_signature_retry_descriptor,
+            channel_pool,
         )
     }
 }
@@ -66,9 +73,12 @@ impl ServiceClient<CommitterServiceClient<tonic::transport::Channel>> for Genera
     async fn prepare_service_client(
         &self,
     ) -> NodeResult<CommitterServiceClient<tonic::transport::Channel>> {
-        CommitterServiceClient::connect(format!("{}{}", "http://", self.committer_endpoint.clone()))
-            .await
-            .map_err(|err| err.into())
+        let channel = self
+            .channel_pool
+            .get_channel(&self.committer_endpoint)
+            .await?;
+
+        Ok(CommitterServiceClient::new(channel))
     }
 }
 
@@ -81,15 +91,17 @@ impl CommitterService for GeneralCommitterClient {
         request_id: Vec<u8>,
         message: Vec<u8>,
         partial_signature: Vec<u8>,
+        group_epoch: usize,
     ) -> NodeResult<bool> {
         let commit_partial_signature_retry_descriptor =
             self.commit_partial_signature_retry_descriptor;
         let retry_strategy =
             ExponentialBackoff::from_millis(commit_partial_signature_retry_descriptor.base)
                 .factor(commit_partial_signature_retry_descriptor.factor)
+                .max_delay(commit_partial_signature_retry_descriptor.get_max_interval())
                 .map(|e| {
                     if commit_partial_signature_retry_descriptor.use_jitter {
-                        jitter(e)
+                        jitter(e, commit_partial_signature_retry_descriptor.jitter_ratio)
                     } else {
                         e
                     }
@@ -111,15 +123,37 @@ impl CommitterService for GeneralCommitterClient {
                     request_id,
                     message,
                     partial_signature,
+                    group_epoch: group_epoch as u32,
                 });
 
                 let mut committer_client = self.prepare_service_client().await?;
 
-                committer_client
-                    .commit_partial_signature(request)
-                    .await
-                    .map(|r| r.into_inner().result)
-                    .map_err(|status| status.into())
+                let call = committer_client.commit_partial_signature(request);
+
+                let response =
+                    match commit_partial_signature_retry_descriptor.per_attempt_timeout_millis {
+                        Some(timeout_millis) if timeout_millis > 0 => {
+                            match timeout(Duration::from_millis(timeout_millis), call).await {
+                                Ok(response) => response,
+                                Err(_) => {
+                                    // the peer may have accepted the connection but never respond
+                                    self.channel_pool.evict(&self.committer_endpoint).await;
+                                    return Err(NodeError::CommitterTimeout(timeout_millis));
+                                }
+                            }
+                        }
+                        _ => call.await,
+                    };
+
+                match response {
+                    Ok(r) => Ok(r.into_inner().result),
+                    Err(status) => {
+                        if status.code() == tonic::Code::Unavailable {
+                            self.channel_pool.evict(&self.committer_endpoint).await;
+                        }
+                        Err(status.into())
+                    }
+                }
             },
             |e: &NodeError| {
                 error!(
@@ -1,8 +1,11 @@
 pub mod client;
+pub mod pool;
 pub mod server;
+pub mod verifier;
 
+use self::pool::CommitterChannelPool;
 use crate::node::error::NodeResult;
-use arpa_node_core::{BLSTaskType, ExponentialBackoffRetryDescriptor};
+use arpa_node_core::{BLSTaskType, ExponentialBackoffRetryDescriptor, RandomnessTask};
 use arpa_node_dal::GroupInfoFetcher;
 use async_trait::async_trait;
 use ethers::types::Address;
@@ -24,6 +27,7 @@ pub(crate) trait CommitterService {
         request_id: Vec<u8>,
         message: Vec<u8>,
         partial_signature: Vec<u8>,
+        group_epoch: usize,
     ) -> NodeResult<bool>;
 }
 
@@ -39,6 +43,7 @@ pub(crate) trait CommitterClient {
         committer_id_address: Address,
         committer_endpoint: String,
         commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        channel_pool: Arc<CommitterChannelPool>,
     ) -> Self;
 }
 
@@ -55,12 +60,27 @@ pub(crate) trait CommitterClientHandler<
 
     fn get_commit_partial_signature_retry_descriptor(&self) -> ExponentialBackoffRetryDescriptor;
 
-    async fn prepare_committer_clients(&self) -> NodeResult<Vec<C>> {
-        let mut committers = self.get_group_cache().read().await.get_committers()?;
+    fn get_committer_channel_pool(&self) -> Arc<CommitterChannelPool>;
+
+    /// Prepares the clients of the committers to try in order for the task, starting from
+    /// the primary committer. Committers after this node are left out since it keeps its own
+    /// partial signature if it is a committer.
+    async fn prepare_committer_clients_for_task(
+        &self,
+        task: &RandomnessTask,
+    ) -> NodeResult<Vec<C>> {
+        let committers = self
+            .get_group_cache()
+            .read()
+            .await
+            .get_committers(Some(task))?;
 
         let id_address = self.get_id_address().await;
 
-        committers.retain(|c| *c != id_address);
+        let committers = committers
+            .into_iter()
+            .take_while(|c| *c != id_address)
+            .collect::<Vec<_>>();
 
         let mut committer_clients = vec![];
 
@@ -80,6 +100,7 @@ pub(crate) trait CommitterClientHandler<
                 committer,
                 endpoint.clone(),
                 self.get_commit_partial_signature_retry_descriptor(),
+                self.get_committer_channel_pool(),
             );
 
             committer_clients.push(committer_client);
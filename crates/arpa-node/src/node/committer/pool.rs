@@ -0,0 +1,194 @@
+use crate::node::error::{NodeError, NodeResult};
+use arpa_node_core::{
+    DEFAULT_COMMITTER_CHANNEL_IDLE_TIMEOUT_MILLIS, DEFAULT_COMMITTER_CHANNEL_POOL_SIZE,
+};
+use std::{
+    collections::HashMap,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    time::{Duration, Instant},
+};
+use tokio::{net::lookup_host, sync::Mutex};
+use tonic::transport::{Channel, Endpoint};
+
+/// Reuses the channels to the committers by endpoint, as a `Channel` multiplexes
+/// the requests over one connection. Channels idle for longer than `idle_timeout`
+/// are evicted, and the least recently used one makes room once the pool is full.
+#[derive(Debug)]
+pub(crate) struct CommitterChannelPool {
+    max_channels: usize,
+    idle_timeout: Duration,
+    channels: Mutex<HashMap<String, PooledChannel>>,
+}
+
+#[derive(Debug)]
+struct PooledChannel {
+    channel: Channel,
+    last_used: Instant,
+}
+
+impl Default for CommitterChannelPool {
+    fn default() -> Self {
+        CommitterChannelPool::new(
+            DEFAULT_COMMITTER_CHANNEL_POOL_SIZE,
+            Duration::from_millis(DEFAULT_COMMITTER_CHANNEL_IDLE_TIMEOUT_MILLIS),
+        )
+    }
+}
+
+impl CommitterChannelPool {
+    pub fn new(max_channels: usize, idle_timeout: Duration) -> Self {
+        CommitterChannelPool {
+            max_channels: max_channels.max(1),
+            idle_timeout,
+            channels: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn get_channel(&self, endpoint: &str) -> NodeResult<Channel> {
+        if let Some(channel) = self.get_pooled_channel(endpoint).await {
+            return Ok(channel);
+        }
+
+        // resolves without holding the lock, so that a slow DNS lookup doesn't block
+        // the requests to the other committers
+        let socket_addr = resolve_endpoint(endpoint).await?;
+
+        // the connection is established on the first request and re-established if it breaks
+        let channel =
+            Endpoint::from_shared(format!("{}{}", "http://", socket_addr))?.connect_lazy();
+
+        let mut channels = self.channels.lock().await;
+
+        let now = Instant::now();
+
+        // another request may have dialed the endpoint in the meantime
+        if let Some(c) = channels.get_mut(endpoint) {
+            c.last_used = now;
+            return Ok(c.channel.clone());
+        }
+
+        if channels.len() >= self.max_channels {
+            if let Some(lru_endpoint) = channels
+                .iter()
+                .min_by_key(|(_, c)| c.last_used)
+                .map(|(endpoint, _)| endpoint.clone())
+            {
+                channels.remove(&lru_endpoint);
+            }
+        }
+
+        channels.insert(
+            endpoint.to_string(),
+            PooledChannel {
+                channel: channel.clone(),
+                last_used: now,
+            },
+        );
+
+        Ok(channel)
+    }
+
+    async fn get_pooled_channel(&self, endpoint: &str) -> Option<Channel> {
+        let mut channels = self.channels.lock().await;
+
+        let now = Instant::now();
+
+        channels.retain(|_, c| now.duration_since(c.last_used) < self.idle_timeout);
+
+        channels.get_mut(endpoint).map(|c| {
+            c.last_used = now;
+            c.channel.clone()
+        })
+    }
+
+    /// Drops the channel so that the next request to the endpoint dials a new one.
+    pub async fn evict(&self, endpoint: &str) {
+        self.channels.lock().await.remove(endpoint);
+    }
+}
+
+/// Parses the advertised committer endpoint into an address to dial. It accepts an IPv4
+/// address, a bracketed or bare IPv6 address, or a hostname resolved by DNS, followed by
+/// the port, with or without the http scheme.
+pub(crate) async fn resolve_endpoint(endpoint: &str) -> NodeResult<SocketAddr> {
+    let unresolvable = || NodeError::UnresolvableEndpoint(endpoint.to_string());
+
+    let target = endpoint
+        .trim()
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+
+    if let Ok(socket_addr) = target.parse::<SocketAddr>() {
+        return Ok(socket_addr);
+    }
+
+    let (host, port) = target.rsplit_once(':').ok_or_else(unresolvable)?;
+
+    let port = port.parse::<u16>().map_err(|_| unresolvable())?;
+
+    if let Ok(ip) = host.parse::<Ipv6Addr>() {
+        return Ok(SocketAddr::new(IpAddr::V6(ip), port));
+    }
+
+    if host.is_empty() || host.contains(':') || host.contains('[') {
+        return Err(unresolvable());
+    }
+
+    lookup_host((host, port))
+        .await
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or_else(unresolvable)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{resolve_endpoint, CommitterChannelPool};
+    use std::{net::SocketAddr, time::Duration};
+
+    #[tokio::test]
+    async fn test_resolve_endpoint() {
+        let expected: SocketAddr = "[::1]:50060".parse().unwrap();
+        assert_eq!(expected, resolve_endpoint("[::1]:50060").await.unwrap());
+        assert_eq!(expected, resolve_endpoint("::1:50060").await.unwrap());
+        assert_eq!(
+            expected,
+            resolve_endpoint("http://[::1]:50060/").await.unwrap()
+        );
+
+        let expected: SocketAddr = "10.0.0.1:50060".parse().unwrap();
+        assert_eq!(expected, resolve_endpoint("10.0.0.1:50060").await.unwrap());
+
+        assert_eq!(
+            50060,
+            resolve_endpoint("localhost:50060").await.unwrap().port()
+        );
+
+        assert!(resolve_endpoint("10.0.0.1").await.is_err());
+        assert!(resolve_endpoint("10.0.0.1:port").await.is_err());
+        assert!(resolve_endpoint("[::1:50060").await.is_err());
+        assert!(resolve_endpoint(":50060").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reuse_and_evict() {
+        let pool = CommitterChannelPool::new(2, Duration::from_secs(60));
+
+        pool.get_channel("127.0.0.1:50061").await.unwrap();
+        pool.get_channel("127.0.0.1:50061").await.unwrap();
+        assert_eq!(1, pool.channels.lock().await.len());
+
+        pool.get_channel("127.0.0.1:50062").await.unwrap();
+        pool.get_channel("127.0.0.1:50063").await.unwrap();
+        assert_eq!(2, pool.channels.lock().await.len());
+
+        pool.evict("127.0.0.1:50063").await;
+        assert_eq!(1, pool.channels.lock().await.len());
+
+        let pool = CommitterChannelPool::new(2, Duration::ZERO);
+
+        pool.get_channel("127.0.0.1:50061").await.unwrap();
+        pool.get_channel("127.0.0.1:50062").await.unwrap();
+        assert_eq!(1, pool.channels.lock().await.len());
+    }
+}
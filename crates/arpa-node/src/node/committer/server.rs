@@ -1,8 +1,13 @@
 use crate::node::context::chain::MainChainFetcher;
 use crate::node::{
-    algorithm::bls::{BLSCore, SimpleBLSCore},
+    algorithm::bls::parse_partial,
+    committer::verifier::{
+        PartialSignatureVerifier, MAX_PARTIAL_VERIFICATION_BATCH_SIZE,
+        MAX_PENDING_PARTIAL_VERIFICATIONS,
+    },
     context::{chain::ChainFetcher, types::GeneralContext, ContextFetcher},
     error::NodeError,
+    telemetry::in_request_span,
 };
 use crate::rpc_stub::committer::{
     committer_service_server::{CommitterService, CommitterServiceServer},
@@ -12,16 +17,21 @@ use arpa_node_contract_client::{
     adapter::AdapterClientBuilder, controller::ControllerClientBuilder,
     coordinator::CoordinatorClientBuilder, provider::ChainProviderBuilder,
 };
-use arpa_node_core::{BLSTaskError, BLSTaskType, ChainIdentity, RandomnessTask};
-use arpa_node_dal::cache::RandomnessResultCache;
+use arpa_node_core::{
+    compute_seed, metrics::PARTIAL_SIGNATURES_RECEIVED, BLSTaskError, BLSTaskType, ChainIdentity,
+    RandomnessTask,
+};
+use arpa_node_dal::cache::{PublicKeyCache, RandomnessResultCache};
 use arpa_node_dal::{
-    BLSTasksFetcher, BLSTasksUpdater, ContextInfoUpdater, GroupInfoFetcher, GroupInfoUpdater,
-    NodeInfoFetcher, NodeInfoUpdater, SignatureResultCacheFetcher, SignatureResultCacheUpdater,
+    BLSResultCacheState, BLSTasksFetcher, BLSTasksUpdater, ContextInfoUpdater, GroupInfoFetcher,
+    GroupInfoUpdater, NodeInfoFetcher, NodeInfoUpdater, SignatureResultCacheFetcher,
+    SignatureResultCacheUpdater,
 };
 use ethers::types::Address;
 use futures::Future;
 use std::{marker::PhantomData, sync::Arc};
-use threshold_bls::group::PairingCurve;
+use subtle::ConstantTimeEq;
+use threshold_bls::{group::PairingCurve, sig::BLSError};
 use tokio::sync::RwLock;
 use tonic::{transport::Server, Request, Response, Status};
 
@@ -43,6 +53,8 @@ pub(crate) struct BLSCommitterServiceServer<
     id_address: Address,
     group_cache: Arc<RwLock<G>>,
     context: NodeContext<N, G, T, C, I, PC>,
+    verifier: PartialSignatureVerifier<PC>,
+    key_cache: RwLock<PublicKeyCache<PC>>,
     c: PhantomData<PC>,
 }
 
@@ -57,18 +69,25 @@ impl<
             + CoordinatorClientBuilder
             + AdapterClientBuilder
             + ChainProviderBuilder,
-        PC: PairingCurve,
+        PC: PairingCurve + Send + Sync + 'static,
     > BLSCommitterServiceServer<N, G, T, C, I, PC>
 {
     pub fn new(
         id_address: Address,
         group_cache: Arc<RwLock<G>>,
         context: NodeContext<N, G, T, C, I, PC>,
+        verification_threads: usize,
     ) -> Self {
         BLSCommitterServiceServer {
             id_address,
             group_cache,
             context,
+            verifier: PartialSignatureVerifier::new(
+                MAX_PARTIAL_VERIFICATION_BATCH_SIZE,
+                verification_threads,
+                MAX_PENDING_PARTIAL_VERIFICATIONS,
+            ),
+            key_cache: RwLock::new(PublicKeyCache::new()),
             c: PhantomData,
         }
     }
@@ -125,99 +144,183 @@ impl<
     ) -> Result<Response<CommitPartialSignatureReply>, Status> {
         let req = request.into_inner();
 
-        if let Err(_) | Ok(false) = self.group_cache.read().await.get_state() {
-            return Err(Status::not_found(NodeError::GroupNotReady.to_string()));
-        }
+        let request_id = req.request_id.clone();
 
-        if let Err(_) | Ok(false) = self.group_cache.read().await.is_committer(self.id_address) {
-            return Err(Status::not_found(NodeError::NotCommitter.to_string()));
-        }
+        in_request_span("commit_partial_signature", &request_id, async move {
+            if let Err(_) | Ok(false) = self.group_cache.read().await.get_state() {
+                return Err(Status::not_found(NodeError::GroupNotReady.to_string()));
+            }
 
-        let req_id_address: Address = req
-            .id_address
-            .parse()
-            .map_err(|_| Status::invalid_argument(NodeError::AddressFormatError.to_string()))?;
-
-        if let Ok(member) = self.group_cache.read().await.get_member(req_id_address) {
-            let partial_public_key = member.partial_public_key.clone().unwrap();
-
-            SimpleBLSCore::<PC>::partial_verify(
-                &partial_public_key,
-                &req.message,
-                &req.partial_signature,
-            )
-            .map_err(|e| Status::internal(e.to_string()))?;
-
-            match BLSTaskType::from(req.task_type) {
-                BLSTaskType::Randomness => {
-                    let randomness_result_cache = self
-                        .context
-                        .read()
-                        .await
-                        .get_main_chain()
-                        .get_randomness_result_cache();
-
-                    if !randomness_result_cache
-                        .read()
-                        .await
-                        .contains(&req.request_id)
-                        .await
-                        .map_err(|e| Status::internal(e.to_string()))?
-                    {
-                        return Err(Status::invalid_argument(
-                            BLSTaskError::CommitterCacheNotExisted.to_string(),
-                        ));
-                        // because we can't assure reliability of requested partial signature to original message,
-                        // we refuse to accept other node's request if the committer has not build this committer cache first.
-                    }
+            if let Err(_) | Ok(false) = self.group_cache.read().await.is_committer(self.id_address)
+            {
+                return Err(Status::not_found(NodeError::NotCommitter.to_string()));
+            }
 
-                    let committer_cache_message = randomness_result_cache
-                        .read()
-                        .await
-                        .get(&req.request_id)
-                        .await
-                        .unwrap()
-                        .result_cache
-                        .message
-                        .clone();
-
-                    if req.message != committer_cache_message {
-                        return Err(Status::invalid_argument(
-                            NodeError::InvalidTaskMessage.to_string(),
-                        ));
+            let current_group_epoch = self
+                .group_cache
+                .read()
+                .await
+                .get_epoch()
+                .map_err(|e| Status::internal(e.to_string()))?;
+
+            // the partial signature is signed by the secret share of another group epoch,
+            // so that it can't be aggregated with the ones of the current epoch.
+            if req.group_epoch as usize != current_group_epoch {
+                return Err(Status::failed_precondition(
+                    NodeError::GroupEpochMismatch(req.group_epoch as usize, current_group_epoch)
+                        .to_string(),
+                ));
+            }
+
+            let req_id_address = parse_commit_partial_signature_request::<PC>(&req)?;
+
+            if let Ok(member) = self.group_cache.read().await.get_member(req_id_address) {
+                let partial_public_key = member.partial_public_key.as_ref().ok_or_else(|| {
+                    Status::invalid_argument(NodeError::PartialPublicKeyNotExisted.to_string())
+                })?;
+
+                // the key is only checked against the subgroup and prepared once per epoch
+                let partial_public_key = self
+                    .key_cache
+                    .write()
+                    .await
+                    .get_or_insert(current_group_epoch, req_id_address, partial_public_key)
+                    .map_err(|e| Status::internal(e.to_string()))?;
+
+                self.verifier
+                    .verify(
+                        partial_public_key.prepared,
+                        req.message.clone(),
+                        req.partial_signature.clone(),
+                    )
+                    .await
+                    .map_err(|e| {
+                        // a partial which doesn't verify is the fault of the peer, the verifier
+                        // failing to run is ours
+                        if e.is::<BLSTaskError>() || e.is::<BLSError>() {
+                            Status::invalid_argument(e.to_string())
+                        } else {
+                            Status::internal(e.to_string())
+                        }
+                    })?;
+
+                match BLSTaskType::from(req.task_type) {
+                    BLSTaskType::Randomness => {
+                        let randomness_result_cache = self
+                            .context
+                            .read()
+                            .await
+                            .get_main_chain()
+                            .get_randomness_result_cache();
+
+                        if !randomness_result_cache
+                            .read()
+                            .await
+                            .contains(&req.request_id)
+                            .await
+                            .map_err(|e| Status::internal(e.to_string()))?
+                        {
+                            return Err(Status::invalid_argument(
+                                BLSTaskError::CommitterCacheNotExisted.to_string(),
+                            ));
+                            // because we can't assure reliability of requested partial signature to original message,
+                            // we refuse to accept other node's request if the committer has not build this committer cache first.
+                        }
+
+                        let committer_cache = randomness_result_cache
+                            .read()
+                            .await
+                            .get(&req.request_id)
+                            .await
+                            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+                        // a task fulfilled by a peer needs no more partial signatures
+                        if committer_cache.state == BLSResultCacheState::CommittedByOthers {
+                            return Err(Status::invalid_argument(
+                                BLSTaskError::AlreadyCommittedByOthers.to_string(),
+                            ));
+                        }
+
+                        let committer_cache = committer_cache.result_cache;
+
+                        // the message must be the one derived from the request on chain,
+                        // so that nodes never sign a message supplied by others
+                        if !constant_time_eq(
+                            &req.message,
+                            &compute_seed(&committer_cache.randomness_task),
+                        ) {
+                            return Err(Status::invalid_argument(
+                                NodeError::InvalidTaskSeed.to_string(),
+                            ));
+                        }
+
+                        let committer_cache_message = committer_cache.message;
+
+                        // messages are compared as raw bytes, no text encoding is assumed
+                        if !constant_time_eq(&req.message, &committer_cache_message) {
+                            return Err(Status::invalid_argument(
+                                NodeError::InvalidTaskMessage.to_string(),
+                            ));
+                        }
+
+                        if !randomness_result_cache
+                            .write()
+                            .await
+                            .add_partial_signature(
+                                req.request_id,
+                                req_id_address,
+                                req.partial_signature,
+                            )
+                            .await
+                            .map_err(|e| Status::internal(e.to_string()))?
+                        {
+                            return Err(Status::invalid_argument(
+                                BLSTaskError::AlreadyCommittedPartialSignature.to_string(),
+                            ));
+                        }
+
+                        PARTIAL_SIGNATURES_RECEIVED.inc();
                     }
 
-                    if !randomness_result_cache
-                        .write()
-                        .await
-                        .add_partial_signature(
-                            req.request_id,
-                            req_id_address,
-                            req.partial_signature,
-                        )
-                        .await
-                        .map_err(|e| Status::internal(e.to_string()))?
-                    {
+                    _ => {
                         return Err(Status::invalid_argument(
-                            BLSTaskError::AlreadyCommittedPartialSignature.to_string(),
+                            NodeError::InvalidTaskType.to_string(),
                         ));
                     }
                 }
 
-                _ => {
-                    return Err(Status::invalid_argument(
-                        NodeError::InvalidTaskType.to_string(),
-                    ));
-                }
+                return Ok(Response::new(CommitPartialSignatureReply { result: true }));
             }
 
-            return Ok(Response::new(CommitPartialSignatureReply { result: true }));
-        }
-
-        Err(Status::not_found(NodeError::MemberNotExisted.to_string()))
+            Err(Status::not_found(NodeError::MemberNotExisted.to_string()))
+        })
+        .await
     }
 }
 
+/// Parses the fields of the request which are taken from the peer as they are, returning the
+/// address of the committing member. Malformed fields are refused with `invalid_argument`,
+/// arbitrary bytes must never make the committer panic.
+pub fn parse_commit_partial_signature_request<PC: PairingCurve>(
+    req: &CommitPartialSignatureRequest,
+) -> Result<Address, Status> {
+    let id_address: Address = req
+        .id_address
+        .parse()
+        .map_err(|_| Status::invalid_argument(NodeError::AddressFormatError.to_string()))?;
+
+    parse_partial::<PC>(&req.partial_signature)
+        .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+    Ok(id_address)
+}
+
+/// Compares two byte strings without short-circuiting on the first mismatch,
+/// so the response time of the committer doesn't reveal how much of a message matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
 pub async fn start_committer_server_with_shutdown<
     F: Future<Output = ()>,
     N: NodeInfoFetcher<PC>
@@ -279,9 +382,16 @@ pub async fn start_committer_server_with_shutdown<
 
     let group_cache = context.read().await.get_main_chain().get_group_cache();
 
+    let verification_threads = context
+        .read()
+        .await
+        .get_config()
+        .committer_verification_threads
+        .unwrap();
+
     Server::builder()
         .add_service(CommitterServiceServer::with_interceptor(
-            BLSCommitterServiceServer::new(id_address, group_cache, context),
+            BLSCommitterServiceServer::new(id_address, group_cache, context, verification_threads),
             intercept,
         ))
         .serve_with_shutdown(addr, shutdown_signal)
@@ -348,9 +458,16 @@ pub async fn start_committer_server<
 
     let group_cache = context.read().await.get_main_chain().get_group_cache();
 
+    let verification_threads = context
+        .read()
+        .await
+        .get_config()
+        .committer_verification_threads
+        .unwrap();
+
     Server::builder()
         .add_service(CommitterServiceServer::with_interceptor(
-            BLSCommitterServiceServer::new(id_address, group_cache, context),
+            BLSCommitterServiceServer::new(id_address, group_cache, context, verification_threads),
             intercept,
         ))
         .serve(addr)
@@ -363,3 +480,59 @@ fn intercept(req: Request<()>) -> Result<Request<()>, Status> {
 
     Ok(req)
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::parse_commit_partial_signature_request;
+    use crate::{
+        node::algorithm::bls::{BLSCore, SimpleBLSCore},
+        rpc_stub::committer::CommitPartialSignatureRequest,
+    };
+    use ethers::types::Address;
+    use threshold_bls::{
+        curve::bn254::{PairingCurve as BN254, Scalar},
+        poly::Poly,
+        sig::Share,
+    };
+    use tonic::Code;
+
+    #[test]
+    fn test_parse_commit_partial_signature_request() {
+        let private = Poly::<Scalar>::new_from(1, &mut rand::thread_rng());
+        let share = Share {
+            index: 1,
+            private: private.eval(1).value,
+        };
+        let partial_signature = SimpleBLSCore::<BN254>::partial_sign(&share, b"test").unwrap();
+
+        let id_address = Address::random();
+        let mut req = CommitPartialSignatureRequest {
+            id_address: format!("{:?}", id_address),
+            message: b"test".to_vec(),
+            partial_signature,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_commit_partial_signature_request::<BN254>(&req).unwrap(),
+            id_address
+        );
+
+        for partial_signature in [vec![], vec![0xff; 7], vec![0; 64]] {
+            req.partial_signature = partial_signature;
+            assert_eq!(
+                parse_commit_partial_signature_request::<BN254>(&req)
+                    .unwrap_err()
+                    .code(),
+                Code::InvalidArgument
+            );
+        }
+
+        req.id_address = "not an address".to_string();
+        assert_eq!(
+            parse_commit_partial_signature_request::<BN254>(&req)
+                .unwrap_err()
+                .code(),
+            Code::InvalidArgument
+        );
+    }
+}
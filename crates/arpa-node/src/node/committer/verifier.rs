@@ -0,0 +1,149 @@
+use crate::node::algorithm::bls::{prepare_negated_generator, BLSCore, SimpleBLSCore};
+use anyhow::{anyhow, Result};
+use log::error;
+use std::sync::Arc;
+use threshold_bls::group::PairingCurve;
+use tokio::sync::{mpsc, oneshot, Semaphore};
+
+/// The maximum number of partial signatures verified in one batch
+pub const MAX_PARTIAL_VERIFICATION_BATCH_SIZE: usize = 64;
+/// The maximum number of partial signatures waiting to be verified
+pub const MAX_PENDING_PARTIAL_VERIFICATIONS: usize = 1024;
+
+struct VerificationRequest<PC: PairingCurve> {
+    partial_public_key: PC::G2Prepared,
+    msg: Vec<u8>,
+    partial: Vec<u8>,
+    result_sender: oneshot::Sender<Result<()>>,
+}
+
+/// Verifies partial signatures committed to this node against partial public keys that have
+/// already been validated and prepared for pairing, see `PublicKeyCache`. Verifications run on
+/// the blocking thread pool, at most `pool_size` batches at a time, so that pairings never block
+/// the async runtime serving the requests. Requests arriving while the pool is busy are queued and
+/// verified together in the next batch, so that the pairing cost is shared when many partials
+/// of different tasks come in at once. Once `max_pending` requests are queued, the following ones
+/// wait for room, so that a flood of partials can't grow the queue without bound.
+pub(crate) struct PartialSignatureVerifier<PC: PairingCurve> {
+    sender: mpsc::Sender<VerificationRequest<PC>>,
+}
+
+impl<PC: PairingCurve + Send + Sync + 'static> PartialSignatureVerifier<PC> {
+    pub fn new(max_batch_size: usize, pool_size: usize, max_pending: usize) -> Self {
+        let (sender, mut receiver) = mpsc::channel::<VerificationRequest<PC>>(max_pending.max(1));
+
+        let pool = Arc::new(Semaphore::new(pool_size));
+
+        let neg_generator = Arc::new(prepare_negated_generator::<PC>());
+
+        tokio::spawn(async move {
+            while let Some(request) = receiver.recv().await {
+                let permit = match pool.clone().acquire_owned().await {
+                    Ok(permit) => permit,
+                    Err(_) => break,
+                };
+
+                let mut batch = vec![request];
+                while batch.len() < max_batch_size {
+                    match receiver.try_recv() {
+                        Ok(request) => batch.push(request),
+                        Err(_) => break,
+                    }
+                }
+
+                let neg_generator = neg_generator.clone();
+
+                tokio::spawn(async move {
+                    let verification = tokio::task::spawn_blocking(move || {
+                        let items = batch
+                            .iter()
+                            .map(|r| (&r.partial_public_key, &r.msg[..], &r.partial[..]))
+                            .collect::<Vec<_>>();
+                        let results = SimpleBLSCore::<PC>::batch_partial_verify_with_prepared_keys(
+                            &neg_generator,
+                            &items,
+                        );
+                        (batch, results)
+                    })
+                    .await;
+
+                    drop(permit);
+
+                    match verification {
+                        Ok((batch, results)) => {
+                            for (request, result) in batch.into_iter().zip(results) {
+                                let _ = request.result_sender.send(result);
+                            }
+                        }
+                        Err(e) => {
+                            // the senders are dropped with the batch, so the waiting requests fail
+                            error!("partial signature verification panicked: {:?}", e);
+                        }
+                    }
+                });
+            }
+        });
+
+        PartialSignatureVerifier { sender }
+    }
+
+    pub async fn verify(
+        &self,
+        partial_public_key: PC::G2Prepared,
+        msg: Vec<u8>,
+        partial: Vec<u8>,
+    ) -> Result<()> {
+        let (result_sender, result_receiver) = oneshot::channel();
+        self.sender
+            .send(VerificationRequest {
+                partial_public_key,
+                msg,
+                partial,
+                result_sender,
+            })
+            .await
+            .map_err(|_| anyhow!("partial signature verifier is stopped"))?;
+        result_receiver
+            .await
+            .map_err(|_| anyhow!("partial signature verification is interrupted"))?
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::PartialSignatureVerifier;
+    use crate::node::algorithm::bls::{BLSCore, SimpleBLSCore};
+    use futures::future::join_all;
+    use threshold_bls::{
+        curve::bls12381::{PairingCurve as BLS12381, Scalar, G2},
+        group::PairingCurve,
+        poly::Poly,
+        sig::Share,
+    };
+
+    #[tokio::test]
+    async fn test_verify_through_a_full_queue() {
+        let rng = &mut rand::thread_rng();
+        let private = Poly::<Scalar>::new_from(1, rng);
+        let public = private.commit::<G2>().eval(1).value;
+        let share = Share {
+            index: 1,
+            private: private.eval(1).value,
+        };
+
+        // the requests beyond the single pending one wait for room instead of being dropped
+        let verifier = PartialSignatureVerifier::<BLS12381>::new(2, 1, 1);
+
+        let results = join_all((0..8u8).map(|i| {
+            let msg = vec![i];
+            let partial = SimpleBLSCore::<BLS12381>::partial_sign(&share, &msg).unwrap();
+            // the last partial is signed over another message
+            let msg = if i == 7 { vec![i + 1] } else { msg };
+            verifier.verify(BLS12381::prepare_g2(&public), msg, partial)
+        }))
+        .await;
+
+        assert!(results[..7].iter().all(|r| r.is_ok()));
+        assert!(results[7].is_err());
+    }
+}
@@ -50,6 +50,8 @@ pub(crate) trait MainChain: Chain {
 
     async fn init_dkg_subscribers(&self, context: &Self::Context);
 
+    async fn init_in_grouping_subscribers(&self, context: &Self::Context);
+
     async fn init_randomness_subscribers(&self, context: &Self::Context);
 }
 
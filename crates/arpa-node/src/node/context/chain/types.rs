@@ -1,20 +1,26 @@
 use crate::node::{
     context::types::GeneralContext,
     listener::{
-        block::BlockListener, new_randomness_task::NewRandomnessTaskListener,
+        block::BlockListener, file_task_source::FileTaskSource,
+        new_randomness_task::NewRandomnessTaskListener,
         post_commit_grouping::PostCommitGroupingListener, post_grouping::PostGroupingListener,
-        pre_grouping::PreGroupingListener,
+        pre_grouping::PreGroupingListener, proxy_upgrade::ProxyUpgradeListener,
         randomness_signature_aggregation::RandomnessSignatureAggregationListener,
-        ready_to_handle_randomness_task::ReadyToHandleRandomnessTaskListener, Listener,
+        ready_to_handle_randomness_task::ReadyToHandleRandomnessTaskListener,
+        share_consistency::ShareConsistencyListener, Listener,
     },
     queue::event_queue::EventQueue,
-    scheduler::{fixed::SimpleFixedTaskScheduler, TaskScheduler},
+    scheduler::{fixed::SimpleFixedTaskScheduler, FixedTaskScheduler, TaskScheduler},
     subscriber::{
-        block::BlockSubscriber, in_grouping::InGroupingSubscriber,
+        block::{BlockHeightSubscriber, BlockSubscriber},
+        dkg_phase::DKGPhaseSubscriber,
+        in_grouping::InGroupingSubscriber,
         post_grouping::PostGroupingSubscriber,
-        post_success_grouping::PostSuccessGroupingSubscriber, pre_grouping::PreGroupingSubscriber,
+        post_success_grouping::PostSuccessGroupingSubscriber,
+        pre_grouping::PreGroupingSubscriber,
         randomness_signature_aggregation::RandomnessSignatureAggregationSubscriber,
-        ready_to_handle_randomness_task::ReadyToHandleRandomnessTaskSubscriber, Subscriber,
+        ready_to_handle_randomness_task::ReadyToHandleRandomnessTaskSubscriber,
+        Subscriber,
     },
 };
 use arpa_node_contract_client::{
@@ -22,8 +28,9 @@ use arpa_node_contract_client::{
     coordinator::CoordinatorClientBuilder, provider::ChainProviderBuilder,
 };
 use arpa_node_core::{
-    ChainIdentity, GeneralChainIdentity, ListenerDescriptor, ListenerType, RandomnessTask,
-    SchedulerResult, TaskType, TimeLimitDescriptor,
+    ChainIdentity, GeneralChainIdentity, ListenerDescriptor, ListenerType, NodeRole,
+    RandomnessTask, RandomnessTaskFilter, SchedulerResult, SubscriberType, TaskType,
+    TimeLimitDescriptor,
 };
 use arpa_node_dal::{
     cache::{InMemoryBlockInfoCache, RandomnessResultCache},
@@ -35,9 +42,9 @@ use arpa_node_sqlite_db::{
 };
 use async_trait::async_trait;
 use log::error;
-use std::{marker::PhantomData, sync::Arc};
+use std::{marker::PhantomData, path::PathBuf, sync::Arc};
 use threshold_bls::group::PairingCurve;
-use tokio::sync::RwLock;
+use tokio::sync::{watch, RwLock};
 
 use super::{Chain, ChainFetcher, ContextFetcher, MainChain, MainChainFetcher};
 
@@ -61,7 +68,10 @@ pub struct GeneralMainChain<
     committer_randomness_result_cache: Arc<RwLock<C>>,
     c: PhantomData<PC>,
     time_limits: TimeLimitDescriptor,
+    minimum_threshold: usize,
     listener_descriptors: Option<Vec<ListenerDescriptor>>,
+    randomness_task_filter: RandomnessTaskFilter,
+    randomness_task_file: Option<PathBuf>,
 }
 
 impl<PC: PairingCurve + Send + Sync + 'static>
@@ -83,7 +93,9 @@ impl<PC: PairingCurve + Send + Sync + 'static>
         randomness_tasks_cache: BLSTasksDBClient<RandomnessTask>,
         randomness_result_cache: SignatureResultDBClient<RandomnessResultCache>,
         time_limits: TimeLimitDescriptor,
+        minimum_threshold: usize,
         listener_descriptors: Option<Vec<ListenerDescriptor>>,
+        randomness_task_filter: RandomnessTaskFilter,
     ) -> Self {
         GeneralMainChain {
             id: chain_identity.get_chain_id(),
@@ -96,9 +108,18 @@ impl<PC: PairingCurve + Send + Sync + 'static>
             group_cache: Arc::new(RwLock::new(group_cache)),
             c: PhantomData,
             time_limits,
+            minimum_threshold,
             listener_descriptors,
+            randomness_task_filter,
+            randomness_task_file: None,
         }
     }
+
+    /// Feeds the randomness tasks from the file instead of subscribing to the adapter logs.
+    pub fn with_randomness_task_file(mut self, randomness_task_file: Option<PathBuf>) -> Self {
+        self.randomness_task_file = randomness_task_file;
+        self
+    }
 }
 
 #[async_trait]
@@ -162,15 +183,36 @@ impl<
         fs: Arc<RwLock<SimpleFixedTaskScheduler>>,
         listener: ListenerDescriptor,
     ) -> SchedulerResult<()> {
+        let cancellation_token = fs
+            .write()
+            .await
+            .get_cancellation_token(&TaskType::Listener(listener.l_type));
+        let task_reporter = fs
+            .read()
+            .await
+            .get_task_registry()
+            .reporter(TaskType::Listener(listener.l_type));
+
         match listener.l_type {
             ListenerType::Block => {
-                let p_block = BlockListener::new(self.id(), self.get_chain_identity(), eq);
+                let p_block = BlockListener::new(
+                    self.id(),
+                    self.get_chain_identity(),
+                    eq,
+                    cancellation_token.clone(),
+                );
 
                 fs.write()
                     .await
                     .add_task(TaskType::Listener(ListenerType::Block), async move {
                         if let Err(e) = p_block
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -178,14 +220,24 @@ impl<
                     })
             }
             ListenerType::PreGrouping => {
-                let p_pre_grouping =
-                    PreGroupingListener::new(self.get_chain_identity(), self.get_group_cache(), eq);
+                let p_pre_grouping = PreGroupingListener::new(
+                    self.get_chain_identity(),
+                    self.get_group_cache(),
+                    eq,
+                    cancellation_token.clone(),
+                );
 
                 fs.write().await.add_task(
                     TaskType::Listener(ListenerType::PreGrouping),
                     async move {
                         if let Err(e) = p_pre_grouping
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -204,7 +256,13 @@ impl<
                     TaskType::Listener(ListenerType::PostCommitGrouping),
                     async move {
                         if let Err(e) = p_post_commit_grouping
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -224,7 +282,40 @@ impl<
                     TaskType::Listener(ListenerType::PostGrouping),
                     async move {
                         if let Err(e) = p_post_grouping
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
+                            .await
+                        {
+                            error!("{:?}", e);
+                        };
+                    },
+                )
+            }
+            ListenerType::NewRandomnessTask if self.randomness_task_file.is_some() => {
+                let p_file_task_source = FileTaskSource::new(
+                    self.id(),
+                    self.randomness_task_file.clone().unwrap(),
+                    self.get_randomness_tasks_cache(),
+                    eq,
+                    self.randomness_task_filter,
+                );
+
+                fs.write().await.add_task(
+                    TaskType::Listener(ListenerType::NewRandomnessTask),
+                    async move {
+                        if let Err(e) = p_file_task_source
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -239,15 +330,25 @@ impl<
                     self.id(),
                     id_address,
                     self.get_chain_identity(),
+                    self.get_node_cache(),
                     self.get_randomness_tasks_cache(),
                     eq,
+                    self.randomness_task_filter,
+                    self.time_limits.urgent_request_confirmations,
+                    cancellation_token.clone(),
                 );
 
                 fs.write().await.add_task(
                     TaskType::Listener(ListenerType::NewRandomnessTask),
                     async move {
                         if let Err(e) = p_new_randomness_task
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -267,13 +368,21 @@ impl<
                     self.get_randomness_tasks_cache(),
                     eq,
                     self.time_limits.randomness_task_exclusive_window,
+                    self.time_limits.urgent_randomness_task_exclusive_window,
+                    self.time_limits.randomness_task_claim_lease_duration,
                 );
 
                 fs.write().await.add_task(
                     TaskType::Listener(ListenerType::ReadyToHandleRandomnessTask),
                     async move {
                         if let Err(e) = p_ready_to_handle_randomness_task
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -288,17 +397,74 @@ impl<
                     RandomnessSignatureAggregationListener::new(
                         self.id(),
                         id_address,
+                        self.get_chain_identity(),
                         self.get_block_cache(),
                         self.get_group_cache(),
                         self.get_randomness_result_cache(),
                         eq,
+                        self.minimum_threshold,
+                        self.time_limits.partial_collection_timeout_blocks,
                     );
 
                 fs.write().await.add_task(
                     TaskType::Listener(ListenerType::RandomnessSignatureAggregation),
                     async move {
                         if let Err(e) = p_randomness_signature_aggregation
-                            .start(listener.interval_millis, listener.use_jitter)
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
+                            .await
+                        {
+                            error!("{:?}", e);
+                        };
+                    },
+                )
+            }
+            ListenerType::ShareConsistency => {
+                let id_address = self.get_node_cache().read().await.get_id_address().unwrap();
+
+                let p_share_consistency = ShareConsistencyListener::new(
+                    id_address,
+                    self.get_chain_identity(),
+                    self.get_group_cache(),
+                );
+
+                fs.write().await.add_task(
+                    TaskType::Listener(ListenerType::ShareConsistency),
+                    async move {
+                        if let Err(e) = p_share_consistency
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
+                            .await
+                        {
+                            error!("{:?}", e);
+                        };
+                    },
+                )
+            }
+            ListenerType::ProxyUpgrade => {
+                let p_proxy_upgrade = ProxyUpgradeListener::new(self.get_chain_identity());
+
+                fs.write().await.add_task(
+                    TaskType::Listener(ListenerType::ProxyUpgrade),
+                    async move {
+                        if let Err(e) = p_proxy_upgrade
+                            .start(
+                                listener.interval_millis,
+                                listener.use_jitter,
+                                listener.jitter_ratio,
+                                cancellation_token,
+                                task_reporter,
+                            )
                             .await
                         {
                             error!("{:?}", e);
@@ -339,6 +505,10 @@ impl<
     async fn init_subscribers(&self, context: &GeneralContext<N, G, T, C, I, PC>) {
         self.init_block_subscribers(context).await;
 
+        if context.get_config().node_role == NodeRole::Verifier {
+            return;
+        }
+
         self.init_dkg_subscribers(context).await;
 
         self.init_randomness_subscribers(context).await;
@@ -468,22 +638,79 @@ impl<
             ),
         )
         .await?;
+        self.init_listener(
+            context.get_event_queue(),
+            context.get_fixed_task_handler(),
+            ListenerDescriptor::default(ListenerType::ShareConsistency),
+        )
+        .await?;
 
         Ok(())
     }
 
     async fn init_block_subscribers(&self, context: &Self::Context) {
-        let s_block =
-            BlockSubscriber::new(self.id(), self.get_block_cache(), context.get_event_queue());
-
-        s_block.subscribe().await;
+        if context
+            .get_config()
+            .is_subscriber_enabled(SubscriberType::Block)
+        {
+            let s_block =
+                BlockSubscriber::new(self.id(), self.get_block_cache(), context.get_event_queue());
+
+            s_block.subscribe().await;
+        }
     }
 
     async fn init_dkg_subscribers(&self, context: &Self::Context) {
-        let s_pre_grouping =
-            PreGroupingSubscriber::new(self.get_group_cache(), context.get_event_queue());
+        let config = context.get_config();
+
+        if config.is_subscriber_enabled(SubscriberType::PreGrouping) {
+            let s_pre_grouping =
+                PreGroupingSubscriber::new(self.get_group_cache(), context.get_event_queue());
+
+            s_pre_grouping.subscribe().await;
+        }
+
+        if config.is_subscriber_enabled(SubscriberType::InGrouping) {
+            self.init_in_grouping_subscribers(context).await;
+        }
+
+        if config.is_subscriber_enabled(SubscriberType::PostSuccessGrouping) {
+            let s_post_success_grouping = PostSuccessGroupingSubscriber::new(
+                self.get_group_cache(),
+                context.get_event_queue(),
+            );
+
+            s_post_success_grouping.subscribe().await;
+        }
+
+        if config.is_subscriber_enabled(SubscriberType::PostGrouping) {
+            let s_post_grouping = PostGroupingSubscriber::new(
+                self.get_chain_identity(),
+                self.get_group_cache(),
+                context.get_event_queue(),
+                context.get_dynamic_task_handler(),
+            );
+
+            s_post_grouping.subscribe().await;
+        }
+    }
+
+    async fn init_in_grouping_subscribers(&self, context: &Self::Context) {
+        let block_height_receiver = if self.time_limits.dkg_wait_for_phase_on_block {
+            let (block_height_sender, block_height_receiver) = watch::channel(0);
+
+            let s_block_height = BlockHeightSubscriber::new(
+                self.id(),
+                block_height_sender,
+                context.get_event_queue(),
+            );
+
+            s_block_height.subscribe().await;
 
-        s_pre_grouping.subscribe().await;
+            Some(block_height_receiver)
+        } else {
+            None
+        };
 
         let s_in_grouping = InGroupingSubscriber::new(
             self.get_chain_identity(),
@@ -492,53 +719,51 @@ impl<
             context.get_event_queue(),
             context.get_dynamic_task_handler(),
             self.time_limits.dkg_wait_for_phase_interval_millis,
+            block_height_receiver,
         );
 
         s_in_grouping.subscribe().await;
 
-        let s_post_success_grouping =
-            PostSuccessGroupingSubscriber::new(self.get_group_cache(), context.get_event_queue());
+        let s_dkg_phase = DKGPhaseSubscriber::new(context.get_event_queue());
 
-        s_post_success_grouping.subscribe().await;
-
-        let s_post_grouping = PostGroupingSubscriber::new(
-            self.get_chain_identity(),
-            self.get_group_cache(),
-            context.get_event_queue(),
-            context.get_dynamic_task_handler(),
-        );
-
-        s_post_grouping.subscribe().await;
+        s_dkg_phase.subscribe().await;
     }
 
     async fn init_randomness_subscribers(&self, context: &Self::Context) {
+        let config = context.get_config();
         let id_address = self.get_node_cache().read().await.get_id_address().unwrap();
 
-        let s_ready_to_handle_randomness_task =
-            ReadyToHandleRandomnessTaskSubscriber::<G, T, C, PC>::new(
-                self.id(),
-                id_address,
-                self.get_group_cache(),
-                self.get_randomness_tasks_cache(),
-                self.get_randomness_result_cache(),
-                context.get_event_queue(),
-                context.get_dynamic_task_handler(),
-                self.time_limits.commit_partial_signature_retry_descriptor,
-            );
+        if config.is_subscriber_enabled(SubscriberType::ReadyToHandleRandomnessTask) {
+            let s_ready_to_handle_randomness_task =
+                ReadyToHandleRandomnessTaskSubscriber::<G, T, C, PC>::new(
+                    self.id(),
+                    id_address,
+                    self.get_group_cache(),
+                    self.get_randomness_tasks_cache(),
+                    self.get_randomness_result_cache(),
+                    context.get_event_queue(),
+                    context.get_dynamic_task_handler(),
+                    self.time_limits.commit_partial_signature_retry_descriptor,
+                );
 
-        s_ready_to_handle_randomness_task.subscribe().await;
+            s_ready_to_handle_randomness_task.subscribe().await;
+        }
 
-        let s_randomness_signature_aggregation =
-            RandomnessSignatureAggregationSubscriber::<I, C, PC>::new(
-                self.id(),
-                id_address,
-                self.get_chain_identity(),
-                self.get_randomness_result_cache(),
-                context.get_event_queue(),
-                context.get_dynamic_task_handler(),
-            );
+        if config.is_subscriber_enabled(SubscriberType::RandomnessSignatureAggregation) {
+            let s_randomness_signature_aggregation =
+                RandomnessSignatureAggregationSubscriber::<I, C, PC>::new(
+                    self.id(),
+                    id_address,
+                    self.get_chain_identity(),
+                    self.get_randomness_result_cache(),
+                    context.get_event_queue(),
+                    context.get_dynamic_task_handler(),
+                    self.time_limits.max_gas_price_gwei,
+                    self.time_limits.min_fulfillment_profit_margin,
+                );
 
-        s_randomness_signature_aggregation.subscribe().await;
+            s_randomness_signature_aggregation.subscribe().await;
+        }
     }
 }
 
@@ -1,4 +1,5 @@
 pub mod chain;
+pub mod sync;
 pub mod types;
 
 use self::types::ContextHandle;
@@ -50,3 +51,7 @@ pub(crate) trait ManagementServerStarter<T: Context> {
         context: Arc<RwLock<T>>,
     ) -> SchedulerResult<()>;
 }
+
+pub(crate) trait MetricsServerStarter {
+    fn start_metrics_server(&mut self, endpoint: String) -> SchedulerResult<()>;
+}
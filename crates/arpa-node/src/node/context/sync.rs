@@ -0,0 +1,682 @@
+use crate::node::error::{NodeError, NodeResult};
+use arpa_node_contract_client::adapter::AdapterViews;
+use arpa_node_contract_client::controller::{ControllerTransactions, ControllerViews};
+use arpa_node_contract_client::provider::TransactionReceiptFetcher;
+use arpa_node_core::{metrics::GROUP_EPOCH, DKGStatus, Group};
+use arpa_node_dal::{
+    cache::RandomnessResultCache, BLSResultCacheState, GroupInfoFetcher, GroupInfoUpdater,
+    SignatureResultCacheFetcher, SignatureResultCacheUpdater,
+};
+use ethers::{types::Address, utils::hex};
+use log::{error, info, warn};
+use rustc_hex::ToHex;
+use threshold_bls::group::{Element, PairingCurve};
+use threshold_bls::serialize::point_to_hex;
+use tokio::sync::RwLock;
+
+/// Checks whether the node is registered to the controller, so that an unregistered node is
+/// caught on startup rather than deep in the grouping or signing paths.
+pub async fn is_node_registered<V: ControllerViews<PC>, PC: PairingCurve>(
+    controller: &V,
+    id_address: Address,
+) -> NodeResult<bool> {
+    let node = controller.get_node(id_address).await?;
+
+    Ok(node.id_address != Address::zero())
+}
+
+/// Makes sure the node is registered to the controller before it joins the network.
+///
+/// An unregistered node is registered with its DKG public key if `auto_register` is set, otherwise
+/// it fails with `NotRegistered` along with the registration call to make.
+pub async fn ensure_node_registered<
+    C: ControllerViews<PC> + ControllerTransactions,
+    PC: PairingCurve,
+>(
+    controller: &C,
+    id_address: Address,
+    dkg_public_key: &PC::G2,
+    auto_register: bool,
+) -> NodeResult<()> {
+    if is_node_registered(controller, id_address).await? {
+        info!(
+            "The node {:?} is registered, skipping registration.",
+            id_address
+        );
+        return Ok(());
+    }
+
+    let id_public_key = bincode::serialize(dkg_public_key)?;
+
+    if !auto_register {
+        return Err(NodeError::NotRegistered {
+            id_address,
+            id_public_key: id_public_key.to_hex(),
+        });
+    }
+
+    let tx_hash = controller.node_register(id_public_key).await?;
+
+    info!(
+        "Registered the node {:?} to the controller, transaction hash: {:?}",
+        id_address, tx_hash
+    );
+
+    Ok(())
+}
+
+/// Catches the group restored from the database up with the controller when the node starts.
+///
+/// If the node crashed after persisting its share but before the group was marked available
+/// locally, the grouping may have been finished on chain meanwhile. In that case the DKG status
+/// and committers are restored from the chain, so that the node resumes signing right away
+/// rather than waiting for the next grouping. Returns whether the group has been restored.
+pub async fn fast_sync_group<
+    G: GroupInfoFetcher<PC> + GroupInfoUpdater<PC>,
+    V: ControllerViews<PC>,
+    PC: PairingCurve,
+>(
+    group_cache: &mut G,
+    controller: &V,
+    id_address: Address,
+) -> NodeResult<bool> {
+    let group_index = match controller.get_belonging_group(id_address).await? {
+        Some((group_index, _)) => group_index,
+        None => {
+            info!("The node doesn't belong to any group on chain, skipping fast-sync.");
+            return Ok(false);
+        }
+    };
+
+    if group_cache.get_secret_share().is_err() {
+        info!(
+            "There is no persisted share of group {}, waiting for the next grouping.",
+            group_index
+        );
+        return Ok(false);
+    }
+
+    let local_group = group_cache.get_group()?.clone();
+
+    if local_group.state {
+        return Ok(false);
+    }
+
+    if local_group.index != group_index {
+        warn!(
+            "The persisted group {} is different from the belonging group {} on chain, waiting for the next grouping.",
+            local_group.index, group_index
+        );
+        return Ok(false);
+    }
+
+    let group = controller.get_group(group_index).await?;
+
+    if group.epoch != local_group.epoch {
+        warn!(
+            "The persisted share of group {} epoch {} is obsolete, the latest epoch is {}, waiting for the next grouping.",
+            group_index, local_group.epoch, group.epoch
+        );
+        return Ok(false);
+    }
+
+    if !group.state {
+        info!(
+            "Group {} epoch {} is still in grouping on chain, skipping fast-sync.",
+            group_index, group.epoch
+        );
+        return Ok(false);
+    }
+
+    if !group.members.contains_key(&id_address) || group.public_key != local_group.public_key {
+        warn!(
+            "The persisted output of group {} epoch {} is inconsistent with the chain, waiting for the next grouping.",
+            group_index, group.epoch
+        );
+        return Ok(false);
+    }
+
+    group_cache
+        .update_dkg_status(group.index, group.epoch, DKGStatus::WaitForPostProcess)
+        .await?;
+
+    group_cache
+        .save_committers(group.index, group.epoch, group.committers)
+        .await?;
+
+    GROUP_EPOCH.set(group.epoch as i64);
+
+    info!(
+        "Group index:{} epoch:{} is restored from chain, committers saved.",
+        group.index, group.epoch
+    );
+
+    Ok(true)
+}
+
+/// Checks that the persisted share still matches the partial public key registered for this
+/// node in the group on chain.
+///
+/// A diverged share, e.g. from a database restored from an old backup or tampered with, would
+/// only produce partial signatures that the committers reject. In that case the DKG status is
+/// set to `ShareInconsistent`, which keeps the node from signing until the next grouping
+/// replaces the share. The group cache is not locked during the view call. Returns whether the
+/// share is consistent, or there is nothing to check.
+pub async fn check_share_consistency<
+    G: GroupInfoFetcher<PC> + GroupInfoUpdater<PC>,
+    V: ControllerViews<PC>,
+    PC: PairingCurve,
+>(
+    group_cache: &RwLock<G>,
+    controller: &V,
+    id_address: Address,
+) -> NodeResult<bool> {
+    let (group_index, group_epoch, derived_partial_public_key) = {
+        let group_cache = group_cache.read().await;
+
+        if let Ok(DKGStatus::ShareInconsistent) = group_cache.get_dkg_status() {
+            return Ok(false);
+        }
+
+        if !matches!(group_cache.get_state(), Ok(true)) {
+            return Ok(true);
+        }
+
+        let mut derived_partial_public_key = PC::G2::one();
+        derived_partial_public_key.mul(&group_cache.get_secret_share()?.private);
+
+        (
+            group_cache.get_index()?,
+            group_cache.get_epoch()?,
+            derived_partial_public_key,
+        )
+    };
+
+    let group = controller.get_group(group_index).await?;
+
+    if group.epoch != group_epoch {
+        // the group is being regrouped, the next grouping will replace the share anyway
+        return Ok(true);
+    }
+
+    let registered_partial_public_key = group
+        .members
+        .get(&id_address)
+        .and_then(|member| member.partial_public_key.clone());
+
+    if registered_partial_public_key.as_ref() == Some(&derived_partial_public_key) {
+        return Ok(true);
+    }
+
+    error!(
+        "!!! The persisted share of group {} epoch {} is INCONSISTENT with the chain, the node stops signing until the next grouping. derived partial public key: {:?}, registered partial public key: {:?}",
+        group_index, group_epoch, derived_partial_public_key, registered_partial_public_key
+    );
+
+    group_cache
+        .write()
+        .await
+        .update_dkg_status(group_index, group_epoch, DKGStatus::ShareInconsistent)
+        .await?;
+
+    Ok(false)
+}
+
+/// Reconciles the randomness results left in `Committing` when the node stopped while sending
+/// their fulfillments, so that a fulfillment which already landed is never sent again.
+///
+/// A result whose in-flight transaction has been mined successfully is settled as `Committed`
+/// by its receipt. Otherwise a task no longer pending on chain is settled as `Committed` if the
+/// node fulfilled it, or as `CommittedByOthers` otherwise. A task still pending, or one whose
+/// fulfillment can't be looked up, is set back to `NotCommitted`, to be checked against the chain
+/// once more before it is sent again.
+pub async fn reconcile_committing_results<
+    C: SignatureResultCacheFetcher<RandomnessResultCache>
+        + SignatureResultCacheUpdater<RandomnessResultCache>,
+    A: AdapterViews,
+    P: TransactionReceiptFetcher,
+>(
+    randomness_result_cache: &mut C,
+    adapter: &A,
+    provider: &P,
+    id_address: Address,
+) -> NodeResult<()> {
+    for result in randomness_result_cache.get_committing_signatures().await? {
+        let request_id = result.randomness_task.request_id;
+
+        let inflight_tx = randomness_result_cache.get_inflight_tx(&request_id).await?;
+
+        // a reverted or unknown transaction doesn't tell who fulfilled the task, if anyone
+        let landed = match inflight_tx {
+            Some(tx_hash) => match provider.get_transaction_status(tx_hash).await {
+                Ok(status) => status == Some(true),
+                Err(e) => {
+                    warn!(
+                        "Failed to look up the receipt of tx {:?} of task request id: {}, caused by: {:?}",
+                        tx_hash,
+                        hex::encode(&request_id),
+                        e
+                    );
+                    false
+                }
+            },
+            None => false,
+        };
+
+        let fulfillment = if landed {
+            Ok(Some(BLSResultCacheState::Committed))
+        } else {
+            match adapter.is_task_pending(&request_id).await {
+                Ok(true) => Ok(None),
+                Ok(false) => adapter
+                    .get_fulfillment(&request_id, result.randomness_task.assignment_block_height)
+                    .await
+                    .map(|fulfillment| {
+                        Some(match fulfillment {
+                            Some((_, fulfiller, _)) if fulfiller == id_address => {
+                                BLSResultCacheState::Committed
+                            }
+                            _ => BLSResultCacheState::CommittedByOthers,
+                        })
+                    }),
+                Err(e) => Err(e),
+            }
+        };
+
+        let state = match fulfillment {
+            Ok(Some(state)) => state,
+            Ok(None) => BLSResultCacheState::NotCommitted,
+            Err(e) => {
+                warn!(
+                    "Failed to look up the fulfillment of task request id: {}, caused by: {:?}",
+                    hex::encode(&request_id),
+                    e
+                );
+                BLSResultCacheState::NotCommitted
+            }
+        };
+
+        info!(
+            "The fulfillment of task request id: {} was being sent when the node stopped, in-flight tx: {:?}, reconciled as {}.",
+            hex::encode(&request_id),
+            inflight_tx,
+            state
+        );
+
+        // the transaction the task was fulfilled with is kept along with the result
+        let tx_hash = inflight_tx.filter(|_| landed);
+
+        randomness_result_cache
+            .update_commit_result(&request_id, state, tx_hash)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Compares the group in the database with the same group on chain, returning one line for
+/// every field that differs. It is the offline counterpart of `fast_sync_group` and
+/// `check_share_consistency`, and changes nothing on either side.
+pub async fn diagnose_group<G: GroupInfoFetcher<PC>, V: ControllerViews<PC>, PC: PairingCurve>(
+    group_cache: &G,
+    controller: &V,
+) -> NodeResult<Vec<String>> {
+    let local_group = group_cache.get_group()?;
+
+    let group = controller.get_group(local_group.index).await?;
+
+    Ok(diff_groups(local_group, &group))
+}
+
+pub fn diff_groups<PC: PairingCurve>(local_group: &Group<PC>, group: &Group<PC>) -> Vec<String> {
+    let mut diff = vec![];
+
+    if local_group.epoch != group.epoch {
+        diff.push(format!(
+            "epoch: local {}, on chain {}",
+            local_group.epoch, group.epoch
+        ));
+    }
+
+    if local_group.threshold != group.threshold {
+        diff.push(format!(
+            "threshold: local {}, on chain {}",
+            local_group.threshold, group.threshold
+        ));
+    }
+
+    if local_group.public_key != group.public_key {
+        diff.push(format!(
+            "public key: local {:?}, on chain {:?}",
+            local_group.public_key.as_ref().map(point_to_hex),
+            group.public_key.as_ref().map(point_to_hex)
+        ));
+    }
+
+    for (id_address, member) in local_group.members.iter() {
+        match group.members.get(id_address) {
+            None => diff.push(format!("member {:?}: local only", id_address)),
+            Some(on_chain_member) => {
+                if member.index != on_chain_member.index {
+                    diff.push(format!(
+                        "member {:?} index: local {}, on chain {}",
+                        id_address, member.index, on_chain_member.index
+                    ));
+                }
+
+                if member.partial_public_key != on_chain_member.partial_public_key {
+                    diff.push(format!(
+                        "member {:?} partial public key: local {:?}, on chain {:?}",
+                        id_address,
+                        member.partial_public_key.as_ref().map(point_to_hex),
+                        on_chain_member
+                            .partial_public_key
+                            .as_ref()
+                            .map(point_to_hex)
+                    ));
+                }
+            }
+        }
+    }
+
+    for id_address in group.members.keys() {
+        if !local_group.members.contains_key(id_address) {
+            diff.push(format!("member {:?}: on chain only", id_address));
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{
+        check_share_consistency, diff_groups, fast_sync_group, reconcile_committing_results,
+    };
+    use arpa_node_contract_client::{adapter::AdapterTransactions, mock::MockChainProvider};
+    use arpa_node_core::{
+        DKGStatus, Group, Member, RandomnessRequestType, RandomnessTask, RandomnessTaskPriority,
+    };
+    use arpa_node_dal::{
+        cache::{InMemoryGroupInfoCache, InMemorySignatureResultCache, RandomnessResultCache},
+        BLSResultCacheState, GroupInfoFetcher, SignatureResultCacheFetcher,
+        SignatureResultCacheUpdater,
+    };
+    use ethers::types::{Address, H256, U256};
+    use std::collections::HashMap;
+    use threshold_bls::{
+        curve::bn254::{PairingCurve as BN254, Scalar, G2},
+        group::Element,
+        sig::Share,
+    };
+    use tokio::sync::RwLock;
+
+    fn build_member(index: usize, id_address: Address) -> Member<BN254> {
+        Member {
+            index,
+            id_address,
+            rpc_endpoint: None,
+            partial_public_key: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_groups() {
+        let mut local_group = Group::<BN254>::new();
+        local_group.epoch = 2;
+        local_group.threshold = 3;
+        local_group.public_key = Some(Element::one());
+        for i in 1..=3 {
+            let id_address = Address::from_low_u64_be(i);
+            local_group
+                .members
+                .insert(id_address, build_member(i as usize - 1, id_address));
+        }
+
+        let mut group = local_group.clone();
+        assert!(diff_groups(&local_group, &group).is_empty());
+
+        group.epoch = 3;
+        group.members.remove(&Address::from_low_u64_be(3));
+        let id_address = Address::from_low_u64_be(4);
+        group
+            .members
+            .insert(id_address, build_member(2, id_address));
+
+        assert_eq!(
+            diff_groups(&local_group, &group),
+            vec![
+                "epoch: local 2, on chain 3".to_string(),
+                format!("member {:?}: local only", Address::from_low_u64_be(3)),
+                format!("member {:?}: on chain only", Address::from_low_u64_be(4)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fast_sync_group() {
+        let id_address = Address::from_low_u64_be(1);
+        let chain = MockChainProvider::new(id_address);
+
+        let mut group = Group::<BN254>::new();
+        group.epoch = 2;
+        group.size = 3;
+        group.threshold = 2;
+        group.state = true;
+        group.public_key = Some(Element::one());
+        for i in 1..=3 {
+            let member_address = Address::from_low_u64_be(i);
+            group
+                .members
+                .insert(member_address, build_member(i as usize - 1, member_address));
+        }
+        group.committers = vec![Address::from_low_u64_be(2), id_address];
+
+        // the node crashed before the group was marked available locally
+        let mut local_group = group.clone();
+        local_group.state = false;
+        local_group.committers = vec![];
+        let build_group_cache = |share: Option<Share<Scalar>>| {
+            InMemoryGroupInfoCache::rebuild(
+                share,
+                local_group.clone(),
+                DKGStatus::CommitSuccess,
+                0,
+                1,
+            )
+        };
+        let share = Some(Share {
+            index: 0,
+            private: Scalar::one(),
+        });
+
+        // the node doesn't belong to any group on chain yet
+        let mut group_cache = build_group_cache(share.clone());
+        assert!(!fast_sync_group(&mut group_cache, &chain, id_address)
+            .await
+            .unwrap());
+
+        // the grouping of the persisted epoch is still in progress on chain
+        let mut grouping = group.clone();
+        grouping.state = false;
+        chain.set_group(&grouping);
+        assert!(!fast_sync_group(&mut group_cache, &chain, id_address)
+            .await
+            .unwrap());
+
+        // the group has moved to another epoch on chain
+        let mut regrouped = group.clone();
+        regrouped.epoch = 3;
+        chain.set_group(&regrouped);
+        assert!(!fast_sync_group(&mut group_cache, &chain, id_address)
+            .await
+            .unwrap());
+
+        chain.set_group(&group);
+
+        // there is no persisted share to sign with
+        let mut group_cache = build_group_cache(None);
+        assert!(!fast_sync_group(&mut group_cache, &chain, id_address)
+            .await
+            .unwrap());
+
+        let mut group_cache = build_group_cache(share);
+        assert!(fast_sync_group(&mut group_cache, &chain, id_address)
+            .await
+            .unwrap());
+        assert!(group_cache.get_state().unwrap());
+        assert_eq!(
+            group_cache.get_dkg_status().unwrap(),
+            DKGStatus::WaitForPostProcess
+        );
+        assert_eq!(
+            group_cache.get_group().unwrap().committers,
+            group.committers
+        );
+
+        // the group has been restored already
+        assert!(!fast_sync_group(&mut group_cache, &chain, id_address)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_check_share_consistency() {
+        let id_address = Address::from_low_u64_be(1);
+        let chain = MockChainProvider::new(id_address);
+
+        let mut private = Scalar::one();
+        private.add(&Scalar::one());
+        let mut partial_public_key = G2::one();
+        partial_public_key.mul(&private);
+
+        let mut group = Group::<BN254>::new();
+        group.epoch = 2;
+        group.state = true;
+        let mut member = build_member(0, id_address);
+        member.partial_public_key = Some(partial_public_key);
+        group.members.insert(id_address, member);
+        chain.set_group(&group);
+
+        let build_group_cache = |private: Scalar| {
+            RwLock::new(InMemoryGroupInfoCache::rebuild(
+                Some(Share { index: 0, private }),
+                group.clone(),
+                DKGStatus::WaitForPostProcess,
+                0,
+                1,
+            ))
+        };
+
+        let group_cache = build_group_cache(private);
+        assert!(check_share_consistency(&group_cache, &chain, id_address)
+            .await
+            .unwrap());
+        assert_eq!(
+            group_cache.read().await.get_dkg_status().unwrap(),
+            DKGStatus::WaitForPostProcess
+        );
+
+        // e.g. a database restored from the backup of another epoch
+        let group_cache = build_group_cache(Scalar::one());
+        assert!(!check_share_consistency(&group_cache, &chain, id_address)
+            .await
+            .unwrap());
+        assert_eq!(
+            group_cache.read().await.get_dkg_status().unwrap(),
+            DKGStatus::ShareInconsistent
+        );
+    }
+
+    fn build_task(request_id: Vec<u8>) -> RandomnessTask {
+        RandomnessTask {
+            request_id,
+            subscription_id: 1,
+            group_index: 0,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 0,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_committing_results() {
+        let id_address = Address::random();
+        let chain = MockChainProvider::new(id_address);
+        let mut cache = InMemorySignatureResultCache::<RandomnessResultCache>::new();
+
+        for request_id in 1..=5u8 {
+            chain.emit_randomness_task(build_task(vec![request_id]));
+
+            // the fulfillment event of the last task can't be found, e.g. as it lies before the
+            // block the lookup starts from, which only its receipt makes up for
+            let mut task = build_task(vec![request_id]);
+            if request_id == 5 {
+                task.assignment_block_height = 1;
+            }
+            cache.add(0, task, vec![], 1).await.unwrap();
+        }
+
+        // the first and the last tasks were fulfilled by the node, the second one by a peer
+        let mut inflight_txs = HashMap::new();
+        for (request_id, fulfiller) in [(1u8, id_address), (2, Address::random()), (5, id_address)]
+        {
+            let tx_hash = chain
+                .with_id_address(fulfiller)
+                .fulfill_randomness(
+                    0,
+                    1,
+                    build_task(vec![request_id]),
+                    vec![7; 64],
+                    HashMap::new(),
+                    None,
+                )
+                .await
+                .unwrap();
+            if fulfiller == id_address {
+                inflight_txs.insert(request_id, tx_hash);
+            }
+        }
+        // the node's transaction of the second task never landed as the peer's came first
+        inflight_txs.insert(2, H256::random());
+        inflight_txs.insert(3, H256::random());
+        for (request_id, tx_hash) in inflight_txs.iter() {
+            cache
+                .update_commit_result(
+                    &[*request_id],
+                    BLSResultCacheState::Committing,
+                    Some(*tx_hash),
+                )
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(
+            cache.get_inflight_tx(&[1]).await.unwrap(),
+            Some(inflight_txs[&1])
+        );
+        assert_eq!(cache.get_inflight_tx(&[4]).await.unwrap(), None);
+
+        reconcile_committing_results(&mut cache, &chain, &chain, id_address)
+            .await
+            .unwrap();
+
+        assert!(cache.get_committing_signatures().await.unwrap().is_empty());
+        for (request_id, state) in [
+            (1u8, BLSResultCacheState::Committed),
+            (2, BLSResultCacheState::CommittedByOthers),
+            (3, BLSResultCacheState::NotCommitted),
+            (4, BLSResultCacheState::NotCommitted),
+            (5, BLSResultCacheState::Committed),
+        ] {
+            assert_eq!(cache.get(&[request_id]).await.unwrap().state, state);
+        }
+    }
+}
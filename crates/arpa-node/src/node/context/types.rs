@@ -1,13 +1,16 @@
 use super::{
     chain::{types::GeneralMainChain, Chain},
-    CommitterServerStarter, Context, ContextFetcher, ManagementServerStarter, TaskWaiter,
+    CommitterServerStarter, Context, ContextFetcher, ManagementServerStarter, MetricsServerStarter,
+    TaskWaiter,
 };
 use crate::node::{
     committer::server as committer_server,
     management::server as management_server,
+    metrics::server as metrics_server,
     queue::event_queue::EventQueue,
     scheduler::{
-        dynamic::SimpleDynamicTaskScheduler, fixed::SimpleFixedTaskScheduler, TaskScheduler,
+        dynamic::SimpleDynamicTaskScheduler, fixed::SimpleFixedTaskScheduler, FixedTaskScheduler,
+        TaskScheduler,
     },
 };
 use arpa_node_contract_client::{
@@ -69,7 +72,9 @@ impl<
     pub fn new(main_chain: GeneralMainChain<N, G, T, C, I, PC>, config: Config) -> Self {
         GeneralContext {
             main_chain,
-            eq: Arc::new(RwLock::new(EventQueue::new())),
+            eq: Arc::new(RwLock::new(EventQueue::with_descriptor(
+                config.event_queue.clone().unwrap_or_default(),
+            ))),
             ts: Arc::new(RwLock::new(SimpleDynamicTaskScheduler::new())),
             f_ts: Arc::new(RwLock::new(SimpleFixedTaskScheduler::new())),
             config,
@@ -132,6 +137,8 @@ impl<
 
         let node_management_rpc_endpoint = self.config.node_management_rpc_endpoint.clone();
 
+        let node_metrics_endpoint = self.config.node_metrics_endpoint.clone();
+
         let context = Arc::new(RwLock::new(self));
 
         f_ts.write()
@@ -142,6 +149,12 @@ impl<
             .await
             .start_management_server(node_management_rpc_endpoint, context.clone())?;
 
+        if let Some(node_metrics_endpoint) = node_metrics_endpoint {
+            f_ts.write()
+                .await
+                .start_metrics_server(node_metrics_endpoint)?;
+        }
+
         let ts = context.read().await.get_dynamic_task_handler();
 
         Ok(ContextHandle { ts })
@@ -288,9 +301,14 @@ impl<
         rpc_endpoint: String,
         context: Arc<RwLock<GeneralContext<N, G, T, C, I, PC>>>,
     ) -> SchedulerResult<()> {
+        let task_reporter = self
+            .get_task_registry()
+            .reporter(TaskType::RpcServer(RpcServerType::Committer));
+
         self.add_task(TaskType::RpcServer(RpcServerType::Committer), async move {
             if let Err(e) = committer_server::start_committer_server(rpc_endpoint, context).await {
                 error!("{:?}", e);
+                task_reporter.report_failure(format!("{:?}", e));
             };
         })
     }
@@ -345,10 +363,30 @@ impl<
         rpc_endpoint: String,
         context: Arc<RwLock<GeneralContext<N, G, T, C, I, PC>>>,
     ) -> SchedulerResult<()> {
+        let task_reporter = self
+            .get_task_registry()
+            .reporter(TaskType::RpcServer(RpcServerType::Management));
+
         self.add_task(TaskType::RpcServer(RpcServerType::Management), async move {
             if let Err(e) = management_server::start_management_server(rpc_endpoint, context).await
             {
                 error!("{:?}", e);
+                task_reporter.report_failure(format!("{:?}", e));
+            };
+        })
+    }
+}
+
+impl MetricsServerStarter for SimpleFixedTaskScheduler {
+    fn start_metrics_server(&mut self, endpoint: String) -> SchedulerResult<()> {
+        let task_reporter = self
+            .get_task_registry()
+            .reporter(TaskType::RpcServer(RpcServerType::Metrics));
+
+        self.add_task(TaskType::RpcServer(RpcServerType::Metrics), async move {
+            if let Err(e) = metrics_server::start_metrics_server(endpoint).await {
+                error!("{:?}", e);
+                task_reporter.report_failure(format!("{:?}", e));
             };
         })
     }
@@ -1,9 +1,8 @@
-use arpa_node_core::WalletSigner;
+use arpa_node_core::{NodeSigner, WalletSigner};
 use ethers::providers::Http as HttpProvider;
 use ethers::{
-    prelude::{signer::SignerMiddlewareError, ContractError, ProviderError},
+    prelude::{signer::SignerMiddlewareError, ContractError, MulticallError, ProviderError},
     providers::Provider,
-    signers::LocalWallet,
 };
 use rustc_hex::FromHexError;
 use thiserror::Error;
@@ -21,7 +20,9 @@ pub enum ContractClientError {
     #[error(transparent)]
     ContractError(#[from] ContractError<WalletSigner>),
     #[error(transparent)]
-    SignerError(#[from] SignerMiddlewareError<Provider<HttpProvider>, LocalWallet>),
+    MulticallError(#[from] MulticallError<WalletSigner>),
+    #[error(transparent)]
+    SignerError(#[from] SignerMiddlewareError<Provider<HttpProvider>, NodeSigner>),
     #[error(transparent)]
     AddressParseError(#[from] FromHexError),
     #[error("can't fetch new block, please check provider")]
@@ -42,4 +43,24 @@ pub enum ContractClientError {
     NoTransactionReceipt,
     #[error("Transaction failed with status equal to 0x0")]
     TransactionFailed,
+    #[error("there is no pending transaction with nonce {0}")]
+    NoPendingTransaction(ethers::types::U256),
+    #[error("circuit breaker of contract {0:?} is open after consecutive failures, call is short-circuited")]
+    CircuitOpen(ethers::types::Address),
+    #[error("the request id of {0} bytes doesn't fit in bytes32")]
+    InvalidRequestId(usize),
+    #[error("deployed contracts don't match the compiled ABI: {0}")]
+    AbiVersionMismatch(String),
+    #[error("the group of {size} participants exceeds max_group_size of {max_group_size}")]
+    GroupTooLarge { size: usize, max_group_size: usize },
+}
+
+/// Whether the provider refused to return a response because it was too large,
+/// which the providers only report in the message of the error.
+pub fn is_response_size_error(e: &ContractClientError) -> bool {
+    let message = e.to_string().to_lowercase();
+
+    ["response size", "response too large", "exceeds the limit"]
+        .iter()
+        .any(|pattern| message.contains(pattern))
 }
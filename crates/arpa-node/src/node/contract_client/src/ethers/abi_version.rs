@@ -0,0 +1,238 @@
+use crate::{
+    contract_stub::{adapter::ADAPTER_ABI, controller::CONTROLLER_ABI, coordinator::Coordinator},
+    error::{ContractClientError, ContractClientResult},
+};
+use arpa_node_core::{ChainIdentity, EIP1967_IMPLEMENTATION_SLOT};
+use ethers::{
+    abi::Abi,
+    prelude::*,
+    providers::{Http as HttpProvider, HttpClientError},
+};
+use log::{info, warn};
+use std::sync::Arc;
+
+/// Checks the deployed controller and adapter against the ABIs compiled into `contract_stub`.
+pub async fn check_main_contract_abis<I: ChainIdentity>(identity: &I) -> ContractClientResult<()> {
+    check_contract_abis(
+        &identity.get_provider(),
+        &[
+            (
+                "Controller",
+                identity.get_controller_address(),
+                &CONTROLLER_ABI,
+            ),
+            ("Adapter", identity.get_adapter_address(), &ADAPTER_ABI),
+        ],
+    )
+    .await
+}
+
+/// Checks a coordinator, which is only known once a DKG task is assigned, against the compiled ABI.
+pub async fn check_coordinator_abi<I: ChainIdentity>(
+    identity: &I,
+    coordinator_address: Address,
+) -> ContractClientResult<()> {
+    check_coordinator_views(identity.get_provider(), coordinator_address).await
+}
+
+/// Calls the views the DKG reads its phases and participants from through the compiled ABI,
+/// so that a coordinator lacking them or returning them in another shape fails with
+/// `AbiVersionMismatch`. A provider that can't be reached is only warned about, as the DKG
+/// retries its calls anyway.
+async fn check_coordinator_views(
+    provider: Arc<Provider<HttpProvider>>,
+    coordinator_address: Address,
+) -> ContractClientResult<()> {
+    let coordinator = Coordinator::new(coordinator_address, provider);
+
+    let result = match coordinator.in_phase().call().await {
+        Ok(_) => coordinator.get_participants().call().await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => {
+            info!(
+                "Coordinator at {:?} matches the compiled ABI",
+                coordinator_address
+            );
+            Ok(())
+        }
+        Err(e) if is_transport_error(&e) => {
+            warn!(
+                "Can't check coordinator at {:?} against the compiled ABI: {}",
+                coordinator_address, e
+            );
+            Ok(())
+        }
+        Err(e) => Err(ContractClientError::AbiVersionMismatch(format!(
+            "Coordinator at {:?} fails its views: {}",
+            coordinator_address, e
+        ))),
+    }
+}
+
+/// Whether the call never got an answer from the provider, as opposed to the contract
+/// reverting or returning data the ABI can't decode.
+fn is_transport_error(e: &ContractError<Provider<HttpProvider>>) -> bool {
+    match e {
+        ContractError::MiddlewareError(ProviderError::JsonRpcClientError(e))
+        | ContractError::ProviderError(ProviderError::JsonRpcClientError(e)) => {
+            matches!(
+                e.downcast_ref::<HttpClientError>(),
+                Some(HttpClientError::ReqwestError(_))
+            )
+        }
+        ContractError::MiddlewareError(ProviderError::HTTPError(_))
+        | ContractError::ProviderError(ProviderError::HTTPError(_)) => true,
+        _ => false,
+    }
+}
+
+/// Fails with `AbiVersionMismatch` listing every contract that doesn't implement all the
+/// functions of its compiled ABI.
+pub async fn check_contract_abis(
+    provider: &Provider<HttpProvider>,
+    contracts: &[(&str, Address, &Abi)],
+) -> ContractClientResult<()> {
+    let mut mismatches = vec![];
+
+    for (name, address, abi) in contracts {
+        let code = get_implementation_code(provider, *address).await?;
+        let missing = find_missing_functions(&code, abi);
+
+        if missing.is_empty() {
+            info!("{} at {:?} matches the compiled ABI", name, address);
+        } else {
+            mismatches.push(format!(
+                "{} at {:?} lacks [{}]",
+                name,
+                address,
+                missing.join(", ")
+            ));
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(ContractClientError::AbiVersionMismatch(
+            mismatches.join("; "),
+        ))
+    }
+}
+
+/// Returns the code behind the address, following an EIP-1967 proxy to its implementation.
+async fn get_implementation_code(
+    provider: &Provider<HttpProvider>,
+    address: Address,
+) -> ContractClientResult<Bytes> {
+    let slot = provider
+        .get_storage_at(address, EIP1967_IMPLEMENTATION_SLOT, None)
+        .await?;
+    let implementation = Address::from(slot);
+
+    let address = if implementation.is_zero() {
+        address
+    } else {
+        implementation
+    };
+
+    Ok(provider.get_code(address, None).await?)
+}
+
+/// Returns the signatures of the functions whose selector doesn't appear in the code.
+///
+/// The dispatcher of a Solidity contract pushes the selector of every external function,
+/// so a function missing from the code is not implemented by the deployed contract.
+pub fn find_missing_functions(code: &[u8], abi: &Abi) -> Vec<String> {
+    let mut missing = abi
+        .functions()
+        .filter(|function| {
+            let selector = function.short_signature();
+            !code.windows(selector.len()).any(|w| w == selector)
+        })
+        .map(|function| function.signature())
+        .collect::<Vec<_>>();
+
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{check_coordinator_views, find_missing_functions};
+    use crate::{
+        contract_stub::{
+            controller::CONTROLLER_ABI,
+            coordinator::{Coordinator, COORDINATOR_ABI, COORDINATOR_BYTECODE},
+        },
+        error::ContractClientError,
+    };
+    use ethers::{
+        prelude::*,
+        providers::Http as HttpProvider,
+        utils::{Anvil, AnvilInstance},
+    };
+    use std::{convert::TryFrom, sync::Arc, time::Duration};
+
+    const PHRASE: &str =
+        "work man father plunge mystery proud hollow address reunion sauce theory bonus";
+
+    fn start_chain() -> (AnvilInstance, Arc<Provider<HttpProvider>>) {
+        let anvil = Anvil::new().chain_id(1u64).mnemonic(PHRASE).spawn();
+        let provider = Provider::<HttpProvider>::try_from(anvil.endpoint())
+            .unwrap()
+            .interval(Duration::from_millis(100));
+        (anvil, Arc::new(provider))
+    }
+
+    #[tokio::test]
+    async fn test_check_coordinator_views() {
+        let (anvil, provider) = start_chain();
+
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let client = Arc::new(SignerMiddleware::new(
+            provider.clone(),
+            wallet.with_chain_id(anvil.chain_id()),
+        ));
+        let coordinator = Coordinator::deploy(client, (3u8, 30u8))
+            .unwrap()
+            .send()
+            .await
+            .unwrap();
+
+        check_coordinator_views(provider.clone(), coordinator.address())
+            .await
+            .unwrap();
+
+        // an account without code answers every view with no data, which can't be decoded
+        assert!(matches!(
+            check_coordinator_views(provider, Address::random()).await,
+            Err(ContractClientError::AbiVersionMismatch(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_coordinator_views_with_unreachable_provider() {
+        let provider = Arc::new(Provider::<HttpProvider>::try_from("http://127.0.0.1:1").unwrap());
+
+        check_coordinator_views(provider, Address::random())
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_find_missing_functions() {
+        assert!(find_missing_functions(&COORDINATOR_BYTECODE, &COORDINATOR_ABI).is_empty());
+
+        assert_eq!(
+            find_missing_functions(&[], &COORDINATOR_ABI).len(),
+            COORDINATOR_ABI.functions().count()
+        );
+
+        // the coordinator doesn't implement the functions of the controller
+        assert!(!find_missing_functions(&COORDINATOR_BYTECODE, &CONTROLLER_ABI).is_empty());
+    }
+}
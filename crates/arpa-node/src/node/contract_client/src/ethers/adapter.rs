@@ -6,18 +6,23 @@ use crate::{
         shared_types::PartialSignature as ContractPartialSignature,
     },
     error::{ContractClientError, ContractClientResult},
-    ServiceClient, TransactionCaller, ViewCaller,
+    with_signer_transaction_type, ServiceClient, TransactionCaller, TransactionSentHook,
+    ViewCaller,
 };
 use arpa_node_core::{
-    pad_to_bytes32, ChainIdentity, ExponentialBackoffRetryDescriptor, GeneralChainIdentity,
-    PartialSignature, RandomnessRequestType, RandomnessTask, WalletSigner,
-    DEFAULT_MINIMUM_THRESHOLD, FULFILL_RANDOMNESS_GAS_EXCEPT_CALLBACK, RANDOMNESS_REWARD_GAS,
-    VERIFICATION_GAS_OVER_MINIMUM_THRESHOLD,
+    estimate_fulfill_randomness_gas, pad_to_bytes32, ChainIdentity, CircuitBreaker,
+    ExponentialBackoffRetryDescriptor, GeneralChainIdentity, PartialSignature,
+    RandomnessRequestType, RandomnessTask, RandomnessTaskFilter, RandomnessTaskPriority,
+    TransactionConfirmationDescriptor, WalletSigner,
 };
 use async_trait::async_trait;
 use ethers::{prelude::*, utils::hex};
 use log::info;
 use std::{collections::HashMap, future::Future, sync::Arc};
+use tokio_util::sync::CancellationToken;
+
+/// The number of blocks queried at a time when replaying the randomness tasks since a block.
+const RANDOMNESS_TASK_REPLAY_PAGE_SIZE: u64 = 1000;
 
 #[allow(dead_code)]
 pub struct AdapterClient {
@@ -26,6 +31,9 @@ pub struct AdapterClient {
     signer: Arc<WalletSigner>,
     contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
     contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+    circuit_breaker: Arc<CircuitBreaker>,
+    fulfill_randomness_gas_limit_multiplier: f64,
 }
 
 impl AdapterClient {
@@ -42,6 +50,10 @@ impl AdapterClient {
             signer: identity.get_signer(),
             contract_transaction_retry_descriptor,
             contract_view_retry_descriptor,
+            transaction_confirmation_descriptor: identity.get_transaction_confirmation_descriptor(),
+            circuit_breaker: identity.get_circuit_breaker(adapter_address),
+            fulfill_randomness_gas_limit_multiplier: identity
+                .get_fulfill_randomness_gas_limit_multiplier(),
         }
     }
 }
@@ -82,9 +94,11 @@ impl AdapterTransactions for AdapterClient {
     async fn fulfill_randomness(
         &self,
         group_index: usize,
+        threshold: usize,
         task: RandomnessTask,
         signature: Vec<u8>,
         partial_signatures: HashMap<Address, PartialSignature>,
+        on_sent: Option<TransactionSentHook>,
     ) -> ContractClientResult<H256> {
         let adapter_contract =
             ServiceClient::<AdapterContract>::prepare_service_client(self).await?;
@@ -117,34 +131,39 @@ impl AdapterTransactions for AdapterClient {
             block_num: task.assignment_block_height.into(),
         };
 
-        let call = adapter_contract.fulfill_randomness(group_index as u32, r_id, sig, rd, ps);
-
-        let partial_signers_count = partial_signatures.len() as u32;
+        let call = with_signer_transaction_type(
+            adapter_contract.fulfill_randomness(group_index as u32, r_id, sig, rd, ps),
+            &self.signer,
+        );
 
-        let extra_verification_gas = if partial_signers_count > DEFAULT_MINIMUM_THRESHOLD {
-            VERIFICATION_GAS_OVER_MINIMUM_THRESHOLD
-                * (partial_signers_count - DEFAULT_MINIMUM_THRESHOLD)
-        } else {
-            0
-        };
-
-        let extra_add_reward_gas = partial_signers_count * RANDOMNESS_REWARD_GAS;
+        let gas_limit = fulfill_randomness_gas_limit(
+            estimate_fulfill_randomness_gas(
+                task.callback_gas_limit,
+                partial_signatures.len() as u32,
+                threshold as u32,
+            ),
+            self.fulfill_randomness_gas_limit_multiplier,
+        );
 
-        AdapterClient::call_contract_transaction(
+        AdapterClient::call_contract_transaction_with_hook(
             "fulfill_randomness",
-            call.gas(
-                task.callback_gas_limit
-                    + FULFILL_RANDOMNESS_GAS_EXCEPT_CALLBACK
-                    + extra_verification_gas
-                    + extra_add_reward_gas,
-            ),
+            call.gas(gas_limit),
             self.contract_transaction_retry_descriptor,
+            self.transaction_confirmation_descriptor,
+            &self.circuit_breaker,
             false,
+            on_sent.as_ref(),
         )
         .await
     }
 }
 
+/// Scales the gas the request is expected to take by the safety multiplier, rounding up,
+/// so that a callback using close to its `callback_gas_limit` doesn't run out of gas.
+fn fulfill_randomness_gas_limit(expected_gas: u32, multiplier: f64) -> U256 {
+    U256::from((expected_gas as f64 * multiplier).ceil() as u64)
+}
+
 #[async_trait]
 impl AdapterViews for AdapterClient {
     async fn get_last_randomness(&self) -> ContractClientResult<U256> {
@@ -155,6 +174,7 @@ impl AdapterViews for AdapterClient {
             "get_last_randomness",
             adapter_contract.get_last_randomness(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
     }
@@ -169,6 +189,7 @@ impl AdapterViews for AdapterClient {
             "get_pending_request",
             adapter_contract.get_pending_request_commitment(r_id),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
         .map(|r| {
@@ -176,6 +197,33 @@ impl AdapterViews for AdapterClient {
             !r.is_zero()
         })
     }
+
+    async fn get_fulfillment(
+        &self,
+        request_id: &[u8],
+        assignment_block_height: usize,
+    ) -> ContractClientResult<Option<(U256, Address, usize)>> {
+        let r_id = pad_to_bytes32(request_id)
+            .ok_or(ContractClientError::InvalidRequestId(request_id.len()))?;
+
+        let adapter_contract =
+            ServiceClient::<AdapterContract>::prepare_service_client(self).await?;
+
+        // the request id is indexed, so the logs are looked up by topic rather than scanned,
+        // and a request can't be fulfilled before it was assigned
+        let logs = adapter_contract
+            .randomness_request_result_filter()
+            .topic1(H256::from(r_id))
+            .from_block(BlockNumber::from(assignment_block_height as u64))
+            .to_block(BlockNumber::Latest)
+            .query_with_meta()
+            .await?;
+
+        Ok(logs
+            .into_iter()
+            .next()
+            .map(|(log, meta)| (log.randommness, log.committer, meta.block_number.as_usize())))
+    }
 }
 
 #[async_trait]
@@ -185,53 +233,117 @@ impl AdapterLogs for AdapterClient {
         F: Future<Output = ContractClientResult<()>> + Send,
     >(
         &self,
+        filter: RandomnessTaskFilter,
+        from_block: Option<usize>,
+        cancellation_token: CancellationToken,
         mut cb: C,
     ) -> ContractClientResult<()> {
         let contract = Adapter::new(self.adapter_address, self.signer.clone());
 
-        let events: Event<WalletSigner, RandomnessRequestFilter> = contract
-            .event::<RandomnessRequestFilter>()
-            .from_block(BlockNumber::Latest);
+        let build_events = |from_block: BlockNumber| {
+            let mut events: Event<WalletSigner, RandomnessRequestFilter> = contract
+                .event::<RandomnessRequestFilter>()
+                .from_block(from_block);
+
+            // topic1 is the request id, topic2 the subscription id and topic3 the group index
+            if let Some(sub_id) = filter.subscription_id {
+                events = events.topic2(H256::from_low_u64_be(sub_id));
+            }
+
+            if let Some(group_index) = filter.group_index {
+                events = events.topic3(H256::from_low_u64_be(group_index as u64));
+            }
+
+            events
+        };
+
+        // the stream only carries the logs from the moment it's opened, so it's opened before the
+        // earlier logs are queried up to the current block, leaving no gap in between. A log
+        // emitted in between may be received twice, which the caller dedups.
+        let events = build_events(BlockNumber::Latest);
 
         // turn the stream into a stream of events
         let mut stream = events.stream().await?.with_meta();
 
-        while let Some(Ok(evt)) = stream.next().await {
-            let (
-                RandomnessRequestFilter {
-                    request_id,
-                    sub_id,
-                    group_index,
-                    request_type,
-                    params,
-                    sender,
-                    seed,
-                    request_confirmations,
-                    callback_gas_limit,
-                    callback_max_gas_price,
-                    estimated_payment: _,
-                },
-                meta,
-            ) = evt;
-
-            info!( "Received randomness task: group_index: {}, request_id: {}, sender: {:?}, sub_id: {}, seed: {}, request_confirmations: {}, callback_gas_limit: {}, callback_max_gas_price: {}, block_number: {}",
-                group_index, hex::encode(request_id), sender, sub_id, seed, request_confirmations, callback_gas_limit, callback_max_gas_price, meta.block_number);
-
-            let task = RandomnessTask {
-                request_id: request_id.to_vec(),
-                subscription_id: sub_id,
-                group_index,
-                request_type: RandomnessRequestType::from(request_type),
-                params: params.to_vec(),
-                requester: sender,
-                seed,
-                request_confirmations,
-                callback_gas_limit,
-                callback_max_gas_price,
-                assignment_block_height: meta.block_number.as_usize(),
+        if let Some(from_block) = from_block {
+            let to_block = self.signer.provider().get_block_number().await?.as_u64();
+
+            info!(
+                "Replaying randomness tasks from block {} to block {}",
+                from_block, to_block
+            );
+
+            // the logs are queried in pages so that a long replay doesn't exceed the range or
+            // response size the provider allows for a single query
+            let mut page_start = from_block as u64;
+            while page_start <= to_block {
+                let page_end = (page_start + RANDOMNESS_TASK_REPLAY_PAGE_SIZE - 1).min(to_block);
+
+                let logs = build_events(BlockNumber::from(page_start))
+                    .to_block(BlockNumber::from(page_end))
+                    .query_with_meta()
+                    .await?;
+
+                for (evt, meta) in logs {
+                    if cancellation_token.is_cancelled() {
+                        return Ok(());
+                    }
+                    cb(to_randomness_task(evt, meta)).await?;
+                }
+
+                page_start = page_end + 1;
+            }
+        }
+
+        loop {
+            // the subscription only stops between two events, never in the middle of a callback
+            let evt = tokio::select! {
+                evt = stream.next() => evt,
+                _ = cancellation_token.cancelled() => return Ok(()),
+            };
+
+            let Some(Ok((evt, meta))) = evt else {
+                break;
             };
-            cb(task).await?;
+
+            cb(to_randomness_task(evt, meta)).await?;
         }
         Err(ContractClientError::FetchingRandomnessTaskError)
     }
 }
+
+fn to_randomness_task(evt: RandomnessRequestFilter, meta: LogMeta) -> RandomnessTask {
+    let RandomnessRequestFilter {
+        request_id,
+        sub_id,
+        group_index,
+        request_type,
+        params,
+        sender,
+        seed,
+        request_confirmations,
+        callback_gas_limit,
+        callback_max_gas_price,
+        estimated_payment,
+    } = evt;
+
+    info!( "Received randomness task: group_index: {}, request_id: {}, sender: {:?}, sub_id: {}, seed: {}, request_confirmations: {}, callback_gas_limit: {}, callback_max_gas_price: {}, block_number: {}",
+        group_index, hex::encode(request_id), sender, sub_id, seed, request_confirmations, callback_gas_limit, callback_max_gas_price, meta.block_number);
+
+    RandomnessTask {
+        request_id: request_id.to_vec(),
+        subscription_id: sub_id,
+        group_index,
+        request_type: RandomnessRequestType::from(request_type),
+        params: params.to_vec(),
+        requester: sender,
+        seed,
+        request_confirmations,
+        callback_gas_limit,
+        callback_max_gas_price,
+        estimated_payment,
+        assignment_block_height: meta.block_number.as_usize(),
+        // the request event doesn't carry a priority, the listener derives it by the node config
+        priority: RandomnessTaskPriority::Normal,
+    }
+}
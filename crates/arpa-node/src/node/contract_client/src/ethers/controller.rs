@@ -1,17 +1,18 @@
 use crate::{
     contract_stub::controller::{
-        CommitDkgParams, Controller, DkgTaskFilter, Group as ContractGroup,
+        CommitDkgParams, Controller, CoordinatorNotFound, DkgTaskFilter, Group as ContractGroup,
     },
     controller::{
         ControllerClientBuilder, ControllerLogs, ControllerTransactions, ControllerViews,
     },
     error::{ContractClientError, ContractClientResult},
-    ServiceClient,
+    with_signer_transaction_type, ServiceClient,
 };
 use crate::{TransactionCaller, ViewCaller};
 use arpa_node_core::{
-    u256_to_vec, ChainIdentity, DKGTask, ExponentialBackoffRetryDescriptor, GeneralChainIdentity,
-    Group, Member, Node, WalletSigner,
+    u256_to_vec, ChainIdentity, CircuitBreaker, DKGTask, ExponentialBackoffRetryDescriptor,
+    GeneralChainIdentity, Group, Member, Node, TransactionConfirmationDescriptor, WalletSigner,
+    PLACEHOLDER_ADDRESS,
 };
 use async_trait::async_trait;
 use ethers::prelude::*;
@@ -20,12 +21,15 @@ use std::collections::BTreeMap;
 use std::marker::PhantomData;
 use std::{future::Future, sync::Arc};
 use threshold_bls::group::PairingCurve;
+use tokio_util::sync::CancellationToken;
 
 pub struct ControllerClient {
     controller_address: Address,
     signer: Arc<WalletSigner>,
     contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
     contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl ControllerClient {
@@ -40,6 +44,8 @@ impl ControllerClient {
             signer: identity.get_signer(),
             contract_transaction_retry_descriptor,
             contract_view_retry_descriptor,
+            transaction_confirmation_descriptor: identity.get_transaction_confirmation_descriptor(),
+            circuit_breaker: identity.get_circuit_breaker(controller_address),
         }
     }
 }
@@ -80,12 +86,17 @@ impl ControllerTransactions for ControllerClient {
         let controller_contract =
             ServiceClient::<ControllerContract>::prepare_service_client(self).await?;
 
-        let call = controller_contract.node_register(id_public_key.into());
+        let call = with_signer_transaction_type(
+            controller_contract.node_register(id_public_key.into()),
+            &self.signer,
+        );
 
         ControllerClient::call_contract_transaction(
             "node_register",
             call,
             self.contract_transaction_retry_descriptor,
+            self.transaction_confirmation_descriptor,
+            &self.circuit_breaker,
             true,
         )
         .await
@@ -102,18 +113,23 @@ impl ControllerTransactions for ControllerClient {
         let controller_contract =
             ServiceClient::<ControllerContract>::prepare_service_client(self).await?;
 
-        let call = controller_contract.commit_dkg(CommitDkgParams {
-            group_index: group_index.into(),
-            group_epoch: group_epoch.into(),
-            public_key: public_key.into(),
-            partial_public_key: partial_public_key.into(),
-            disqualified_nodes,
-        });
+        let call = with_signer_transaction_type(
+            controller_contract.commit_dkg(CommitDkgParams {
+                group_index: group_index.into(),
+                group_epoch: group_epoch.into(),
+                public_key: public_key.into(),
+                partial_public_key: partial_public_key.into(),
+                disqualified_nodes,
+            }),
+            &self.signer,
+        );
 
         ControllerClient::call_contract_transaction(
             "commit_dkg",
             call,
             self.contract_transaction_retry_descriptor,
+            self.transaction_confirmation_descriptor,
+            &self.circuit_breaker,
             true,
         )
         .await
@@ -127,15 +143,50 @@ impl ControllerTransactions for ControllerClient {
         let controller_contract =
             ServiceClient::<ControllerContract>::prepare_service_client(self).await?;
 
-        let call = controller_contract.post_process_dkg(group_index.into(), group_epoch.into());
+        // the coordinator is removed once the group has been post-processed
+        let coordinator = ControllerClient::call_contract_view(
+            "get_coordinator",
+            controller_contract.get_coordinator(group_index.into()),
+            self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
+        )
+        .await?;
 
-        ControllerClient::call_contract_transaction(
+        if coordinator == PLACEHOLDER_ADDRESS {
+            info!(
+                "group {} epoch {} has already been post-processed, skip post_process_dkg",
+                group_index, group_epoch
+            );
+            return Ok(H256::zero());
+        }
+
+        let call = with_signer_transaction_type(
+            controller_contract.post_process_dkg(group_index.into(), group_epoch.into()),
+            &self.signer,
+        );
+
+        match ControllerClient::call_contract_transaction(
             "post_process_dkg",
             call,
             self.contract_transaction_retry_descriptor,
+            self.transaction_confirmation_descriptor,
+            &self.circuit_breaker,
             false,
         )
         .await
+        {
+            // another member got there first between the check and the call
+            Err(ContractClientError::ContractError(e))
+                if e.decode_revert::<CoordinatorNotFound>().is_some() =>
+            {
+                info!(
+                    "group {} epoch {} has already been post-processed by others",
+                    group_index, group_epoch
+                );
+                Ok(H256::zero())
+            }
+            res => res,
+        }
     }
 }
 
@@ -149,11 +200,36 @@ impl<C: PairingCurve> ControllerViews<C> for ControllerClient {
             "get_group",
             controller_contract.get_group(group_index.into()),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
         .map(parse_contract_group)
     }
 
+    async fn get_belonging_group(
+        &self,
+        id_address: Address,
+    ) -> ContractClientResult<Option<(usize, usize)>> {
+        let controller_contract =
+            ServiceClient::<ControllerContract>::prepare_service_client(self).await?;
+
+        ControllerClient::call_contract_view(
+            "get_belonging_group",
+            controller_contract.get_belonging_group(id_address),
+            self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
+        )
+        .await
+        .map(|(group_index, member_index)| {
+            // -1 is returned when the node doesn't belong to any group
+            if group_index.is_negative() || member_index.is_negative() {
+                None
+            } else {
+                Some((group_index.as_usize(), member_index.as_usize()))
+            }
+        })
+    }
+
     async fn get_node(&self, id_address: Address) -> ContractClientResult<Node> {
         let controller_contract =
             ServiceClient::<ControllerContract>::prepare_service_client(self).await?;
@@ -162,6 +238,7 @@ impl<C: PairingCurve> ControllerViews<C> for ControllerClient {
             "get_node",
             controller_contract.get_node(id_address),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
         .map(|n| Node {
@@ -180,6 +257,7 @@ impl<C: PairingCurve> ControllerViews<C> for ControllerClient {
             "get_coordinator",
             controller_contract.get_coordinator(group_index.into()),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
     }
@@ -192,6 +270,7 @@ impl ControllerLogs for ControllerClient {
         F: Future<Output = ContractClientResult<()>> + Send,
     >(
         &self,
+        cancellation_token: CancellationToken,
         mut cb: C,
     ) -> ContractClientResult<()> {
         let contract = Controller::new(self.controller_address, self.signer.clone());
@@ -203,7 +282,17 @@ impl ControllerLogs for ControllerClient {
         // turn the stream into a stream of events
         let mut stream = events.stream().await?.with_meta();
 
-        while let Some(Ok(evt)) = stream.next().await {
+        loop {
+            // the subscription only stops between two events, never in the middle of a callback
+            let evt = tokio::select! {
+                evt = stream.next() => evt,
+                _ = cancellation_token.cancelled() => return Ok(()),
+            };
+
+            let Some(Ok(evt)) = evt else {
+                break;
+            };
+
             let (
                 DkgTaskFilter {
                     global_epoch: _,
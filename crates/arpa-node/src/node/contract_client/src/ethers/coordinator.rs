@@ -1,29 +1,41 @@
 use crate::{
     contract_stub::coordinator::Coordinator,
     coordinator::{
-        CoordinatorClientBuilder, CoordinatorTransactions, CoordinatorViews, DKGContractError,
+        to_payloads, CoordinatorClientBuilder, CoordinatorTransactions, CoordinatorViews,
+        DKGContractError, DKGPayloads, DKGState,
     },
-    error::ContractClientResult,
-    ServiceClient, TransactionCaller, ViewCaller,
+    error::{ContractClientError, ContractClientResult},
+    with_signer_transaction_type, ServiceClient, TransactionCaller, ViewCaller,
 };
 use arpa_node_core::{
-    ChainIdentity, ExponentialBackoffRetryDescriptor, GeneralChainIdentity, WalletSigner,
+    ChainIdentity, CircuitBreaker, ExponentialBackoffRetryDescriptor, GeneralChainIdentity,
+    TransactionConfirmationDescriptor, WalletSigner,
 };
 use async_trait::async_trait;
 use dkg_core::{
     primitives::{BundledJustification, BundledResponses, BundledShares},
     BoardPublisher,
 };
-use ethers::prelude::*;
-use log::info;
+use ethers::{
+    abi::{self, ParamType, Token},
+    prelude::*,
+};
+use log::{debug, info};
 use std::sync::Arc;
 use threshold_bls::group::Curve;
 
+/// Number of phases the coordinator runs through before the DKG ends,
+/// the last one being reserved for committing the DKG result.
+const DKG_PHASE_COUNT: usize = 4;
+
 pub struct CoordinatorClient {
     coordinator_address: Address,
     signer: Arc<WalletSigner>,
     contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
     contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+    circuit_breaker: Arc<CircuitBreaker>,
+    max_group_size: usize,
 }
 
 impl CoordinatorClient {
@@ -38,14 +50,20 @@ impl CoordinatorClient {
             signer: identity.get_signer(),
             contract_transaction_retry_descriptor,
             contract_view_retry_descriptor,
+            transaction_confirmation_descriptor: identity.get_transaction_confirmation_descriptor(),
+            circuit_breaker: identity.get_circuit_breaker(coordinator_address),
+            max_group_size: identity.get_max_group_size(),
         }
     }
 }
 
-impl<C: Curve + 'static> CoordinatorClientBuilder<C> for GeneralChainIdentity {
-    type Service = CoordinatorClient;
+impl CoordinatorClientBuilder for GeneralChainIdentity {
+    type Service<C: Curve + 'static> = CoordinatorClient;
 
-    fn build_coordinator_client(&self, contract_address: Address) -> CoordinatorClient {
+    fn build_coordinator_client<C: Curve + 'static>(
+        &self,
+        contract_address: Address,
+    ) -> CoordinatorClient {
         CoordinatorClient::new(
             contract_address,
             self,
@@ -55,6 +73,49 @@ impl<C: Curve + 'static> CoordinatorClientBuilder<C> for GeneralChainIdentity {
     }
 }
 
+const SHARE_EXISTED_REVERT_REASON: &str = "share existed";
+
+impl CoordinatorClient {
+    async fn has_published_shares(&self) -> ContractClientResult<bool> {
+        let id_address = self.signer.address();
+
+        let participants = self.get_participants().await?;
+
+        let shares = self.get_shares().await?;
+
+        Ok(participants
+            .iter()
+            .position(|participant| *participant == id_address)
+            .and_then(|index| shares.get(index))
+            .map_or(false, |share| share.is_some()))
+    }
+
+    /// Rejects a pathologically large list of participants before the DKG works through it.
+    fn check_participants(&self, participants: Vec<Address>) -> ContractClientResult<Vec<Address>> {
+        if participants.len() > self.max_group_size {
+            return Err(ContractClientError::GroupTooLarge {
+                size: participants.len(),
+                max_group_size: self.max_group_size,
+            });
+        }
+
+        Ok(participants)
+    }
+}
+
+fn is_share_existed_revert(e: &ContractClientError) -> bool {
+    match e {
+        ContractClientError::ContractError(ContractError::Revert(data)) if data.len() > 4 => {
+            // revert data of require is the selector of Error(string) followed by the reason
+            matches!(
+                abi::decode(&[ParamType::String], &data[4..]).as_deref(),
+                Ok([Token::String(reason)]) if reason == SHARE_EXISTED_REVERT_REASON
+            )
+        }
+        e => e.to_string().contains(SHARE_EXISTED_REVERT_REASON),
+    }
+}
+
 type CoordinatorContract = Coordinator<WalletSigner>;
 
 #[async_trait]
@@ -78,12 +139,15 @@ impl CoordinatorTransactions for CoordinatorClient {
         let coordinator_contract =
             ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
 
-        let call = coordinator_contract.publish(value.into());
+        let call =
+            with_signer_transaction_type(coordinator_contract.publish(value.into()), &self.signer);
 
         CoordinatorClient::call_contract_transaction(
             "publish",
             call,
             self.contract_transaction_retry_descriptor,
+            self.transaction_confirmation_descriptor,
+            &self.circuit_breaker,
             false,
         )
         .await
@@ -92,7 +156,7 @@ impl CoordinatorTransactions for CoordinatorClient {
 
 #[async_trait]
 impl CoordinatorViews for CoordinatorClient {
-    async fn get_shares(&self) -> ContractClientResult<Vec<Vec<u8>>> {
+    async fn get_shares(&self) -> ContractClientResult<DKGPayloads> {
         let coordinator_contract =
             ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
 
@@ -100,12 +164,13 @@ impl CoordinatorViews for CoordinatorClient {
             "get_shares",
             coordinator_contract.get_shares(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
-        .map(|r| r.iter().map(|b| b.to_vec()).collect::<Vec<Vec<u8>>>())
+        .map(|r| to_payloads(&r))
     }
 
-    async fn get_responses(&self) -> ContractClientResult<Vec<Vec<u8>>> {
+    async fn get_responses(&self) -> ContractClientResult<DKGPayloads> {
         let coordinator_contract =
             ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
 
@@ -113,12 +178,13 @@ impl CoordinatorViews for CoordinatorClient {
             "get_responses",
             coordinator_contract.get_responses(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
-        .map(|r| r.iter().map(|b| b.to_vec()).collect::<Vec<Vec<u8>>>())
+        .map(|r| to_payloads(&r))
     }
 
-    async fn get_justifications(&self) -> ContractClientResult<Vec<Vec<u8>>> {
+    async fn get_justifications(&self) -> ContractClientResult<DKGPayloads> {
         let coordinator_contract =
             ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
 
@@ -126,9 +192,10 @@ impl CoordinatorViews for CoordinatorClient {
             "get_justifications",
             coordinator_contract.get_justifications(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
-        .map(|r| r.iter().map(|b| b.to_vec()).collect::<Vec<Vec<u8>>>())
+        .map(|r| to_payloads(&r))
     }
 
     async fn get_participants(&self) -> ContractClientResult<Vec<Address>> {
@@ -139,8 +206,42 @@ impl CoordinatorViews for CoordinatorClient {
             "get_participants",
             coordinator_contract.get_participants(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
+        .and_then(|participants| self.check_participants(participants))
+    }
+
+    async fn get_participants_paged(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> ContractClientResult<Vec<Address>> {
+        if offset.saturating_add(limit) > self.max_group_size {
+            return Err(ContractClientError::GroupTooLarge {
+                size: offset.saturating_add(limit),
+                max_group_size: self.max_group_size,
+            });
+        }
+
+        let coordinator_contract =
+            ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
+
+        let mut participants = Vec::with_capacity(limit);
+
+        for index in offset..offset + limit {
+            participants.push(
+                CoordinatorClient::call_contract_view(
+                    "participants",
+                    coordinator_contract.participants(index.into()),
+                    self.contract_view_retry_descriptor,
+                    &self.circuit_breaker,
+                )
+                .await?,
+            );
+        }
+
+        Ok(participants)
     }
 
     async fn get_dkg_keys(&self) -> ContractClientResult<(usize, Vec<Vec<u8>>)> {
@@ -151,6 +252,7 @@ impl CoordinatorViews for CoordinatorClient {
             "get_dkg_keys",
             coordinator_contract.get_dkg_keys(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
         .map(|(t, keys)| {
@@ -169,9 +271,94 @@ impl CoordinatorViews for CoordinatorClient {
             "in_phase",
             coordinator_contract.in_phase(),
             self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
         )
         .await
     }
+
+    async fn get_dkg_state(&self) -> ContractClientResult<DKGState> {
+        let coordinator_contract =
+            ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
+
+        let mut multicall = match Multicall::new(self.signer.clone(), None).await {
+            Ok(multicall) => multicall,
+            Err(MulticallError::InvalidChainId) => {
+                debug!(
+                    "no known Multicall deployment on the chain, fetching the dkg state one by one"
+                );
+
+                return Ok((
+                    self.get_participants().await?,
+                    self.get_shares().await?,
+                    self.get_responses().await?,
+                    self.get_justifications().await?,
+                    self.in_phase().await?,
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        multicall
+            .add_call(coordinator_contract.get_participants(), false)
+            .add_call(coordinator_contract.get_shares(), false)
+            .add_call(coordinator_contract.get_responses(), false)
+            .add_call(coordinator_contract.get_justifications(), false)
+            .add_call(coordinator_contract.in_phase(), false);
+
+        CoordinatorClient::call_contract_multicall_view::<(
+            Vec<Address>,
+            Vec<Bytes>,
+            Vec<Bytes>,
+            Vec<Bytes>,
+            i8,
+        )>(
+            "get_dkg_state",
+            multicall,
+            self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
+        )
+        .await
+        .and_then(|(participants, shares, responses, justifications, phase)| {
+            Ok((
+                self.check_participants(participants)?,
+                to_payloads(&shares),
+                to_payloads(&responses),
+                to_payloads(&justifications),
+                phase,
+            ))
+        })
+    }
+
+    async fn get_phase_deadlines(&self) -> ContractClientResult<Vec<usize>> {
+        let coordinator_contract =
+            ServiceClient::<CoordinatorContract>::prepare_service_client(self).await?;
+
+        let start_block = CoordinatorClient::call_contract_view(
+            "start_block",
+            coordinator_contract.start_block(),
+            self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
+        )
+        .await?
+        .as_usize();
+
+        if start_block == 0 {
+            return Ok(vec![]);
+        }
+
+        let phase_duration = CoordinatorClient::call_contract_view(
+            "phase_duration",
+            coordinator_contract.phase_duration(),
+            self.contract_view_retry_descriptor,
+            &self.circuit_breaker,
+        )
+        .await?
+        .as_usize();
+
+        Ok((1..=DKG_PHASE_COUNT)
+            .map(|phase| start_block + phase * phase_duration)
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -181,8 +368,19 @@ impl<C: Curve + 'static> BoardPublisher<C> for CoordinatorClient {
     async fn publish_shares(&mut self, shares: BundledShares<C>) -> Result<(), Self::Error> {
         info!("called publish_shares");
         let serialized = bincode::serialize(&shares)?;
-        self.publish(serialized).await?;
-        Ok(())
+        match self.publish(serialized).await {
+            Ok(_) => Ok(()),
+            // a duplicate submission, e.g. after a reorg, reverts even though our shares are on-chain
+            Err(e) if is_share_existed_revert(&e) => {
+                if self.has_published_shares().await? {
+                    info!("shares have already been published, treated as success");
+                    Ok(())
+                } else {
+                    Err(e.into())
+                }
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 
     async fn publish_responses(&mut self, responses: BundledResponses) -> Result<(), Self::Error> {
@@ -219,6 +417,106 @@ pub mod coordinator_tests {
     use std::{convert::TryFrom, sync::Arc, time::Duration};
     use threshold_bls::schemes::bn254::G2Scheme;
 
+    #[test]
+    fn test_decode_payloads() {
+        use crate::coordinator::decode_payloads;
+        use dkg_core::primitives::{BundledResponses, Response};
+
+        let responses = BundledResponses {
+            share_idx: 1,
+            responses: vec![Response {
+                dealer_idx: 0,
+                status: false.into(),
+            }],
+        };
+
+        let payloads = vec![
+            Some(bincode::serialize(&responses).unwrap()),
+            None,
+            Some(vec![0xff]),
+        ];
+
+        let decoded = decode_payloads::<BundledResponses>(&payloads);
+
+        assert_eq!(decoded.len(), 3);
+        assert_eq!(decoded[0].as_ref().unwrap().share_idx, 1);
+        assert!(decoded[1].is_none());
+        assert!(decoded[2].is_none());
+    }
+
+    #[test]
+    fn test_decode_sparse_shares() {
+        use crate::coordinator::{decode_payloads, to_payloads};
+        use dkg_core::primitives::BundledShares;
+        use threshold_bls::schemes::bn254::G2Curve;
+
+        let shares = BundledShares::<G2Curve> {
+            dealer_idx: 2,
+            shares: vec![],
+            public: vec![].into(),
+        };
+
+        // participants 0 and 3 haven't published, participant 1 has published a `0` element
+        let elements = vec![
+            Bytes::new(),
+            Bytes::from(vec![0]),
+            Bytes::from(bincode::serialize(&shares).unwrap()),
+            Bytes::new(),
+        ];
+
+        let payloads = to_payloads(&elements);
+
+        assert_eq!(payloads.len(), 4);
+        assert_eq!(
+            payloads.iter().map(Option::is_some).collect::<Vec<_>>(),
+            vec![false, false, true, false]
+        );
+
+        let decoded = decode_payloads::<BundledShares<G2Curve>>(&payloads);
+
+        assert_eq!(decoded.len(), 4);
+        assert_eq!(decoded[2].as_ref().unwrap().dealer_idx, 2);
+        assert!(decoded
+            .iter()
+            .enumerate()
+            .all(|(i, s)| s.is_some() == (i == 2)));
+    }
+
+    #[test]
+    fn test_is_share_existed_revert() {
+        use super::is_share_existed_revert;
+        use crate::error::ContractClientError;
+        use ethers::abi::{encode, Token};
+        use ethers::prelude::ContractError;
+
+        let revert = |reason: &str| {
+            let mut data = vec![0x08, 0xc3, 0x79, 0xa0];
+            data.extend(encode(&[Token::String(reason.to_string())]));
+            ContractClientError::ContractError(ContractError::Revert(data.into()))
+        };
+
+        assert!(is_share_existed_revert(&revert("share existed")));
+        assert!(!is_share_existed_revert(&revert("DKG Publish has ended")));
+        assert!(!is_share_existed_revert(
+            &ContractClientError::TransactionFailed
+        ));
+    }
+
+    #[test]
+    fn test_is_response_size_error() {
+        use crate::error::{is_response_size_error, ContractClientError};
+        use ethers::providers::ProviderError;
+
+        assert!(is_response_size_error(
+            &ContractClientError::ChainProviderError(ProviderError::CustomError(
+                "Response size exceeded the limit of 10485760 bytes".to_string()
+            ))
+        ));
+        assert!(!is_response_size_error(
+            &ContractClientError::TransactionFailed
+        ));
+    }
+
     #[test]
     fn test_cargo_manifest_parent_dir() {
         let dir = env!("CARGO_MANIFEST_DIR");
@@ -308,6 +606,12 @@ pub mod coordinator_tests {
                 .unwrap()
                 .contract_transaction_retry_descriptor,
             config.time_limits.unwrap().contract_view_retry_descriptor,
+            config
+                .time_limits
+                .unwrap()
+                .get_transaction_confirmation_descriptor(),
+            config.time_limits.unwrap().circuit_breaker_descriptor,
+            config.fulfill_randomness_gas_limit_multiplier.unwrap(),
         );
 
         let client = CoordinatorClient::new(
@@ -1,3 +1,4 @@
+pub mod abi_version;
 pub mod adapter;
 pub mod controller;
 pub mod coordinator;
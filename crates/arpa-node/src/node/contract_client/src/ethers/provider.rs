@@ -1,21 +1,32 @@
 use crate::{
     error::{ContractClientError, ContractClientResult},
-    provider::{BlockFetcher, ChainProviderBuilder},
+    provider::{
+        BlockFetcher, ChainProviderBuilder, TransactionReceiptFetcher, TransactionReplacer,
+    },
+};
+use arpa_node_core::{
+    metrics::BLOCK_LISTENER_LAG, ChainIdentity, GeneralChainIdentity, WalletSigner,
 };
-use arpa_node_core::{ChainIdentity, GeneralChainIdentity};
 use async_trait::async_trait;
 use ethers::prelude::*;
 use ethers::providers::Http as HttpProvider;
-use std::{future::Future, sync::Arc};
+use log::info;
+use std::{collections::VecDeque, future::Future, sync::Arc, time::Duration};
+use tokio_util::sync::CancellationToken;
+
+// gas of a plain transfer, which is all the replacement transaction takes
+const TRANSFER_GAS: u64 = 21000;
 
 pub struct ChainProvider {
     provider: Arc<Provider<HttpProvider>>,
+    signer: Arc<WalletSigner>,
 }
 
 impl ChainProvider {
     pub fn new(identity: &GeneralChainIdentity) -> Self {
         ChainProvider {
             provider: identity.get_provider(),
+            signer: identity.get_signer(),
         }
     }
 }
@@ -35,10 +46,34 @@ impl BlockFetcher for ChainProvider {
         F: Future<Output = ContractClientResult<()>> + Send,
     >(
         &self,
+        cancellation_token: CancellationToken,
         mut cb: C,
     ) -> ContractClientResult<()> {
         let mut stream = self.provider.watch_blocks().await?;
-        while let Some(block_hash) = stream.next().await {
+        // the blocks notified but not handled yet, i.e. how far the listener lags behind the head
+        let mut pending_block_hashes = VecDeque::new();
+        loop {
+            // the subscription only stops between two events, never in the middle of a callback
+            let block_hash = match pending_block_hashes.pop_front() {
+                Some(block_hash) => Some(block_hash),
+                None => tokio::select! {
+                    block_hash = stream.next() => block_hash,
+                    _ = cancellation_token.cancelled() => return Ok(()),
+                },
+            };
+
+            let Some(block_hash) = block_hash else {
+                break;
+            };
+
+            // takes the hashes the watcher already holds without waiting for the next poll
+            while let Ok(Some(next_block_hash)) =
+                tokio::time::timeout(Duration::ZERO, stream.next()).await
+            {
+                pending_block_hashes.push_back(next_block_hash);
+            }
+            BLOCK_LISTENER_LAG.set(pending_block_hashes.len() as i64);
+
             let block = self
                 .provider
                 .get_block(block_hash)
@@ -53,3 +88,111 @@ impl BlockFetcher for ChainProvider {
         Err(ContractClientError::FetchingBlockError)
     }
 }
+
+#[async_trait]
+impl TransactionReplacer for ChainProvider {
+    async fn replace_stuck_transaction(
+        &self,
+        nonce: U256,
+        new_fee_gwei: u64,
+    ) -> ContractClientResult<H256> {
+        let address = self.signer.address();
+
+        let mined_nonce = self
+            .provider
+            .get_transaction_count(address, Some(BlockNumber::Latest.into()))
+            .await?;
+
+        let pending_nonce = self
+            .provider
+            .get_transaction_count(address, Some(BlockNumber::Pending.into()))
+            .await?;
+
+        let tx = build_replacement_transaction(
+            address,
+            nonce,
+            mined_nonce,
+            pending_nonce,
+            new_fee_gwei,
+        )?;
+
+        let gas_price = tx.gas_price.unwrap_or_default();
+
+        let pending_tx = self
+            .signer
+            .send_transaction(tx, None)
+            .await
+            .map_err(ContractError::<WalletSigner>::MiddlewareError)?;
+
+        let tx_hash = pending_tx.tx_hash();
+
+        info!(
+            "Replacement of the pending transaction with nonce {} sent, gas_price: {}, tx_hash: {:?}",
+            nonce, gas_price, tx_hash
+        );
+
+        Ok(tx_hash)
+    }
+}
+
+#[async_trait]
+impl TransactionReceiptFetcher for ChainProvider {
+    async fn get_transaction_status(&self, tx_hash: H256) -> ContractClientResult<Option<bool>> {
+        let receipt = self.provider.get_transaction_receipt(tx_hash).await?;
+
+        Ok(receipt.map(|receipt| receipt.status == Some(U64::one())))
+    }
+}
+
+/// Builds an empty transfer from `address` to itself at `nonce`, which has to be taken by a
+/// transaction sent but not mined yet, i.e. lie between the mined and the pending nonce.
+fn build_replacement_transaction(
+    address: Address,
+    nonce: U256,
+    mined_nonce: U256,
+    pending_nonce: U256,
+    new_fee_gwei: u64,
+) -> ContractClientResult<TransactionRequest> {
+    if nonce < mined_nonce || nonce >= pending_nonce {
+        return Err(ContractClientError::NoPendingTransaction(nonce));
+    }
+
+    let gas_price = U256::from(new_fee_gwei) * U256::exp10(9);
+
+    Ok(TransactionRequest::new()
+        .from(address)
+        .to(address)
+        .value(0)
+        .nonce(nonce)
+        .gas(TRANSFER_GAS)
+        .gas_price(gas_price))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{build_replacement_transaction, TRANSFER_GAS};
+    use crate::error::ContractClientError;
+    use ethers::types::{Address, NameOrAddress, U256};
+
+    #[test]
+    fn test_build_replacement_transaction() {
+        let address = Address::random();
+
+        // the nonces 3 and 4 are taken by the pending transactions
+        let tx = build_replacement_transaction(address, 4.into(), 3.into(), 5.into(), 20).unwrap();
+        assert_eq!(tx.from, Some(address));
+        assert_eq!(tx.to, Some(NameOrAddress::Address(address)));
+        assert_eq!(tx.value, Some(U256::zero()));
+        assert_eq!(tx.nonce, Some(4.into()));
+        assert_eq!(tx.gas, Some(TRANSFER_GAS.into()));
+        assert_eq!(tx.gas_price, Some(U256::from(20_000_000_000u64)));
+
+        // the transaction at nonce 2 is mined already, and none is sent at nonce 5 yet
+        for nonce in [2, 5] {
+            assert!(matches!(
+                build_replacement_transaction(address, nonce.into(), 3.into(), 5.into(), 20),
+                Err(ContractClientError::NoPendingTransaction(n)) if n == nonce.into()
+            ));
+        }
+    }
+}
@@ -1,46 +1,108 @@
 use crate::error::ContractClientError;
 use ::ethers::abi::Detokenize;
+use ::ethers::prelude::{ContractError, Multicall};
 use ::ethers::types::U64;
 use ::ethers::{prelude::builders::ContractCall, types::H256};
-use arpa_node_core::{jitter, ExponentialBackoffRetryDescriptor, WalletSigner};
+use arpa_node_core::{
+    jitter, metrics::TRANSACTION_RETRIES, CircuitBreaker, ExponentialBackoffRetryDescriptor,
+    TransactionConfirmationDescriptor, WalletSigner,
+};
 use async_trait::async_trait;
 use error::ContractClientResult;
 use log::{error, info};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use tokio_retry::strategy::ExponentialBackoff;
 use tokio_retry::{Retry, RetryIf};
 
 pub mod contract_stub;
 pub mod error;
 pub mod ethers;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
 
 #[async_trait]
 pub trait ServiceClient<C> {
     async fn prepare_service_client(&self) -> ContractClientResult<C>;
 }
 
+/// Switches the call to a legacy transaction if the signer has EIP-155 disabled,
+/// since the chains without replay protection don't know typed transactions either.
+pub fn with_signer_transaction_type<D>(
+    call: ContractCall<WalletSigner, D>,
+    signer: &WalletSigner,
+) -> ContractCall<WalletSigner, D> {
+    if signer.signer().is_eip155() {
+        call
+    } else {
+        call.legacy()
+    }
+}
+
+/// Called with the hash of every attempt of a transaction as soon as it is sent, before its
+/// receipt is waited for, e.g. to persist it in case the node stops meanwhile.
+pub type TransactionSentHook =
+    Box<dyn Fn(H256) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
 #[async_trait]
 pub trait TransactionCaller {
     async fn call_contract_transaction<D: Detokenize + std::fmt::Debug + Send + Sync + 'static>(
         info: &str,
         call: ContractCall<WalletSigner, D>,
         contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+        circuit_breaker: &CircuitBreaker,
+        retry_on_transaction_fail: bool,
+    ) -> ContractClientResult<H256> {
+        Self::call_contract_transaction_with_hook(
+            info,
+            call,
+            contract_transaction_retry_descriptor,
+            transaction_confirmation_descriptor,
+            circuit_breaker,
+            retry_on_transaction_fail,
+            None,
+        )
+        .await
+    }
+
+    async fn call_contract_transaction_with_hook<
+        D: Detokenize + std::fmt::Debug + Send + Sync + 'static,
+    >(
+        info: &str,
+        call: ContractCall<WalletSigner, D>,
+        contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+        circuit_breaker: &CircuitBreaker,
         retry_on_transaction_fail: bool,
+        on_sent: Option<&TransactionSentHook>,
     ) -> ContractClientResult<H256> {
+        acquire_circuit_breaker(circuit_breaker)?;
+
         let retry_strategy =
             ExponentialBackoff::from_millis(contract_transaction_retry_descriptor.base)
                 .factor(contract_transaction_retry_descriptor.factor)
+                .max_delay(contract_transaction_retry_descriptor.get_max_interval())
                 .map(|e| {
                     if contract_transaction_retry_descriptor.use_jitter {
-                        jitter(e)
+                        jitter(e, contract_transaction_retry_descriptor.jitter_ratio)
                     } else {
                         e
                     }
                 })
                 .take(contract_transaction_retry_descriptor.max_attempts);
 
-        let transaction_hash = RetryIf::spawn(
+        let attempts = AtomicUsize::new(0);
+
+        let res = RetryIf::spawn(
             retry_strategy,
             || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) > 0 {
+                    TRANSACTION_RETRIES.with_label_values(&[info]).inc();
+                }
+
                 let pending_tx = call.send().await.map_err(|e| {
                     let e: ContractClientError = e.into();
                     e
@@ -52,7 +114,15 @@ pub trait TransactionCaller {
                     pending_tx.tx_hash()
                 );
 
+                if let Some(on_sent) = on_sent {
+                    on_sent(pending_tx.tx_hash()).await;
+                }
+
                 let receipt = pending_tx
+                    .interval(Duration::from_millis(
+                        transaction_confirmation_descriptor.polling_interval_millis,
+                    ))
+                    .confirmations(transaction_confirmation_descriptor.confirmations)
                     .await
                     .map_err(|e| {
                         let e: ContractClientError = e.into();
@@ -73,9 +143,11 @@ pub trait TransactionCaller {
                 retry_on_transaction_fail || !matches!(e, ContractClientError::TransactionFailed)
             },
         )
-        .await?;
+        .await;
 
-        Ok(transaction_hash)
+        record_call_outcome(circuit_breaker, &res);
+
+        res
     }
 }
 
@@ -85,12 +157,16 @@ pub trait ViewCaller {
         info: &str,
         call: ContractCall<WalletSigner, D>,
         contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        circuit_breaker: &CircuitBreaker,
     ) -> ContractClientResult<D> {
+        acquire_circuit_breaker(circuit_breaker)?;
+
         let retry_strategy = ExponentialBackoff::from_millis(contract_view_retry_descriptor.base)
             .factor(contract_view_retry_descriptor.factor)
+            .max_delay(contract_view_retry_descriptor.get_max_interval())
             .map(|e| {
                 if contract_view_retry_descriptor.use_jitter {
-                    jitter(e)
+                    jitter(e, contract_view_retry_descriptor.jitter_ratio)
                 } else {
                     e
                 }
@@ -107,9 +183,73 @@ pub trait ViewCaller {
 
             Result::<D, ContractClientError>::Ok(result)
         })
-        .await?;
+        .await;
+
+        record_call_outcome(circuit_breaker, &res);
 
-        Ok(res)
+        res
+    }
+
+    /// Same as `call_contract_view` but fetches all the views aggregated in the multicall
+    /// in a single RPC round-trip.
+    async fn call_contract_multicall_view<
+        D: Detokenize + std::fmt::Debug + Send + Sync + 'static,
+    >(
+        info: &str,
+        multicall: Multicall<WalletSigner>,
+        contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        circuit_breaker: &CircuitBreaker,
+    ) -> ContractClientResult<D> {
+        acquire_circuit_breaker(circuit_breaker)?;
+
+        let retry_strategy = ExponentialBackoff::from_millis(contract_view_retry_descriptor.base)
+            .factor(contract_view_retry_descriptor.factor)
+            .max_delay(contract_view_retry_descriptor.get_max_interval())
+            .map(|e| {
+                if contract_view_retry_descriptor.use_jitter {
+                    jitter(e, contract_view_retry_descriptor.jitter_ratio)
+                } else {
+                    e
+                }
+            })
+            .take(contract_view_retry_descriptor.max_attempts);
+
+        let res = Retry::spawn(retry_strategy, || async {
+            let result = multicall.call::<D>().await.map_err(|e| {
+                let e: ContractClientError = e.into();
+                e
+            })?;
+
+            info!("Calling contract multicall view {}: {:?}", info, result);
+
+            Result::<D, ContractClientError>::Ok(result)
+        })
+        .await;
+
+        record_call_outcome(circuit_breaker, &res);
+
+        res
+    }
+}
+
+fn acquire_circuit_breaker(circuit_breaker: &CircuitBreaker) -> ContractClientResult<()> {
+    if circuit_breaker.try_acquire() {
+        Ok(())
+    } else {
+        Err(ContractClientError::CircuitOpen(
+            circuit_breaker.get_contract_address(),
+        ))
+    }
+}
+
+/// A call that reverts has still reached the contract, so only the other errors,
+/// after all the retries, count as failures of the contract endpoint.
+fn record_call_outcome<T>(circuit_breaker: &CircuitBreaker, result: &ContractClientResult<T>) {
+    match result {
+        Err(ContractClientError::TransactionFailed)
+        | Err(ContractClientError::ContractError(ContractError::Revert(_)))
+        | Ok(_) => circuit_breaker.record_success(),
+        Err(_) => circuit_breaker.record_failure(),
     }
 }
 
@@ -121,6 +261,7 @@ pub mod controller {
     use ethers::types::H256;
     use std::future::Future;
     use threshold_bls::group::PairingCurve;
+    use tokio_util::sync::CancellationToken;
 
     #[async_trait]
     pub trait ControllerTransactions {
@@ -135,6 +276,9 @@ pub mod controller {
             disqualified_nodes: Vec<Address>,
         ) -> ContractClientResult<H256>;
 
+        /// Finalizes the grouping of the given group and epoch. It is idempotent: if the group
+        /// has already been post-processed, by this node or another member, nothing is sent
+        /// and a zero transaction hash is returned.
         async fn post_process_dkg(
             &self,
             group_index: usize,
@@ -148,16 +292,26 @@ pub mod controller {
 
         async fn get_group(&self, group_index: usize) -> ContractClientResult<Group<C>>;
 
+        /// Returns the index of the group the node belongs to and its index in the group,
+        /// or None if the node is not a member of any group
+        async fn get_belonging_group(
+            &self,
+            id_address: Address,
+        ) -> ContractClientResult<Option<(usize, usize)>>;
+
         async fn get_coordinator(&self, group_index: usize) -> ContractClientResult<Address>;
     }
 
     #[async_trait]
     pub trait ControllerLogs {
+        /// Subscribes to the DKG tasks until the stream errors, or returns `Ok` once the
+        /// cancellation token is cancelled.
         async fn subscribe_dkg_task<
             C: FnMut(DKGTask) -> F + Send,
             F: Future<Output = ContractClientResult<()>> + Send,
         >(
             &self,
+            cancellation_token: CancellationToken,
             cb: C,
         ) -> ContractClientResult<()>;
     }
@@ -171,11 +325,16 @@ pub mod controller {
 
 pub mod coordinator {
     use async_trait::async_trait;
-    use dkg_core::BoardPublisher;
+    use dkg_core::{
+        primitives::{BundledJustification, BundledResponses, BundledShares},
+        BoardPublisher,
+    };
     use ethers::core::types::Address;
     use ethers::types::H256;
+    use log::warn;
+    use serde::de::DeserializeOwned;
     use thiserror::Error;
-    use threshold_bls::{group::Curve, schemes::bn254::G2Curve};
+    use threshold_bls::group::Curve;
 
     use crate::error::{ContractClientError, ContractClientResult};
 
@@ -195,54 +354,149 @@ pub mod coordinator {
         async fn publish(&self, value: Vec<u8>) -> ContractClientResult<H256>;
     }
 
+    /// The payload each participant published for a phase, in the order of the participants.
+    /// A participant that has not published is `None`.
+    pub type DKGPayloads = Vec<Option<Vec<u8>>>;
+
+    /// The participants along with their published shares, responses and justifications,
+    /// and the current phase of the DKG.
+    pub type DKGState = (Vec<Address>, DKGPayloads, DKGPayloads, DKGPayloads, i8);
+
     #[async_trait]
     pub trait CoordinatorViews {
         // Helpers to fetch data in the mappings. If a participant has registered but not
-        // published their data for a phase, the array element at their index is expected to be 0
+        // published their data for a phase, the array element at their index is expected to be 0,
+        // which is returned as `None`, see `to_payloads`
 
         /// Gets the participants' shares
-        async fn get_shares(&self) -> ContractClientResult<Vec<Vec<u8>>>;
+        async fn get_shares(&self) -> ContractClientResult<DKGPayloads>;
 
         /// Gets the participants' responses
-        async fn get_responses(&self) -> ContractClientResult<Vec<Vec<u8>>>;
+        async fn get_responses(&self) -> ContractClientResult<DKGPayloads>;
 
         /// Gets the participants' justifications
-        async fn get_justifications(&self) -> ContractClientResult<Vec<Vec<u8>>>;
+        async fn get_justifications(&self) -> ContractClientResult<DKGPayloads>;
 
         /// Gets the participants' ethereum addresses
         async fn get_participants(&self) -> ContractClientResult<Vec<Address>>;
 
+        /// Gets the participants' ethereum addresses from `offset` on, at most `limit` of them,
+        /// reading them one by one by index. It serves groups too large for `get_participants`
+        /// to fit in a single RPC response, so `offset + limit` should not exceed the group size.
+        async fn get_participants_paged(
+            &self,
+            offset: usize,
+            limit: usize,
+        ) -> ContractClientResult<Vec<Address>>;
+
         /// Gets the participants' BLS keys along with the thershold of the DKG
         async fn get_dkg_keys(&self) -> ContractClientResult<(usize, Vec<Vec<u8>>)>;
 
         /// Returns the current phase of the DKG.
         async fn in_phase(&self) -> ContractClientResult<i8>;
+
+        /// Gets the participants, shares, responses, justifications and the current phase
+        /// of the DKG at once. They are fetched in a single Multicall if the chain has a
+        /// known Multicall deployment, otherwise one by one.
+        async fn get_dkg_state(&self) -> ContractClientResult<DKGState>;
+
+        /// Returns the last block of each phase of the DKG in order, starting from Phase 1.
+        /// The list is empty if the DKG has not started yet.
+        async fn get_phase_deadlines(&self) -> ContractClientResult<Vec<usize>>;
     }
 
-    pub trait CoordinatorClientBuilder<C: Curve = G2Curve> {
-        type Service: CoordinatorTransactions + CoordinatorViews + BoardPublisher<C> + Sync + Send;
+    /// Decodes the views of the coordinator into the DKG payloads, keeping the order of the
+    /// participants. A participant that has not published for the phase, or whose payload
+    /// can't be deserialized, is `None`. The raw views are still available in `CoordinatorViews`.
+    #[async_trait]
+    pub trait CoordinatorPayloadViews: CoordinatorViews + Sync {
+        async fn get_decoded_shares<C: Curve>(
+            &self,
+        ) -> ContractClientResult<Vec<Option<BundledShares<C>>>> {
+            Ok(decode_payloads(&self.get_shares().await?))
+        }
 
-        fn build_coordinator_client(&self, contract_address: Address) -> Self::Service;
+        async fn get_decoded_responses(
+            &self,
+        ) -> ContractClientResult<Vec<Option<BundledResponses>>> {
+            Ok(decode_payloads(&self.get_responses().await?))
+        }
+
+        async fn get_decoded_justifications<C: Curve>(
+            &self,
+        ) -> ContractClientResult<Vec<Option<BundledJustification<C>>>> {
+            Ok(decode_payloads(&self.get_justifications().await?))
+        }
+    }
+
+    impl<T: CoordinatorViews + Sync> CoordinatorPayloadViews for T {}
+
+    /// Converts the raw elements of a coordinator view into payloads. An empty element, or a
+    /// single `0` byte, is an empty slot of a participant that has not published, and becomes `None`.
+    pub fn to_payloads<B: AsRef<[u8]>>(elements: &[B]) -> DKGPayloads {
+        elements
+            .iter()
+            .map(|element| match element.as_ref() {
+                [] | [0] => None,
+                payload => Some(payload.to_vec()),
+            })
+            .collect()
+    }
+
+    /// Deserializes the payload of each participant, an absent or malformed one becomes `None`.
+    pub fn decode_payloads<D: DeserializeOwned>(payloads: &[Option<Vec<u8>>]) -> Vec<Option<D>> {
+        payloads
+            .iter()
+            .enumerate()
+            .map(|(index, payload)| {
+                let payload = payload.as_ref()?;
+
+                bincode::deserialize::<D>(payload)
+                    .map_err(|e| {
+                        warn!("malformed DKG payload of participant {}: {:?}", index, e);
+                    })
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// Builds the client of a DKG coordinator, the curve of which follows the curve the node runs with.
+    pub trait CoordinatorClientBuilder {
+        type Service<C: Curve + 'static>: CoordinatorTransactions
+            + CoordinatorViews
+            + BoardPublisher<C>
+            + Sync
+            + Send;
+
+        fn build_coordinator_client<C: Curve + 'static>(
+            &self,
+            contract_address: Address,
+        ) -> Self::Service<C>;
     }
 }
 
 pub mod adapter {
-    use arpa_node_core::{PartialSignature, RandomnessTask};
+    use arpa_node_core::{PartialSignature, RandomnessTask, RandomnessTaskFilter};
     use async_trait::async_trait;
     use ethers::core::types::Address;
     use ethers::types::{H256, U256};
     use std::{collections::HashMap, future::Future};
+    use tokio_util::sync::CancellationToken;
 
-    use crate::error::ContractClientResult;
+    use crate::{error::ContractClientResult, TransactionSentHook};
 
     #[async_trait]
     pub trait AdapterTransactions {
+        /// Fulfills the randomness of the task, calling `on_sent` with the hash of every
+        /// transaction sent for it. Returns the hash of the one that succeeded.
         async fn fulfill_randomness(
             &self,
             group_index: usize,
+            threshold: usize,
             task: RandomnessTask,
             signature: Vec<u8>,
             partial_signatures: HashMap<Address, PartialSignature>,
+            on_sent: Option<TransactionSentHook>,
         ) -> ContractClientResult<H256>;
     }
 
@@ -251,15 +505,31 @@ pub mod adapter {
         async fn get_last_randomness(&self) -> ContractClientResult<U256>;
 
         async fn is_task_pending(&self, request_id: &[u8]) -> ContractClientResult<bool>;
+
+        /// Returns the randomness, the committer who fulfilled it and the block number of
+        /// the fulfillment of the request, or None if the request is not fulfilled yet.
+        /// Only the blocks since the request was assigned at are looked up.
+        async fn get_fulfillment(
+            &self,
+            request_id: &[u8],
+            assignment_block_height: usize,
+        ) -> ContractClientResult<Option<(U256, Address, usize)>>;
     }
 
     #[async_trait]
     pub trait AdapterLogs {
+        /// Subscribes to the randomness tasks matching the filter, which is applied to the
+        /// indexed topics of the request logs, so that the other tasks are never decoded.
+        /// The tasks requested since `from_block` are replayed first, only the new ones are
+        /// received if not set. Returns `Ok` once the cancellation token is cancelled.
         async fn subscribe_randomness_task<
             C: FnMut(RandomnessTask) -> F + Send,
             F: Future<Output = ContractClientResult<()>> + Send,
         >(
             &self,
+            filter: RandomnessTaskFilter,
+            from_block: Option<usize>,
+            cancellation_token: CancellationToken,
             cb: C,
         ) -> ContractClientResult<()>;
     }
@@ -274,24 +544,49 @@ pub mod adapter {
 pub mod provider {
 
     use std::future::Future;
+    use tokio_util::sync::CancellationToken;
 
     use async_trait::async_trait;
+    use ethers::types::{H256, U256};
 
     use crate::error::ContractClientResult;
 
     #[async_trait]
     pub trait BlockFetcher {
+        /// Subscribes to the new blocks until the stream errors, or returns `Ok` once the
+        /// cancellation token is cancelled.
         async fn subscribe_new_block_height<
             C: FnMut(usize) -> F + Send,
             F: Future<Output = ContractClientResult<()>> + Send,
         >(
             &self,
+            cancellation_token: CancellationToken,
             cb: C,
         ) -> ContractClientResult<()>;
     }
 
+    #[async_trait]
+    pub trait TransactionReplacer {
+        /// Replaces the pending transaction of the node at `nonce` with an empty transfer to the
+        /// node itself paying `new_fee_gwei` per gas, so that the transactions queued behind a
+        /// stuck one can go through. Returns the hash of the replacement transaction.
+        async fn replace_stuck_transaction(
+            &self,
+            nonce: U256,
+            new_fee_gwei: u64,
+        ) -> ContractClientResult<H256>;
+    }
+
+    #[async_trait]
+    pub trait TransactionReceiptFetcher {
+        /// Returns whether the transaction succeeded once it is mined, or `None` if it is not,
+        /// e.g. as it is still pending or has been dropped.
+        async fn get_transaction_status(&self, tx_hash: H256)
+            -> ContractClientResult<Option<bool>>;
+    }
+
     pub trait ChainProviderBuilder {
-        type Service: BlockFetcher + Send + Sync;
+        type Service: BlockFetcher + TransactionReplacer + TransactionReceiptFetcher + Send + Sync;
 
         fn build_chain_provider(&self) -> Self::Service;
     }
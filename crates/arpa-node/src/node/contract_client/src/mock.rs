@@ -0,0 +1,998 @@
+use crate::{
+    adapter::{AdapterClientBuilder, AdapterLogs, AdapterTransactions, AdapterViews},
+    controller::{
+        ControllerClientBuilder, ControllerLogs, ControllerTransactions, ControllerViews,
+    },
+    coordinator::{CoordinatorViews, DKGPayloads, DKGState},
+    error::{ContractClientError, ContractClientResult},
+    provider::{
+        BlockFetcher, ChainProviderBuilder, TransactionReceiptFetcher, TransactionReplacer,
+    },
+    TransactionSentHook,
+};
+use arpa_node_core::{
+    derive_randomness, ChainIdentity, CircuitBreaker, DKGTask, ExponentialBackoffRetryDescriptor,
+    GeneralChainIdentity, Group, Node, PartialSignature, RandomnessTask, RandomnessTaskFilter,
+    TransactionConfirmationDescriptor, WalletSigner,
+};
+use async_trait::async_trait;
+use ethers::{
+    providers::{Http, Provider, ProviderError},
+    types::{Address, H256, U256},
+};
+use log::info;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+use threshold_bls::group::PairingCurve;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+/// A DKG commitment sent to the mock controller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockDKGCommitment {
+    pub id_address: Address,
+    pub group_index: usize,
+    pub group_epoch: usize,
+    pub public_key: Vec<u8>,
+    pub partial_public_key: Vec<u8>,
+    pub disqualified_nodes: Vec<Address>,
+}
+
+#[derive(Default)]
+struct MockChainState {
+    block_height: usize,
+    transaction_count: u64,
+    // every transaction is mined at once, and only the successful ones are sent
+    mined_transactions: HashSet<H256>,
+    nodes: HashMap<Address, (Vec<u8>, bool, usize)>,
+    // the groups are kept serialized, so that they can be read back with the curve of the caller
+    groups: BTreeMap<usize, Vec<u8>>,
+    // the id address of each member to the index of its group and its index in the group
+    belonging_groups: HashMap<Address, (usize, usize)>,
+    coordinators: HashMap<usize, Address>,
+    dkg_tasks: Vec<DKGTask>,
+    dkg_commitments: Vec<MockDKGCommitment>,
+    post_processed_groups: Vec<(usize, usize)>,
+    randomness_tasks: Vec<RandomnessTask>,
+    pending_request_ids: Vec<Vec<u8>>,
+    fulfillments: HashMap<Vec<u8>, (U256, Address, usize)>,
+    last_randomness: U256,
+}
+
+impl MockChainState {
+    fn next_transaction_hash(&mut self) -> H256 {
+        self.transaction_count += 1;
+        let tx_hash = H256::from_low_u64_be(self.transaction_count);
+        self.mined_transactions.insert(tx_hash);
+        tx_hash
+    }
+}
+
+/// An in-memory chain that serves the controller, the adapter and the provider at once, so that
+/// the event pipeline of the node can be driven end-to-end in tests without Anvil or any other
+/// external process.
+///
+/// Blocks are only mined by `advance_blocks`, and the DKG and randomness tasks only show up once
+/// they are emitted, which keeps the tests deterministic. The clones share the same chain, each
+/// one sending its transactions as its own `id_address`.
+#[derive(Clone)]
+pub struct MockChainProvider {
+    id_address: Address,
+    state: Arc<Mutex<MockChainState>>,
+    // bumped on every change of the chain to wake up the subscriptions
+    revision: Arc<watch::Sender<u64>>,
+}
+
+impl Default for MockChainProvider {
+    fn default() -> Self {
+        Self::new(Address::zero())
+    }
+}
+
+impl MockChainProvider {
+    pub fn new(id_address: Address) -> Self {
+        let (revision, _) = watch::channel(0);
+
+        MockChainProvider {
+            id_address,
+            state: Arc::new(Mutex::new(MockChainState::default())),
+            revision: Arc::new(revision),
+        }
+    }
+
+    /// Returns a client of the same chain that sends its transactions as `id_address`.
+    pub fn with_id_address(&self, id_address: Address) -> Self {
+        MockChainProvider {
+            id_address,
+            state: self.state.clone(),
+            revision: self.revision.clone(),
+        }
+    }
+
+    pub fn get_id_address(&self) -> Address {
+        self.id_address
+    }
+
+    fn update<T>(&self, f: impl FnOnce(&mut MockChainState) -> T) -> T {
+        let res = f(&mut self.state.lock().unwrap());
+        self.revision.send_modify(|revision| *revision += 1);
+        res
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&MockChainState) -> T) -> T {
+        f(&self.state.lock().unwrap())
+    }
+
+    pub fn get_block_height(&self) -> usize {
+        self.read(|state| state.block_height)
+    }
+
+    /// Mines `n` empty blocks, each of which is delivered to the block subscriptions in turn.
+    pub fn advance_blocks(&self, n: usize) -> usize {
+        self.update(|state| {
+            state.block_height += n;
+            state.block_height
+        })
+    }
+
+    /// Sets the group as it would be returned by the controller, along with the belonging group
+    /// of each of its members.
+    pub fn set_group<C: PairingCurve>(&self, group: &Group<C>) {
+        let bytes = bincode::serialize(group).expect("a group can always be serialized");
+
+        self.update(|state| {
+            state
+                .belonging_groups
+                .retain(|_, (group_index, _)| *group_index != group.index);
+            for member in group.members.values() {
+                state
+                    .belonging_groups
+                    .insert(member.id_address, (group.index, member.index));
+            }
+            state.groups.insert(group.index, bytes);
+        });
+    }
+
+    /// Emits a DKG task from the controller, which also deploys its coordinator.
+    pub fn emit_dkg_task(&self, task: DKGTask) {
+        self.update(|state| {
+            state
+                .coordinators
+                .insert(task.group_index, task.coordinator_address);
+            state.dkg_tasks.push(task);
+        });
+    }
+
+    /// Emits a randomness request from the adapter, which stays pending until it is fulfilled.
+    pub fn emit_randomness_task(&self, task: RandomnessTask) {
+        self.update(|state| {
+            state.pending_request_ids.push(task.request_id.clone());
+            state.randomness_tasks.push(task);
+        });
+    }
+
+    /// Returns the DKG commitments sent to the controller so far, in order.
+    pub fn get_dkg_commitments(&self) -> Vec<MockDKGCommitment> {
+        self.read(|state| state.dkg_commitments.clone())
+    }
+
+    /// Returns the groups post-processed so far as pairs of group index and epoch, in order.
+    pub fn get_post_processed_groups(&self) -> Vec<(usize, usize)> {
+        self.read(|state| state.post_processed_groups.clone())
+    }
+
+    /// Calls `cb` with each item of the log selected by `log` from the one at `cursor` on, then
+    /// with each new one as it is emitted, until the cancellation token is cancelled.
+    async fn subscribe_log<
+        T: Clone + Send,
+        C: FnMut(T) -> F + Send,
+        F: Future<Output = ContractClientResult<()>> + Send,
+    >(
+        &self,
+        log: impl Fn(&MockChainState) -> &[T] + Send,
+        mut cursor: usize,
+        cancellation_token: CancellationToken,
+        mut cb: C,
+    ) -> ContractClientResult<()> {
+        let mut revision = self.revision.subscribe();
+
+        loop {
+            let items = self.read(|state| log(state)[cursor..].to_vec());
+            cursor += items.len();
+
+            for item in items {
+                cb(item).await?;
+            }
+
+            tokio::select! {
+                _ = revision.changed() => {},
+                _ = cancellation_token.cancelled() => return Ok(()),
+            }
+        }
+    }
+}
+
+impl<C: PairingCurve> ControllerClientBuilder<C> for MockChainProvider {
+    type Service = MockChainProvider;
+
+    fn build_controller_client(&self) -> MockChainProvider {
+        self.clone()
+    }
+}
+
+impl AdapterClientBuilder for MockChainProvider {
+    type Service = MockChainProvider;
+
+    fn build_adapter_client(&self, main_id_address: Address) -> MockChainProvider {
+        self.with_id_address(main_id_address)
+    }
+}
+
+impl ChainProviderBuilder for MockChainProvider {
+    type Service = MockChainProvider;
+
+    fn build_chain_provider(&self) -> MockChainProvider {
+        self.clone()
+    }
+}
+
+/// The identity of a node on a `MockChainProvider`, so that the listeners and subscribers
+/// generic over the chain identity can run against the mock chain.
+///
+/// The contract clients are built from the mock chain, everything else comes from the wrapped
+/// identity, whose provider is never reached by the clients.
+pub struct MockChainIdentity {
+    identity: GeneralChainIdentity,
+    chain: MockChainProvider,
+}
+
+impl MockChainIdentity {
+    pub fn new(identity: GeneralChainIdentity, chain: MockChainProvider) -> Self {
+        let chain = chain.with_id_address(identity.get_id_address());
+
+        MockChainIdentity { identity, chain }
+    }
+
+    pub fn get_chain(&self) -> &MockChainProvider {
+        &self.chain
+    }
+}
+
+impl std::fmt::Debug for MockChainIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockChainIdentity")
+            .field("identity", &self.identity)
+            .field("chain", &"ignored")
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ChainIdentity for MockChainIdentity {
+    fn get_chain_id(&self) -> usize {
+        self.identity.get_chain_id()
+    }
+
+    fn get_id_address(&self) -> Address {
+        self.identity.get_id_address()
+    }
+
+    fn get_controller_address(&self) -> Address {
+        self.identity.get_controller_address()
+    }
+
+    fn get_adapter_address(&self) -> Address {
+        self.identity.get_adapter_address()
+    }
+
+    fn get_provider(&self) -> Arc<Provider<Http>> {
+        self.identity.get_provider()
+    }
+
+    fn get_signer(&self) -> Arc<WalletSigner> {
+        self.identity.get_signer()
+    }
+
+    fn is_eip155(&self) -> bool {
+        self.identity.is_eip155()
+    }
+
+    fn get_contract_transaction_retry_descriptor(&self) -> ExponentialBackoffRetryDescriptor {
+        self.identity.get_contract_transaction_retry_descriptor()
+    }
+
+    fn get_contract_view_retry_descriptor(&self) -> ExponentialBackoffRetryDescriptor {
+        self.identity.get_contract_view_retry_descriptor()
+    }
+
+    fn get_transaction_confirmation_descriptor(&self) -> TransactionConfirmationDescriptor {
+        self.identity.get_transaction_confirmation_descriptor()
+    }
+
+    fn get_circuit_breaker(&self, contract_address: Address) -> Arc<CircuitBreaker> {
+        self.identity.get_circuit_breaker(contract_address)
+    }
+
+    fn get_fulfill_randomness_gas_limit_multiplier(&self) -> f64 {
+        self.identity.get_fulfill_randomness_gas_limit_multiplier()
+    }
+
+    fn get_max_group_size(&self) -> usize {
+        self.identity.get_max_group_size()
+    }
+
+    async fn get_current_gas_price(&self) -> Result<U256, ProviderError> {
+        Ok(U256::one())
+    }
+
+    fn get_proxy_addresses(&self) -> Vec<Address> {
+        vec![]
+    }
+
+    fn get_implementation_address(&self, _proxy_address: Address) -> Option<Address> {
+        None
+    }
+
+    async fn resolve_implementation_address(
+        &self,
+        proxy_address: Address,
+    ) -> Result<Address, ProviderError> {
+        Ok(proxy_address)
+    }
+}
+
+impl<C: PairingCurve> ControllerClientBuilder<C> for MockChainIdentity {
+    type Service = MockChainProvider;
+
+    fn build_controller_client(&self) -> MockChainProvider {
+        self.chain.clone()
+    }
+}
+
+impl AdapterClientBuilder for MockChainIdentity {
+    type Service = MockChainProvider;
+
+    fn build_adapter_client(&self, main_id_address: Address) -> MockChainProvider {
+        self.chain.with_id_address(main_id_address)
+    }
+}
+
+impl ChainProviderBuilder for MockChainIdentity {
+    type Service = MockChainProvider;
+
+    fn build_chain_provider(&self) -> MockChainProvider {
+        self.chain.clone()
+    }
+}
+
+#[async_trait]
+impl ControllerTransactions for MockChainProvider {
+    async fn node_register(&self, id_public_key: Vec<u8>) -> ContractClientResult<H256> {
+        let id_address = self.id_address;
+
+        self.update(|state| {
+            if state.nodes.contains_key(&id_address) {
+                return Err(ContractClientError::TransactionFailed);
+            }
+            state.nodes.insert(id_address, (id_public_key, true, 0));
+
+            info!("mock chain registered node {:?}", id_address);
+
+            Ok(state.next_transaction_hash())
+        })
+    }
+
+    async fn commit_dkg(
+        &self,
+        group_index: usize,
+        group_epoch: usize,
+        public_key: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<Address>,
+    ) -> ContractClientResult<H256> {
+        let id_address = self.id_address;
+
+        self.update(|state| {
+            state.dkg_commitments.push(MockDKGCommitment {
+                id_address,
+                group_index,
+                group_epoch,
+                public_key,
+                partial_public_key,
+                disqualified_nodes,
+            });
+
+            Ok(state.next_transaction_hash())
+        })
+    }
+
+    async fn post_process_dkg(
+        &self,
+        group_index: usize,
+        group_epoch: usize,
+    ) -> ContractClientResult<H256> {
+        self.update(|state| {
+            if state
+                .post_processed_groups
+                .contains(&(group_index, group_epoch))
+            {
+                return Ok(H256::zero());
+            }
+            state.post_processed_groups.push((group_index, group_epoch));
+
+            Ok(state.next_transaction_hash())
+        })
+    }
+}
+
+#[async_trait]
+impl<C: PairingCurve> ControllerViews<C> for MockChainProvider {
+    async fn get_node(&self, id_address: Address) -> ContractClientResult<Node> {
+        self.read(|state| {
+            let (id_public_key, node_state, pending_until_block) = state
+                .nodes
+                .get(&id_address)
+                .cloned()
+                .unwrap_or((vec![], false, 0));
+
+            Ok(Node {
+                id_address: if id_public_key.is_empty() {
+                    Address::zero()
+                } else {
+                    id_address
+                },
+                id_public_key,
+                state: node_state,
+                pending_until_block,
+            })
+        })
+    }
+
+    async fn get_group(&self, group_index: usize) -> ContractClientResult<Group<C>> {
+        self.read(|state| match state.groups.get(&group_index) {
+            Some(bytes) => bincode::deserialize(bytes)
+                .map_err(|e| ContractClientError::HandlingLogSubscriptionError(e.into())),
+            None => Ok(Group::new()),
+        })
+    }
+
+    async fn get_belonging_group(
+        &self,
+        id_address: Address,
+    ) -> ContractClientResult<Option<(usize, usize)>> {
+        self.read(|state| Ok(state.belonging_groups.get(&id_address).copied()))
+    }
+
+    async fn get_coordinator(&self, group_index: usize) -> ContractClientResult<Address> {
+        self.read(|state| {
+            Ok(state
+                .coordinators
+                .get(&group_index)
+                .copied()
+                .unwrap_or_else(Address::zero))
+        })
+    }
+}
+
+#[async_trait]
+impl ControllerLogs for MockChainProvider {
+    async fn subscribe_dkg_task<
+        C: FnMut(DKGTask) -> F + Send,
+        F: Future<Output = ContractClientResult<()>> + Send,
+    >(
+        &self,
+        cancellation_token: CancellationToken,
+        cb: C,
+    ) -> ContractClientResult<()> {
+        self.subscribe_log(
+            |state| state.dkg_tasks.as_slice(),
+            0,
+            cancellation_token,
+            cb,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl AdapterTransactions for MockChainProvider {
+    async fn fulfill_randomness(
+        &self,
+        _group_index: usize,
+        _threshold: usize,
+        task: RandomnessTask,
+        signature: Vec<u8>,
+        _partial_signatures: HashMap<Address, PartialSignature>,
+        on_sent: Option<TransactionSentHook>,
+    ) -> ContractClientResult<H256> {
+        let id_address = self.id_address;
+
+        let tx_hash = self.update(|state| {
+            // like the adapter, reverts if the request is unknown or already fulfilled
+            let Some(position) = state
+                .pending_request_ids
+                .iter()
+                .position(|request_id| *request_id == task.request_id)
+            else {
+                return Err(ContractClientError::TransactionFailed);
+            };
+            state.pending_request_ids.remove(position);
+
+            let randomness = U256::from_big_endian(&derive_randomness(&signature));
+            state.last_randomness = randomness;
+            state.fulfillments.insert(
+                task.request_id,
+                (randomness, id_address, state.block_height),
+            );
+
+            Ok(state.next_transaction_hash())
+        })?;
+
+        if let Some(on_sent) = on_sent {
+            on_sent(tx_hash).await;
+        }
+
+        Ok(tx_hash)
+    }
+}
+
+#[async_trait]
+impl AdapterViews for MockChainProvider {
+    async fn get_last_randomness(&self) -> ContractClientResult<U256> {
+        self.read(|state| Ok(state.last_randomness))
+    }
+
+    async fn is_task_pending(&self, request_id: &[u8]) -> ContractClientResult<bool> {
+        self.read(|state| {
+            Ok(state
+                .pending_request_ids
+                .iter()
+                .any(|pending| pending == request_id))
+        })
+    }
+
+    async fn get_fulfillment(
+        &self,
+        request_id: &[u8],
+        assignment_block_height: usize,
+    ) -> ContractClientResult<Option<(U256, Address, usize)>> {
+        if request_id.len() > 32 {
+            return Err(ContractClientError::InvalidRequestId(request_id.len()));
+        }
+
+        self.read(|state| {
+            Ok(state
+                .fulfillments
+                .get(request_id)
+                .filter(|(_, _, block_height)| *block_height >= assignment_block_height)
+                .cloned())
+        })
+    }
+}
+
+#[async_trait]
+impl AdapterLogs for MockChainProvider {
+    async fn subscribe_randomness_task<
+        C: FnMut(RandomnessTask) -> F + Send,
+        F: Future<Output = ContractClientResult<()>> + Send,
+    >(
+        &self,
+        filter: RandomnessTaskFilter,
+        from_block: Option<usize>,
+        cancellation_token: CancellationToken,
+        mut cb: C,
+    ) -> ContractClientResult<()> {
+        // like the real client, the tasks emitted before subscribing are only replayed when a
+        // block to start from is given
+        let cursor = match from_block {
+            Some(_) => 0,
+            None => self.read(|state| state.randomness_tasks.len()),
+        };
+
+        self.subscribe_log(
+            |state| state.randomness_tasks.as_slice(),
+            cursor,
+            cancellation_token,
+            move |task: RandomnessTask| {
+                let matched = filter.matches(&task)
+                    && task.assignment_block_height >= from_block.unwrap_or_default();
+                let fut = matched.then(|| cb(task));
+                async move {
+                    match fut {
+                        Some(fut) => fut.await,
+                        None => Ok(()),
+                    }
+                }
+            },
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl BlockFetcher for MockChainProvider {
+    async fn subscribe_new_block_height<
+        C: FnMut(usize) -> F + Send,
+        F: Future<Output = ContractClientResult<()>> + Send,
+    >(
+        &self,
+        cancellation_token: CancellationToken,
+        mut cb: C,
+    ) -> ContractClientResult<()> {
+        let mut revision = self.revision.subscribe();
+        let mut last_block_height = self.get_block_height();
+
+        loop {
+            let block_height = self.get_block_height();
+
+            for height in last_block_height + 1..=block_height {
+                cb(height).await?;
+            }
+            last_block_height = block_height;
+
+            tokio::select! {
+                _ = revision.changed() => {},
+                _ = cancellation_token.cancelled() => return Ok(()),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionReplacer for MockChainProvider {
+    async fn replace_stuck_transaction(
+        &self,
+        nonce: U256,
+        _new_fee_gwei: u64,
+    ) -> ContractClientResult<H256> {
+        // the mock chain mines every transaction at once, so none of them is ever stuck
+        Err(ContractClientError::NoPendingTransaction(nonce))
+    }
+}
+
+#[async_trait]
+impl TransactionReceiptFetcher for MockChainProvider {
+    async fn get_transaction_status(&self, tx_hash: H256) -> ContractClientResult<Option<bool>> {
+        Ok(self
+            .read(|state| state.mined_transactions.contains(&tx_hash))
+            .then_some(true))
+    }
+}
+
+/// The views of a DKG coordinator on the mock chain, where no participant has published yet.
+///
+/// Like a provider limiting the size of its responses, it refuses to return more than
+/// `max_response_participants` participants at once if the limit is set.
+#[derive(Debug, Clone, Default)]
+pub struct MockCoordinator {
+    threshold: usize,
+    participants: Vec<Address>,
+    max_response_participants: Option<usize>,
+}
+
+impl MockCoordinator {
+    pub fn new(threshold: usize, participants: Vec<Address>) -> Self {
+        MockCoordinator {
+            threshold,
+            participants,
+            max_response_participants: None,
+        }
+    }
+
+    pub fn with_max_response_participants(mut self, max_response_participants: usize) -> Self {
+        self.max_response_participants = Some(max_response_participants);
+        self
+    }
+
+    fn check_response_size(&self, size: usize) -> ContractClientResult<()> {
+        match self.max_response_participants {
+            Some(max) if size > max => Err(ContractClientError::ChainProviderError(
+                ProviderError::CustomError(format!(
+                    "response size of {} participants exceeds the limit of {}",
+                    size, max
+                )),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl CoordinatorViews for MockCoordinator {
+    async fn get_shares(&self) -> ContractClientResult<DKGPayloads> {
+        Ok(vec![None; self.participants.len()])
+    }
+
+    async fn get_responses(&self) -> ContractClientResult<DKGPayloads> {
+        Ok(vec![None; self.participants.len()])
+    }
+
+    async fn get_justifications(&self) -> ContractClientResult<DKGPayloads> {
+        Ok(vec![None; self.participants.len()])
+    }
+
+    async fn get_participants(&self) -> ContractClientResult<Vec<Address>> {
+        self.check_response_size(self.participants.len())?;
+
+        Ok(self.participants.clone())
+    }
+
+    async fn get_participants_paged(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> ContractClientResult<Vec<Address>> {
+        self.check_response_size(limit)?;
+
+        Ok(self
+            .participants
+            .iter()
+            .skip(offset)
+            .take(limit)
+            .copied()
+            .collect())
+    }
+
+    async fn get_dkg_keys(&self) -> ContractClientResult<(usize, Vec<Vec<u8>>)> {
+        Ok((self.threshold, vec![vec![]; self.participants.len()]))
+    }
+
+    async fn in_phase(&self) -> ContractClientResult<i8> {
+        Ok(-1)
+    }
+
+    async fn get_dkg_state(&self) -> ContractClientResult<DKGState> {
+        Ok((
+            self.get_participants().await?,
+            self.get_shares().await?,
+            self.get_responses().await?,
+            self.get_justifications().await?,
+            self.in_phase().await?,
+        ))
+    }
+
+    async fn get_phase_deadlines(&self) -> ContractClientResult<Vec<usize>> {
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::MockChainProvider;
+    use crate::{
+        adapter::{AdapterLogs, AdapterTransactions, AdapterViews},
+        controller::ControllerViews,
+        error::ContractClientError,
+        provider::BlockFetcher,
+    };
+    use arpa_node_core::{
+        Group, Member, RandomnessRequestType, RandomnessTask, RandomnessTaskFilter,
+        RandomnessTaskPriority,
+    };
+    use ethers::types::{Address, U256};
+    use std::{
+        collections::{BTreeMap, HashMap},
+        marker::PhantomData,
+        sync::{Arc, Mutex},
+    };
+    use threshold_bls::curve::bn254::PairingCurve as BN254;
+    use tokio_util::sync::CancellationToken;
+
+    fn build_task(request_id: Vec<u8>, group_index: u32) -> RandomnessTask {
+        RandomnessTask {
+            request_id,
+            subscription_id: 1,
+            group_index,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 0,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_new_block_height() {
+        let chain = MockChainProvider::default();
+        let token = CancellationToken::new();
+        let heights = Arc::new(Mutex::new(vec![]));
+
+        let subscription = {
+            let (chain, token, heights) = (chain.clone(), token.clone(), heights.clone());
+            tokio::spawn(async move {
+                chain
+                    .subscribe_new_block_height(token, |height| {
+                        heights.lock().unwrap().push(height);
+                        async { Ok(()) }
+                    })
+                    .await
+            })
+        };
+        tokio::task::yield_now().await;
+
+        chain.advance_blocks(3);
+        while heights.lock().unwrap().len() < 3 {
+            tokio::task::yield_now().await;
+        }
+        token.cancel();
+        subscription.await.unwrap().unwrap();
+
+        assert_eq!(*heights.lock().unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_randomness_task_flow() {
+        let chain = MockChainProvider::default();
+        let token = CancellationToken::new();
+        let received = Arc::new(Mutex::new(vec![]));
+
+        chain.emit_randomness_task(build_task(vec![1], 0));
+        chain.emit_randomness_task(build_task(vec![2], 1));
+
+        let subscription = {
+            let (chain, token, received) = (chain.clone(), token.clone(), received.clone());
+            tokio::spawn(async move {
+                chain
+                    .subscribe_randomness_task(
+                        RandomnessTaskFilter {
+                            group_index: Some(0),
+                            subscription_id: None,
+                        },
+                        None,
+                        token,
+                        |task| {
+                            received.lock().unwrap().push(task.request_id);
+                            async { Ok(()) }
+                        },
+                    )
+                    .await
+            })
+        };
+        tokio::task::yield_now().await;
+
+        chain.emit_randomness_task(build_task(vec![3], 0));
+        chain.emit_randomness_task(build_task(vec![4], 1));
+        while received.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        token.cancel();
+        subscription.await.unwrap().unwrap();
+
+        // without a block to start from, only the tasks emitted after subscribing are received
+        assert_eq!(*received.lock().unwrap(), vec![vec![3]]);
+
+        let committer = Address::random();
+        let client = chain.with_id_address(committer);
+        chain.advance_blocks(5);
+
+        assert!(client.is_task_pending(&[1]).await.unwrap());
+        client
+            .fulfill_randomness(
+                0,
+                3,
+                build_task(vec![1], 0),
+                vec![7; 64],
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        // a request can't be fulfilled twice
+        assert!(client
+            .fulfill_randomness(
+                0,
+                3,
+                build_task(vec![1], 0),
+                vec![7; 64],
+                HashMap::new(),
+                None
+            )
+            .await
+            .is_err());
+
+        assert!(!client.is_task_pending(&[1]).await.unwrap());
+        let (randomness, fulfilled_by, block_height) =
+            client.get_fulfillment(&[1], 0).await.unwrap().unwrap();
+        assert_eq!(randomness, client.get_last_randomness().await.unwrap());
+        assert_eq!(fulfilled_by, committer);
+        assert_eq!(block_height, 5);
+        // the request can't be fulfilled before it was assigned
+        assert!(client.get_fulfillment(&[1], 6).await.unwrap().is_none());
+        assert!(matches!(
+            client.get_fulfillment(&[1; 33], 0).await,
+            Err(ContractClientError::InvalidRequestId(33))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_randomness_task_from_block() {
+        let chain = MockChainProvider::default();
+        let token = CancellationToken::new();
+        let received = Arc::new(Mutex::new(vec![]));
+
+        for (request_id, assignment_block_height) in [(1, 10), (2, 20), (3, 30)] {
+            let mut task = build_task(vec![request_id], 0);
+            task.assignment_block_height = assignment_block_height;
+            chain.emit_randomness_task(task);
+        }
+
+        let subscription = {
+            let (chain, token, received) = (chain.clone(), token.clone(), received.clone());
+            tokio::spawn(async move {
+                chain
+                    .subscribe_randomness_task(
+                        RandomnessTaskFilter::default(),
+                        Some(20),
+                        token,
+                        |task| {
+                            received.lock().unwrap().push(task.request_id);
+                            async { Ok(()) }
+                        },
+                    )
+                    .await
+            })
+        };
+
+        while received.lock().unwrap().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+        token.cancel();
+        subscription.await.unwrap().unwrap();
+
+        // the tasks requested before the block are ignored
+        assert_eq!(*received.lock().unwrap(), vec![vec![2], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn test_group_views() {
+        let chain = MockChainProvider::default();
+        let member = Address::random();
+
+        let mut members = BTreeMap::new();
+        members.insert(
+            member,
+            Member {
+                index: 2,
+                id_address: member,
+                rpc_endpoint: None,
+                partial_public_key: None,
+            },
+        );
+        chain.set_group(&Group::<BN254> {
+            index: 1,
+            epoch: 3,
+            size: 1,
+            threshold: 1,
+            state: true,
+            public_key: None,
+            members,
+            committers: vec![],
+            c: PhantomData,
+        });
+
+        let group = ControllerViews::<BN254>::get_group(&chain, 1)
+            .await
+            .unwrap();
+        assert_eq!(group.epoch, 3);
+        assert_eq!(
+            ControllerViews::<BN254>::get_belonging_group(&chain, member)
+                .await
+                .unwrap(),
+            Some((1, 2))
+        );
+        assert_eq!(
+            ControllerViews::<BN254>::get_belonging_group(&chain, Address::random())
+                .await
+                .unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! A rolling file appender which rolls the log file over before a record is written.
+//!
+//! The `RollingFileAppender` of log4rs only applies its policy after a record is written, so
+//! that the log file grows beyond the size limit and the first record of a period ends up in
+//! the file of the previous one. This appender encodes the record first and rolls the log file
+//! over if the record would take it beyond the size limit or belongs to the next period.
+
+use super::policy::{available_target, TimeRollingPeriod};
+use log::Record;
+use log4rs::{
+    append::Append,
+    encode::{writer::simple::SimpleWriter, Encode},
+};
+use parking_lot::Mutex;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollingTrigger {
+    /// Deletes the log file once the next record would take it beyond the size in bytes.
+    Size(u64),
+    /// Renames the log file with the suffix of its period once a record of the next one comes.
+    Time(TimeRollingPeriod),
+}
+
+#[derive(Debug)]
+struct LogFile {
+    writer: BufWriter<File>,
+    len: u64,
+    suffix: String,
+}
+
+#[derive(Debug)]
+pub struct RollingFileAppender {
+    path: PathBuf,
+    encoder: Box<dyn Encode>,
+    trigger: RollingTrigger,
+    file: Mutex<LogFile>,
+}
+
+impl RollingFileAppender {
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        encoder: Box<dyn Encode>,
+        trigger: RollingTrigger,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file = open_log_file(&path, trigger)?;
+
+        Ok(RollingFileAppender {
+            path,
+            encoder,
+            trigger,
+            file: Mutex::new(file),
+        })
+    }
+
+    fn roll(&self, file: &mut LogFile) -> io::Result<()> {
+        file.writer.flush()?;
+
+        match self.trigger {
+            RollingTrigger::Size(_) => fs::remove_file(&self.path)?,
+            RollingTrigger::Time(_) => {
+                fs::rename(&self.path, available_target(&self.path, &file.suffix))?
+            }
+        }
+
+        *file = open_log_file(&self.path, self.trigger)?;
+
+        Ok(())
+    }
+}
+
+impl Append for RollingFileAppender {
+    fn append(&self, record: &Record) -> anyhow::Result<()> {
+        let mut encoded = SimpleWriter(Vec::new());
+        self.encoder.encode(&mut encoded, record)?;
+        let encoded = encoded.0;
+
+        let mut file = self.file.lock();
+
+        let should_roll = match self.trigger {
+            // a record larger than the limit on its own is still written to an empty file
+            RollingTrigger::Size(limit) => file.len > 0 && file.len + encoded.len() as u64 > limit,
+            RollingTrigger::Time(period) => file.suffix != period.current_suffix(),
+        };
+
+        if should_roll {
+            self.roll(&mut file)?;
+        }
+
+        file.writer.write_all(&encoded)?;
+        file.writer.flush()?;
+        file.len += encoded.len() as u64;
+
+        Ok(())
+    }
+
+    fn flush(&self) {}
+}
+
+fn open_log_file(path: &Path, trigger: RollingTrigger) -> io::Result<LogFile> {
+    let file = OpenOptions::new().append(true).create(true).open(path)?;
+    let len = file.metadata()?.len();
+
+    let suffix = match trigger {
+        RollingTrigger::Size(_) => String::new(),
+        RollingTrigger::Time(period) => period.current_suffix(),
+    };
+
+    Ok(LogFile {
+        writer: BufWriter::new(file),
+        len,
+        suffix,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use log4rs::encode::pattern::PatternEncoder;
+
+    #[test]
+    fn rolls_over_before_exceeding_the_size() {
+        let dir =
+            std::env::temp_dir().join(format!("arpa_node_appender_test_{}", std::process::id()));
+        let path = dir.join("node.log");
+
+        let appender = RollingFileAppender::new(
+            &path,
+            Box::new(PatternEncoder::new("{m}{n}")),
+            RollingTrigger::Size(25),
+        )
+        .unwrap();
+
+        let mut lens = vec![];
+        for _ in 0..5 {
+            appender
+                .append(&Record::builder().args(format_args!("0123456789")).build())
+                .unwrap();
+            lens.push(fs::metadata(&path).unwrap().len());
+        }
+
+        // two records of 11 bytes fit, the third one goes to a new file
+        assert_eq!(lens, vec![11, 22, 11, 22, 11]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rolls_over_once_the_period_is_over() {
+        let dir = std::env::temp_dir().join(format!(
+            "arpa_node_time_appender_test_{}",
+            std::process::id()
+        ));
+        let path = dir.join("node.log");
+
+        let appender = RollingFileAppender::new(
+            &path,
+            Box::new(PatternEncoder::new("{m}{n}")),
+            RollingTrigger::Time(TimeRollingPeriod::Daily),
+        )
+        .unwrap();
+
+        appender
+            .append(&Record::builder().args(format_args!("today")).build())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "today\n");
+
+        // pretend the log file was started on an earlier day
+        appender.file.lock().suffix = "2023-03-20".to_string();
+
+        appender
+            .append(&Record::builder().args(format_args!("next day")).build())
+            .unwrap();
+
+        // the log file is renamed with the suffix of its day and a new one is started
+        let rolled = dir.join("node.log.2023-03-20");
+        assert_eq!(fs::read_to_string(&rolled).unwrap(), "today\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "next day\n");
+
+        // a record of the same day doesn't roll the log file over
+        appender
+            .append(&Record::builder().args(format_args!("same day")).build())
+            .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "next day\nsame day\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -17,6 +17,9 @@
 //!     "target": "foo::bar",
 //!     "thread": "main",
 //!     "thread_id": 123,
+//!     "node_id": "0",
+//!     "group_index": "1",
+//!     "request_id": "7a9e...",
 //!     "mdc": {
 //!         "request_id": "123e4567-e89b-12d3-a456-426655440000"
 //!     }
@@ -25,6 +28,9 @@
 //!     }
 //! }
 //! ```
+//!
+//! `group_index` is only written when context logging is enabled and the node is in a group,
+//! `request_id` is only written when the record is emitted while handling a randomness request.
 
 use chrono::{
     format::{DelayedFormat, Fixed, Item},
@@ -42,7 +48,7 @@ use std::{
 
 lazy_static! {
     pub static ref CONTEXT_INFO: RwLock<Vec<String>> =
-        RwLock::new(vec!["".to_string(), "".to_string()]);
+        RwLock::new(vec!["".to_string(), "".to_string(), "".to_string()]);
 }
 
 /// An `Encode`r which writes a JSON object.
@@ -85,6 +91,12 @@ impl JsonEncoder {
         } else {
             "".to_string()
         };
+        let group_index = if self.show_context {
+            CONTEXT_INFO.read()[2].clone()
+        } else {
+            "".to_string()
+        };
+        let request_id = log_mdc::get("request_id", |v| v.map(|v| v.to_owned()));
 
         let message = Message {
             time: time.format_with_items(Some(Item::Fixed(Fixed::RFC3339)).into_iter()),
@@ -97,6 +109,8 @@ impl JsonEncoder {
             thread: thread.name(),
             thread_id: thread_id::get(),
             node_id: &self.node_id,
+            group_index: Some(group_index.as_str()).filter(|g| !g.is_empty()),
+            request_id,
             mdc: Mdc,
             node_info: &node_info,
             group_info: &group_info,
@@ -130,6 +144,10 @@ struct Message<'a> {
     thread: Option<&'a str>,
     thread_id: usize,
     node_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_index: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
     mdc: Mdc,
     node_info: &'a str,
     group_info: &'a str,
@@ -221,4 +239,32 @@ mod test {
         );
         assert_eq!(expected, String::from_utf8(buf).unwrap().trim());
     }
+
+    #[test]
+    fn request_id() {
+        let time = DateTime::parse_from_rfc3339("2016-03-20T14:22:20.644420340-08:00")
+            .unwrap()
+            .with_timezone(&Local);
+        log_mdc::insert("request_id", "abcd");
+
+        let encoder = JsonEncoder::new("test".to_string());
+
+        let mut buf = vec![];
+        encoder
+            .encode_inner(
+                &mut SimpleWriter(&mut buf),
+                time,
+                &Record::builder()
+                    .level(Level::Info)
+                    .target("target")
+                    .args(format_args!("{}", "message"))
+                    .build(),
+            )
+            .unwrap();
+
+        log_mdc::remove("request_id");
+
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains("\"node_id\":\"test\",\"request_id\":\"abcd\""));
+    }
 }
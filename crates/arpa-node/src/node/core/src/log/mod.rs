@@ -1 +1,3 @@
+pub mod appender;
 pub mod encoder;
+pub mod policy;
@@ -0,0 +1,73 @@
+//! The periods by which the log file is rolled over.
+//!
+//! Once the current hour or day is over, the log file is renamed with a suffix of the
+//! period it covers, e.g. `node.log.2023-03-20` for daily and `node.log.2023-03-20-14`
+//! for hourly rollover, and a new log file is started.
+
+use chrono::Local;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeRollingPeriod {
+    Daily,
+    Hourly,
+}
+
+impl TimeRollingPeriod {
+    fn suffix_format(&self) -> &'static str {
+        match self {
+            TimeRollingPeriod::Daily => "%Y-%m-%d",
+            TimeRollingPeriod::Hourly => "%Y-%m-%d-%H",
+        }
+    }
+
+    pub(super) fn current_suffix(&self) -> String {
+        Local::now().format(self.suffix_format()).to_string()
+    }
+}
+
+pub(super) fn available_target(path: &Path, suffix: &str) -> String {
+    let target = format!("{}.{}", path.display(), suffix);
+
+    if !Path::new(&target).exists() {
+        return target;
+    }
+
+    let mut index = 1;
+    loop {
+        let indexed_target = format!("{}.{}", target, index);
+        if !Path::new(&indexed_target).exists() {
+            return indexed_target;
+        }
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn suffix_format() {
+        assert_eq!(TimeRollingPeriod::Daily.current_suffix().len(), 10);
+        assert_eq!(TimeRollingPeriod::Hourly.current_suffix().len(), 13);
+    }
+
+    #[test]
+    fn available_target_skips_existing_files() {
+        let dir =
+            std::env::temp_dir().join(format!("arpa_node_policy_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("node.log");
+
+        let target = available_target(&path, "2023-03-20");
+        assert_eq!(target, format!("{}.2023-03-20", path.display()));
+
+        fs::write(&target, "").unwrap();
+        let target = available_target(&path, "2023-03-20");
+        assert_eq!(target, format!("{}.2023-03-20.1", path.display()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+use lazy_static::lazy_static;
+use prometheus::{
+    register_int_counter, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    Encoder, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+};
+
+lazy_static! {
+    pub static ref FULFILLMENTS_SUBMITTED: IntCounter = register_int_counter!(
+        "arpa_node_fulfillments_submitted_total",
+        "Number of randomness fulfillment transactions submitted by this node"
+    )
+    .unwrap();
+    pub static ref FULFILLMENTS_SUCCEEDED: IntCounter = register_int_counter!(
+        "arpa_node_fulfillments_succeeded_total",
+        "Number of randomness fulfillment transactions that succeeded"
+    )
+    .unwrap();
+    pub static ref FULFILLMENTS_FAILED: IntCounter = register_int_counter!(
+        "arpa_node_fulfillments_failed_total",
+        "Number of randomness fulfillment transactions that failed"
+    )
+    .unwrap();
+    pub static ref PARTIAL_SIGNATURES_RECEIVED: IntCounter = register_int_counter!(
+        "arpa_node_partial_signatures_received_total",
+        "Number of partial signatures accepted by the committer server"
+    )
+    .unwrap();
+    pub static ref GROUP_EPOCH: IntGauge = register_int_gauge!(
+        "arpa_node_group_epoch",
+        "Epoch of the group this node currently belongs to"
+    )
+    .unwrap();
+    pub static ref DKG_PHASE: IntGauge = register_int_gauge!(
+        "arpa_node_dkg_phase",
+        "Current DKG phase observed from the coordinator"
+    )
+    .unwrap();
+    pub static ref DKG_STATUS: IntGauge = register_int_gauge!(
+        "arpa_node_dkg_status",
+        "Current DKG status of the grouping, 0: None, 1: InPhase, 2: CommitSuccess, 3: WaitForPostProcess, 4: Timeout"
+    )
+    .unwrap();
+    pub static ref BLOCK_HEIGHT: IntGauge = register_int_gauge!(
+        "arpa_node_block_height",
+        "Latest block height handled by the block listener"
+    )
+    .unwrap();
+    pub static ref BLOCK_LISTENER_LAG: IntGauge = register_int_gauge!(
+        "arpa_node_block_listener_lag",
+        "Number of blocks the block listener is behind the chain head"
+    )
+    .unwrap();
+    pub static ref EVENTS_DROPPED: IntCounterVec = register_int_counter_vec!(
+        "arpa_node_events_dropped_total",
+        "Number of events dropped or rejected because the event queue of the topic is full, by topic",
+        &["topic"]
+    )
+    .unwrap();
+    pub static ref EVENTS_DEAD_LETTERED: IntCounterVec = register_int_counter_vec!(
+        "arpa_node_events_dead_lettered_total",
+        "Number of events which subscribers failed to handle after all the retries, by topic",
+        &["topic"]
+    )
+    .unwrap();
+    pub static ref TRANSACTION_RETRIES: IntCounterVec = register_int_counter_vec!(
+        "arpa_node_transaction_retries_total",
+        "Number of contract transaction retries, by transaction",
+        &["transaction"]
+    )
+    .unwrap();
+    pub static ref RESULT_CACHE_STATE_TRANSITIONS: IntCounterVec = register_int_counter_vec!(
+        "arpa_node_result_cache_state_transitions_total",
+        "Number of state transitions of the randomness result caches of the committer, by the state before and after",
+        &["from", "to"]
+    )
+    .unwrap();
+    pub static ref RESULT_CACHE_SIZE: IntGauge = register_int_gauge!(
+        "arpa_node_result_cache_size",
+        "Number of randomness result caches held by the committer"
+    )
+    .unwrap();
+    pub static ref RESULT_CACHE_EVICTIONS: IntCounter = register_int_counter!(
+        "arpa_node_result_cache_evictions_total",
+        "Number of randomness result caches evicted as the committer cache exceeds its max entries"
+    )
+    .unwrap();
+    pub static ref CONTRACT_CIRCUIT_BREAKER_STATE: IntGaugeVec = register_int_gauge_vec!(
+        "arpa_node_contract_circuit_breaker_state",
+        "State of the circuit breaker of each contract, 0: Closed, 1: Open, 2: HalfOpen",
+        &["contract"]
+    )
+    .unwrap();
+}
+
+/// Encodes all registered metrics in the Prometheus text exposition format.
+pub fn gather_metrics() -> Vec<u8> {
+    let encoder = TextEncoder::new();
+    let metric_families = prometheus::gather();
+    let mut buffer = vec![];
+    encoder.encode(&metric_families, &mut buffer).unwrap();
+    buffer
+}
+
+pub fn metrics_content_type() -> String {
+    TextEncoder::new().format_type().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gather_metrics() {
+        FULFILLMENTS_SUBMITTED.inc();
+        TRANSACTION_RETRIES.with_label_values(&["test"]).inc();
+
+        let output = String::from_utf8(gather_metrics()).unwrap();
+
+        assert!(output.contains("arpa_node_fulfillments_submitted_total"));
+        assert!(output.contains("arpa_node_transaction_retries_total{transaction=\"test\"}"));
+    }
+}
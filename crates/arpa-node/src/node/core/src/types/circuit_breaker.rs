@@ -0,0 +1,177 @@
+use crate::{address_to_string, metrics::CONTRACT_CIRCUIT_BREAKER_STATE, CircuitBreakerDescriptor};
+use ethers_core::types::Address;
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CircuitBreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl CircuitBreakerState {
+    pub fn to_i64(self) -> i64 {
+        match self {
+            CircuitBreakerState::Closed => 0,
+            CircuitBreakerState::Open => 1,
+            CircuitBreakerState::HalfOpen => 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CircuitBreakerStatus {
+    state: CircuitBreakerState,
+    consecutive_failures: usize,
+    // when the breaker opened, or when the current trial call was let through
+    changed_at: Instant,
+}
+
+/// Tracks the consecutive failed calls to a contract. The breaker opens after
+/// `failure_threshold` of them, and calls are short-circuited until the cooldown has passed.
+/// Then it half-opens and lets a single trial call through, which closes the breaker
+/// if it succeeds or opens it again if it fails.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    contract_address: Address,
+    descriptor: CircuitBreakerDescriptor,
+    status: Mutex<CircuitBreakerStatus>,
+}
+
+impl CircuitBreaker {
+    pub fn new(contract_address: Address, descriptor: CircuitBreakerDescriptor) -> Self {
+        CONTRACT_CIRCUIT_BREAKER_STATE
+            .with_label_values(&[&address_to_string(contract_address)])
+            .set(CircuitBreakerState::Closed.to_i64());
+
+        CircuitBreaker {
+            contract_address,
+            descriptor,
+            status: Mutex::new(CircuitBreakerStatus {
+                state: CircuitBreakerState::Closed,
+                consecutive_failures: 0,
+                changed_at: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn get_contract_address(&self) -> Address {
+        self.contract_address
+    }
+
+    pub fn get_state(&self) -> CircuitBreakerState {
+        self.status.lock().state
+    }
+
+    /// Returns whether a call can go through. A trial call is let through every cooldown
+    /// while the breaker is not closed, so that a trial which never reports back
+    /// can't keep the breaker half-open forever.
+    pub fn try_acquire(&self) -> bool {
+        if self.descriptor.failure_threshold == 0 {
+            return true;
+        }
+
+        let mut status = self.status.lock();
+
+        match status.state {
+            CircuitBreakerState::Closed => true,
+            CircuitBreakerState::Open | CircuitBreakerState::HalfOpen => {
+                if status.changed_at.elapsed()
+                    >= Duration::from_millis(self.descriptor.cooldown_millis)
+                {
+                    status.changed_at = Instant::now();
+                    self.transit(&mut status, CircuitBreakerState::HalfOpen);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut status = self.status.lock();
+
+        status.consecutive_failures = 0;
+        self.transit(&mut status, CircuitBreakerState::Closed);
+    }
+
+    pub fn record_failure(&self) {
+        if self.descriptor.failure_threshold == 0 {
+            return;
+        }
+
+        let mut status = self.status.lock();
+
+        status.consecutive_failures += 1;
+
+        if status.state == CircuitBreakerState::HalfOpen
+            || status.consecutive_failures >= self.descriptor.failure_threshold
+        {
+            status.changed_at = Instant::now();
+            self.transit(&mut status, CircuitBreakerState::Open);
+        }
+    }
+
+    fn transit(&self, status: &mut CircuitBreakerStatus, state: CircuitBreakerState) {
+        if status.state != state {
+            status.state = state;
+
+            CONTRACT_CIRCUIT_BREAKER_STATE
+                .with_label_values(&[&address_to_string(self.contract_address)])
+                .set(state.to_i64());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitBreaker, CircuitBreakerState};
+    use crate::CircuitBreakerDescriptor;
+    use ethers_core::types::Address;
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(
+            Address::random(),
+            CircuitBreakerDescriptor {
+                failure_threshold: 2,
+                cooldown_millis: 0,
+            },
+        );
+
+        breaker.record_failure();
+        assert_eq!(breaker.get_state(), CircuitBreakerState::Closed);
+        assert!(breaker.try_acquire());
+
+        breaker.record_failure();
+        assert_eq!(breaker.get_state(), CircuitBreakerState::Open);
+
+        // the cooldown has passed, so a trial call goes through
+        assert!(breaker.try_acquire());
+        assert_eq!(breaker.get_state(), CircuitBreakerState::HalfOpen);
+
+        breaker.record_failure();
+        assert_eq!(breaker.get_state(), CircuitBreakerState::Open);
+
+        assert!(breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.get_state(), CircuitBreakerState::Closed);
+    }
+
+    #[test]
+    fn test_open_circuit_breaker_short_circuits_until_cooldown() {
+        let breaker = CircuitBreaker::new(
+            Address::random(),
+            CircuitBreakerDescriptor {
+                failure_threshold: 1,
+                cooldown_millis: 60000,
+            },
+        );
+
+        breaker.record_failure();
+        assert!(!breaker.try_acquire());
+        assert_eq!(breaker.get_state(), CircuitBreakerState::Open);
+    }
+}
@@ -1,21 +1,44 @@
-use crate::{ConfigError, SchedulerError};
+use crate::{
+    ChainIdentity, ConfigError, GeneralChainIdentity, NodeSigner, RandomnessTask, SchedulerError,
+};
 use ethers_core::rand::{thread_rng, Rng};
-use ethers_core::{k256::ecdsa::SigningKey, types::Address};
-use ethers_signers::{coins_bip39::English, LocalWallet, MnemonicBuilder, Wallet};
+use ethers_core::{
+    k256::ecdsa::SigningKey,
+    types::{Address, U256},
+};
+use ethers_providers::{Http, Middleware, Provider};
+use ethers_signers::{
+    coins_bip39::English, LocalWallet, MnemonicBuilder, Signer, Wallet, WalletError,
+};
+use log::LevelFilter;
 use serde::de;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::env;
 use std::fmt;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
 use std::time::Duration;
 
 pub const PLACEHOLDER_ADDRESS: Address = Address::zero();
 
+const REDACTED: &str = "***";
+
+// indices from 2^31 on are reserved for hardened derivation
+pub const MAX_HD_WALLET_INDEX: u32 = 0x8000_0000;
+
 pub const DEFAULT_LISTENER_INTERVAL_MILLIS: u64 = 10000;
 pub const DEFAULT_LISTENER_USE_JITTER: bool = true;
+pub const DEFAULT_JITTER_RATIO: f64 = 0.5;
+pub const DEFAULT_SHARE_CONSISTENCY_LISTENER_INTERVAL_MILLIS: u64 = 60000;
+pub const DEFAULT_PROXY_UPGRADE_LISTENER_INTERVAL_MILLIS: u64 = 60000;
 
 pub const DEFAULT_DKG_TIMEOUT_DURATION: usize = 10 * 4;
 pub const DEFAULT_RANDOMNESS_TASK_EXCLUSIVE_WINDOW: usize = 10;
+pub const DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION: usize = 20;
+pub const DEFAULT_PARTIAL_COLLECTION_TIMEOUT_BLOCKS: usize = 100;
 pub const DEFAULT_DKG_WAIT_FOR_PHASE_INTERVAL_MILLIS: u64 = 10000;
 pub const DEFAULT_DKG_WAIT_FOR_PHASE_USE_JITTER: bool = true;
 
@@ -23,6 +46,9 @@ pub const DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_BASE: u64 = 2;
 pub const DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_FACTOR: u64 = 1000;
 pub const DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_MAX_ATTEMPTS: usize = 5;
 pub const DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_USE_JITTER: bool = true;
+pub const DEFAULT_COMMIT_PARTIAL_SIGNATURE_PER_ATTEMPT_TIMEOUT_MILLIS: u64 = 5000;
+pub const DEFAULT_COMMITTER_CHANNEL_POOL_SIZE: usize = 32;
+pub const DEFAULT_COMMITTER_CHANNEL_IDLE_TIMEOUT_MILLIS: u64 = 60000;
 
 pub const DEFAULT_CONTRACT_TRANSACTION_RETRY_BASE: u64 = 2;
 pub const DEFAULT_CONTRACT_TRANSACTION_RETRY_FACTOR: u64 = 1000;
@@ -35,18 +61,75 @@ pub const DEFAULT_CONTRACT_VIEW_RETRY_MAX_ATTEMPTS: usize = 3;
 pub const DEFAULT_CONTRACT_VIEW_RETRY_USE_JITTER: bool = true;
 
 pub const DEFAULT_PROVIDER_POLLING_INTERVAL_MILLIS: u64 = 10000;
+pub const DEFAULT_TRANSACTION_CONFIRMATIONS: usize = 1;
+pub const DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD: usize = 5;
+pub const DEFAULT_CIRCUIT_BREAKER_COOLDOWN_MILLIS: u64 = 60000;
 
 pub const DEFAULT_DYNAMIC_TASK_CLEANER_INTERVAL_MILLIS: u64 = 1000;
 
+pub const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 1000;
+pub const DEFAULT_NOTIFY_RETRY_BASE: u64 = 2;
+pub const DEFAULT_NOTIFY_RETRY_FACTOR: u64 = 500;
+pub const DEFAULT_NOTIFY_RETRY_MAX_ATTEMPTS: usize = 0;
+pub const DEFAULT_NOTIFY_RETRY_USE_JITTER: bool = true;
+
 pub const FULFILL_RANDOMNESS_GAS_EXCEPT_CALLBACK: u32 = 650000;
 pub const RANDOMNESS_REWARD_GAS: u32 = 9000;
 pub const VERIFICATION_GAS_OVER_MINIMUM_THRESHOLD: u32 = 50000;
 pub const DEFAULT_MINIMUM_THRESHOLD: u32 = 3;
+pub const DEFAULT_MAX_GROUP_SIZE: usize = 256;
+pub const DEFAULT_MAX_COMMITTER_CACHE_ENTRIES: usize = 10000;
+pub const DEFAULT_FULFILL_RANDOMNESS_GAS_LIMIT_MULTIPLIER: f64 = 1.0;
 
 pub const DEFAULT_ROLLING_LOG_FILE_SIZE: u64 = 10 * 1024 * 1024 * 1024;
 
-pub fn jitter(duration: Duration) -> Duration {
-    duration.mul_f64(thread_rng().gen_range(0.5..=1.0))
+/// Gas the adapter is expected to take to fulfill a request, following how the adapter
+/// estimates the payment to freeze for it. Every partial signature over the threshold of the
+/// group takes extra gas to verify.
+pub fn estimate_fulfill_randomness_gas(
+    callback_gas_limit: u32,
+    partial_signers_count: u32,
+    group_threshold: u32,
+) -> u32 {
+    let extra_verification_gas = if partial_signers_count > group_threshold {
+        VERIFICATION_GAS_OVER_MINIMUM_THRESHOLD * (partial_signers_count - group_threshold)
+    } else {
+        0
+    };
+
+    let extra_add_reward_gas = partial_signers_count * RANDOMNESS_REWARD_GAS;
+
+    callback_gas_limit
+        + FULFILL_RANDOMNESS_GAS_EXCEPT_CALLBACK
+        + extra_verification_gas
+        + extra_add_reward_gas
+}
+
+/// The least payment for a fulfillment to be worth its cost, i.e. cost * (1 + margin).
+/// The margin is applied in millionths so that the calculation stays in integers.
+pub fn min_fulfillment_payment(cost: U256, margin: f64) -> U256 {
+    let ratio_ppm = ((1.0 + margin) * 1_000_000.0).round().max(0.0) as u64;
+    cost * U256::from(ratio_ppm) / U256::from(1_000_000u64)
+}
+
+/// Shortens the duration by a random share of at most `jitter_ratio`, i.e. multiplies it
+/// by a factor in `1 - jitter_ratio..=1`.
+pub fn jitter(duration: Duration, jitter_ratio: f64) -> Duration {
+    jitter_with_rng(duration, jitter_ratio, &mut thread_rng())
+}
+
+/// Same as `jitter` but draws the factor from the given rng, so that a seeded one
+/// makes the intervals reproducible, e.g. in tests over the retry path.
+pub fn jitter_with_rng<R: Rng + ?Sized>(
+    duration: Duration,
+    jitter_ratio: f64,
+    rng: &mut R,
+) -> Duration {
+    duration.mul_f64(rng.gen_range((1.0 - jitter_ratio)..=1.0))
+}
+
+fn default_jitter_ratio() -> f64 {
+    DEFAULT_JITTER_RATIO
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,16 +138,59 @@ pub struct Config {
     pub node_advertised_committer_rpc_endpoint: Option<String>,
     pub node_management_rpc_endpoint: String,
     pub node_management_rpc_token: String,
+    // Bind address of the Prometheus metrics exporter, disabled if not set
+    pub node_metrics_endpoint: Option<String>,
     pub provider_endpoint: String,
     pub chain_id: usize,
     pub controller_address: String,
     pub adapter_address: String,
+    // whether the controller and the adapter are EIP-1967 proxies, whose implementations are followed across upgrades
+    #[serde(default)]
+    pub controller_is_proxy: bool,
+    #[serde(default)]
+    pub adapter_is_proxy: bool,
+    // Pairing curve of the BLS keys, which should match the deployed contracts
+    #[serde(default)]
+    pub curve: CurveType,
+    // A verifier node never joins DKG or fulfills randomness
+    #[serde(default)]
+    pub node_role: NodeRole,
+    // registers the node to the controller on start if it isn't registered yet
+    #[serde(default)]
+    pub auto_register: bool,
+    // derives the DKG keypair from the account key rather than generating it randomly
+    #[serde(default)]
+    pub derive_dkg_key_from_account: bool,
     // Data file for persistence
     pub data_path: Option<String>,
     pub account: Account,
+    // Address of the node account, resolved from the signer when the node starts
+    #[serde(skip)]
+    pub id_address: Address,
     pub listeners: Option<Vec<ListenerDescriptor>>,
+    // only the listed subscribers are spawned, all of them if not set
+    pub subscribers: Option<Vec<SubscriberType>>,
     pub logger: Option<LoggerDescriptor>,
     pub time_limits: Option<TimeLimitDescriptor>,
+    pub event_queue: Option<EventQueueDescriptor>,
+    // the least threshold of a group that the node aggregates signatures for
+    pub minimum_threshold: Option<usize>,
+    // the largest group the node takes part in or verifies, larger ones are rejected
+    pub max_group_size: Option<usize>,
+    // the most randomness results the committer holds in memory, the oldest uncommitted ones are evicted beyond it
+    pub max_committer_cache_entries: Option<usize>,
+    // safety margin applied to the gas limit of fulfill_randomness transactions
+    pub fulfill_randomness_gas_limit_multiplier: Option<f64>,
+    // max number of threads verifying committed partial signatures at the same time
+    pub committer_verification_threads: Option<usize>,
+    // only the randomness tasks matching the filter are subscribed, all of them if not set
+    pub randomness_task_filter: Option<RandomnessTaskFilter>,
+    // randomness tasks are read from this JSON file instead of the adapter logs, for local testing
+    pub randomness_task_file: Option<String>,
+    // the randomness tasks requested before this block are ignored on the first run, only the new ones are processed if not set
+    pub start_block: Option<usize>,
+    // adapters deployed on other chains, each served with its own account and endpoint
+    pub adapters: Option<Vec<Adapter>>,
 }
 
 impl Default for Config {
@@ -74,16 +200,156 @@ impl Default for Config {
             node_advertised_committer_rpc_endpoint: Some("[::1]:50060".to_string()),
             node_management_rpc_endpoint: "[::1]:50099".to_string(),
             node_management_rpc_token: "for_test".to_string(),
+            node_metrics_endpoint: None,
             provider_endpoint: "localhost:8545".to_string(),
             chain_id: 0,
             controller_address: "0xdc64a140aa3e981100a9beca4e685f962f0cf6c9".to_string(),
             adapter_address: "0xa513e6e4b8f2a923d98304ec87f64353c4d5c853".to_string(),
+            controller_is_proxy: false,
+            adapter_is_proxy: false,
+            curve: Default::default(),
+            node_role: Default::default(),
+            auto_register: false,
+            derive_dkg_key_from_account: false,
             data_path: None,
             account: Default::default(),
+            id_address: PLACEHOLDER_ADDRESS,
             listeners: Default::default(),
+            subscribers: Default::default(),
             logger: Default::default(),
             time_limits: Default::default(),
+            event_queue: Default::default(),
+            minimum_threshold: Default::default(),
+            max_group_size: Default::default(),
+            max_committer_cache_entries: Default::default(),
+            fulfill_randomness_gas_limit_multiplier: Default::default(),
+            committer_verification_threads: Default::default(),
+            randomness_task_filter: Default::default(),
+            randomness_task_file: Default::default(),
+            start_block: Default::default(),
+            adapters: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventQueueDescriptor {
+    // max number of pending events of a topic which are not yet delivered to subscribers
+    #[serde(default = "default_event_queue_capacity")]
+    pub default_capacity: usize,
+    // topic name -> capacity, e.g. NewBlock: 100
+    #[serde(default)]
+    pub capacities: HashMap<String, usize>,
+    #[serde(default)]
+    pub overflow_policy: OverflowPolicy,
+    // retries of notifying a subscriber before the event goes to the dead-letter handler
+    #[serde(default = "default_notify_retry_descriptor")]
+    pub notify_retry_descriptor: ExponentialBackoffRetryDescriptor,
+}
+
+fn default_event_queue_capacity() -> usize {
+    DEFAULT_EVENT_QUEUE_CAPACITY
+}
+
+fn default_notify_retry_descriptor() -> ExponentialBackoffRetryDescriptor {
+    ExponentialBackoffRetryDescriptor {
+        base: DEFAULT_NOTIFY_RETRY_BASE,
+        factor: DEFAULT_NOTIFY_RETRY_FACTOR,
+        max_attempts: DEFAULT_NOTIFY_RETRY_MAX_ATTEMPTS,
+        use_jitter: DEFAULT_NOTIFY_RETRY_USE_JITTER,
+        jitter_ratio: DEFAULT_JITTER_RATIO,
+        per_attempt_timeout_millis: None,
+        max_interval_millis: None,
+    }
+}
+
+impl Default for EventQueueDescriptor {
+    fn default() -> Self {
+        Self {
+            default_capacity: DEFAULT_EVENT_QUEUE_CAPACITY,
+            capacities: HashMap::new(),
+            overflow_policy: OverflowPolicy::default(),
+            notify_retry_descriptor: default_notify_retry_descriptor(),
+        }
+    }
+}
+
+impl EventQueueDescriptor {
+    pub fn get_capacity(&self, topic_name: &str) -> usize {
+        self.capacities
+            .get(topic_name)
+            .copied()
+            .unwrap_or(self.default_capacity)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum OverflowPolicy {
+    // discards the oldest pending event of the topic to make room for the new one
+    DropOldest,
+    // waits until a pending event of the topic is delivered
+    Block,
+    // rejects the new event
+    Error,
+}
+
+impl Default for OverflowPolicy {
+    fn default() -> Self {
+        OverflowPolicy::Block
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum NodeRole {
+    // participates in DKG, signs and fulfills randomness
+    Full,
+    // only follows blocks and serves the committer and management endpoints, never holds a share
+    Verifier,
+}
+
+impl Default for NodeRole {
+    fn default() -> Self {
+        NodeRole::Full
+    }
+}
+
+impl NodeRole {
+    /// Returns the features the node runs in this role, along with the listeners each of them depends on.
+    pub fn listener_dependencies(&self) -> Vec<(&'static str, Vec<ListenerType>)> {
+        let mut dependencies = vec![("block tracking", vec![ListenerType::Block])];
+
+        if *self == NodeRole::Full {
+            dependencies.push((
+                "DKG",
+                vec![
+                    ListenerType::PreGrouping,
+                    ListenerType::PostCommitGrouping,
+                    ListenerType::PostGrouping,
+                ],
+            ));
+            dependencies.push((
+                "randomness fulfillment",
+                vec![
+                    ListenerType::NewRandomnessTask,
+                    ListenerType::ReadyToHandleRandomnessTask,
+                    ListenerType::RandomnessSignatureAggregation,
+                ],
+            ));
         }
+
+        dependencies
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum CurveType {
+    Bn254,
+    Bls12_381,
+}
+
+impl Default for CurveType {
+    fn default() -> Self {
+        CurveType::Bn254
     }
 }
 
@@ -94,6 +360,12 @@ pub struct LoggerDescriptor {
     pub log_file_path: String,
     #[serde(deserialize_with = "deserialize_limit")]
     pub rolling_file_size: u64,
+    #[serde(default)]
+    pub rolling_policy: RollingPolicy,
+    #[serde(default)]
+    pub format: LogFormat,
+    // module path -> level, e.g. arpa_node_contract_client: debug
+    pub level_filters: Option<HashMap<String, String>>,
 }
 
 impl Default for LoggerDescriptor {
@@ -103,7 +375,68 @@ impl Default for LoggerDescriptor {
             context_logging: false,
             log_file_path: "log/running".to_string(),
             rolling_file_size: DEFAULT_ROLLING_LOG_FILE_SIZE,
+            rolling_policy: RollingPolicy::default(),
+            format: LogFormat::default(),
+            level_filters: None,
+        }
+    }
+}
+
+impl LoggerDescriptor {
+    /// Returns the global log level, which is read from `RUST_LOG` if it is a valid level.
+    pub fn get_root_level_filter(&self) -> LevelFilter {
+        env::var("RUST_LOG")
+            .ok()
+            .and_then(|level| level.parse::<LevelFilter>().ok())
+            .unwrap_or(LevelFilter::Info)
+    }
+
+    /// Returns the per-module log levels, which take precedence over the global log level.
+    pub fn get_level_filters(&self) -> Result<Vec<(String, LevelFilter)>, ConfigError> {
+        let mut level_filters = vec![];
+
+        if let Some(filters) = &self.level_filters {
+            for (module, level) in filters {
+                let level_filter =
+                    level
+                        .parse::<LevelFilter>()
+                        .map_err(|_| ConfigError::InvalidLogLevel {
+                            module: module.clone(),
+                            level: level.clone(),
+                        })?;
+                level_filters.push((module.clone(), level_filter));
+            }
         }
+
+        Ok(level_filters)
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RollingPolicy {
+    // deletes the log file before it would exceed rolling_file_size
+    Size,
+    // renames the log file with a date suffix every day
+    Daily,
+    // renames the log file with a date and hour suffix every hour
+    Hourly,
+}
+
+impl Default for RollingPolicy {
+    fn default() -> Self {
+        RollingPolicy::Size
+    }
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum LogFormat {
+    Plain,
+    Json,
+}
+
+impl Default for LogFormat {
+    fn default() -> Self {
+        LogFormat::Json
     }
 }
 
@@ -184,11 +517,31 @@ where
     d.deserialize_any(V)
 }
 
+/// Narrows down the randomness tasks to subscribe to on the adapter, a field that is not set
+/// matches any task.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RandomnessTaskFilter {
+    pub group_index: Option<u32>,
+    pub subscription_id: Option<u64>,
+}
+
+impl RandomnessTaskFilter {
+    pub fn matches(&self, task: &RandomnessTask) -> bool {
+        self.group_index.map_or(true, |i| i == task.group_index)
+            && self
+                .subscription_id
+                .map_or(true, |i| i == task.subscription_id)
+    }
+}
+
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub struct ListenerDescriptor {
     pub l_type: ListenerType,
     pub interval_millis: u64,
     pub use_jitter: bool,
+    // max share of the interval cut off by the jitter, in (0, 1]
+    #[serde(default = "default_jitter_ratio")]
+    pub jitter_ratio: f64,
 }
 
 impl ListenerDescriptor {
@@ -197,14 +550,23 @@ impl ListenerDescriptor {
             l_type,
             interval_millis,
             use_jitter: DEFAULT_LISTENER_USE_JITTER,
+            jitter_ratio: DEFAULT_JITTER_RATIO,
         }
     }
 
+    /// The listeners calling a view on the chain every round default to a longer interval.
     pub fn default(l_type: ListenerType) -> Self {
+        let interval_millis = match l_type {
+            ListenerType::ShareConsistency => DEFAULT_SHARE_CONSISTENCY_LISTENER_INTERVAL_MILLIS,
+            ListenerType::ProxyUpgrade => DEFAULT_PROXY_UPGRADE_LISTENER_INTERVAL_MILLIS,
+            _ => DEFAULT_LISTENER_INTERVAL_MILLIS,
+        };
+
         Self {
             l_type,
-            interval_millis: DEFAULT_LISTENER_INTERVAL_MILLIS,
+            interval_millis,
             use_jitter: DEFAULT_LISTENER_USE_JITTER,
+            jitter_ratio: DEFAULT_JITTER_RATIO,
         }
     }
 }
@@ -213,12 +575,80 @@ impl ListenerDescriptor {
 pub struct TimeLimitDescriptor {
     pub listener_interval_millis: u64,
     pub dkg_wait_for_phase_interval_millis: u64,
+    // advance DKG phases on new blocks instead of polling the coordinator on a timer
+    #[serde(default)]
+    pub dkg_wait_for_phase_on_block: bool,
     pub dkg_timeout_duration: usize,
     pub randomness_task_exclusive_window: usize,
+    // exclusive window of the urgent randomness tasks, 0 means other groups can take them over at once
+    #[serde(default)]
+    pub urgent_randomness_task_exclusive_window: usize,
+    // requests asking for no more confirmations than this are urgent, none are if not set
+    #[serde(default)]
+    pub urgent_request_confirmations: Option<u16>,
+    // blocks a claimed randomness task waits to be handled before it becomes available again
+    #[serde(default)]
+    pub randomness_task_claim_lease_duration: usize,
+    // blocks the committer collects partial signatures of a task for before giving it up
+    #[serde(default)]
+    pub partial_collection_timeout_blocks: usize,
     pub provider_polling_interval_millis: u64,
     pub contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
     pub contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
     pub commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    // fulfillment is deferred while the gas price is above this cap
+    #[serde(default)]
+    pub max_gas_price_gwei: Option<u64>,
+    // fulfillment is deferred unless the payment exceeds the estimated cost by this ratio
+    #[serde(default)]
+    pub min_fulfillment_profit_margin: Option<f64>,
+    // interval of polling the receipt of a sent transaction, provider_polling_interval_millis if not set
+    #[serde(default)]
+    pub transaction_confirmation_polling_interval_millis: Option<u64>,
+    // blocks a transaction waits for to be confirmed, counting the one it is mined in
+    #[serde(default)]
+    pub transaction_confirmations: Option<usize>,
+    // short-circuits the calls to a contract that keeps failing
+    #[serde(default)]
+    pub circuit_breaker_descriptor: CircuitBreakerDescriptor,
+}
+
+impl TimeLimitDescriptor {
+    pub fn get_transaction_confirmation_descriptor(&self) -> TransactionConfirmationDescriptor {
+        TransactionConfirmationDescriptor {
+            polling_interval_millis: self
+                .transaction_confirmation_polling_interval_millis
+                .unwrap_or(self.provider_polling_interval_millis),
+            confirmations: self
+                .transaction_confirmations
+                .unwrap_or(DEFAULT_TRANSACTION_CONFIRMATIONS),
+        }
+    }
+}
+
+/// When the calls to a contract are short-circuited, see `CircuitBreaker`.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerDescriptor {
+    // consecutive failed calls after which the breaker opens, 0 disables the breaker
+    pub failure_threshold: usize,
+    // how long the breaker stays open before letting a trial call through
+    pub cooldown_millis: u64,
+}
+
+impl Default for CircuitBreakerDescriptor {
+    fn default() -> Self {
+        CircuitBreakerDescriptor {
+            failure_threshold: DEFAULT_CIRCUIT_BREAKER_FAILURE_THRESHOLD,
+            cooldown_millis: DEFAULT_CIRCUIT_BREAKER_COOLDOWN_MILLIS,
+        }
+    }
+}
+
+/// How a sent transaction is waited for until it is confirmed.
+#[derive(Debug, Copy, Clone)]
+pub struct TransactionConfirmationDescriptor {
+    pub polling_interval_millis: u64,
+    pub confirmations: usize,
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -227,9 +657,148 @@ pub struct ExponentialBackoffRetryDescriptor {
     pub factor: u64,
     pub max_attempts: usize,
     pub use_jitter: bool,
+    // max share of the interval cut off by the jitter, in (0, 1]
+    #[serde(default = "default_jitter_ratio")]
+    pub jitter_ratio: f64,
+    // an attempt that doesn't complete in time fails and gets retried, no timeout if not set
+    #[serde(default)]
+    pub per_attempt_timeout_millis: Option<u64>,
+    // ceiling of each backoff interval before the jitter, unbounded if not set
+    #[serde(default)]
+    pub max_interval_millis: Option<u64>,
+}
+
+impl ExponentialBackoffRetryDescriptor {
+    pub fn get_max_interval(&self) -> Duration {
+        Duration::from_millis(self.max_interval_millis.unwrap_or(u64::MAX))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Yaml,
+    Toml,
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detects the format of a config file by its extension.
+    pub fn from_path(path: &Path) -> Result<Self, ConfigError> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default();
+
+        match extension.to_ascii_lowercase().as_str() {
+            "yml" | "yaml" => Ok(ConfigFormat::Yaml),
+            "toml" => Ok(ConfigFormat::Toml),
+            "json" => Ok(ConfigFormat::Json),
+            _ => Err(ConfigError::UnsupportedConfigFormat(extension.to_string())),
+        }
+    }
+
+    fn deserialize<T: de::DeserializeOwned>(&self, s: &str) -> Result<T, ConfigError> {
+        let result = match self {
+            ConfigFormat::Yaml => serde_yaml::from_str(s).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(s).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::from_str(s).map_err(|e| e.to_string()),
+        };
+
+        result.map_err(|reason| ConfigError::ConfigDeserializationError {
+            format: *self,
+            reason,
+        })
+    }
+}
+
+impl fmt::Display for ConfigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigFormat::Yaml => write!(f, "YAML"),
+            ConfigFormat::Toml => write!(f, "TOML"),
+            ConfigFormat::Json => write!(f, "JSON"),
+        }
+    }
 }
 
 impl Config {
+    /// Reads the config file, choosing the format by its extension.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+
+        let config_str =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::ConfigFileError {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+
+        Config::from_str_with_format(&config_str, format)
+    }
+
+    /// Reads a partial config file to be merged into a base config, see `Config::merge`.
+    pub fn read_overlay<P: AsRef<Path>>(path: P) -> Result<serde_json::Value, ConfigError> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)?;
+
+        let overlay_str =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::ConfigFileError {
+                path: path.display().to_string(),
+                reason: e.to_string(),
+            })?;
+
+        format.deserialize(&overlay_str)
+    }
+
+    /// Deep-merges the overlay into the base config.
+    ///
+    /// Only the fields set in the overlay win, nested items like time_limits are merged field by
+    /// field, and listeners are merged with the base listener of the same type. An empty list of
+    /// listeners clears the base ones, while an unset one keeps them.
+    pub fn merge(base: Config, overlay: serde_json::Value) -> Result<Self, ConfigError> {
+        let mut value =
+            serde_json::to_value(&base).map_err(|e| ConfigError::BadFormat(e.to_string()))?;
+
+        merge_config_values(&mut value, overlay);
+
+        serde_json::from_value(value).map_err(|e| ConfigError::ConfigDeserializationError {
+            format: ConfigFormat::Json,
+            reason: e.to_string(),
+        })
+    }
+
+    pub fn from_str_with_format(
+        config_str: &str,
+        format: ConfigFormat,
+    ) -> Result<Self, ConfigError> {
+        format.deserialize(config_str)
+    }
+
+    /// Returns the contract addresses configured as proxies, which are assumed to be validated.
+    pub fn get_proxy_addresses(&self) -> Vec<Address> {
+        let mut proxy_addresses = vec![];
+        if self.controller_is_proxy {
+            proxy_addresses.push(self.controller_address.parse().unwrap());
+        }
+        if self.adapter_is_proxy {
+            proxy_addresses.push(self.adapter_address.parse().unwrap());
+        }
+        proxy_addresses
+    }
+
+    /// Sets the address of the node account resolved from its signer, which must not be zero.
+    pub fn set_id_address(&mut self, signer: &NodeSigner) -> Result<(), ConfigError> {
+        let id_address = signer.address();
+
+        if id_address == PLACEHOLDER_ADDRESS {
+            return Err(ConfigError::ZeroIdAddress);
+        }
+
+        self.id_address = id_address;
+
+        Ok(())
+    }
+
     pub fn get_node_management_rpc_token(&self) -> Result<String, ConfigError> {
         if self.node_management_rpc_token.eq("env") {
             let token = env::var("ARPA_NODE_MANAGEMENT_SERVER_TOKEN")?;
@@ -238,6 +807,347 @@ impl Config {
         Ok(self.node_management_rpc_token.clone())
     }
 
+    /// Expands `${VAR}` references in all string fields from the environment.
+    ///
+    /// The literal `env` placeholder of secret fields is left as is and still resolved on use.
+    pub fn interpolate_env_vars(self) -> Result<Self, ConfigError> {
+        let mut value =
+            serde_json::to_value(&self).map_err(|e| ConfigError::BadFormat(e.to_string()))?;
+
+        interpolate_env_vars_in_value(&mut value)?;
+
+        serde_json::from_value(value).map_err(|e| ConfigError::BadFormat(e.to_string()))
+    }
+
+    /// Returns a copy of the config with all secrets masked, which is safe to print or log.
+    pub fn to_redacted(&self) -> Self {
+        let mut config = self.clone();
+
+        config.node_management_rpc_token = REDACTED.to_string();
+
+        redact_account(&mut config.account);
+        if let Some(adapters) = config.adapters.as_mut() {
+            for adapter in adapters.iter_mut() {
+                redact_account(&mut adapter.account);
+            }
+        }
+
+        config
+    }
+
+    pub fn is_listener_enabled(&self, l_type: ListenerType) -> bool {
+        self.listeners.as_ref().map_or(true, |listeners| {
+            listeners.iter().any(|l| l.l_type == l_type)
+        })
+    }
+
+    pub fn is_subscriber_enabled(&self, s_type: SubscriberType) -> bool {
+        self.subscribers
+            .as_ref()
+            .map_or(true, |subscribers| subscribers.contains(&s_type))
+    }
+
+    /// Checks the whole config and returns all the problems found at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut errors = vec![];
+
+        for (field, value) in [
+            ("controller_address", &self.controller_address),
+            ("adapter_address", &self.adapter_address),
+        ] {
+            if value.parse::<Address>().is_err() {
+                errors.push(ConfigError::InvalidAddress {
+                    field: field.to_string(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        let mut bind_endpoints = vec![
+            (
+                "node_committer_rpc_endpoint",
+                &self.node_committer_rpc_endpoint,
+            ),
+            (
+                "node_management_rpc_endpoint",
+                &self.node_management_rpc_endpoint,
+            ),
+        ];
+        if let Some(node_metrics_endpoint) = &self.node_metrics_endpoint {
+            bind_endpoints.push(("node_metrics_endpoint", node_metrics_endpoint));
+        }
+        for (field, value) in bind_endpoints {
+            if value.parse::<SocketAddr>().is_err() {
+                errors.push(ConfigError::InvalidEndpoint {
+                    field: field.to_string(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        if let Some(endpoint) = &self.node_advertised_committer_rpc_endpoint {
+            if !is_valid_host_port(endpoint) {
+                errors.push(ConfigError::InvalidEndpoint {
+                    field: "node_advertised_committer_rpc_endpoint".to_string(),
+                    value: endpoint.clone(),
+                });
+            }
+        }
+
+        if !is_valid_provider_endpoint(&self.provider_endpoint) {
+            errors.push(ConfigError::InvalidEndpoint {
+                field: "provider_endpoint".to_string(),
+                value: self.provider_endpoint.clone(),
+            });
+        }
+
+        match [
+            self.account.hdwallet.is_some(),
+            self.account.keystore.is_some(),
+            self.account.private_key.is_some(),
+            self.account.kms.is_some(),
+            self.account.ledger.is_some(),
+        ]
+        .iter()
+        .filter(|set| **set)
+        .count()
+        {
+            0 => errors.push(ConfigError::LackOfAccount),
+            1 => {}
+            _ => errors.push(ConfigError::MultipleAccounts),
+        }
+
+        // the account is decrypted once when the signer is built, so only its settings are checked here
+        if let Some(hd) = &self.account.hdwallet {
+            if hd.index >= MAX_HD_WALLET_INDEX {
+                errors.push(ConfigError::InvalidHDWalletIndex(hd.index));
+            }
+        }
+
+        if !self.account.eip155 && (self.account.kms.is_some() || self.account.ledger.is_some()) {
+            errors.push(ConfigError::RemoteSignerWithoutEip155);
+        }
+
+        if self.derive_dkg_key_from_account
+            && (self.account.kms.is_some() || self.account.ledger.is_some())
+        {
+            errors.push(ConfigError::RemoteSignerWithDerivedDkgKey);
+        }
+
+        if let Some(ledger) = &self.account.ledger {
+            if ledger.chain_id != self.chain_id {
+                errors.push(ConfigError::MismatchedLedgerChainId {
+                    expected: self.chain_id,
+                    found: ledger.chain_id,
+                });
+            }
+        }
+
+        if let Some(listeners) = &self.listeners {
+            if !listeners.iter().any(|l| l.l_type == ListenerType::Block) {
+                errors.push(ConfigError::LackOfBlockListener);
+            }
+
+            for listener in listeners.iter() {
+                if !is_valid_jitter_ratio(listener.jitter_ratio) {
+                    errors.push(ConfigError::InvalidJitterRatio {
+                        field: format!("{} listener", listener.l_type),
+                        value: listener.jitter_ratio,
+                    });
+                }
+            }
+        }
+
+        // selected subscribers are checked against the listeners they depend on below
+        if self.listeners.is_some() && self.subscribers.is_none() {
+            let dependencies = self.node_role.listener_dependencies();
+
+            // a missing Block listener is reported on its own above
+            let missing = dependencies
+                .iter()
+                .flat_map(|(_, listeners)| listeners.iter())
+                .filter(|&&l| l != ListenerType::Block && !self.is_listener_enabled(l))
+                .map(|l| l.to_string())
+                .collect::<Vec<_>>();
+
+            if !missing.is_empty() {
+                errors.push(ConfigError::MissingListeners {
+                    role: format!("{:?}", self.node_role),
+                    missing: missing.join(", "),
+                    dependencies: dependencies
+                        .iter()
+                        .map(|(feature, listeners)| {
+                            format!(
+                                "{} -> [{}]",
+                                feature,
+                                listeners
+                                    .iter()
+                                    .map(|l| l.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join(", ")
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; "),
+                });
+            }
+        }
+
+        if let Some(subscribers) = &self.subscribers {
+            if !subscribers.contains(&SubscriberType::Block) {
+                errors.push(ConfigError::LackOfBlockSubscriber);
+            }
+
+            for subscriber in subscribers.iter() {
+                for dependency in subscriber.dependencies() {
+                    let enabled = match dependency {
+                        TaskType::Listener(l) => self.is_listener_enabled(*l),
+                        TaskType::Subscriber(s) => self.is_subscriber_enabled(*s),
+                        TaskType::RpcServer(_) => true,
+                    };
+                    if !enabled {
+                        errors.push(ConfigError::IncoherentSubscriber {
+                            subscriber: subscriber.to_string(),
+                            dependency: match dependency {
+                                TaskType::Listener(l) => format!("{} listener", l),
+                                TaskType::Subscriber(s) => format!("{} subscriber", s),
+                                TaskType::RpcServer(r) => format!("{} rpc server", r),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(time_limits) = &self.time_limits {
+            for (field, descriptor) in [
+                (
+                    "contract_transaction_retry_descriptor",
+                    &time_limits.contract_transaction_retry_descriptor,
+                ),
+                (
+                    "contract_view_retry_descriptor",
+                    &time_limits.contract_view_retry_descriptor,
+                ),
+                (
+                    "commit_partial_signature_retry_descriptor",
+                    &time_limits.commit_partial_signature_retry_descriptor,
+                ),
+            ] {
+                if !is_valid_jitter_ratio(descriptor.jitter_ratio) {
+                    errors.push(ConfigError::InvalidJitterRatio {
+                        field: field.to_string(),
+                        value: descriptor.jitter_ratio,
+                    });
+                }
+            }
+        }
+
+        if let Some(event_queue) = &self.event_queue {
+            if event_queue.default_capacity == 0 {
+                errors.push(ConfigError::ZeroEventQueueCapacity(
+                    "default_capacity".to_string(),
+                ));
+            }
+
+            for (topic, capacity) in event_queue.capacities.iter() {
+                if *capacity == 0 {
+                    errors.push(ConfigError::ZeroEventQueueCapacity(topic.to_string()));
+                }
+            }
+        }
+
+        if let Some(adapters) = &self.adapters {
+            let mut ids = vec![];
+            for adapter in adapters.iter() {
+                if ids.contains(&adapter.id) {
+                    errors.push(ConfigError::DuplicateAdapterId(adapter.id));
+                }
+                ids.push(adapter.id);
+
+                if adapter.address.parse::<Address>().is_err() {
+                    errors.push(ConfigError::InvalidAddress {
+                        field: format!("adapters[{}].address", adapter.id),
+                        value: adapter.address.clone(),
+                    });
+                }
+
+                if !is_valid_provider_endpoint(&adapter.endpoint) {
+                    errors.push(ConfigError::InvalidEndpoint {
+                        field: format!("adapters[{}].endpoint", adapter.id),
+                        value: adapter.endpoint.clone(),
+                    });
+                }
+            }
+        }
+
+        if self.minimum_threshold == Some(0) {
+            errors.push(ConfigError::ZeroMinimumThreshold);
+        }
+
+        if self.max_group_size == Some(0) {
+            errors.push(ConfigError::ZeroMaxGroupSize);
+        }
+
+        if self.max_committer_cache_entries == Some(0) {
+            errors.push(ConfigError::ZeroMaxCommitterCacheEntries);
+        }
+
+        if let Some(margin) = self
+            .time_limits
+            .and_then(|t| t.min_fulfillment_profit_margin)
+        {
+            if !margin.is_finite() || margin <= -1.0 {
+                errors.push(ConfigError::InvalidProfitMargin(margin));
+            }
+        }
+
+        if let Some(multiplier) = self.fulfill_randomness_gas_limit_multiplier {
+            if !multiplier.is_finite() || multiplier < 1.0 {
+                errors.push(ConfigError::InvalidGasLimitMultiplier(multiplier));
+            }
+        }
+
+        if self.committer_verification_threads == Some(0) {
+            errors.push(ConfigError::ZeroCommitterVerificationThreads);
+        }
+
+        if let Some(time_limits) = &self.time_limits {
+            if time_limits.transaction_confirmation_polling_interval_millis == Some(0) {
+                errors.push(ConfigError::ZeroTransactionConfirmationSetting(
+                    "transaction_confirmation_polling_interval_millis".to_string(),
+                ));
+            }
+
+            if time_limits.transaction_confirmations == Some(0) {
+                errors.push(ConfigError::ZeroTransactionConfirmationSetting(
+                    "transaction_confirmations".to_string(),
+                ));
+            }
+
+            for (field, value) in [
+                (
+                    "randomness_task_claim_lease_duration",
+                    time_limits.randomness_task_claim_lease_duration,
+                ),
+                (
+                    "partial_collection_timeout_blocks",
+                    time_limits.partial_collection_timeout_blocks,
+                ),
+            ] {
+                if value == 0 {
+                    errors.push(ConfigError::ZeroTimeLimit(field.to_string()));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::InvalidConfig(errors))
+        }
+    }
+
     pub fn initialize(mut self) -> Self {
         if self.node_advertised_committer_rpc_endpoint.is_none() {
             self.node_advertised_committer_rpc_endpoint =
@@ -248,6 +1158,35 @@ impl Config {
             self.data_path = Some(String::from("data.sqlite"));
         }
 
+        if self.event_queue.is_none() {
+            self.event_queue = Some(EventQueueDescriptor::default());
+        }
+
+        if self.minimum_threshold.is_none() {
+            self.minimum_threshold = Some(DEFAULT_MINIMUM_THRESHOLD as usize);
+        }
+
+        if self.max_group_size.is_none() {
+            self.max_group_size = Some(DEFAULT_MAX_GROUP_SIZE);
+        }
+
+        if self.max_committer_cache_entries.is_none() {
+            self.max_committer_cache_entries = Some(DEFAULT_MAX_COMMITTER_CACHE_ENTRIES);
+        }
+
+        if self.fulfill_randomness_gas_limit_multiplier.is_none() {
+            self.fulfill_randomness_gas_limit_multiplier =
+                Some(DEFAULT_FULFILL_RANDOMNESS_GAS_LIMIT_MULTIPLIER);
+        }
+
+        if self.committer_verification_threads.is_none() {
+            self.committer_verification_threads = Some(
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1),
+            );
+        }
+
         if self.logger.is_none() {
             self.logger = Some(LoggerDescriptor::default());
         }
@@ -261,8 +1200,38 @@ impl Config {
                 ListenerDescriptor::default(ListenerType::NewRandomnessTask),
                 ListenerDescriptor::default(ListenerType::ReadyToHandleRandomnessTask),
                 ListenerDescriptor::default(ListenerType::RandomnessSignatureAggregation),
+                ListenerDescriptor::default(ListenerType::ShareConsistency),
             ];
             self.listeners = Some(listeners);
+
+            if self.controller_is_proxy || self.adapter_is_proxy {
+                self.listeners
+                    .as_mut()
+                    .unwrap()
+                    .push(ListenerDescriptor::default(ListenerType::ProxyUpgrade));
+            }
+        }
+
+        if self.node_role == NodeRole::Verifier {
+            if let Some(listeners) = self.listeners.as_mut() {
+                listeners.retain(|l| {
+                    l.l_type == ListenerType::Block || l.l_type == ListenerType::ProxyUpgrade
+                });
+            }
+        }
+
+        // defaulted on their own, as they are left unset by the configs written before them
+        // along with any of the fields defaulted below
+        if let Some(time_limits) = self.time_limits.as_mut() {
+            if time_limits.randomness_task_claim_lease_duration == 0 {
+                time_limits.randomness_task_claim_lease_duration =
+                    DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION;
+            }
+
+            if time_limits.partial_collection_timeout_blocks == 0 {
+                time_limits.partial_collection_timeout_blocks =
+                    DEFAULT_PARTIAL_COLLECTION_TIMEOUT_BLOCKS;
+            }
         }
 
         match self.time_limits.as_mut() {
@@ -289,27 +1258,49 @@ impl Config {
                 self.time_limits = Some(TimeLimitDescriptor {
                     listener_interval_millis: DEFAULT_LISTENER_INTERVAL_MILLIS,
                     dkg_wait_for_phase_interval_millis: DEFAULT_DKG_WAIT_FOR_PHASE_INTERVAL_MILLIS,
+                    dkg_wait_for_phase_on_block: false,
                     dkg_timeout_duration: DEFAULT_DKG_TIMEOUT_DURATION,
                     randomness_task_exclusive_window: DEFAULT_RANDOMNESS_TASK_EXCLUSIVE_WINDOW,
+                    urgent_randomness_task_exclusive_window: 0,
+                    urgent_request_confirmations: None,
+                    randomness_task_claim_lease_duration:
+                        DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+                    partial_collection_timeout_blocks: DEFAULT_PARTIAL_COLLECTION_TIMEOUT_BLOCKS,
                     provider_polling_interval_millis: DEFAULT_PROVIDER_POLLING_INTERVAL_MILLIS,
                     contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor {
                         base: DEFAULT_CONTRACT_TRANSACTION_RETRY_BASE,
                         factor: DEFAULT_CONTRACT_TRANSACTION_RETRY_FACTOR,
                         max_attempts: DEFAULT_CONTRACT_TRANSACTION_RETRY_MAX_ATTEMPTS,
                         use_jitter: DEFAULT_CONTRACT_TRANSACTION_RETRY_USE_JITTER,
+                        jitter_ratio: DEFAULT_JITTER_RATIO,
+                        per_attempt_timeout_millis: None,
+                        max_interval_millis: None,
                     },
                     contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor {
                         base: DEFAULT_CONTRACT_VIEW_RETRY_BASE,
                         factor: DEFAULT_CONTRACT_VIEW_RETRY_FACTOR,
                         max_attempts: DEFAULT_CONTRACT_VIEW_RETRY_MAX_ATTEMPTS,
                         use_jitter: DEFAULT_CONTRACT_VIEW_RETRY_USE_JITTER,
+                        jitter_ratio: DEFAULT_JITTER_RATIO,
+                        per_attempt_timeout_millis: None,
+                        max_interval_millis: None,
                     },
                     commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor {
                         base: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_BASE,
                         factor: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_FACTOR,
                         max_attempts: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_MAX_ATTEMPTS,
                         use_jitter: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_USE_JITTER,
+                        jitter_ratio: DEFAULT_JITTER_RATIO,
+                        per_attempt_timeout_millis: Some(
+                            DEFAULT_COMMIT_PARTIAL_SIGNATURE_PER_ATTEMPT_TIMEOUT_MILLIS,
+                        ),
+                        max_interval_millis: None,
                     },
+                    max_gas_price_gwei: None,
+                    min_fulfillment_profit_margin: None,
+                    transaction_confirmation_polling_interval_millis: None,
+                    transaction_confirmations: None,
+                    circuit_breaker_descriptor: CircuitBreakerDescriptor::default(),
                 });
             }
         };
@@ -317,6 +1308,95 @@ impl Config {
     }
 }
 
+fn merge_config_values(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    match (base, overlay) {
+        // an unset item of the overlay keeps the base one
+        (_, serde_json::Value::Null) => {}
+        (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(base_value) => merge_config_values(base_value, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        // an empty list of the overlay replaces the base one rather than keeping it as is
+        (serde_json::Value::Array(base), serde_json::Value::Array(overlay))
+            if !overlay.is_empty() && overlay.iter().all(|v| v.get("l_type").is_some()) =>
+        {
+            for listener in overlay {
+                match base
+                    .iter_mut()
+                    .find(|l| l.get("l_type") == listener.get("l_type"))
+                {
+                    Some(base_listener) => merge_config_values(base_listener, listener),
+                    None => base.push(listener),
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn interpolate_env_vars_in_value(value: &mut serde_json::Value) -> Result<(), ConfigError> {
+    match value {
+        serde_json::Value::String(s) => *s = interpolate_env_vars(s)?,
+        serde_json::Value::Array(values) => {
+            for v in values {
+                interpolate_env_vars_in_value(v)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for v in map.values_mut() {
+                interpolate_env_vars_in_value(v)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+pub fn interpolate_env_vars(s: &str) -> Result<String, ConfigError> {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+
+        let reference = &rest[start + 2..];
+        let end = reference
+            .find('}')
+            .ok_or_else(|| ConfigError::InvalidEnvVarReference(s.to_string()))?;
+
+        let name = &reference[..end];
+        let var = env::var(name).map_err(|_| ConfigError::UndefinedEnvVar(name.to_string()))?;
+        result.push_str(&var);
+
+        rest = &reference[end + 1..];
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn is_valid_jitter_ratio(jitter_ratio: f64) -> bool {
+    jitter_ratio > 0.0 && jitter_ratio <= 1.0
+}
+
+fn is_valid_host_port(endpoint: &str) -> bool {
+    match endpoint.rsplit_once(':') {
+        Some((host, port)) => !host.is_empty() && port.parse::<u16>().is_ok(),
+        None => false,
+    }
+}
+
+fn is_valid_provider_endpoint(endpoint: &str) -> bool {
+    (endpoint.starts_with("http://") || endpoint.starts_with("https://"))
+        && Http::from_str(endpoint).is_ok()
+}
+
 #[derive(Debug, Eq, Clone, Copy, Hash, PartialEq)]
 pub enum TaskType {
     Listener(ListenerType),
@@ -343,6 +1423,8 @@ pub enum ListenerType {
     NewRandomnessTask,
     ReadyToHandleRandomnessTask,
     RandomnessSignatureAggregation,
+    ShareConsistency,
+    ProxyUpgrade,
 }
 
 impl TryFrom<i32> for ListenerType {
@@ -357,6 +1439,8 @@ impl TryFrom<i32> for ListenerType {
             4 => Ok(ListenerType::NewRandomnessTask),
             5 => Ok(ListenerType::ReadyToHandleRandomnessTask),
             6 => Ok(ListenerType::RandomnessSignatureAggregation),
+            7 => Ok(ListenerType::ShareConsistency),
+            8 => Ok(ListenerType::ProxyUpgrade),
             _ => Err(SchedulerError::TaskNotFound),
         }
     }
@@ -374,11 +1458,13 @@ impl std::fmt::Display for ListenerType {
             }
             ListenerType::PostCommitGrouping => write!(f, "PostCommitGrouping"),
             ListenerType::NewRandomnessTask => write!(f, "NewRandomnessTask"),
+            ListenerType::ShareConsistency => write!(f, "ShareConsistency"),
+            ListenerType::ProxyUpgrade => write!(f, "ProxyUpgrade"),
         }
     }
 }
 
-#[derive(Debug, Eq, Clone, Copy, Hash, PartialEq)]
+#[derive(Debug, Eq, Clone, Copy, Hash, PartialEq, Serialize, Deserialize)]
 pub enum SubscriberType {
     Block,
     PreGrouping,
@@ -407,10 +1493,38 @@ impl std::fmt::Display for SubscriberType {
     }
 }
 
+impl SubscriberType {
+    /// Returns the tasks that produce the events this subscriber handles.
+    pub fn dependencies(&self) -> &'static [TaskType] {
+        match self {
+            SubscriberType::Block => &[TaskType::Listener(ListenerType::Block)],
+            SubscriberType::PreGrouping => &[TaskType::Listener(ListenerType::PreGrouping)],
+            SubscriberType::InGrouping => &[TaskType::Subscriber(SubscriberType::PreGrouping)],
+            SubscriberType::PostSuccessGrouping => {
+                &[TaskType::Listener(ListenerType::PostCommitGrouping)]
+            }
+            SubscriberType::PostGrouping => &[TaskType::Listener(ListenerType::PostGrouping)],
+            SubscriberType::ReadyToHandleRandomnessTask => &[
+                TaskType::Listener(ListenerType::NewRandomnessTask),
+                TaskType::Listener(ListenerType::ReadyToHandleRandomnessTask),
+            ],
+            SubscriberType::RandomnessSignatureAggregation => &[
+                TaskType::Listener(ListenerType::NewRandomnessTask),
+                TaskType::Listener(ListenerType::RandomnessSignatureAggregation),
+            ],
+            // partial signatures are sent by the tasks of the ReadyToHandleRandomnessTask subscriber
+            SubscriberType::SendingPartialSignature => &[TaskType::Subscriber(
+                SubscriberType::ReadyToHandleRandomnessTask,
+            )],
+        }
+    }
+}
+
 #[derive(Debug, Eq, Clone, Copy, Hash, PartialEq)]
 pub enum RpcServerType {
     Committer,
     Management,
+    Metrics,
 }
 
 impl std::fmt::Display for RpcServerType {
@@ -418,24 +1532,54 @@ impl std::fmt::Display for RpcServerType {
         match self {
             RpcServerType::Committer => write!(f, "Committer"),
             RpcServerType::Management => write!(f, "Management"),
+            RpcServerType::Metrics => write!(f, "Metrics"),
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Adapter {
     pub id: usize,
     pub name: String,
     pub endpoint: String,
     pub account: Account,
+    pub chain_id: usize,
+    pub address: String,
+    // fall back to the descriptors of time_limits if not set
+    pub contract_transaction_retry_descriptor: Option<ExponentialBackoffRetryDescriptor>,
+    pub contract_view_retry_descriptor: Option<ExponentialBackoffRetryDescriptor>,
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Account {
     pub hdwallet: Option<HDWallet>,
     pub keystore: Option<Keystore>,
     // not recommended
     pub private_key: Option<String>,
+    // requires the `aws-kms` feature
+    pub kms: Option<KmsConfig>,
+    // requires the `ledger` feature
+    pub ledger: Option<LedgerConfig>,
+    // disabled only for private networks without replay protection, where a local key signs legacy transactions
+    #[serde(default = "default_eip155")]
+    pub eip155: bool,
+}
+
+fn default_eip155() -> bool {
+    true
+}
+
+impl Default for Account {
+    fn default() -> Self {
+        Self {
+            hdwallet: None,
+            keystore: None,
+            private_key: None,
+            kms: None,
+            ledger: None,
+            eip155: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -444,6 +1588,22 @@ pub struct Keystore {
     pub password: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KmsConfig {
+    pub key_id: String,
+    pub region: String,
+    // the key is not available locally, so the node database is encrypted with this instead
+    pub db_cipher_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerConfig {
+    pub derivation_path: String,
+    pub chain_id: usize,
+    // the key is not available locally, so the node database is encrypted with this instead
+    pub db_cipher_key: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HDWallet {
     pub mnemonic: String,
@@ -457,19 +1617,13 @@ pub fn build_wallet_from_config(account: &Account) -> Result<Wallet<SigningKey>,
         if hd.mnemonic.eq("env") {
             hd.mnemonic = env::var("ARPA_NODE_HD_ACCOUNT_MNEMONIC")?;
         }
-        let mut wallet = MnemonicBuilder::<English>::default().phrase(&*hd.mnemonic);
-
-        if hd.path.is_some() {
-            wallet = wallet.derivation_path(&hd.path.unwrap()).unwrap();
-        }
-        if hd.passphrase.is_some() {
-            wallet = wallet.password(&hd.passphrase.unwrap());
-        }
-        return Ok(wallet.index(hd.index).unwrap().build()?);
+        return build_hd_wallet(&hd);
     } else if account.keystore.is_some() {
         let mut keystore = account.keystore.clone().unwrap();
         if keystore.password.eq("env") {
             keystore.password = env::var("ARPA_NODE_ACCOUNT_KEYSTORE_PASSWORD")?;
+        } else if let Some(path) = keystore.password.strip_prefix("file:") {
+            keystore.password = read_secret_file(path)?;
         }
         return Ok(LocalWallet::decrypt_keystore(
             &keystore.path,
@@ -486,11 +1640,364 @@ pub fn build_wallet_from_config(account: &Account) -> Result<Wallet<SigningKey>,
     Err(ConfigError::LackOfAccount)
 }
 
+fn build_hd_wallet(hd: &HDWallet) -> Result<Wallet<SigningKey>, ConfigError> {
+    if hd.index >= MAX_HD_WALLET_INDEX {
+        return Err(ConfigError::InvalidHDWalletIndex(hd.index));
+    }
+
+    let mut wallet = MnemonicBuilder::<English>::default().phrase(&*hd.mnemonic);
+
+    if let Some(path) = &hd.path {
+        wallet = wallet.derivation_path(path)?;
+    }
+    if let Some(passphrase) = &hd.passphrase {
+        wallet = wallet.password(passphrase);
+    }
+    Ok(wallet.index(hd.index)?.build()?)
+}
+
+/// Lists the first `count` accounts derived from the mnemonic the same way as a configured `hdwallet`,
+/// so that operators running several nodes from one seed can pick their indices.
+pub fn derive_accounts(
+    mnemonic: &str,
+    path: Option<&str>,
+    count: u32,
+) -> Result<Vec<(u32, Address)>, ConfigError> {
+    (0..count)
+        .map(|index| {
+            let hd = HDWallet {
+                mnemonic: mnemonic.to_string(),
+                path: path.map(|p| p.to_string()),
+                index,
+                passphrase: None,
+            };
+            build_hd_wallet(&hd).map(|wallet| (index, wallet.address()))
+        })
+        .collect()
+}
+
+/// Derives the wallet at `index` from the mnemonic and writes it to an encrypted keystore file at `path`,
+/// which can then be configured as the keystore account of the node.
+pub fn create_keystore(
+    mnemonic: &str,
+    index: u32,
+    path: impl AsRef<Path>,
+    password: &str,
+) -> Result<Address, ConfigError> {
+    let wallet = build_hd_wallet(&HDWallet {
+        mnemonic: mnemonic.to_string(),
+        path: None,
+        index,
+        passphrase: None,
+    })?;
+
+    let path = path.as_ref();
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path.file_name().and_then(|name| name.to_str());
+
+    eth_keystore::encrypt_key(
+        dir,
+        &mut thread_rng(),
+        wallet.signer().to_bytes(),
+        password,
+        name,
+    )
+    .map_err(WalletError::from)?;
+
+    Ok(wallet.address())
+}
+
+fn redact_account(account: &mut Account) {
+    if let Some(hd) = account.hdwallet.as_mut() {
+        hd.mnemonic = REDACTED.to_string();
+        if hd.passphrase.is_some() {
+            hd.passphrase = Some(REDACTED.to_string());
+        }
+    }
+    if let Some(keystore) = account.keystore.as_mut() {
+        keystore.password = REDACTED.to_string();
+    }
+    if account.private_key.is_some() {
+        account.private_key = Some(REDACTED.to_string());
+    }
+    if let Some(kms) = account.kms.as_mut() {
+        kms.db_cipher_key = REDACTED.to_string();
+    }
+    if let Some(ledger) = account.ledger.as_mut() {
+        ledger.db_cipher_key = REDACTED.to_string();
+    }
+}
+
+/// Reads a secret mounted as a file, e.g. a kubernetes secret, ignoring surrounding whitespace.
+fn read_secret_file(path: &str) -> Result<String, ConfigError> {
+    let secret = std::fs::read_to_string(path).map_err(|e| ConfigError::SecretFileError {
+        path: path.to_string(),
+        reason: e.to_string(),
+    })?;
+
+    let secret = secret.trim();
+
+    if secret.is_empty() {
+        return Err(ConfigError::SecretFileError {
+            path: path.to_string(),
+            reason: "file is empty".to_string(),
+        });
+    }
+
+    Ok(secret.to_string())
+}
+
+/// Builds the signer of the node, which may hold the key locally or delegate signing to KMS or a Ledger device.
+pub async fn build_signer_from_config(
+    account: &Account,
+    chain_id: usize,
+) -> Result<NodeSigner, ConfigError> {
+    if let Some(kms) = &account.kms {
+        return build_kms_signer(kms, chain_id).await;
+    }
+
+    if let Some(ledger) = &account.ledger {
+        return build_ledger_signer(ledger).await;
+    }
+
+    let wallet = build_wallet_from_config(account)?;
+
+    if account.eip155 {
+        Ok(wallet.into())
+    } else {
+        Ok(NodeSigner::Legacy(wallet))
+    }
+}
+
+/// Compares the chain id reported by the provider with the configured one,
+/// which the signer tags every transaction with for replay protection.
+pub async fn verify_chain_id(
+    provider: &Provider<Http>,
+    configured: usize,
+) -> Result<(), ConfigError> {
+    let actual = provider.get_chainid().await?.as_usize();
+
+    if actual != configured {
+        return Err(ConfigError::ChainIdMismatch { configured, actual });
+    }
+
+    Ok(())
+}
+
+/// Builds the chain identity of the main chain from the initialized and validated config,
+/// signed for by the node account.
+pub fn build_main_chain_identity(config: &Config, wallet: NodeSigner) -> GeneralChainIdentity {
+    let time_limits = config.time_limits.unwrap();
+
+    GeneralChainIdentity::new(
+        config.chain_id,
+        wallet,
+        config.provider_endpoint.clone(),
+        time_limits.provider_polling_interval_millis,
+        config
+            .controller_address
+            .parse()
+            .expect("bad format of controller_address"),
+        config
+            .adapter_address
+            .parse()
+            .expect("bad format of adapter_address"),
+        time_limits.contract_transaction_retry_descriptor,
+        time_limits.contract_view_retry_descriptor,
+        time_limits.get_transaction_confirmation_descriptor(),
+        time_limits.circuit_breaker_descriptor,
+        config.fulfill_randomness_gas_limit_multiplier.unwrap(),
+    )
+    .with_proxies(config.get_proxy_addresses())
+    .with_max_group_size(config.max_group_size.unwrap())
+}
+
+/// Builds one chain identity per configured adapter, keyed by adapter id, and checks that each
+/// endpoint serves the chain its adapter is configured with.
+pub async fn build_adapter_identities(
+    config: &Config,
+) -> Result<HashMap<usize, GeneralChainIdentity>, ConfigError> {
+    let mut identities = HashMap::new();
+
+    for adapter in config.adapters.iter().flatten() {
+        let signer = build_signer_from_config(&adapter.account, adapter.chain_id).await?;
+
+        let identity = build_adapter_identity(config, adapter, signer)?;
+
+        verify_chain_id(&identity.get_provider(), adapter.chain_id).await?;
+
+        if identities.insert(adapter.id, identity).is_some() {
+            return Err(ConfigError::DuplicateAdapterId(adapter.id));
+        }
+    }
+
+    Ok(identities)
+}
+
+/// Builds the chain identity of an adapter from the initialized config, signed for by the
+/// account of the adapter. Adapter chains have no controller, and the retry descriptors not
+/// configured for the adapter fall back to the ones of `time_limits`.
+pub fn build_adapter_identity(
+    config: &Config,
+    adapter: &Adapter,
+    signer: NodeSigner,
+) -> Result<GeneralChainIdentity, ConfigError> {
+    let time_limits = config.time_limits.unwrap();
+
+    let adapter_address =
+        adapter
+            .address
+            .parse::<Address>()
+            .map_err(|_| ConfigError::InvalidAddress {
+                field: format!("adapters[{}].address", adapter.id),
+                value: adapter.address.clone(),
+            })?;
+
+    let identity = GeneralChainIdentity::new(
+        adapter.chain_id,
+        signer,
+        adapter.endpoint.clone(),
+        time_limits.provider_polling_interval_millis,
+        PLACEHOLDER_ADDRESS,
+        adapter_address,
+        adapter
+            .contract_transaction_retry_descriptor
+            .unwrap_or(time_limits.contract_transaction_retry_descriptor),
+        adapter
+            .contract_view_retry_descriptor
+            .unwrap_or(time_limits.contract_view_retry_descriptor),
+        time_limits.get_transaction_confirmation_descriptor(),
+        time_limits.circuit_breaker_descriptor,
+        config.fulfill_randomness_gas_limit_multiplier.unwrap(),
+    )
+    .with_max_group_size(config.max_group_size.unwrap());
+
+    Ok(identity)
+}
+
+#[cfg(feature = "aws-kms")]
+async fn build_kms_signer(kms: &KmsConfig, chain_id: usize) -> Result<NodeSigner, ConfigError> {
+    use crate::KmsSigner;
+    use rusoto_core::Region;
+    use rusoto_kms::KmsClient;
+
+    let region = Region::from_str(&kms.region)
+        .map_err(|_| ConfigError::InvalidKmsRegion(kms.region.clone()))?;
+
+    let signer = KmsSigner::new(KmsClient::new(region), kms.key_id.clone(), chain_id as u64)
+        .await
+        .map_err(|e| ConfigError::BuildingKmsSignerError(e.to_string()))?;
+
+    Ok(signer.into())
+}
+
+#[cfg(not(feature = "aws-kms"))]
+async fn build_kms_signer(_kms: &KmsConfig, _chain_id: usize) -> Result<NodeSigner, ConfigError> {
+    Err(ConfigError::FeatureNotEnabled("aws-kms".to_string()))
+}
+
+#[cfg(feature = "ledger")]
+async fn build_ledger_signer(ledger: &LedgerConfig) -> Result<NodeSigner, ConfigError> {
+    use ethers_signers::{HDPath, Ledger};
+
+    let signer = Ledger::new(
+        HDPath::Other(ledger.derivation_path.clone()),
+        ledger.chain_id as u64,
+    )
+    .await
+    .map_err(|e| ConfigError::BuildingLedgerSignerError(e.to_string()))?;
+
+    Ok(signer.into())
+}
+
+#[cfg(not(feature = "ledger"))]
+async fn build_ledger_signer(_ledger: &LedgerConfig) -> Result<NodeSigner, ConfigError> {
+    Err(ConfigError::FeatureNotEnabled("ledger".to_string()))
+}
+
+/// Returns the key to encrypt the node database with.
+///
+/// A local signer uses its private key, while a remote signer uses the cipher key configured with it.
+#[cfg_attr(
+    not(any(feature = "aws-kms", feature = "ledger")),
+    allow(unused_variables)
+)]
+pub fn build_db_cipher_key(account: &Account, signer: &NodeSigner) -> Result<Vec<u8>, ConfigError> {
+    match signer {
+        NodeSigner::Local(wallet) | NodeSigner::Legacy(wallet) => {
+            Ok(wallet.signer().to_bytes().to_vec())
+        }
+        #[cfg(any(feature = "aws-kms", feature = "ledger"))]
+        _ => configured_db_cipher_key(account)?.ok_or(ConfigError::LackOfAccount),
+    }
+}
+
+/// Returns the key to encrypt the node database with from the account alone, without reaching
+/// KMS or a Ledger device, so that the database can be read on a host without them. The key of
+/// a local account still has to be decrypted or derived.
+pub fn build_db_cipher_key_from_account(account: &Account) -> Result<Vec<u8>, ConfigError> {
+    match configured_db_cipher_key(account)? {
+        Some(cipher_key) => Ok(cipher_key),
+        None => Ok(build_wallet_from_config(account)?
+            .signer()
+            .to_bytes()
+            .to_vec()),
+    }
+}
+
+/// The cipher key configured with a remote signer, none for a local account.
+fn configured_db_cipher_key(account: &Account) -> Result<Option<Vec<u8>>, ConfigError> {
+    let cipher_key = account
+        .kms
+        .as_ref()
+        .map(|kms| kms.db_cipher_key.clone())
+        .or_else(|| {
+            account
+                .ledger
+                .as_ref()
+                .map(|ledger| ledger.db_cipher_key.clone())
+        });
+
+    match cipher_key {
+        Some(cipher_key) if cipher_key.eq("env") => {
+            Ok(Some(env::var("ARPA_NODE_DB_CIPHER_KEY")?.into_bytes()))
+        }
+        Some(cipher_key) => Ok(Some(cipher_key.into_bytes())),
+        None => Ok(None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{fs::read_to_string, time::Duration};
 
-    use crate::{jitter, Config, ListenerType};
+    use crate::ChainIdentity;
+    use crate::{
+        estimate_fulfill_randomness_gas, jitter, jitter_with_rng, min_fulfillment_payment, Config,
+        ConfigError, ConfigFormat, ListenerDescriptor, ListenerType, LoggerDescriptor, NodeRole,
+        SubscriberType, DEFAULT_JITTER_RATIO, DEFAULT_LISTENER_INTERVAL_MILLIS,
+        DEFAULT_PARTIAL_COLLECTION_TIMEOUT_BLOCKS, DEFAULT_PROVIDER_POLLING_INTERVAL_MILLIS,
+        DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+        DEFAULT_SHARE_CONSISTENCY_LISTENER_INTERVAL_MILLIS, DEFAULT_TRANSACTION_CONFIRMATIONS,
+        FULFILL_RANDOMNESS_GAS_EXCEPT_CALLBACK, RANDOMNESS_REWARD_GAS,
+        VERIFICATION_GAS_OVER_MINIMUM_THRESHOLD,
+    };
+    use ethers_core::types::{Address, U256};
+    use ethers_signers::Signer;
+    use log::LevelFilter;
+    use std::collections::HashMap;
+
+    /// A config which passes validation once initialized.
+    fn valid_config() -> Config {
+        let mut config = Config::default();
+        config.provider_endpoint = "http://localhost:8545".to_string();
+        config.account.private_key =
+            Some("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318".to_string());
+        config
+    }
 
     #[test]
     fn test_enum_serialization() {
@@ -514,12 +2021,704 @@ mod tests {
         println!("config = {:?}", config.initialize());
     }
 
+    #[test]
+    fn test_proxy_upgrade_listener() {
+        let config = Config::default().initialize();
+        assert!(config.get_proxy_addresses().is_empty());
+        assert!(!config
+            .listeners
+            .unwrap()
+            .iter()
+            .any(|l| l.l_type == ListenerType::ProxyUpgrade));
+
+        let config = Config {
+            adapter_is_proxy: true,
+            node_role: NodeRole::Verifier,
+            ..Default::default()
+        }
+        .initialize();
+        assert_eq!(
+            config.get_proxy_addresses(),
+            vec![config.adapter_address.parse::<Address>().unwrap()]
+        );
+        // a verifier still follows the upgrades of the proxies
+        let listeners = config.listeners.unwrap();
+        assert_eq!(listeners.len(), 2);
+        assert!(listeners
+            .iter()
+            .any(|l| l.l_type == ListenerType::ProxyUpgrade));
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = Config::from_file("../../../conf/config.yml").unwrap();
+        let base_time_limits = base.time_limits.clone().unwrap();
+
+        let overlay = serde_json::json!({
+            "provider_endpoint": "http://10.0.0.1:8545",
+            "node_metrics_endpoint": null,
+            "listeners": [
+                { "l_type": "Block", "interval_millis": 500 },
+                { "l_type": "ProxyUpgrade", "interval_millis": 30000, "use_jitter": false }
+            ],
+            "time_limits": {
+                "listener_interval_millis": 5000,
+                "contract_view_retry_descriptor": { "max_attempts": 10 }
+            }
+        });
+
+        let config = Config::merge(base.clone(), overlay).unwrap();
+
+        assert_eq!(config.provider_endpoint, "http://10.0.0.1:8545");
+        assert_eq!(config.node_metrics_endpoint, base.node_metrics_endpoint);
+        assert_eq!(config.chain_id, base.chain_id);
+
+        let listeners = config.listeners.unwrap();
+        assert_eq!(listeners.len(), base.listeners.as_ref().unwrap().len() + 1);
+        let block_listener = listeners
+            .iter()
+            .find(|l| l.l_type == ListenerType::Block)
+            .unwrap();
+        assert_eq!(block_listener.interval_millis, 500);
+        assert!(block_listener.use_jitter);
+        assert!(listeners
+            .iter()
+            .any(|l| l.l_type == ListenerType::ProxyUpgrade && !l.use_jitter));
+
+        let time_limits = config.time_limits.unwrap();
+        assert_eq!(time_limits.listener_interval_millis, 5000);
+        assert_eq!(
+            time_limits.dkg_timeout_duration,
+            base_time_limits.dkg_timeout_duration
+        );
+        assert_eq!(time_limits.contract_view_retry_descriptor.max_attempts, 10);
+        assert_eq!(
+            time_limits.contract_view_retry_descriptor.factor,
+            base_time_limits.contract_view_retry_descriptor.factor
+        );
+
+        let base_listeners_len = base.listeners.as_ref().unwrap().len();
+        let config = Config::merge(base.clone(), serde_json::json!({ "listeners": null })).unwrap();
+        assert_eq!(config.listeners.unwrap().len(), base_listeners_len);
+
+        let config = Config::merge(base, serde_json::json!({ "listeners": [] })).unwrap();
+        assert!(config.listeners.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_deserialization_from_all_formats() {
+        let config = Config::from_file("../../../conf/config.yml").unwrap();
+        let expected = serde_json::to_value(&config).unwrap();
+
+        let json_str = serde_json::to_string(&config).unwrap();
+        let toml_str = toml::Value::try_from(&config).unwrap().to_string();
+
+        for (config_str, format) in [
+            (json_str, ConfigFormat::Json),
+            (toml_str, ConfigFormat::Toml),
+        ] {
+            let config = Config::from_str_with_format(&config_str, format).unwrap();
+            assert_eq!(serde_json::to_value(&config).unwrap(), expected);
+        }
+
+        assert!(matches!(
+            Config::from_file("config.ini"),
+            Err(ConfigError::UnsupportedConfigFormat(extension)) if extension == "ini"
+        ));
+        assert!(matches!(
+            Config::from_str_with_format("provider_endpoint: [", ConfigFormat::Yaml),
+            Err(ConfigError::ConfigDeserializationError {
+                format: ConfigFormat::Yaml,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn test_jitter() {
         for _ in 0..100 {
-            let jitter = jitter(Duration::from_millis(1000));
+            let jitter = jitter(Duration::from_millis(1000), DEFAULT_JITTER_RATIO);
             // println!("jitter = {:?}", jitter(Duration::from_millis(1000)));
             assert!(500 <= jitter.as_millis() && jitter.as_millis() <= 1000);
         }
+
+        for _ in 0..100 {
+            let jitter = jitter(Duration::from_millis(1000), 0.1);
+            assert!(900 <= jitter.as_millis() && jitter.as_millis() <= 1000);
+        }
+    }
+
+    #[test]
+    fn test_jitter_with_seeded_rng() {
+        use ethers_core::rand::{rngs::StdRng, SeedableRng};
+
+        let duration = Duration::from_millis(1000);
+
+        let draw = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..100)
+                .map(|_| jitter_with_rng(duration, DEFAULT_JITTER_RATIO, &mut rng))
+                .collect::<Vec<_>>()
+        };
+
+        let jitters = draw(42);
+
+        // the jitter shortens the duration by at most the jitter ratio
+        for jitter in jitters.iter() {
+            assert!(*jitter <= duration);
+            assert!(*jitter >= duration.mul_f64(1.0 - DEFAULT_JITTER_RATIO));
+        }
+
+        // the same seed reproduces the same sequence, another one doesn't
+        assert_eq!(jitters, draw(42));
+        assert_ne!(jitters, draw(43));
+    }
+
+    #[test]
+    fn test_validate_jitter_ratio() {
+        let mut config = valid_config().initialize();
+
+        let time_limits = config.time_limits.as_mut().unwrap();
+        time_limits.contract_view_retry_descriptor.jitter_ratio = 0.0;
+        time_limits
+            .commit_partial_signature_retry_descriptor
+            .jitter_ratio = 1.5;
+        config.listeners.as_mut().unwrap()[0].jitter_ratio = 1.0;
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => assert_eq!(errors.len(), 2),
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_min_fulfillment_payment() {
+        let cost = U256::from(2_000_000u64);
+        assert_eq!(min_fulfillment_payment(cost, 0.0), cost);
+        assert_eq!(min_fulfillment_payment(cost, 0.1), U256::from(2_200_000u64));
+        assert_eq!(
+            min_fulfillment_payment(cost, -0.5),
+            U256::from(1_000_000u64)
+        );
+    }
+
+    #[test]
+    fn test_estimate_fulfill_randomness_gas() {
+        let base = 100_000 + FULFILL_RANDOMNESS_GAS_EXCEPT_CALLBACK;
+        assert_eq!(
+            estimate_fulfill_randomness_gas(100_000, 3, 3),
+            base + 3 * RANDOMNESS_REWARD_GAS
+        );
+        // only the partial signatures over the threshold of the group take the extra gas
+        assert_eq!(
+            estimate_fulfill_randomness_gas(100_000, 6, 4),
+            base + 6 * RANDOMNESS_REWARD_GAS + 2 * VERIFICATION_GAS_OVER_MINIMUM_THRESHOLD
+        );
+    }
+
+    #[test]
+    fn test_level_filters() {
+        let mut level_filters = HashMap::new();
+        level_filters.insert("arpa_node_contract_client".to_string(), "debug".to_string());
+
+        let mut logger = LoggerDescriptor {
+            level_filters: Some(level_filters),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            logger.get_level_filters().unwrap(),
+            vec![("arpa_node_contract_client".to_string(), LevelFilter::Debug)]
+        );
+
+        logger
+            .level_filters
+            .as_mut()
+            .unwrap()
+            .insert("arpa_node_dal".to_string(), "verbose".to_string());
+
+        assert!(logger.get_level_filters().is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut config = valid_config().initialize();
+
+        assert!(config.validate().is_ok());
+
+        config.controller_address = "0x123".to_string();
+        config.provider_endpoint = "localhost:8545".to_string();
+        config.listeners = Some(vec![ListenerDescriptor::default(ListenerType::PreGrouping)]);
+        config.fulfill_randomness_gas_limit_multiplier = Some(0.8);
+        config.committer_verification_threads = Some(0);
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => assert_eq!(errors.len(), 6),
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_validate_missing_listeners() {
+        let mut config = valid_config().initialize();
+
+        config.listeners.as_mut().unwrap().retain(|l| {
+            l.l_type != ListenerType::NewRandomnessTask && l.l_type != ListenerType::PostGrouping
+        });
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => {
+                assert_eq!(errors.len(), 1);
+                let message = errors[0].to_string();
+                assert!(message.contains("PostGrouping, NewRandomnessTask"));
+                assert!(message.contains(
+                    "randomness fulfillment -> [NewRandomnessTask, ReadyToHandleRandomnessTask, RandomnessSignatureAggregation]"
+                ));
+            }
+            _ => panic!("config should be invalid"),
+        }
+
+        // a verifier only depends on the Block listener
+        config.node_role = NodeRole::Verifier;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_transaction_confirmation_descriptor() {
+        let mut config = valid_config().initialize();
+
+        let descriptor = config
+            .time_limits
+            .unwrap()
+            .get_transaction_confirmation_descriptor();
+        assert_eq!(
+            descriptor.polling_interval_millis,
+            DEFAULT_PROVIDER_POLLING_INTERVAL_MILLIS
+        );
+        assert_eq!(descriptor.confirmations, DEFAULT_TRANSACTION_CONFIRMATIONS);
+
+        let time_limits = config.time_limits.as_mut().unwrap();
+        time_limits.transaction_confirmation_polling_interval_millis = Some(0);
+        time_limits.transaction_confirmations = Some(0);
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => assert_eq!(errors.len(), 2),
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_task_time_limits() {
+        let mut config = valid_config().initialize();
+
+        // unset along with another defaulted field
+        let time_limits = config.time_limits.as_mut().unwrap();
+        time_limits.listener_interval_millis = 0;
+        time_limits.randomness_task_claim_lease_duration = 0;
+        time_limits.partial_collection_timeout_blocks = 0;
+        let mut config = config.initialize();
+
+        let time_limits = config.time_limits.unwrap();
+        assert_eq!(
+            time_limits.listener_interval_millis,
+            DEFAULT_LISTENER_INTERVAL_MILLIS
+        );
+        assert_eq!(
+            time_limits.randomness_task_claim_lease_duration,
+            DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION
+        );
+        assert_eq!(
+            time_limits.partial_collection_timeout_blocks,
+            DEFAULT_PARTIAL_COLLECTION_TIMEOUT_BLOCKS
+        );
+        assert!(config.validate().is_ok());
+
+        let time_limits = config.time_limits.as_mut().unwrap();
+        time_limits.randomness_task_claim_lease_duration = 0;
+        time_limits.partial_collection_timeout_blocks = 0;
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => assert_eq!(errors.len(), 2),
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_validate_subscribers() {
+        let mut config = valid_config();
+        config.subscribers = Some(vec![
+            SubscriberType::Block,
+            SubscriberType::ReadyToHandleRandomnessTask,
+            SubscriberType::RandomnessSignatureAggregation,
+        ]);
+        let mut config = config.initialize();
+        assert!(config.validate().is_ok());
+        assert!(!config.is_subscriber_enabled(SubscriberType::PreGrouping));
+
+        // aggregation without the task listener
+        config
+            .listeners
+            .as_mut()
+            .unwrap()
+            .retain(|l| l.l_type != ListenerType::NewRandomnessTask);
+        config.subscribers = Some(vec![
+            SubscriberType::Block,
+            SubscriberType::RandomnessSignatureAggregation,
+            SubscriberType::InGrouping,
+        ]);
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => assert_eq!(errors.len(), 2),
+            _ => panic!("config should be invalid"),
+        }
+
+        config.subscribers = Some(vec![]);
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => assert_eq!(errors.len(), 1),
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_verifier_listeners() {
+        let config = Config {
+            node_role: NodeRole::Verifier,
+            ..Default::default()
+        }
+        .initialize();
+
+        let listeners = config.listeners.unwrap();
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].l_type, ListenerType::Block);
+
+        let config = Config::default().initialize();
+        let listeners = config.listeners.unwrap();
+        assert_eq!(listeners.len(), 8);
+        assert!(listeners
+            .iter()
+            .any(|l| l.l_type == ListenerType::ShareConsistency
+                && l.interval_millis == DEFAULT_SHARE_CONSISTENCY_LISTENER_INTERVAL_MILLIS));
+    }
+
+    #[test]
+    fn test_interpolate_env_vars() {
+        std::env::set_var("ARPA_NODE_TEST_RPC_HOST", "127.0.0.1");
+
+        let mut config = Config::default();
+        config.provider_endpoint = "http://${ARPA_NODE_TEST_RPC_HOST}:8545".to_string();
+        config.node_management_rpc_token = "env".to_string();
+
+        let config = config.interpolate_env_vars().unwrap();
+
+        assert_eq!(config.provider_endpoint, "http://127.0.0.1:8545");
+        assert_eq!(config.node_management_rpc_token, "env");
+
+        let mut config = Config::default();
+        config.provider_endpoint = "${ARPA_NODE_TEST_UNDEFINED}".to_string();
+
+        assert!(matches!(
+            config.interpolate_env_vars(),
+            Err(ConfigError::UndefinedEnvVar(name)) if name == "ARPA_NODE_TEST_UNDEFINED"
+        ));
+
+        assert!(matches!(
+            crate::interpolate_env_vars("${ARPA_NODE_TEST_RPC_HOST"),
+            Err(ConfigError::InvalidEnvVarReference(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_secret_file() {
+        let path =
+            std::env::temp_dir().join(format!("arpa_node_secret_test_{}", std::process::id()));
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(&path, "keystore_pw\n").unwrap();
+        assert_eq!(super::read_secret_file(path_str).unwrap(), "keystore_pw");
+
+        std::fs::write(&path, " \n").unwrap();
+        assert!(matches!(
+            super::read_secret_file(path_str),
+            Err(ConfigError::SecretFileError { .. })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            super::read_secret_file(path_str),
+            Err(ConfigError::SecretFileError { .. })
+        ));
+    }
+
+    #[test]
+    fn test_create_keystore() {
+        let mnemonic = "test test test test test test test test test test test junk";
+        let dir =
+            std::env::temp_dir().join(format!("arpa_node_keystore_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("node.keystore");
+
+        let address = crate::create_keystore(mnemonic, 1, &path, "password").unwrap();
+
+        let account = crate::Account {
+            keystore: Some(crate::Keystore {
+                path: path.to_str().unwrap().to_string(),
+                password: "password".to_string(),
+            }),
+            ..Default::default()
+        };
+        let wallet = crate::build_wallet_from_config(&account).unwrap();
+
+        assert_eq!(
+            address,
+            "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(wallet.address(), address);
+
+        // the account is derived the same way as a configured hdwallet
+        let account = crate::Account {
+            hdwallet: Some(crate::HDWallet {
+                mnemonic: mnemonic.to_string(),
+                path: None,
+                index: 1,
+                passphrase: None,
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            crate::build_wallet_from_config(&account).unwrap().address(),
+            address
+        );
+
+        assert!(matches!(
+            crate::create_keystore(mnemonic, crate::MAX_HD_WALLET_INDEX, &path, "password"),
+            Err(ConfigError::InvalidHDWalletIndex(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_derive_accounts() {
+        let mnemonic = "test test test test test test test test test test test junk";
+
+        let accounts = crate::derive_accounts(mnemonic, None, 2).unwrap();
+
+        assert_eq!(
+            accounts,
+            vec![
+                (
+                    0,
+                    "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266"
+                        .parse()
+                        .unwrap()
+                ),
+                (
+                    1,
+                    "0x70997970C51812dc3A010C7d01b50e0d17dc79C8"
+                        .parse()
+                        .unwrap()
+                )
+            ]
+        );
+
+        let mut config = Config::default();
+        config.account.hdwallet = Some(crate::HDWallet {
+            mnemonic: mnemonic.to_string(),
+            path: None,
+            index: crate::MAX_HD_WALLET_INDEX,
+            passphrase: None,
+        });
+        let config = config.initialize();
+
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_adapters() {
+        let mut config = valid_config();
+
+        let adapter = crate::Adapter {
+            id: 1,
+            name: "optimism".to_string(),
+            endpoint: "http://localhost:9545".to_string(),
+            account: config.account.clone(),
+            chain_id: 10,
+            address: "0xa513e6e4b8f2a923d98304ec87f64353c4d5c853".to_string(),
+            contract_transaction_retry_descriptor: None,
+            contract_view_retry_descriptor: None,
+        };
+        config.adapters = Some(vec![adapter.clone()]);
+        let mut config = config.initialize();
+
+        assert!(config.validate().is_ok());
+
+        config.adapters = Some(vec![
+            adapter.clone(),
+            crate::Adapter {
+                endpoint: "localhost:9545".to_string(),
+                address: "0x123".to_string(),
+                ..adapter
+            },
+        ]);
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => {
+                assert_eq!(errors.len(), 3);
+                assert!(matches!(errors[0], ConfigError::DuplicateAdapterId(1)));
+            }
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_build_main_chain_identity() {
+        let mut config = valid_config().initialize();
+        let wallet = crate::build_wallet_from_config(&config.account).unwrap();
+
+        config.set_id_address(&wallet.clone().into()).unwrap();
+        assert_eq!(config.id_address, wallet.address());
+
+        let identity = crate::build_main_chain_identity(&config, wallet.into());
+
+        assert_eq!(identity.get_id_address(), config.id_address);
+        assert_eq!(identity.get_chain_id(), config.chain_id);
+        assert_eq!(
+            identity.get_adapter_address(),
+            config.adapter_address.parse::<Address>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_build_adapter_identity() {
+        let config = valid_config().initialize();
+        let time_limits = config.time_limits.unwrap();
+
+        let adapter_account = crate::Account {
+            private_key: Some(
+                "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d".to_string(),
+            ),
+            ..Default::default()
+        };
+        let mut adapter = crate::Adapter {
+            id: 1,
+            name: "optimism".to_string(),
+            endpoint: "http://localhost:9545".to_string(),
+            account: adapter_account.clone(),
+            chain_id: 10,
+            address: "0xa513e6e4b8f2a923d98304ec87f64353c4d5c853".to_string(),
+            contract_transaction_retry_descriptor: None,
+            contract_view_retry_descriptor: None,
+        };
+
+        let wallet = crate::build_wallet_from_config(&adapter_account).unwrap();
+        let identity =
+            crate::build_adapter_identity(&config, &adapter, wallet.clone().into()).unwrap();
+
+        assert_eq!(identity.get_chain_id(), 10);
+        assert_eq!(identity.get_id_address(), wallet.address());
+        assert_eq!(
+            identity.get_controller_address(),
+            crate::PLACEHOLDER_ADDRESS
+        );
+        assert_eq!(
+            identity.get_adapter_address(),
+            adapter.address.parse::<Address>().unwrap()
+        );
+        // the descriptors not configured for the adapter fall back to the ones of time_limits
+        assert_eq!(
+            identity.get_contract_transaction_retry_descriptor().base,
+            time_limits.contract_transaction_retry_descriptor.base
+        );
+
+        let mut descriptor = time_limits.contract_view_retry_descriptor;
+        descriptor.base += 1;
+        adapter.contract_view_retry_descriptor = Some(descriptor);
+
+        let identity =
+            crate::build_adapter_identity(&config, &adapter, wallet.clone().into()).unwrap();
+
+        assert_eq!(
+            identity.get_contract_view_retry_descriptor().base,
+            descriptor.base
+        );
+
+        adapter.address = "0x123".to_string();
+
+        assert!(matches!(
+            crate::build_adapter_identity(&config, &adapter, wallet.into()),
+            Err(ConfigError::InvalidAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_eip155() {
+        let mut config = valid_config();
+        config.account.eip155 = false;
+        let mut config = config.initialize();
+
+        assert!(config.validate().is_ok());
+
+        config.account.private_key = None;
+        config.account.kms = Some(crate::KmsConfig {
+            key_id: "key_id".to_string(),
+            region: "us-east-1".to_string(),
+            db_cipher_key: "db_cipher_key".to_string(),
+        });
+
+        match config.validate() {
+            Err(ConfigError::InvalidConfig(errors)) => {
+                assert!(errors
+                    .iter()
+                    .any(|e| matches!(e, ConfigError::RemoteSignerWithoutEip155)));
+            }
+            _ => panic!("config should be invalid"),
+        }
+    }
+
+    #[test]
+    fn test_to_redacted() {
+        let mut config = Config::default();
+        config.account.private_key = Some("private_key".to_string());
+        config.account.hdwallet = Some(crate::HDWallet {
+            mnemonic: "mnemonic".to_string(),
+            path: None,
+            index: 0,
+            passphrase: Some("passphrase".to_string()),
+        });
+        config.account.keystore = Some(crate::Keystore {
+            path: "test.keystore".to_string(),
+            password: "password".to_string(),
+        });
+
+        config.adapters = Some(vec![crate::Adapter {
+            id: 1,
+            name: "optimism".to_string(),
+            endpoint: "http://localhost:9545".to_string(),
+            account: crate::Account {
+                private_key: Some("adapter_private_key".to_string()),
+                ..Default::default()
+            },
+            chain_id: 10,
+            address: "0xa513e6e4b8f2a923d98304ec87f64353c4d5c853".to_string(),
+            contract_transaction_retry_descriptor: None,
+            contract_view_retry_descriptor: None,
+        }]);
+
+        let redacted = format!("{:?}", config.to_redacted());
+
+        for secret in [
+            "\"private_key\"",
+            "\"mnemonic\"",
+            "\"passphrase\"",
+            "\"password\"",
+            "\"for_test\"",
+            "\"adapter_private_key\"",
+        ] {
+            assert!(!redacted.contains(secret), "{} is not redacted", secret);
+        }
+        assert!(redacted.contains("test.keystore"));
     }
 }
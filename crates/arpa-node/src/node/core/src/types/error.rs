@@ -1,3 +1,5 @@
+use crate::ConfigFormat;
+use ethers_providers::ProviderError;
 use ethers_signers::WalletError;
 use std::env::VarError;
 use std::string::FromUtf8Error;
@@ -14,6 +16,12 @@ pub enum BLSTaskError {
     #[error("already committed partial signature")]
     AlreadyCommittedPartialSignature,
 
+    #[error("task has already been fulfilled by others")]
+    AlreadyCommittedByOthers,
+
+    #[error("invalid curve point")]
+    InvalidCurvePoint,
+
     #[error(transparent)]
     TaskMsgError(#[from] FromUtf8Error),
 }
@@ -31,12 +39,108 @@ pub enum SchedulerError {
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
-    #[error("please provide at least a hdwallet, keystore or plain private key(not recommended)")]
+    #[error("please provide at least a hdwallet, keystore, kms key, ledger or plain private key(not recommended)")]
     LackOfAccount,
-    #[error("bad format")]
-    BadFormat,
+    #[error("bad format: {0}")]
+    BadFormat(String),
+    #[error("invalid log level {level} for module {module}")]
+    InvalidLogLevel { module: String, level: String },
+    #[error("hdwallet index {0} is out of range, it must be less than 2^31")]
+    InvalidHDWalletIndex(u32),
+    #[error("the account resolves to the zero address")]
+    ZeroIdAddress,
+    #[error("please provide only one of hdwallet, keystore, kms key, ledger or plain private key")]
+    MultipleAccounts,
+    #[error("invalid address {value} for {field}")]
+    InvalidAddress { field: String, value: String },
+    #[error("invalid endpoint {value} for {field}")]
+    InvalidEndpoint { field: String, value: String },
+    #[error("listeners must contain a Block listener")]
+    LackOfBlockListener,
+    #[error(
+        "listeners {missing} required by the {role} node are missing, the listeners each feature depends on: {dependencies}"
+    )]
+    MissingListeners {
+        role: String,
+        missing: String,
+        dependencies: String,
+    },
+    #[error("subscribers must contain a Block subscriber")]
+    LackOfBlockSubscriber,
+    #[error("{subscriber} subscriber requires the {dependency} to be enabled")]
+    IncoherentSubscriber {
+        subscriber: String,
+        dependency: String,
+    },
+    #[error("adapter id {0} is configured more than once")]
+    DuplicateAdapterId(usize),
+    #[error("event queue capacity of {0} must be greater than 0")]
+    ZeroEventQueueCapacity(String),
+    #[error("minimum_threshold must be greater than 0")]
+    ZeroMinimumThreshold,
+    #[error("max_group_size must be greater than 0")]
+    ZeroMaxGroupSize,
+    #[error("max_committer_cache_entries must be greater than 0")]
+    ZeroMaxCommitterCacheEntries,
+    #[error("committer_verification_threads must be greater than 0")]
+    ZeroCommitterVerificationThreads,
+    #[error("{0} must be greater than 0")]
+    ZeroTransactionConfirmationSetting(String),
+    #[error("{0} must be greater than 0")]
+    ZeroTimeLimit(String),
+    #[error("jitter_ratio {value} of {field} must be in (0, 1]")]
+    InvalidJitterRatio { field: String, value: f64 },
+    #[error("fulfill_randomness_gas_limit_multiplier {0} must be a finite number no less than 1")]
+    InvalidGasLimitMultiplier(f64),
+    #[error("min_fulfillment_profit_margin {0} must be a finite number greater than -1")]
+    InvalidProfitMargin(f64),
+    #[error("invalid config: {}", join_config_errors(.0))]
+    InvalidConfig(Vec<ConfigError>),
+    #[error("environment variable {0} referenced in config is not defined")]
+    UndefinedEnvVar(String),
+    #[error("unterminated environment variable reference in {0}")]
+    InvalidEnvVarReference(String),
+    #[error("the {0} cargo feature is required by the config but not enabled")]
+    FeatureNotEnabled(String),
+    #[error("invalid kms region {0}")]
+    InvalidKmsRegion(String),
+    #[error("failed to build kms signer: {0}")]
+    BuildingKmsSignerError(String),
+    #[error("failed to build ledger signer: {0}")]
+    BuildingLedgerSignerError(String),
+    #[error("eip155 can only be disabled for an account with a local key")]
+    RemoteSignerWithoutEip155,
+    #[error("the DKG key can only be derived from an account with a local key")]
+    RemoteSignerWithDerivedDkgKey,
+    #[error("ledger chain_id {found} doesn't match chain_id {expected}")]
+    MismatchedLedgerChainId { expected: usize, found: usize },
+    #[error("failed to read secret from file {path}: {reason}")]
+    SecretFileError { path: String, reason: String },
+    #[error("unsupported config file extension {0:?}, expected one of yml, yaml, toml or json")]
+    UnsupportedConfigFormat(String),
+    #[error("failed to read config file {path}: {reason}")]
+    ConfigFileError { path: String, reason: String },
+    #[error("failed to parse {format} config: {reason}")]
+    ConfigDeserializationError {
+        format: ConfigFormat,
+        reason: String,
+    },
+    #[error(
+        "configured chain_id {configured} doesn't match chain id {actual} reported by the provider"
+    )]
+    ChainIdMismatch { configured: usize, actual: usize },
     #[error(transparent)]
     EnvVarNotExisted(#[from] VarError),
     #[error(transparent)]
     BuildingAccountError(#[from] WalletError),
+    #[error(transparent)]
+    ProviderError(#[from] ProviderError),
+}
+
+fn join_config_errors(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
 }
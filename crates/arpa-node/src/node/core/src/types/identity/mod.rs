@@ -1,12 +1,21 @@
 use async_trait::async_trait;
-use ethers_core::types::{Address, U256};
+use ethers_core::types::{Address, H256, U256};
 use ethers_providers::{Http, Provider, ProviderError};
 use std::sync::Arc;
 
+mod signer;
 mod types;
+pub use signer::*;
 pub use types::*;
 
-use crate::ExponentialBackoffRetryDescriptor;
+use crate::{CircuitBreaker, ExponentialBackoffRetryDescriptor, TransactionConfirmationDescriptor};
+
+/// Storage slot holding the implementation address of an EIP-1967 proxy,
+/// i.e. `bytes32(uint256(keccak256("eip1967.proxy.implementation")) - 1)`.
+pub const EIP1967_IMPLEMENTATION_SLOT: H256 = H256([
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+]);
 
 #[async_trait]
 pub trait ChainIdentity {
@@ -22,9 +31,54 @@ pub trait ChainIdentity {
 
     fn get_signer(&self) -> Arc<WalletSigner>;
 
+    /// Whether transactions are signed with EIP-155 replay protection, false only for a legacy signer.
+    fn is_eip155(&self) -> bool;
+
     fn get_contract_transaction_retry_descriptor(&self) -> ExponentialBackoffRetryDescriptor;
 
     fn get_contract_view_retry_descriptor(&self) -> ExponentialBackoffRetryDescriptor;
 
+    fn get_transaction_confirmation_descriptor(&self) -> TransactionConfirmationDescriptor;
+
+    /// Returns the circuit breaker of the contract, which is shared by all of its clients.
+    fn get_circuit_breaker(&self, contract_address: Address) -> Arc<CircuitBreaker>;
+
+    fn get_fulfill_randomness_gas_limit_multiplier(&self) -> f64;
+
+    /// Returns the largest group accepted from the contracts, a larger one is rejected.
+    fn get_max_group_size(&self) -> usize;
+
     async fn get_current_gas_price(&self) -> Result<U256, ProviderError>;
+
+    /// Returns the contract addresses known to be EIP-1967 proxies.
+    fn get_proxy_addresses(&self) -> Vec<Address>;
+
+    /// Returns the last resolved implementation of the proxy, None if it's not resolved yet.
+    fn get_implementation_address(&self, proxy_address: Address) -> Option<Address>;
+
+    /// Reads the current implementation of the proxy from chain and records it,
+    /// logging the change if the proxy has been upgraded since the last resolution.
+    async fn resolve_implementation_address(
+        &self,
+        proxy_address: Address,
+    ) -> Result<Address, ProviderError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EIP1967_IMPLEMENTATION_SLOT;
+    use ethers_core::{
+        types::{H256, U256},
+        utils::keccak256,
+    };
+
+    #[test]
+    fn test_eip1967_implementation_slot() {
+        let slot = U256::from(keccak256(b"eip1967.proxy.implementation")) - 1;
+
+        let mut bytes = [0; 32];
+        slot.to_big_endian(&mut bytes);
+
+        assert_eq!(EIP1967_IMPLEMENTATION_SLOT, H256(bytes));
+    }
 }
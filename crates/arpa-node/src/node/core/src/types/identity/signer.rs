@@ -0,0 +1,268 @@
+use async_trait::async_trait;
+use ethers_core::types::{
+    transaction::{eip2718::TypedTransaction, eip712::Eip712},
+    Address, Signature,
+};
+use ethers_signers::{LocalWallet, Signer, WalletError};
+use thiserror::Error;
+
+#[cfg(feature = "aws-kms")]
+use ethers_signers::{AwsSigner, AwsSignerError};
+#[cfg(feature = "ledger")]
+use ethers_signers::{Ledger, LedgerError};
+#[cfg(feature = "aws-kms")]
+use rusoto_kms::KmsClient;
+#[cfg(feature = "ledger")]
+use std::sync::Arc;
+
+/// The signer behind `WalletSigner`.
+///
+/// Keys are held locally by default. With the `aws-kms` feature the key can also live in AWS KMS,
+/// and with the `ledger` feature on a Ledger device, in which case every signature is requested
+/// from KMS or the device respectively.
+///
+/// A `Legacy` signer holds a local key but signs transactions without EIP-155 replay protection,
+/// which is only meant for private networks that reject chain-id-tagged signatures.
+#[derive(Debug, Clone)]
+pub enum NodeSigner {
+    Local(LocalWallet),
+    Legacy(LocalWallet),
+    #[cfg(feature = "aws-kms")]
+    Aws(KmsSigner),
+    #[cfg(feature = "ledger")]
+    Ledger(LedgerSigner),
+}
+
+/// A signer of a Ledger device, which is shared by the clones of the signer.
+///
+/// The device connection can't be cloned, so `with_chain_id` on a shared signer can't move the
+/// device to the new chain id. The signer then refuses to sign rather than signing for the chain
+/// id the device was opened with.
+#[cfg(feature = "ledger")]
+#[derive(Debug, Clone)]
+pub struct LedgerSigner {
+    ledger: Arc<Ledger>,
+    chain_id: u64,
+}
+
+#[cfg(feature = "ledger")]
+impl LedgerSigner {
+    fn ledger(&self) -> Result<&Ledger, NodeSignerError> {
+        check_ledger_chain_id(self.ledger.chain_id(), self.chain_id)?;
+        Ok(&self.ledger)
+    }
+}
+
+#[cfg(feature = "ledger")]
+fn check_ledger_chain_id(device: u64, requested: u64) -> Result<(), NodeSignerError> {
+    if device != requested {
+        return Err(NodeSignerError::LedgerChainIdMismatch { device, requested });
+    }
+
+    Ok(())
+}
+
+/// A signer of a key in AWS KMS which owns its client.
+///
+/// `AwsSigner` only borrows the client, so it is built for every signature, which fetches the
+/// public key from KMS once more.
+#[cfg(feature = "aws-kms")]
+#[derive(Clone)]
+pub struct KmsSigner {
+    client: KmsClient,
+    key_id: String,
+    chain_id: u64,
+    address: Address,
+}
+
+#[cfg(feature = "aws-kms")]
+impl std::fmt::Debug for KmsSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KmsSigner")
+            .field("key_id", &self.key_id)
+            .field("chain_id", &self.chain_id)
+            .field("address", &self.address)
+            .finish()
+    }
+}
+
+#[cfg(feature = "aws-kms")]
+impl KmsSigner {
+    pub async fn new(
+        client: KmsClient,
+        key_id: String,
+        chain_id: u64,
+    ) -> Result<Self, AwsSignerError> {
+        let address = AwsSigner::new(&client, &key_id, chain_id).await?.address();
+
+        Ok(KmsSigner {
+            client,
+            key_id,
+            chain_id,
+            address,
+        })
+    }
+
+    async fn signer(&self) -> Result<AwsSigner<'_>, AwsSignerError> {
+        AwsSigner::new(&self.client, &self.key_id, self.chain_id).await
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NodeSignerError {
+    #[error(transparent)]
+    LocalWalletError(#[from] WalletError),
+    #[error("typed transactions can't be signed without EIP-155")]
+    TypedTransactionWithoutEip155,
+    #[cfg(feature = "aws-kms")]
+    #[error(transparent)]
+    AwsSignerError(#[from] AwsSignerError),
+    #[cfg(feature = "ledger")]
+    #[error(transparent)]
+    LedgerError(#[from] LedgerError),
+    #[cfg(feature = "ledger")]
+    #[error("the ledger device signs for chain id {device} instead of {requested}")]
+    LedgerChainIdMismatch { device: u64, requested: u64 },
+}
+
+impl From<LocalWallet> for NodeSigner {
+    fn from(wallet: LocalWallet) -> Self {
+        NodeSigner::Local(wallet)
+    }
+}
+
+#[cfg(feature = "aws-kms")]
+impl From<KmsSigner> for NodeSigner {
+    fn from(signer: KmsSigner) -> Self {
+        NodeSigner::Aws(signer)
+    }
+}
+
+#[cfg(feature = "ledger")]
+impl From<Ledger> for NodeSigner {
+    fn from(signer: Ledger) -> Self {
+        NodeSigner::Ledger(LedgerSigner {
+            chain_id: signer.chain_id(),
+            ledger: Arc::new(signer),
+        })
+    }
+}
+
+impl NodeSigner {
+    pub fn is_eip155(&self) -> bool {
+        !matches!(self, NodeSigner::Legacy(_))
+    }
+}
+
+#[async_trait]
+impl Signer for NodeSigner {
+    type Error = NodeSignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            NodeSigner::Local(wallet) | NodeSigner::Legacy(wallet) => {
+                Ok(wallet.sign_message(message).await?)
+            }
+            #[cfg(feature = "aws-kms")]
+            NodeSigner::Aws(signer) => Ok(signer.signer().await?.sign_message(message).await?),
+            #[cfg(feature = "ledger")]
+            NodeSigner::Ledger(signer) => Ok(signer.ledger()?.sign_message(message).await?),
+        }
+    }
+
+    async fn sign_transaction(&self, message: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            NodeSigner::Local(wallet) => Ok(wallet.sign_transaction(message).await?),
+            // the sighash of a legacy transaction without chain id is the one before EIP-155
+            NodeSigner::Legacy(wallet) => match message {
+                TypedTransaction::Legacy(tx) => {
+                    let mut tx = tx.clone();
+                    tx.chain_id = None;
+                    Ok(wallet.sign_hash(tx.sighash()))
+                }
+                _ => Err(NodeSignerError::TypedTransactionWithoutEip155),
+            },
+            #[cfg(feature = "aws-kms")]
+            NodeSigner::Aws(signer) => Ok(signer.signer().await?.sign_transaction(message).await?),
+            #[cfg(feature = "ledger")]
+            NodeSigner::Ledger(signer) => Ok(signer.ledger()?.sign_transaction(message).await?),
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            NodeSigner::Local(wallet) | NodeSigner::Legacy(wallet) => {
+                Ok(wallet.sign_typed_data(payload).await?)
+            }
+            #[cfg(feature = "aws-kms")]
+            NodeSigner::Aws(signer) => Ok(signer.signer().await?.sign_typed_data(payload).await?),
+            #[cfg(feature = "ledger")]
+            NodeSigner::Ledger(signer) => Ok(signer.ledger()?.sign_typed_data(payload).await?),
+        }
+    }
+
+    fn address(&self) -> Address {
+        match self {
+            NodeSigner::Local(wallet) | NodeSigner::Legacy(wallet) => wallet.address(),
+            #[cfg(feature = "aws-kms")]
+            NodeSigner::Aws(signer) => signer.address,
+            #[cfg(feature = "ledger")]
+            NodeSigner::Ledger(signer) => signer.ledger.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        match self {
+            NodeSigner::Local(wallet) | NodeSigner::Legacy(wallet) => wallet.chain_id(),
+            #[cfg(feature = "aws-kms")]
+            NodeSigner::Aws(signer) => signer.chain_id,
+            #[cfg(feature = "ledger")]
+            NodeSigner::Ledger(signer) => signer.chain_id,
+        }
+    }
+
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            NodeSigner::Local(wallet) => NodeSigner::Local(wallet.with_chain_id(chain_id)),
+            NodeSigner::Legacy(wallet) => NodeSigner::Legacy(wallet.with_chain_id(chain_id)),
+            #[cfg(feature = "aws-kms")]
+            NodeSigner::Aws(signer) => NodeSigner::Aws(KmsSigner {
+                chain_id: chain_id.into(),
+                ..signer
+            }),
+            #[cfg(feature = "ledger")]
+            NodeSigner::Ledger(signer) => {
+                let chain_id = chain_id.into();
+                match Arc::try_unwrap(signer.ledger) {
+                    Ok(ledger) => ledger.with_chain_id(chain_id).into(),
+                    // a shared device keeps its chain id, so signing fails until they match again
+                    Err(ledger) => NodeSigner::Ledger(LedgerSigner { ledger, chain_id }),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "ledger"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ledger_chain_id_override_is_rejected() {
+        assert!(check_ledger_chain_id(1, 1).is_ok());
+
+        assert!(matches!(
+            check_ledger_chain_id(1, 5),
+            Err(NodeSignerError::LedgerChainIdMismatch {
+                device: 1,
+                requested: 5
+            })
+        ));
+    }
+}
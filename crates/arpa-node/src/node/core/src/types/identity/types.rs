@@ -1,14 +1,19 @@
-use crate::ExponentialBackoffRetryDescriptor;
+use crate::{
+    CircuitBreaker, CircuitBreakerDescriptor, ExponentialBackoffRetryDescriptor,
+    TransactionConfirmationDescriptor, DEFAULT_MAX_GROUP_SIZE,
+};
 
-use super::ChainIdentity;
+use super::{ChainIdentity, NodeSigner, EIP1967_IMPLEMENTATION_SLOT};
 use async_trait::async_trait;
 use ethers_core::types::{Address, U256};
 use ethers_middleware::{NonceManagerMiddleware, SignerMiddleware};
 use ethers_providers::{Http, Middleware, Provider, ProviderError};
-use ethers_signers::{LocalWallet, Signer};
-use std::{sync::Arc, time::Duration};
+use ethers_signers::Signer;
+use log::{info, warn};
+use parking_lot::Mutex;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-pub type WalletSigner = SignerMiddleware<NonceManagerMiddleware<Arc<Provider<Http>>>, LocalWallet>;
+pub type WalletSigner = SignerMiddleware<NonceManagerMiddleware<Arc<Provider<Http>>>, NodeSigner>;
 
 #[derive(Debug, Clone)]
 pub struct GeneralChainIdentity {
@@ -19,19 +24,30 @@ pub struct GeneralChainIdentity {
     adapter_address: Address,
     contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
     contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+    circuit_breaker_descriptor: CircuitBreakerDescriptor,
+    // contract address -> circuit breaker, shared by all the clients of the contract
+    circuit_breakers: Arc<Mutex<HashMap<Address, Arc<CircuitBreaker>>>>,
+    fulfill_randomness_gas_limit_multiplier: f64,
+    max_group_size: usize,
+    // proxy address -> its current implementation, None until resolved
+    proxy_implementations: Arc<Mutex<HashMap<Address, Option<Address>>>>,
 }
 
 impl GeneralChainIdentity {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         chain_id: usize,
-        wallet: LocalWallet,
+        wallet: impl Into<NodeSigner>,
         provider_rpc_endpoint: String,
         provider_polling_interval_millis: u64,
         controller_address: Address,
         adapter_address: Address,
         contract_transaction_retry_descriptor: ExponentialBackoffRetryDescriptor,
         contract_view_retry_descriptor: ExponentialBackoffRetryDescriptor,
+        transaction_confirmation_descriptor: TransactionConfirmationDescriptor,
+        circuit_breaker_descriptor: CircuitBreakerDescriptor,
+        fulfill_randomness_gas_limit_multiplier: f64,
     ) -> Self {
         let provider = Arc::new(
             Provider::<Http>::try_from(provider_rpc_endpoint)
@@ -39,7 +55,7 @@ impl GeneralChainIdentity {
                 .interval(Duration::from_millis(provider_polling_interval_millis)),
         );
 
-        let wallet = wallet.with_chain_id(chain_id as u32);
+        let wallet = wallet.into().with_chain_id(chain_id as u32);
 
         let nonce_manager = NonceManagerMiddleware::new(provider.clone(), wallet.address());
 
@@ -54,8 +70,32 @@ impl GeneralChainIdentity {
             adapter_address,
             contract_transaction_retry_descriptor,
             contract_view_retry_descriptor,
+            transaction_confirmation_descriptor,
+            circuit_breaker_descriptor,
+            circuit_breakers: Arc::new(Mutex::new(HashMap::new())),
+            fulfill_randomness_gas_limit_multiplier,
+            max_group_size: DEFAULT_MAX_GROUP_SIZE,
+            proxy_implementations: Arc::new(Mutex::new(HashMap::new())),
         }
     }
+
+    /// Bounds the number of participants accepted from the contracts of this chain.
+    pub fn with_max_group_size(mut self, max_group_size: usize) -> Self {
+        self.max_group_size = max_group_size;
+        self
+    }
+
+    /// Marks the contract addresses as EIP-1967 proxies, so that their implementations
+    /// can be resolved and followed across upgrades.
+    pub fn with_proxies(self, proxy_addresses: Vec<Address>) -> Self {
+        {
+            let mut proxy_implementations = self.proxy_implementations.lock();
+            for proxy_address in proxy_addresses {
+                proxy_implementations.entry(proxy_address).or_insert(None);
+            }
+        }
+        self
+    }
 }
 
 #[async_trait]
@@ -80,6 +120,10 @@ impl ChainIdentity for GeneralChainIdentity {
         self.signer.clone()
     }
 
+    fn is_eip155(&self) -> bool {
+        self.signer.signer().is_eip155()
+    }
+
     fn get_provider(&self) -> Arc<Provider<Http>> {
         self.provider.clone()
     }
@@ -92,7 +136,75 @@ impl ChainIdentity for GeneralChainIdentity {
         self.contract_view_retry_descriptor
     }
 
+    fn get_transaction_confirmation_descriptor(&self) -> TransactionConfirmationDescriptor {
+        self.transaction_confirmation_descriptor
+    }
+
+    fn get_circuit_breaker(&self, contract_address: Address) -> Arc<CircuitBreaker> {
+        self.circuit_breakers
+            .lock()
+            .entry(contract_address)
+            .or_insert_with(|| {
+                Arc::new(CircuitBreaker::new(
+                    contract_address,
+                    self.circuit_breaker_descriptor,
+                ))
+            })
+            .clone()
+    }
+
+    fn get_fulfill_randomness_gas_limit_multiplier(&self) -> f64 {
+        self.fulfill_randomness_gas_limit_multiplier
+    }
+
+    fn get_max_group_size(&self) -> usize {
+        self.max_group_size
+    }
+
     async fn get_current_gas_price(&self) -> Result<U256, ProviderError> {
         self.provider.get_gas_price().await
     }
+
+    fn get_proxy_addresses(&self) -> Vec<Address> {
+        self.proxy_implementations.lock().keys().copied().collect()
+    }
+
+    fn get_implementation_address(&self, proxy_address: Address) -> Option<Address> {
+        self.proxy_implementations
+            .lock()
+            .get(&proxy_address)
+            .copied()
+            .flatten()
+    }
+
+    async fn resolve_implementation_address(
+        &self,
+        proxy_address: Address,
+    ) -> Result<Address, ProviderError> {
+        let slot = self
+            .provider
+            .get_storage_at(proxy_address, EIP1967_IMPLEMENTATION_SLOT, None)
+            .await?;
+        let implementation_address = Address::from(slot);
+
+        let previous = self
+            .proxy_implementations
+            .lock()
+            .insert(proxy_address, Some(implementation_address))
+            .flatten();
+
+        match previous {
+            Some(previous) if previous != implementation_address => warn!(
+                "proxy {:?} has been upgraded, implementation changed from {:?} to {:?}",
+                proxy_address, previous, implementation_address
+            ),
+            Some(_) => {}
+            None => info!(
+                "proxy {:?} resolves to implementation {:?}",
+                proxy_address, implementation_address
+            ),
+        }
+
+        Ok(implementation_address)
+    }
 }
@@ -1,9 +1,11 @@
+mod circuit_breaker;
 mod config;
 mod contract;
 mod error;
 mod identity;
 mod node;
 
+pub use circuit_breaker::*;
 pub use config::*;
 pub use contract::*;
 pub use error::*;
@@ -1,6 +1,7 @@
+use crate::{hex_bytes, u256_to_vec};
 use ethers_core::{
     types::{Address, U256},
-    utils::hex,
+    utils::{hex, keccak256},
 };
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, marker::PhantomData};
@@ -14,6 +15,8 @@ pub trait Task {
 pub struct BLSTask<T: Task> {
     pub task: T,
     pub state: bool,
+    // the block height until which the task is claimed, if it has been claimed
+    pub lease_expiry_block_height: Option<usize>,
 }
 
 impl Task for RandomnessTask {
@@ -22,19 +25,24 @@ impl Task for RandomnessTask {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct RandomnessTask {
+    #[serde(with = "hex_bytes")]
     pub request_id: Vec<u8>,
     pub subscription_id: u64,
     pub group_index: u32,
     pub request_type: RandomnessRequestType,
+    #[serde(with = "hex_bytes")]
     pub params: Vec<u8>,
     pub requester: Address,
     pub seed: U256,
     pub request_confirmations: u16,
     pub callback_gas_limit: u32,
     pub callback_max_gas_price: U256,
+    // payment frozen from the subscription when requested, zero if unknown
+    pub estimated_payment: U256,
     pub assignment_block_height: usize,
+    pub priority: RandomnessTaskPriority,
 }
 
 impl std::fmt::Debug for RandomnessTask {
@@ -44,10 +52,26 @@ impl std::fmt::Debug for RandomnessTask {
             .field("seed", &self.seed)
             .field("group_index", &self.group_index)
             .field("assignment_block_height", &self.assignment_block_height)
+            .field("priority", &self.priority)
             .finish()
     }
 }
 
+/// Derives the message to sign for the task, which the Adapter rebuilds on fulfillment
+/// as abi.encodePacked(seed, assignment_block_height).
+pub fn compute_seed(task: &RandomnessTask) -> Vec<u8> {
+    encode_seed(task.seed, task.assignment_block_height)
+}
+
+/// Encodes the seed of a request and the block it was assigned at as abi.encodePacked does.
+pub fn encode_seed(seed: U256, assignment_block_height: usize) -> Vec<u8> {
+    [
+        &u256_to_vec(&seed)[..],
+        &u256_to_vec(&U256::from(assignment_block_height))[..],
+    ]
+    .concat()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DKGTask {
     pub group_index: usize,
@@ -59,6 +83,27 @@ pub struct DKGTask {
     pub coordinator_address: Address,
 }
 
+impl RandomnessTask {
+    /// Renders the task as JSON, with the byte fields as hex strings.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+}
+
+impl DKGTask {
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(s: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(s)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GroupRelayTask {
     pub controller_global_epoch: usize,
@@ -126,6 +171,66 @@ impl<C: PairingCurve> Group<C> {
     }
 }
 
+/// Returns the committers of the group, or none if fewer members than `minimum_threshold`
+/// are left to aggregate the signature.
+///
+/// The set of committers is fixed by the coordinator for each group epoch. Without a task they
+/// are returned in the order decided by the coordinator. Given a task, they are sorted by address
+/// regardless of the order stored in the group, so that every member gets the same result, and
+/// rotated by the selection seed of the task, which makes the first one the primary committer of
+/// the task and the rest its fallbacks.
+pub fn select_committers<C: PairingCurve>(
+    group: &Group<C>,
+    task: Option<&RandomnessTask>,
+    minimum_threshold: usize,
+) -> Vec<Address> {
+    if group.members.len() < minimum_threshold {
+        return vec![];
+    }
+
+    let committers = group
+        .committers
+        .iter()
+        .filter(|committer| group.members.contains_key(committer))
+        .copied()
+        .collect::<Vec<_>>();
+
+    order_committers(committers, task)
+}
+
+/// Sorts the committers by address and rotates them by the selection seed of the task if given,
+/// otherwise keeps them in the order decided by the coordinator.
+pub fn order_committers(
+    mut committers: Vec<Address>,
+    task: Option<&RandomnessTask>,
+) -> Vec<Address> {
+    if let Some(task) = task {
+        committers.sort();
+        committers.dedup();
+
+        if !committers.is_empty() {
+            let offset = committer_selection_seed(task) % U256::from(committers.len());
+            committers.rotate_left(offset.as_usize());
+        }
+    }
+
+    committers
+}
+
+/// Derives the seed to order the committers for the task from
+/// keccak256(request_id || assignment_block_height as uint256).
+pub fn committer_selection_seed(task: &RandomnessTask) -> U256 {
+    let seed = keccak256(
+        [
+            &task.request_id[..],
+            &u256_to_vec(&U256::from(task.assignment_block_height))[..],
+        ]
+        .concat(),
+    );
+
+    U256::from_big_endian(&seed)
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Member<C: PairingCurve> {
     pub index: usize,
@@ -187,6 +292,11 @@ pub enum DKGStatus {
     InPhase,
     CommitSuccess,
     WaitForPostProcess,
+    // the DKG didn't finish in time, waiting for the next grouping
+    Timeout,
+    // the persisted share doesn't match the partial public key registered on chain,
+    // stop signing until the next grouping
+    ShareInconsistent,
 }
 
 impl DKGStatus {
@@ -196,6 +306,8 @@ impl DKGStatus {
             DKGStatus::InPhase => 1,
             DKGStatus::CommitSuccess => 2,
             DKGStatus::WaitForPostProcess => 3,
+            DKGStatus::Timeout => 4,
+            DKGStatus::ShareInconsistent => 5,
         }
     }
 }
@@ -206,6 +318,8 @@ impl From<usize> for DKGStatus {
             1 => DKGStatus::InPhase,
             2 => DKGStatus::CommitSuccess,
             3 => DKGStatus::WaitForPostProcess,
+            4 => DKGStatus::Timeout,
+            5 => DKGStatus::ShareInconsistent,
             _ => DKGStatus::None,
         }
     }
@@ -237,3 +351,219 @@ impl From<u8> for RandomnessRequestType {
         }
     }
 }
+
+/// Urgent tasks can be taken over by other groups after a shorter exclusive window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Hash, Eq, Serialize, Deserialize)]
+pub enum RandomnessTaskPriority {
+    #[default]
+    Normal,
+    Urgent,
+}
+
+impl RandomnessTaskPriority {
+    /// A request asking for no more confirmations than `urgent_request_confirmations` wants its
+    /// randomness as soon as possible, so it is urgent. No request is urgent if that is not set.
+    pub fn of_request(
+        request_confirmations: u16,
+        urgent_request_confirmations: Option<u16>,
+    ) -> Self {
+        match urgent_request_confirmations {
+            Some(urgent) if request_confirmations <= urgent => RandomnessTaskPriority::Urgent,
+            _ => RandomnessTaskPriority::Normal,
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            RandomnessTaskPriority::Normal => 0,
+            RandomnessTaskPriority::Urgent => 1,
+        }
+    }
+}
+
+impl From<u8> for RandomnessTaskPriority {
+    fn from(s: u8) -> Self {
+        match s {
+            1 => RandomnessTaskPriority::Urgent,
+            _ => RandomnessTaskPriority::Normal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        committer_selection_seed, compute_seed, select_committers, DKGTask, Group, Member,
+        RandomnessRequestType, RandomnessTask, RandomnessTaskPriority,
+    };
+    use ethers_core::types::{Address, U256};
+    use std::collections::BTreeMap;
+    use threshold_bls::curve::bn254::PairingCurve as BN254;
+
+    fn build_group(members: &[Address], committers: &[Address], threshold: usize) -> Group<BN254> {
+        let mut group = Group::new();
+        group.size = members.len();
+        group.threshold = threshold;
+        group.members = members
+            .iter()
+            .enumerate()
+            .map(|(index, id_address)| {
+                (
+                    *id_address,
+                    Member {
+                        index,
+                        id_address: *id_address,
+                        rpc_endpoint: None,
+                        partial_public_key: None,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>();
+        group.committers = committers.to_vec();
+        group
+    }
+
+    fn build_task(request_id: Vec<u8>, assignment_block_height: usize) -> RandomnessTask {
+        RandomnessTask {
+            request_id,
+            subscription_id: 0,
+            group_index: 0,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::zero(),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    #[test]
+    fn test_select_committers() {
+        let members = (1..=5).map(Address::from_low_u64_be).collect::<Vec<_>>();
+        let committers = vec![members[3], members[0], members[2]];
+
+        // without a task the committers keep the order decided by the coordinator
+        let group = build_group(&members, &committers, 3);
+        assert_eq!(committers, select_committers(&group, None, group.threshold));
+
+        let mut group = build_group(&members, &committers, 3);
+        group.remove_disqualified_nodes(&[members[0]]);
+        assert_eq!(
+            vec![members[3], members[2]],
+            select_committers(&group, None, group.threshold)
+        );
+
+        group.remove_disqualified_nodes(&[members[1], members[4]]);
+        assert!(select_committers(&group, None, group.threshold).is_empty());
+    }
+
+    #[test]
+    fn test_select_committers_for_task() {
+        let members = (1..=5).map(Address::from_low_u64_be).collect::<Vec<_>>();
+
+        // every node should agree on the committers regardless of the order they are stored
+        let groups = [
+            build_group(&members, &[members[3], members[0], members[2]], 3),
+            build_group(&members, &[members[2], members[3], members[0]], 3),
+            build_group(
+                &members.iter().rev().copied().collect::<Vec<_>>(),
+                &[members[0], members[2], members[3]],
+                3,
+            ),
+        ];
+
+        let cases = [
+            (vec![1], 100, vec![members[3], members[0], members[2]]),
+            (vec![2], 100, vec![members[2], members[3], members[0]]),
+            (vec![1], 101, vec![members[0], members[2], members[3]]),
+        ];
+
+        for (request_id, assignment_block_height, expected) in cases {
+            let task = build_task(request_id, assignment_block_height);
+
+            for group in groups.iter() {
+                assert_eq!(
+                    expected,
+                    select_committers(group, Some(&task), group.threshold)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compute_seed() {
+        let mut task = build_task(vec![1], 100);
+        task.seed = U256::from(7);
+
+        let seed = compute_seed(&task);
+        assert_eq!(64, seed.len());
+        assert_eq!(U256::from(7), U256::from_big_endian(&seed[..32]));
+        assert_eq!(U256::from(100), U256::from_big_endian(&seed[32..]));
+    }
+
+    #[test]
+    fn test_committer_selection_seed() {
+        assert_eq!(
+            U256::from_str_radix(
+                "e4ab7f2b60aa9160f8328724a419a790b6d74c548581275de749fab929ef6439",
+                16
+            )
+            .unwrap(),
+            committer_selection_seed(&build_task(vec![1], 100))
+        );
+    }
+
+    #[test]
+    fn test_task_json() {
+        let mut task = build_task(vec![0xab, 0xcd], 100);
+        task.params = vec![1];
+        task.seed = U256::from(256);
+
+        let json = task.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["request_id"], "0xabcd");
+        assert_eq!(value["params"], "0x01");
+        assert_eq!(value["seed"], "0x100");
+        assert_eq!(RandomnessTask::from_json(&json).unwrap(), task);
+
+        let dkg_task = DKGTask {
+            group_index: 1,
+            epoch: 2,
+            size: 1,
+            threshold: 1,
+            members: vec![Address::from_low_u64_be(1)],
+            assignment_block_height: 100,
+            coordinator_address: Address::from_low_u64_be(2),
+        };
+
+        let json = dkg_task.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value["coordinator_address"],
+            "0x0000000000000000000000000000000000000002"
+        );
+        let decoded = DKGTask::from_json(&json).unwrap();
+        assert_eq!(decoded.members, dkg_task.members);
+        assert_eq!(decoded.epoch, dkg_task.epoch);
+    }
+
+    #[test]
+    fn test_priority_of_request() {
+        assert_eq!(
+            RandomnessTaskPriority::of_request(3, None),
+            RandomnessTaskPriority::Normal
+        );
+        assert_eq!(
+            RandomnessTaskPriority::of_request(3, Some(3)),
+            RandomnessTaskPriority::Urgent
+        );
+        assert_eq!(
+            RandomnessTaskPriority::of_request(4, Some(3)),
+            RandomnessTaskPriority::Normal
+        );
+    }
+}
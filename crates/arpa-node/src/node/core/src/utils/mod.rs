@@ -1,5 +1,8 @@
 use chrono::Local;
-use ethers_core::types::{Address, U256};
+use ethers_core::{
+    types::{Address, U256},
+    utils::keccak256,
+};
 
 pub fn format_now_date() -> String {
     let fmt = "%Y-%m-%d %H:%M:%S";
@@ -16,6 +19,13 @@ pub fn u256_to_vec(x: &U256) -> Vec<u8> {
     x_bytes
 }
 
+/// Derives the randomness the Adapter emits for a fulfillment from the group signature, i.e.
+/// `uint256(keccak256(abi.encode(signature)))`, where the compressed signature is a uint256
+/// encoded as its 32 bytes as they are.
+pub fn derive_randomness(signature: &[u8]) -> [u8; 32] {
+    keccak256(signature)
+}
+
 pub fn pad_to_bytes32(s: &[u8]) -> Option<[u8; 32]> {
     let s_len = s.len();
 
@@ -30,12 +40,28 @@ pub fn pad_to_bytes32(s: &[u8]) -> Option<[u8; 32]> {
     Some(result)
 }
 
+/// Serializes bytes as a 0x-prefixed hex string rather than an array of numbers,
+/// to be used with `#[serde(with = "hex_bytes")]`.
+pub mod hex_bytes {
+    use ethers_core::utils::hex;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        hex::decode(s.trim_start_matches("0x")).map_err(D::Error::custom)
+    }
+}
+
 #[cfg(test)]
 pub mod util_tests {
 
-    use ethers_core::types::Address;
+    use ethers_core::{types::Address, utils::hex};
 
-    use crate::{address_to_string, format_now_date};
+    use crate::{address_to_string, derive_randomness, format_now_date};
 
     #[test]
     fn test_format_now_date() {
@@ -55,4 +81,13 @@ pub mod util_tests {
         let address = bad_address_in_str.parse::<Address>();
         assert!(address.is_err());
     }
+
+    #[test]
+    fn test_derive_randomness() {
+        // keccak256 of 32 zero bytes, i.e. of abi.encode(uint256(0))
+        assert_eq!(
+            hex::encode(derive_randomness(&[0; 32])),
+            "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563"
+        );
+    }
 }
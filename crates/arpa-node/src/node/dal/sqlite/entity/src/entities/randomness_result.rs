@@ -1,8 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "randomness_result")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -15,6 +16,10 @@ pub struct Model {
     pub threshold: i32,
     pub partial_signatures: String,
     pub state: i32,
+    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
+    pub tx_hash: Option<Vec<u8>>,
+    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))", nullable)]
+    pub randomness: Option<Vec<u8>>,
     pub create_at: String,
     pub update_at: String,
 }
@@ -1,8 +1,9 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.3
 
 use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "randomness_task")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -21,8 +22,12 @@ pub struct Model {
     pub callback_gas_limit: i32,
     #[sea_orm(column_type = "Binary(BlobSize::Blob(None))")]
     pub callback_max_gas_price: Vec<u8>,
+    #[sea_orm(column_type = "Binary(BlobSize::Blob(None))")]
+    pub estimated_payment: Vec<u8>,
     pub assignment_block_height: i32,
+    pub priority: i32,
     pub state: i32,
+    pub lease_expiry_block_height: i32,
     pub create_at: String,
     pub update_at: String,
 }
@@ -6,6 +6,13 @@ mod m20220920_000003_create_randomness_task_table;
 mod m20220920_000004_create_randomness_task_index;
 mod m20230612_000005_create_randomness_result_table;
 mod m20230612_000006_create_randomness_result_index;
+mod m20230620_000007_version_group_info_blobs;
+mod m20230621_000008_add_randomness_task_priority;
+mod m20230622_000009_add_randomness_task_estimated_payment;
+mod m20230623_000010_add_randomness_task_lease_expiry;
+mod m20230701_000011_add_randomness_result_tx_hash;
+mod m20230702_000012_add_randomness_result_randomness;
+mod m20230703_000013_add_node_info_last_processed_block;
 
 pub struct Migrator;
 
@@ -19,6 +26,13 @@ impl MigratorTrait for Migrator {
             Box::new(m20220920_000004_create_randomness_task_index::Migration),
             Box::new(m20230612_000005_create_randomness_result_table::Migration),
             Box::new(m20230612_000006_create_randomness_result_index::Migration),
+            Box::new(m20230620_000007_version_group_info_blobs::Migration),
+            Box::new(m20230621_000008_add_randomness_task_priority::Migration),
+            Box::new(m20230622_000009_add_randomness_task_estimated_payment::Migration),
+            Box::new(m20230623_000010_add_randomness_task_lease_expiry::Migration),
+            Box::new(m20230701_000011_add_randomness_result_tx_hash::Migration),
+            Box::new(m20230702_000012_add_randomness_result_randomness::Migration),
+            Box::new(m20230703_000013_add_node_info_last_processed_block::Migration),
         ]
     }
 }
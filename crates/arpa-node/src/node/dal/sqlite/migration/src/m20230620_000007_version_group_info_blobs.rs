@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+/// Prefixes the DKG output blobs persisted before serialization versioning with version 1.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "UPDATE group_info SET public_key = CAST(X'01' || public_key AS BLOB) WHERE public_key IS NOT NULL",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "UPDATE group_info SET share = CAST(X'01' || share AS BLOB) WHERE share IS NOT NULL",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared(
+            "UPDATE group_info SET public_key = substr(public_key, 2) WHERE public_key IS NOT NULL",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "UPDATE group_info SET share = substr(share, 2) WHERE share IS NOT NULL",
+        )
+        .await?;
+
+        Ok(())
+    }
+}
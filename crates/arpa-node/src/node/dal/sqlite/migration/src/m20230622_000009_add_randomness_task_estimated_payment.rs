@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RandomnessTask::Table)
+                    .add_column(
+                        ColumnDef::new(RandomnessTask::EstimatedPayment)
+                            .blob(BlobSize::Blob(None))
+                            .not_null()
+                            .default(Vec::<u8>::new()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(RandomnessTask::Table)
+                    .drop_column(RandomnessTask::EstimatedPayment)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum RandomnessTask {
+    Table,
+    EstimatedPayment,
+}
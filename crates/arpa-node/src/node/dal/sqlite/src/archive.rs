@@ -0,0 +1,214 @@
+use crate::core::{GroupQuery, NodeQuery, RandomnessResultQuery, RandomnessTaskQuery};
+use crate::{DBError, DBResult, SqliteDB};
+use arpa_node_dal::BLSResultCacheState;
+use entity::{group_info, node_info, randomness_result, randomness_task};
+use sea_orm::{ActiveModelTrait, Set, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Version prefixed to the node state archive.
+/// Bump it whenever the layout of the archive or of the tables it holds changes.
+pub const STATE_ARCHIVE_VERSION: u8 = 4;
+
+/// Everything a node needs to resume on another host: the node info with its DKG keypair,
+/// the current group with the secret share, the unhandled randomness tasks and the signatures
+/// still being aggregated.
+///
+/// The archive holds the DKG private key and the share in plain, so it should be handled with
+/// the same care as the account key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeStateArchive {
+    pub node_info: node_info::Model,
+    pub group_info: Option<group_info::Model>,
+    pub randomness_tasks: Vec<randomness_task::Model>,
+    pub randomness_results: Vec<randomness_result::Model>,
+}
+
+impl NodeStateArchive {
+    pub fn read(path: impl AsRef<Path>) -> DBResult<Self> {
+        let bytes = fs::read(path).map_err(|e| DBError::StateArchiveError(e.to_string()))?;
+
+        match bytes.split_first() {
+            Some((&STATE_ARCHIVE_VERSION, payload)) => {
+                bincode::deserialize(payload).map_err(|e| DBError::StateArchiveError(e.to_string()))
+            }
+            Some((&version, _)) => Err(DBError::UnsupportedStateArchiveVersion(version)),
+            None => Err(DBError::StateArchiveError("empty archive".to_string())),
+        }
+    }
+
+    /// Writes the archive to a temporary file renamed to `path` once complete,
+    /// so that an interrupted export never leaves a truncated archive behind.
+    pub fn write(&self, path: impl AsRef<Path>) -> DBResult<()> {
+        let path = path.as_ref();
+
+        let mut bytes = vec![STATE_ARCHIVE_VERSION];
+        bytes.extend(
+            bincode::serialize(self).map_err(|e| DBError::StateArchiveError(e.to_string()))?,
+        );
+
+        let tmp_path = path.with_extension("tmp");
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        options
+            .open(&tmp_path)
+            .and_then(|mut file| {
+                file.write_all(&bytes)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp_path, path))
+            .map_err(|e| DBError::StateArchiveError(e.to_string()))
+    }
+}
+
+impl SqliteDB {
+    /// Exports the state of the node to a single archive at `path`.
+    pub async fn export_state(&self, path: impl AsRef<Path>) -> DBResult<NodeStateArchive> {
+        let archive = self.build_state_archive().await?;
+
+        archive.write(path)?;
+
+        Ok(archive)
+    }
+
+    /// Restores the archive at `path` into this database, which must not hold a node yet.
+    pub async fn import_state(&self, path: impl AsRef<Path>) -> DBResult<NodeStateArchive> {
+        let archive = NodeStateArchive::read(path)?;
+
+        self.import_state_archive(&archive).await?;
+
+        Ok(archive)
+    }
+
+    pub async fn build_state_archive(&self) -> DBResult<NodeStateArchive> {
+        let conn = &*self.connection;
+
+        let node_info = NodeQuery::find_current_node_info(conn)
+            .await?
+            .ok_or(DBError::NoNodeRecord)?;
+
+        let group_info = GroupQuery::find_current_group_info(conn).await?;
+
+        let randomness_tasks = RandomnessTaskQuery::select_unhandled(conn).await?;
+
+        let mut randomness_results = vec![];
+        for state in [
+            BLSResultCacheState::NotCommitted,
+            BLSResultCacheState::Committing,
+        ] {
+            randomness_results
+                .extend(RandomnessResultQuery::select_by_state(conn, state.to_i32()).await?);
+        }
+
+        Ok(NodeStateArchive {
+            node_info,
+            group_info,
+            randomness_tasks,
+            randomness_results,
+        })
+    }
+
+    /// Inserts all the records of the archive in a single transaction,
+    /// so that a failed import leaves the database untouched.
+    pub async fn import_state_archive(&self, archive: &NodeStateArchive) -> DBResult<()> {
+        let conn = &*self.connection;
+
+        if NodeQuery::find_current_node_info(conn).await?.is_some() {
+            return Err(DBError::ExistingNodeRecord);
+        }
+
+        let txn = conn.begin().await?;
+
+        let node_info = archive.node_info.clone();
+        node_info::ActiveModel {
+            id: Set(node_info.id),
+            id_address: Set(node_info.id_address),
+            node_rpc_endpoint: Set(node_info.node_rpc_endpoint),
+            dkg_private_key: Set(node_info.dkg_private_key),
+            dkg_public_key: Set(node_info.dkg_public_key),
+            last_processed_block: Set(node_info.last_processed_block),
+            create_at: Set(node_info.create_at),
+            update_at: Set(node_info.update_at),
+        }
+        .insert(&txn)
+        .await?;
+
+        if let Some(group_info) = archive.group_info.clone() {
+            group_info::ActiveModel {
+                id: Set(group_info.id),
+                index: Set(group_info.index),
+                epoch: Set(group_info.epoch),
+                size: Set(group_info.size),
+                threshold: Set(group_info.threshold),
+                state: Set(group_info.state),
+                public_key: Set(group_info.public_key),
+                members: Set(group_info.members),
+                committers: Set(group_info.committers),
+                share: Set(group_info.share),
+                dkg_status: Set(group_info.dkg_status),
+                self_member_index: Set(group_info.self_member_index),
+                dkg_start_block_height: Set(group_info.dkg_start_block_height),
+                create_at: Set(group_info.create_at),
+                update_at: Set(group_info.update_at),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        for task in archive.randomness_tasks.iter().cloned() {
+            randomness_task::ActiveModel {
+                id: Set(task.id),
+                request_id: Set(task.request_id),
+                subscription_id: Set(task.subscription_id),
+                group_index: Set(task.group_index),
+                request_type: Set(task.request_type),
+                params: Set(task.params),
+                requester: Set(task.requester),
+                seed: Set(task.seed),
+                request_confirmations: Set(task.request_confirmations),
+                callback_gas_limit: Set(task.callback_gas_limit),
+                callback_max_gas_price: Set(task.callback_max_gas_price),
+                estimated_payment: Set(task.estimated_payment),
+                assignment_block_height: Set(task.assignment_block_height),
+                priority: Set(task.priority),
+                state: Set(task.state),
+                lease_expiry_block_height: Set(task.lease_expiry_block_height),
+                create_at: Set(task.create_at),
+                update_at: Set(task.update_at),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        for result in archive.randomness_results.iter().cloned() {
+            randomness_result::ActiveModel {
+                id: Set(result.id),
+                request_id: Set(result.request_id),
+                group_index: Set(result.group_index),
+                message: Set(result.message),
+                threshold: Set(result.threshold),
+                partial_signatures: Set(result.partial_signatures),
+                state: Set(result.state),
+                tx_hash: Set(result.tx_hash),
+                randomness: Set(result.randomness),
+                create_at: Set(result.create_at),
+                update_at: Set(result.update_at),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(())
+    }
+}
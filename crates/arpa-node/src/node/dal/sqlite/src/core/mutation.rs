@@ -18,6 +18,7 @@ impl NodeMutation {
             node_rpc_endpoint: Set(model.node_rpc_endpoint.to_owned()),
             dkg_private_key: Set(model.dkg_private_key.to_owned()),
             dkg_public_key: Set(model.dkg_public_key.to_owned()),
+            last_processed_block: Set(model.last_processed_block),
             create_at: Set(model.create_at.to_owned()),
             update_at: Set(model.update_at.to_owned()),
             ..Default::default()
@@ -56,6 +57,20 @@ impl NodeMutation {
 
         node_info.update(db).await
     }
+
+    pub async fn update_last_processed_block(
+        db: &DbConn,
+        model: node_info::Model,
+        last_processed_block: i32,
+    ) -> Result<node_info::Model, DbErr> {
+        let mut node_info: node_info::ActiveModel = model.into();
+
+        node_info.last_processed_block = Set(Some(last_processed_block));
+
+        node_info.update_at = Set(format_now_date());
+
+        node_info.update(db).await
+    }
 }
 
 pub struct GroupMutation;
@@ -156,7 +171,9 @@ impl RandomnessTaskMutation {
         request_confirmations: i32,
         callback_gas_limit: i32,
         callback_max_gas_price: Vec<u8>,
+        estimated_payment: Vec<u8>,
         assignment_block_height: i32,
+        priority: i32,
     ) -> Result<randomness_task::ActiveModel, DbErr> {
         randomness_task::ActiveModel {
             request_id: Set(request_id),
@@ -169,7 +186,9 @@ impl RandomnessTaskMutation {
             request_confirmations: Set(request_confirmations),
             callback_gas_limit: Set(callback_gas_limit),
             callback_max_gas_price: Set(callback_max_gas_price),
+            estimated_payment: Set(estimated_payment),
             assignment_block_height: Set(assignment_block_height),
+            priority: Set(priority),
             create_at: Set(format_now_date()),
             update_at: Set(format_now_date()),
             state: Set(0),
@@ -179,19 +198,38 @@ impl RandomnessTaskMutation {
         .await
     }
 
+    /// Claims the available tasks until `lease_expiry_block_height`, including the claimed ones
+    /// whose lease has expired before `current_block_height` without being handled.
+    /// The state of a task is 0 when available, 2 when claimed and 1 when handled.
     pub async fn fetch_available_tasks(
         db: &DbConn,
         group_index: i32,
         assignment_block_height: i32,
+        urgent_assignment_block_height: i32,
+        current_block_height: i32,
+        lease_expiry_block_height: i32,
     ) -> Result<Vec<randomness_task::Model>, DbErr> {
         randomness_task::Model::find_by_statement(Statement::from_sql_and_values(
                 DbBackend::Sqlite,
-                r#"update randomness_task set state = 1 where state = 0 and (group_index = $1 or assignment_block_height < $2) 
+                r#"update randomness_task set state = 2, lease_expiry_block_height = $5 where (state = 0 or (state = 2 and lease_expiry_block_height < $4)) and (group_index = $1 or (priority = 0 and assignment_block_height < $2) or (priority = 1 and assignment_block_height < $3)) 
                 returning *"#,
-                vec![group_index.into(),assignment_block_height.into()],
+                vec![group_index.into(),assignment_block_height.into(),urgent_assignment_block_height.into(),current_block_height.into(),lease_expiry_block_height.into()],
             ))
             .all(db).await
     }
+
+    pub async fn mark_handled(
+        db: &DbConn,
+        model: randomness_task::Model,
+    ) -> Result<randomness_task::Model, DbErr> {
+        let mut randomness_task: randomness_task::ActiveModel = model.into();
+
+        randomness_task.state = Set(1);
+
+        randomness_task.update_at = Set(format_now_date());
+
+        randomness_task.update(db).await
+    }
 }
 
 pub struct RandomnessResultMutation;
@@ -246,11 +284,28 @@ impl RandomnessResultMutation {
         db: &DbConn,
         model: randomness_result::Model,
         status: i32,
+        tx_hash: Option<Vec<u8>>,
     ) -> Result<randomness_result::Model, DbErr> {
         let mut randomness_result: randomness_result::ActiveModel = model.into();
 
         randomness_result.state = Set(status);
 
+        randomness_result.tx_hash = Set(tx_hash);
+
+        randomness_result.update_at = Set(format_now_date());
+
+        randomness_result.update(db).await
+    }
+
+    pub async fn update_randomness(
+        db: &DbConn,
+        model: randomness_result::Model,
+        randomness: Vec<u8>,
+    ) -> Result<randomness_result::Model, DbErr> {
+        let mut randomness_result: randomness_result::ActiveModel = model.into();
+
+        randomness_result.randomness = Set(Some(randomness));
+
         randomness_result.update_at = Set(format_now_date());
 
         randomness_result.update(db).await
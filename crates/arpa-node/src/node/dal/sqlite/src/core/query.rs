@@ -4,7 +4,7 @@ use entity::{
     prelude::{GroupInfo, RandomnessResult, RandomnessTask},
     randomness_result, randomness_task,
 };
-use sea_orm::{ColumnTrait, DbConn, DbErr, EntityTrait, QueryFilter, QueryOrder};
+use sea_orm::{ColumnTrait, DbConn, DbErr, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder};
 
 pub struct NodeQuery;
 
@@ -40,6 +40,21 @@ impl RandomnessTaskQuery {
             .one(db)
             .await
     }
+
+    /// Returns the tasks which are available or claimed but not handled yet.
+    pub async fn select_unhandled(db: &DbConn) -> Result<Vec<randomness_task::Model>, DbErr> {
+        RandomnessTask::find()
+            .filter(randomness_task::Column::State.ne(1))
+            .all(db)
+            .await
+    }
+
+    pub async fn count_by_state(db: &DbConn, state: i32) -> Result<u64, DbErr> {
+        RandomnessTask::find()
+            .filter(randomness_task::Column::State.eq(state))
+            .count(db)
+            .await
+    }
 }
 
 pub struct RandomnessResultQuery;
@@ -64,4 +79,11 @@ impl RandomnessResultQuery {
             .all(db)
             .await
     }
+
+    pub async fn count_by_state(db: &DbConn, state: i32) -> Result<u64, DbErr> {
+        RandomnessResult::find()
+            .filter(randomness_result::Column::State.eq(state))
+            .count(db)
+            .await
+    }
 }
@@ -0,0 +1,105 @@
+use crate::core::{RandomnessResultQuery, RandomnessTaskQuery};
+use crate::SqliteDB;
+use arpa_node_dal::{BLSResultCacheState, GroupInfoFetcher, NodeInfoFetcher};
+use std::fmt::Write;
+use threshold_bls::group::PairingCurve;
+use threshold_bls::serialize::point_to_hex;
+
+/// Dumps the node info, the current group and the randomness task counts of the database at
+/// `data_path` as a readable report.
+///
+/// The database is opened read-only and nothing else is touched, neither the ports nor the chain,
+/// so it is safe to run against the database of a live node. A database lacking any migration
+/// of the node fails with its schema version instead.
+pub async fn inspect<C: PairingCurve>(
+    data_path: &str,
+    signing_key: &[u8],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let db = SqliteDB::build_read_only(data_path, signing_key).await?;
+
+    let mut report = String::new();
+
+    writeln!(
+        report,
+        "Schema version: {}",
+        db.get_applied_migrations()
+            .await?
+            .last()
+            .map_or("none", String::as_str)
+    )?;
+
+    let mut node_cache = db.get_node_info_client::<C>();
+    writeln!(report, "Node info:")?;
+    if node_cache.refresh_current_node_info().await? {
+        writeln!(report, "  id address: {:?}", node_cache.get_id_address()?)?;
+        writeln!(
+            report,
+            "  rpc endpoint: {}",
+            node_cache.get_node_rpc_endpoint()?
+        )?;
+        writeln!(
+            report,
+            "  dkg public key: {}",
+            point_to_hex(node_cache.get_dkg_public_key()?)
+        )?;
+    } else {
+        writeln!(report, "  none")?;
+    }
+
+    let mut group_cache = db.get_group_info_client::<C>();
+    writeln!(report, "Group info:")?;
+    if group_cache.refresh_current_group_info().await? {
+        writeln!(report, "  index: {}", group_cache.get_index()?)?;
+        writeln!(report, "  epoch: {}", group_cache.get_epoch()?)?;
+        writeln!(report, "  size: {}", group_cache.get_size()?)?;
+        writeln!(report, "  threshold: {}", group_cache.get_threshold()?)?;
+        writeln!(report, "  state: {}", group_cache.get_state()?)?;
+        writeln!(report, "  dkg status: {:?}", group_cache.get_dkg_status()?)?;
+        writeln!(
+            report,
+            "  public key: {}",
+            group_cache
+                .get_public_key()
+                .map_or("none".to_string(), point_to_hex)
+        )?;
+        writeln!(
+            report,
+            "  committers: {:?}",
+            group_cache.get_committers(None)?
+        )?;
+        writeln!(report, "  members:")?;
+        for member in group_cache.get_members()?.values() {
+            writeln!(
+                report,
+                "    {} {:?} {}",
+                member.index,
+                member.id_address,
+                member.rpc_endpoint.as_deref().unwrap_or("-")
+            )?;
+        }
+    } else {
+        writeln!(report, "  none")?;
+    }
+
+    let conn = &*db.connection;
+
+    // available and claimed tasks are both still pending
+    let pending_tasks = RandomnessTaskQuery::count_by_state(conn, 0).await?
+        + RandomnessTaskQuery::count_by_state(conn, 2).await?;
+    let handled_tasks = RandomnessTaskQuery::count_by_state(conn, 1).await?;
+
+    let mut fulfilled_results = 0;
+    for state in [
+        BLSResultCacheState::Committed,
+        BLSResultCacheState::CommittedByOthers,
+    ] {
+        fulfilled_results += RandomnessResultQuery::count_by_state(conn, state.to_i32()).await?;
+    }
+
+    writeln!(report, "Randomness tasks:")?;
+    writeln!(report, "  pending: {}", pending_tasks)?;
+    writeln!(report, "  handled: {}", handled_tasks)?;
+    writeln!(report, "  fulfilled: {}", fulfilled_results)?;
+
+    Ok(report)
+}
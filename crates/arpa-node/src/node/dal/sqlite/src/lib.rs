@@ -1,4 +1,6 @@
+pub mod archive;
 pub mod core;
+pub mod inspect;
 use crate::core::GroupMutation;
 use crate::core::GroupQuery;
 use crate::core::NodeMutation;
@@ -12,12 +14,17 @@ use arpa_node_core::BLSTaskError;
 use arpa_node_core::Group;
 use arpa_node_core::Member;
 use arpa_node_core::RandomnessRequestType;
+use arpa_node_core::RandomnessTaskPriority;
+use arpa_node_core::DEFAULT_MAX_GROUP_SIZE;
 use arpa_node_core::{address_to_string, format_now_date, RandomnessTask, Task};
 use arpa_node_dal::cache::BLSResultCache;
 use arpa_node_dal::cache::InMemoryGroupInfoCache;
 use arpa_node_dal::cache::InMemoryNodeInfoCache;
 use arpa_node_dal::cache::InMemorySignatureResultCache;
 use arpa_node_dal::cache::RandomnessResultCache;
+use arpa_node_dal::check_dkg_output;
+use arpa_node_dal::check_group_size;
+use arpa_node_dal::check_partial_public_keys;
 use arpa_node_dal::error::DataAccessResult;
 use arpa_node_dal::error::GroupError;
 use arpa_node_dal::error::RandomnessTaskError;
@@ -35,11 +42,12 @@ use async_trait::async_trait;
 use entity::group_info;
 use entity::node_info;
 use ethers_core::types::Address;
+use ethers_core::types::H256;
 use ethers_core::types::U256;
 use ethers_core::utils::hex;
 use log::LevelFilter;
 pub use migration::Migrator;
-use migration::MigratorTrait;
+use migration::{MigrationName, MigratorTrait};
 use sea_orm::ConnectionTrait;
 use sea_orm::DatabaseBackend;
 use sea_orm::DbConn;
@@ -50,6 +58,7 @@ use std::collections::BTreeMap;
 use std::{marker::PhantomData, sync::Arc, time::Duration};
 use threshold_bls::group::Curve;
 use threshold_bls::group::PairingCurve;
+use threshold_bls::poly::Eval;
 use threshold_bls::serialize::point_to_hex;
 use threshold_bls::sig::Share;
 mod test_helper;
@@ -63,6 +72,18 @@ pub type DBResult<A> = Result<A, DBError>;
 pub enum DBError {
     #[error("there is no node record yet, please run node with new-run mode")]
     NoNodeRecord,
+    #[error(
+        "there is already a node record, the state can only be imported into a fresh database"
+    )]
+    ExistingNodeRecord,
+    #[error("failed to read or write the state archive: {0}")]
+    StateArchiveError(String),
+    #[error("unsupported state archive version {0}")]
+    UnsupportedStateArchiveVersion(u8),
+    #[error(
+        "the database schema is at {version} and lacks the migrations [{pending}], please run the node on it once to migrate it"
+    )]
+    OutdatedSchema { version: String, pending: String },
     #[error(transparent)]
     DbError(#[from] DbErr),
     #[error(transparent)]
@@ -75,6 +96,27 @@ impl From<DBError> for DataAccessError {
     }
 }
 
+/// Version prefixed to the bincode blobs of the DKG output persisted in group_info.
+/// Bump it along with a migration whenever the serialization of the curve types changes.
+pub const SERIALIZATION_VERSION: u8 = 1;
+
+fn serialize_versioned<T: serde::Serialize>(value: &T) -> DataAccessResult<Vec<u8>> {
+    let mut bytes = vec![SERIALIZATION_VERSION];
+    bytes.extend(bincode::serialize(value)?);
+    Ok(bytes)
+}
+
+fn deserialize_versioned<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> DataAccessResult<T> {
+    match bytes.split_first() {
+        Some((&SERIALIZATION_VERSION, payload)) => Ok(bincode::deserialize(payload)?),
+        Some((&version, _)) => Err(DataAccessError::UnsupportedSerializationVersion(version)),
+        None => Err(bincode::Error::from(bincode::ErrorKind::Custom(
+            "empty persisted data".to_string(),
+        ))
+        .into()),
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct SqliteDB {
     connection: Arc<DatabaseConnection>,
@@ -85,7 +127,61 @@ impl SqliteDB {
         db_path: &str,
         signing_key: &[u8],
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut opt = ConnectOptions::new(format!("sqlite://{}?mode=rwc", db_path));
+        let db = SqliteDB::connect(&format!("sqlite://{}?mode=rwc", db_path), signing_key).await?;
+
+        Migrator::up(&*db.connection, None).await?;
+
+        Ok(db)
+    }
+
+    /// Opens an existing database without creating it or running the migrations,
+    /// so that it can be read safely while a node is running on it.
+    ///
+    /// Fails with `OutdatedSchema` if the database lacks any migration of the node, as its
+    /// tables wouldn't have the columns the node reads.
+    pub async fn build_read_only(
+        db_path: &str,
+        signing_key: &[u8],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let db = SqliteDB::connect(&format!("sqlite://{}?mode=ro", db_path), signing_key).await?;
+
+        let applied = db.get_applied_migrations().await?;
+        let pending = Migrator::migrations()
+            .iter()
+            .map(|migration| migration.name().to_owned())
+            .filter(|name| !applied.contains(name))
+            .collect::<Vec<_>>();
+
+        if !pending.is_empty() {
+            return Err(DBError::OutdatedSchema {
+                version: applied.last().cloned().unwrap_or_else(|| "none".to_owned()),
+                pending: pending.join(", "),
+            }
+            .into());
+        }
+
+        Ok(db)
+    }
+
+    /// Returns the names of the migrations applied to the database in order, the last of which
+    /// is the version of its schema.
+    pub async fn get_applied_migrations(&self) -> DBResult<Vec<String>> {
+        // read from the table the migrator records to, which is only written by Migrator::up
+        let rows = self
+            .connection
+            .query_all(Statement::from_string(
+                DatabaseBackend::Sqlite,
+                "SELECT version FROM seaql_migrations ORDER BY version;".to_owned(),
+            ))
+            .await?;
+
+        rows.iter()
+            .map(|row| row.try_get::<String>("", "version").map_err(DBError::from))
+            .collect()
+    }
+
+    async fn connect(url: &str, signing_key: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut opt = ConnectOptions::new(url.to_owned());
         opt.max_connections(100)
             .min_connections(5)
             .connect_timeout(Duration::from_secs(8))
@@ -104,8 +200,6 @@ impl SqliteDB {
         db.integrity_check().await.map_err(|e|
             format!("Node identity is different from the database, please check the (account)cipher key. Original error: {:?}", e.to_string()))?;
 
-        Migrator::up(&*db.connection, None).await?;
-
         Ok(db)
     }
 
@@ -126,6 +220,7 @@ impl SqliteDB {
             db_client: Arc::new(self.clone()),
             group_info_cache: None,
             group_info_cache_model: None,
+            max_group_size: DEFAULT_MAX_GROUP_SIZE,
         }
     }
 
@@ -139,10 +234,11 @@ impl SqliteDB {
     pub async fn get_randomness_result_client(
         &self,
     ) -> DataAccessResult<SignatureResultDBClient<RandomnessResultCache>> {
-        // set commit result of committing records(if any) to not committed
-        let committing_models = RandomnessResultQuery::select_by_state(
+        // committing records are kept as they are, so that the node can reconcile them with the
+        // chain on startup rather than sending a fulfillment which may have landed once more
+        let mut models = RandomnessResultQuery::select_by_state(
             &self.connection,
-            BLSResultCacheState::Committing.to_i32(),
+            BLSResultCacheState::NotCommitted.to_i32(),
         )
         .await
         .map_err(|e| {
@@ -150,29 +246,17 @@ impl SqliteDB {
             e
         })?;
 
-        for committing_model in committing_models {
-            RandomnessResultMutation::update_commit_result(
+        models.extend(
+            RandomnessResultQuery::select_by_state(
                 &self.connection,
-                committing_model,
-                BLSResultCacheState::NotCommitted.to_i32(),
+                BLSResultCacheState::Committing.to_i32(),
             )
             .await
             .map_err(|e| {
                 let e: DBError = e.into();
                 e
-            })?;
-        }
-
-        // load all not committed records
-        let models = RandomnessResultQuery::select_by_state(
-            &self.connection,
-            BLSResultCacheState::NotCommitted.to_i32(),
-        )
-        .await
-        .map_err(|e| {
-            let e: DBError = e.into();
-            e
-        })?;
+            })?,
+        );
 
         let mut results = vec![];
 
@@ -197,7 +281,9 @@ impl SqliteDB {
                         callback_max_gas_price: U256::from_big_endian(
                             &model.callback_max_gas_price,
                         ),
+                        estimated_payment: U256::from_big_endian(&model.estimated_payment),
                         assignment_block_height: model.assignment_block_height as usize,
+                        priority: RandomnessTaskPriority::from(model.priority as u8),
                     })
                     .ok_or_else(|| {
                         RandomnessTaskError::NoRandomnessTask(format!("{:?}", &model.request_id))
@@ -217,6 +303,11 @@ impl SqliteDB {
             results.push(BLSResultCache {
                 result_cache: signature_result_cache,
                 state: BLSResultCacheState::from(model.state),
+                tx_hash: model.tx_hash.as_deref().map(H256::from_slice),
+                randomness: model
+                    .randomness
+                    .as_deref()
+                    .and_then(|randomness| randomness.try_into().ok()),
             });
         }
 
@@ -287,6 +378,9 @@ impl<C: PairingCurve> NodeInfoDBClient<C> {
                     node_info.node_rpc_endpoint.clone(),
                     bincode::deserialize(&node_info.dkg_private_key).unwrap(),
                     bincode::deserialize(&node_info.dkg_public_key).unwrap(),
+                    node_info
+                        .last_processed_block
+                        .map(|last_processed_block| last_processed_block as usize),
                 );
 
                 node_info_cache.refresh_context_entry();
@@ -316,6 +410,7 @@ impl<C: PairingCurve> NodeInfoDBClient<C> {
             node_rpc_endpoint,
             dkg_private_key: bincode::serialize(&dkg_private_key).unwrap(),
             dkg_public_key: bincode::serialize(&dkg_public_key).unwrap(),
+            last_processed_block: None,
             create_at: format_now_date(),
             update_at: format_now_date(),
         };
@@ -345,6 +440,7 @@ pub struct GroupInfoDBClient<C: PairingCurve> {
     db_client: Arc<SqliteDB>,
     group_info_cache_model: Option<group_info::Model>,
     group_info_cache: Option<InMemoryGroupInfoCache<C>>,
+    max_group_size: usize,
 }
 
 impl<C: PairingCurve> std::fmt::Debug for GroupInfoDBClient<C> {
@@ -375,10 +471,21 @@ impl<C: PairingCurve> std::fmt::Debug for GroupInfoDBClient<C> {
 }
 
 impl<C: PairingCurve> GroupInfoDBClient<C> {
-    pub async fn refresh_current_group_info(&mut self) -> DBResult<bool> {
+    /// Bounds the size of the groups saved, a larger group is rejected with `GroupTooLarge`.
+    pub fn with_max_group_size(mut self, max_group_size: usize) -> Self {
+        self.max_group_size = max_group_size;
+        self
+    }
+
+    pub async fn refresh_current_group_info(&mut self) -> DataAccessResult<bool> {
         let conn = &self.db_client.connection;
 
-        match GroupQuery::find_current_group_info(conn).await? {
+        match GroupQuery::find_current_group_info(conn)
+            .await
+            .map_err(|e| {
+                let e: DBError = e.into();
+                e
+            })? {
             Some(group_info) => {
                 let group = Group {
                     index: group_info.index as usize,
@@ -389,7 +496,8 @@ impl<C: PairingCurve> GroupInfoDBClient<C> {
                     public_key: group_info
                         .public_key
                         .as_ref()
-                        .map(|bytes| bincode::deserialize(bytes).unwrap()),
+                        .map(|bytes| deserialize_versioned(bytes))
+                        .transpose()?,
                     members: serde_json::from_str(&group_info.members).unwrap(),
                     committers: group_info
                         .committers
@@ -402,12 +510,14 @@ impl<C: PairingCurve> GroupInfoDBClient<C> {
                     group_info
                         .share
                         .as_ref()
-                        .map(|bytes| bincode::deserialize(bytes).unwrap()),
+                        .map(|bytes| deserialize_versioned(bytes))
+                        .transpose()?,
                     group,
                     (group_info.dkg_status as usize).into(),
                     group_info.self_member_index as usize,
                     group_info.dkg_start_block_height as usize,
-                );
+                )
+                .with_max_group_size(self.max_group_size);
 
                 group_info_cache.refresh_context_entry();
 
@@ -463,6 +573,11 @@ impl SignatureResultDBClient<RandomnessResultCache> {
     pub fn get_connection(&self) -> &DbConn {
         &self.db_client.connection
     }
+
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.signature_results_cache = self.signature_results_cache.with_max_entries(max_entries);
+        self
+    }
 }
 
 impl<C: PairingCurve> NodeInfoFetcher<C> for NodeInfoDBClient<C> {
@@ -484,6 +599,13 @@ impl<C: PairingCurve> NodeInfoFetcher<C> for NodeInfoDBClient<C> {
     fn get_dkg_public_key(&self) -> DataAccessResult<&C::G2> {
         self.node_info_cache.as_ref().unwrap().get_dkg_public_key()
     }
+
+    fn get_last_processed_block(&self) -> DataAccessResult<Option<usize>> {
+        self.node_info_cache
+            .as_ref()
+            .unwrap()
+            .get_last_processed_block()
+    }
 }
 
 #[async_trait]
@@ -526,6 +648,23 @@ impl<C: PairingCurve + Sync + Send> NodeInfoUpdater<C> for NodeInfoDBClient<C> {
 
         Ok(())
     }
+
+    async fn set_last_processed_block(&mut self, block_height: usize) -> DataAccessResult<()> {
+        NodeMutation::update_last_processed_block(
+            self.get_connection(),
+            self.node_info_cache_model.to_owned().unwrap(),
+            block_height as i32,
+        )
+        .await
+        .map_err(|e| {
+            let e: DBError = e.into();
+            e
+        })?;
+
+        self.refresh_current_node_info().await?;
+
+        Ok(())
+    }
 }
 
 impl<C: PairingCurve> GroupInfoFetcher<C> for GroupInfoDBClient<C> {
@@ -617,12 +756,12 @@ impl<C: PairingCurve> GroupInfoFetcher<C> for GroupInfoDBClient<C> {
         group_info.get_member(id_address)
     }
 
-    fn get_committers(&self) -> DataAccessResult<Vec<Address>> {
+    fn get_committers(&self, task: Option<&RandomnessTask>) -> DataAccessResult<Vec<Address>> {
         self.only_has_group_task()?;
 
         let group_info = self.group_info_cache.as_ref().unwrap();
 
-        group_info.get_committers()
+        group_info.get_committers(task)
     }
 
     fn get_dkg_start_block_height(&self) -> DataAccessResult<usize> {
@@ -657,6 +796,8 @@ impl<PC: PairingCurve + Sync + Send> GroupInfoUpdater<PC> for GroupInfoDBClient<
         self_index: usize,
         task: arpa_node_core::DKGTask,
     ) -> DataAccessResult<()> {
+        check_group_size(task.size.max(task.members.len()), self.max_group_size)?;
+
         let members: BTreeMap<Address, Member<PC>> = task
             .members
             .iter()
@@ -717,6 +858,10 @@ impl<PC: PairingCurve + Sync + Send> GroupInfoUpdater<PC> for GroupInfoDBClient<
             return Err(GroupError::GroupAlreadyReady.into());
         }
 
+        check_group_size(group.size, self.max_group_size)?;
+
+        check_dkg_output(&group, &output)?;
+
         // every member index is started from 0
         let qualified_node_indices = output
             .qual
@@ -737,6 +882,20 @@ impl<PC: PairingCurve + Sync + Send> GroupInfoUpdater<PC> for GroupInfoDBClient<
         let public_key: PC::G2 =
             bincode::deserialize(&bincode::serialize(&output.public.public_key())?)?;
 
+        let partial_public_keys = qualified_node_indices
+            .iter()
+            .map(|index| {
+                Ok(Eval {
+                    value: bincode::deserialize(&bincode::serialize(
+                        &output.public.eval(*index as u32).value,
+                    )?)?,
+                    index: *index as u32,
+                })
+            })
+            .collect::<DataAccessResult<Vec<Eval<PC::G2>>>>()?;
+
+        check_partial_public_keys::<PC>(group.threshold, &public_key, &partial_public_keys)?;
+
         let mut partial_public_key = PC::G2::new();
 
         for (_, member) in group.members.iter_mut() {
@@ -751,10 +910,10 @@ impl<PC: PairingCurve + Sync + Send> GroupInfoUpdater<PC> for GroupInfoDBClient<
                 }
             }
 
-            let member_partial_public_key = bincode::deserialize(&bincode::serialize(
-                &output.public.eval(member.index as u32).value,
-            )?)?;
-            member.partial_public_key = Some(member_partial_public_key);
+            member.partial_public_key = partial_public_keys
+                .iter()
+                .find(|key| key.index as usize == member.index)
+                .map(|key| key.value.clone());
 
             if self_index == member.index {
                 partial_public_key = member.partial_public_key.clone().unwrap();
@@ -765,8 +924,8 @@ impl<PC: PairingCurve + Sync + Send> GroupInfoUpdater<PC> for GroupInfoDBClient<
             self.get_connection(),
             self.group_info_cache_model.to_owned().unwrap(),
             qualified_node_indices.len() as i32,
-            bincode::serialize(&public_key).unwrap(),
-            bincode::serialize(&output.share).unwrap(),
+            serialize_versioned(&public_key)?,
+            serialize_versioned(&output.share)?,
             serde_json::to_string(&group.members).unwrap(),
         )
         .await
@@ -892,7 +1051,9 @@ impl BLSTasksFetcher<RandomnessTask> for BLSTasksDBClient<RandomnessTask> {
             request_confirmations: model.request_confirmations as u16,
             callback_gas_limit: model.callback_gas_limit as u32,
             callback_max_gas_price: U256::from_big_endian(&model.callback_max_gas_price),
+            estimated_payment: U256::from_big_endian(&model.estimated_payment),
             assignment_block_height: model.assignment_block_height as usize,
+            priority: RandomnessTaskPriority::from(model.priority as u8),
         })
         .ok_or_else(|| {
             RandomnessTaskError::NoRandomnessTask(format!("{:?}", task_request_id)).into()
@@ -929,7 +1090,9 @@ impl BLSTasksUpdater<RandomnessTask> for BLSTasksDBClient<RandomnessTask> {
             task.request_confirmations as i32,
             task.callback_gas_limit as i32,
             u256_to_vec(&task.callback_max_gas_price),
+            u256_to_vec(&task.estimated_payment),
             task.assignment_block_height as i32,
+            task.priority.to_u8() as i32,
         )
         .await
         .map_err(|e| {
@@ -945,17 +1108,20 @@ impl BLSTasksUpdater<RandomnessTask> for BLSTasksDBClient<RandomnessTask> {
         current_block_height: usize,
         current_group_index: usize,
         randomness_task_exclusive_window: usize,
+        urgent_randomness_task_exclusive_window: usize,
+        claim_lease_duration: usize,
     ) -> DataAccessResult<Vec<RandomnessTask>> {
         let before_assignment_block_height =
-            if current_block_height > randomness_task_exclusive_window {
-                current_block_height - randomness_task_exclusive_window
-            } else {
-                0
-            };
+            current_block_height.saturating_sub(randomness_task_exclusive_window);
+        let urgent_before_assignment_block_height =
+            current_block_height.saturating_sub(urgent_randomness_task_exclusive_window);
         RandomnessTaskMutation::fetch_available_tasks(
             self.get_connection(),
             current_group_index as i32,
             before_assignment_block_height as i32,
+            urgent_before_assignment_block_height as i32,
+            current_block_height as i32,
+            (current_block_height + claim_lease_duration) as i32,
         )
         .await
         .map(|models| {
@@ -972,7 +1138,9 @@ impl BLSTasksUpdater<RandomnessTask> for BLSTasksDBClient<RandomnessTask> {
                     request_confirmations: model.request_confirmations as u16,
                     callback_gas_limit: model.callback_gas_limit as u32,
                     callback_max_gas_price: U256::from_big_endian(&model.callback_max_gas_price),
+                    estimated_payment: U256::from_big_endian(&model.estimated_payment),
                     assignment_block_height: model.assignment_block_height as usize,
+                    priority: RandomnessTaskPriority::from(model.priority as u8),
                 })
                 .collect::<Vec<_>>()
         })
@@ -982,6 +1150,28 @@ impl BLSTasksUpdater<RandomnessTask> for BLSTasksDBClient<RandomnessTask> {
             e
         })
     }
+
+    async fn mark_handled(&mut self, task_request_id: &[u8]) -> DataAccessResult<()> {
+        let model =
+            RandomnessTaskQuery::select_by_request_id(self.get_connection(), task_request_id)
+                .await
+                .map_err(|e| {
+                    let e: DBError = e.into();
+                    e
+                })?
+                .ok_or_else(|| {
+                    RandomnessTaskError::NoRandomnessTask(format!("{:?}", task_request_id))
+                })?;
+
+        RandomnessTaskMutation::mark_handled(self.get_connection(), model)
+            .await
+            .map_err(|e| {
+                let e: DBError = e.into();
+                e
+            })?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -1031,7 +1221,9 @@ impl SignatureResultCacheFetcher<RandomnessResultCache>
                     request_confirmations: model.request_confirmations as u16,
                     callback_gas_limit: model.callback_gas_limit as u32,
                     callback_max_gas_price: U256::from_big_endian(&model.callback_max_gas_price),
+                    estimated_payment: U256::from_big_endian(&model.estimated_payment),
                     assignment_block_height: model.assignment_block_height as usize,
+                    priority: RandomnessTaskPriority::from(model.priority as u8),
                 })
                 .ok_or_else(|| {
                     RandomnessTaskError::NoRandomnessTask(format!("{:?}", task_request_id))
@@ -1049,8 +1241,52 @@ impl SignatureResultCacheFetcher<RandomnessResultCache>
                 threshold: model.threshold as usize,
             },
             state: BLSResultCacheState::from(model.state),
+            tx_hash: model.tx_hash.as_deref().map(H256::from_slice),
+            randomness: model
+                .randomness
+                .as_deref()
+                .and_then(|randomness| randomness.try_into().ok()),
         })
     }
+
+    async fn count_by_state(&self) -> DataAccessResult<Vec<(BLSResultCacheState, usize)>> {
+        let mut counts = vec![];
+
+        for state in BLSResultCacheState::ALL {
+            let count =
+                RandomnessResultQuery::count_by_state(self.get_connection(), state.to_i32())
+                    .await
+                    .map_err(|e| {
+                        let e: DBError = e.into();
+                        e
+                    })?;
+            counts.push((state, count as usize));
+        }
+
+        Ok(counts)
+    }
+
+    async fn get_committing_signatures(&self) -> DataAccessResult<Vec<RandomnessResultCache>> {
+        self.signature_results_cache
+            .get_committing_signatures()
+            .await
+    }
+
+    async fn get_inflight_tx(&self, task_request_id: &[u8]) -> DataAccessResult<Option<H256>> {
+        let model =
+            RandomnessResultQuery::select_by_request_id(self.get_connection(), task_request_id)
+                .await
+                .map_err(|e| {
+                    let e: DBError = e.into();
+                    e
+                })?
+                .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
+
+        Ok(model
+            .tx_hash
+            .filter(|_| model.state == BLSResultCacheState::Committing.to_i32())
+            .map(|tx_hash| H256::from_slice(&tx_hash)))
+    }
 }
 
 #[async_trait]
@@ -1082,6 +1318,7 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
                 self.get_connection(),
                 model,
                 BLSResultCacheState::Committing.to_i32(),
+                None,
             )
             .await
             .map_err(|e| {
@@ -1097,6 +1334,7 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
         &mut self,
         task_request_id: &[u8],
         status: BLSResultCacheState,
+        tx_hash: Option<H256>,
     ) -> DataAccessResult<()> {
         let model =
             RandomnessResultQuery::select_by_request_id(self.get_connection(), task_request_id)
@@ -1111,6 +1349,39 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
             self.get_connection(),
             model,
             status.to_i32(),
+            tx_hash.map(|tx_hash| tx_hash.as_bytes().to_vec()),
+        )
+        .await
+        .map_err(|e| {
+            let e: DBError = e.into();
+            e
+        })?;
+
+        self.signature_results_cache
+            .update_commit_result(task_request_id, status, tx_hash)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_randomness(
+        &mut self,
+        task_request_id: &[u8],
+        randomness: [u8; 32],
+    ) -> DataAccessResult<()> {
+        let model =
+            RandomnessResultQuery::select_by_request_id(self.get_connection(), task_request_id)
+                .await
+                .map_err(|e| {
+                    let e: DBError = e.into();
+                    e
+                })?
+                .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
+
+        RandomnessResultMutation::update_randomness(
+            self.get_connection(),
+            model,
+            randomness.to_vec(),
         )
         .await
         .map_err(|e| {
@@ -1119,12 +1390,51 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
         })?;
 
         self.signature_results_cache
-            .update_commit_result(task_request_id, status)
+            .save_randomness(task_request_id, randomness)
             .await?;
 
         Ok(())
     }
 
+    async fn expire_signatures(
+        &mut self,
+        current_block_height: usize,
+        timeout_blocks: usize,
+    ) -> DataAccessResult<Vec<RandomnessResultCache>> {
+        let expired_signatures = self
+            .signature_results_cache
+            .expire_signatures(current_block_height, timeout_blocks)
+            .await?;
+
+        // the records are kept so that the expired tasks can still be inspected
+        for signature in expired_signatures.iter() {
+            let model = RandomnessResultQuery::select_by_request_id(
+                self.get_connection(),
+                signature.request_id(),
+            )
+            .await
+            .map_err(|e| {
+                let e: DBError = e.into();
+                e
+            })?
+            .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
+
+            RandomnessResultMutation::update_commit_result(
+                self.get_connection(),
+                model,
+                BLSResultCacheState::Expired.to_i32(),
+                None,
+            )
+            .await
+            .map_err(|e| {
+                let e: DBError = e.into();
+                e
+            })?;
+        }
+
+        Ok(expired_signatures)
+    }
+
     async fn add(
         &mut self,
         group_index: usize,
@@ -1145,9 +1455,47 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
             e
         })?;
 
-        self.signature_results_cache
-            .add(group_index, task, message, threshold)
-            .await?;
+        let (_, evicted) = self.signature_results_cache.insert(BLSResultCache {
+            result_cache: RandomnessResultCache {
+                group_index,
+                randomness_task: task,
+                message,
+                threshold,
+                partial_signatures: BTreeMap::new(),
+            },
+            state: BLSResultCacheState::NotCommitted,
+            tx_hash: None,
+            randomness: None,
+        });
+
+        // an evicted result is given up like an expired one, so that it isn't loaded on restart
+        for result in evicted
+            .iter()
+            .filter(|result| result.state == BLSResultCacheState::NotCommitted)
+        {
+            let model = RandomnessResultQuery::select_by_request_id(
+                self.get_connection(),
+                result.result_cache.request_id(),
+            )
+            .await
+            .map_err(|e| {
+                let e: DBError = e.into();
+                e
+            })?
+            .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
+
+            RandomnessResultMutation::update_commit_result(
+                self.get_connection(),
+                model,
+                BLSResultCacheState::Expired.to_i32(),
+                None,
+            )
+            .await
+            .map_err(|e| {
+                let e: DBError = e.into();
+                e
+            })?;
+        }
 
         Ok(true)
     }
@@ -1190,26 +1538,41 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
 #[cfg(test)]
 pub mod sqlite_tests {
     use crate::test_helper;
-    use crate::SqliteDB;
+    use crate::{deserialize_versioned, serialize_versioned, SERIALIZATION_VERSION};
+    use crate::{Migrator, SqliteDB};
     use arpa_node_core::DKGStatus;
     use arpa_node_core::DKGTask;
     use arpa_node_core::RandomnessRequestType;
     use arpa_node_core::RandomnessTask;
+    use arpa_node_core::RandomnessTaskPriority;
+    use arpa_node_core::DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION;
     use arpa_node_core::DEFAULT_RANDOMNESS_TASK_EXCLUSIVE_WINDOW;
     use arpa_node_core::PLACEHOLDER_ADDRESS;
+    use arpa_node_dal::check_partial_public_keys;
+    use arpa_node_dal::error::DataAccessError;
+    use arpa_node_dal::error::GroupError;
+    use arpa_node_dal::BLSResultCacheState;
     use arpa_node_dal::BLSTasksFetcher;
     use arpa_node_dal::BLSTasksUpdater;
     use arpa_node_dal::GroupInfoFetcher;
     use arpa_node_dal::GroupInfoUpdater;
     use arpa_node_dal::NodeInfoFetcher;
     use arpa_node_dal::NodeInfoUpdater;
+    use arpa_node_dal::SignatureResultCacheFetcher;
+    use arpa_node_dal::SignatureResultCacheUpdater;
     use ethers_core::types::Address;
     use ethers_core::types::U256;
+    use migration::MigratorTrait;
+    use sea_orm::{ConnectionTrait, DatabaseBackend, Statement};
     use std::{fs, path::PathBuf};
     use threshold_bls::curve::bn254::PairingCurve;
+    use threshold_bls::curve::bn254::Scalar;
+    use threshold_bls::curve::bn254::G2;
+    use threshold_bls::group::Element;
     use threshold_bls::schemes::bn254::G2Curve;
     use threshold_bls::schemes::bn254::G2Scheme;
     use threshold_bls::sig::Scheme;
+    use threshold_bls::sig::Share;
 
     const DB_PATH: &str = "test.sqlite";
 
@@ -1229,6 +1592,24 @@ pub mod sqlite_tests {
         SqliteDB::build(DB_PATH, CIPHER_KEY.as_bytes()).await
     }
 
+    fn build_task(request_id: &[u8]) -> RandomnessTask {
+        RandomnessTask {
+            request_id: request_id.to_vec(),
+            subscription_id: 0,
+            group_index: 2,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: PLACEHOLDER_ADDRESS,
+            seed: U256::from_big_endian(&String::from("test task").into_bytes()),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: 0.into(),
+            estimated_payment: 0.into(),
+            assignment_block_height: 100,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
     #[tokio::test]
     async fn test_build_db() {
         setup();
@@ -1240,6 +1621,37 @@ pub mod sqlite_tests {
         teardown();
     }
 
+    #[tokio::test]
+    async fn test_build_read_only_db_of_outdated_schema() {
+        setup();
+
+        let db = build_sqlite_db().await.unwrap();
+        let applied = db.get_applied_migrations().await.unwrap();
+        assert_eq!(applied.len(), Migrator::migrations().len());
+        assert!(SqliteDB::build_read_only(DB_PATH, CIPHER_KEY.as_bytes())
+            .await
+            .is_ok());
+
+        // as if the database was left by a node of the previous version
+        let latest = applied.last().unwrap();
+        db.connection
+            .execute(Statement::from_string(
+                DatabaseBackend::Sqlite,
+                format!("DELETE FROM seaql_migrations WHERE version = '{}';", latest),
+            ))
+            .await
+            .unwrap();
+
+        let err = SqliteDB::build_read_only(DB_PATH, CIPHER_KEY.as_bytes())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(&applied[applied.len() - 2]));
+        assert!(err.contains(latest.as_str()));
+
+        teardown();
+    }
+
     #[tokio::test]
     async fn test_integrity_check() {
         setup();
@@ -1319,12 +1731,12 @@ pub mod sqlite_tests {
     }
 
     #[tokio::test]
-    async fn test_save_node_dkg_key_pair() {
+    async fn test_save_last_processed_block() {
         setup();
 
-        let db = build_sqlite_db().await.unwrap();
+        let sqlite_db = build_sqlite_db().await.unwrap();
 
-        let mut db = db.get_node_info_client::<PairingCurve>();
+        let mut db = sqlite_db.get_node_info_client::<PairingCurve>();
 
         let id_address = "0x0000000000000000000000000000000000000001"
             .parse()
@@ -1335,16 +1747,50 @@ pub mod sqlite_tests {
 
         let (private_key, public_key) = G2Scheme::keypair(rng);
 
-        if let Err(e) = db
-            .save_node_info(id_address, node_rpc_endpoint, private_key, public_key)
+        db.save_node_info(id_address, node_rpc_endpoint, private_key, public_key)
             .await
-        {
-            println!("{:?}", e);
-        }
+            .unwrap();
 
-        let rng = &mut rand::thread_rng();
+        assert_eq!(None, db.get_last_processed_block().unwrap());
 
-        let (private_key, public_key) = G2Scheme::keypair(rng);
+        db.set_last_processed_block(100).await.unwrap();
+
+        // the block is loaded back along with the node info
+        let mut db = sqlite_db.get_node_info_client::<PairingCurve>();
+        db.refresh_current_node_info().await.unwrap();
+
+        assert_eq!(Some(100), db.get_last_processed_block().unwrap());
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_save_node_dkg_key_pair() {
+        setup();
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut db = db.get_node_info_client::<PairingCurve>();
+
+        let id_address = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let node_rpc_endpoint = String::from("127.0.0.1");
+
+        let rng = &mut rand::thread_rng();
+
+        let (private_key, public_key) = G2Scheme::keypair(rng);
+
+        if let Err(e) = db
+            .save_node_info(id_address, node_rpc_endpoint, private_key, public_key)
+            .await
+        {
+            println!("{:?}", e);
+        }
+
+        let rng = &mut rand::thread_rng();
+
+        let (private_key, public_key) = G2Scheme::keypair(rng);
 
         if let Err(e) = db.set_dkg_key_pair(private_key, public_key).await {
             println!("{:?}", e);
@@ -1418,6 +1864,43 @@ pub mod sqlite_tests {
         teardown();
     }
 
+    #[tokio::test]
+    async fn test_save_task_info_exceeding_max_group_size() {
+        setup();
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut db = db
+            .get_group_info_client::<PairingCurve>()
+            .with_max_group_size(2);
+
+        let members: Vec<Address> = (1..=3).map(Address::from_low_u64_be).collect();
+
+        let task = DKGTask {
+            group_index: 1,
+            epoch: 1,
+            size: 3,
+            threshold: 2,
+            members,
+            assignment_block_height: 100,
+            coordinator_address: "0x00000000000000000000000000000000000000c1"
+                .parse()
+                .unwrap(),
+        };
+
+        let res = db.save_task_info(0, task).await;
+
+        assert!(matches!(
+            res,
+            Err(DataAccessError::GroupError(GroupError::GroupTooLarge {
+                size: 3,
+                max_group_size: 2
+            }))
+        ));
+        assert!(db.get_group().is_err());
+
+        teardown();
+    }
+
     #[tokio::test]
     async fn test_update_dkg_status() {
         setup();
@@ -1534,6 +2017,118 @@ pub mod sqlite_tests {
         teardown();
     }
 
+    #[tokio::test]
+    async fn test_save_inconsistent_output() {
+        setup();
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut db = db.get_group_info_client::<PairingCurve>();
+        let members: Vec<Address> = [
+            "0x0000000000000000000000000000000000000001"
+                .parse()
+                .unwrap(),
+            "0x0000000000000000000000000000000000000002"
+                .parse()
+                .unwrap(),
+            "0x0000000000000000000000000000000000000003"
+                .parse()
+                .unwrap(),
+        ]
+        .to_vec();
+
+        let task = DKGTask {
+            group_index: 1,
+            epoch: 1,
+            size: 3,
+            threshold: 2,
+            members,
+            assignment_block_height: 100,
+            coordinator_address: "0x00000000000000000000000000000000000000c1"
+                .parse()
+                .unwrap(),
+        };
+
+        db.save_task_info(0, task).await.unwrap();
+
+        let rng = &mut rand::thread_rng();
+
+        let (mut board, phase0s) = test_helper::setup::<G2Curve, G2Scheme, _>(3, 3, rng);
+
+        let mut outputs = test_helper::run_dkg::<G2Curve, G2Scheme>(&mut board, phase0s).await;
+
+        let res = db.save_output(1, 1, outputs.remove(0)).await;
+
+        assert!(matches!(
+            res,
+            Err(DataAccessError::InconsistentDkgOutput(_))
+        ));
+        assert!(db.get_public_key().is_err());
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_check_corrupt_partial_public_keys() {
+        let rng = &mut rand::thread_rng();
+
+        let (mut board, phase0s) = test_helper::setup::<G2Curve, G2Scheme, _>(3, 2, rng);
+
+        let outputs = test_helper::run_dkg::<G2Curve, G2Scheme>(&mut board, phase0s).await;
+
+        let public = &outputs[0].public;
+        let threshold = public.degree() + 1;
+        let partial_public_keys = (0..3).map(|index| public.eval(index)).collect::<Vec<_>>();
+
+        assert!(check_partial_public_keys::<PairingCurve>(
+            threshold,
+            public.public_key(),
+            &partial_public_keys
+        )
+        .is_ok());
+
+        // a corrupt key is detected whether it is used for the interpolation or not
+        for corrupt_index in 0..partial_public_keys.len() {
+            let mut corrupt_partial_public_keys = partial_public_keys.clone();
+            corrupt_partial_public_keys[corrupt_index]
+                .value
+                .add(&G2::one());
+
+            let res = check_partial_public_keys::<PairingCurve>(
+                threshold,
+                public.public_key(),
+                &corrupt_partial_public_keys,
+            );
+
+            assert!(matches!(
+                res,
+                Err(DataAccessError::InconsistentPublicKeys(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_versioned() {
+        let share = Share {
+            index: 1,
+            private: Scalar::one(),
+        };
+
+        let bytes = serialize_versioned(&share).unwrap();
+        assert_eq!(SERIALIZATION_VERSION, bytes[0]);
+        let res: Share<Scalar> = deserialize_versioned(&bytes).unwrap();
+        assert_eq!(share, res);
+
+        let mut bytes = bytes;
+        bytes[0] = SERIALIZATION_VERSION + 1;
+        let res = deserialize_versioned::<Share<Scalar>>(&bytes);
+        assert!(matches!(
+            res,
+            Err(DataAccessError::UnsupportedSerializationVersion(version)) if version == SERIALIZATION_VERSION + 1
+        ));
+
+        assert!(deserialize_versioned::<Share<Scalar>>(&[]).is_err());
+    }
+
     #[tokio::test]
     async fn test_add_and_get_randomness_task_with_assigned_group() {
         setup();
@@ -1548,19 +2143,7 @@ pub mod sqlite_tests {
 
         let seed = U256::from_big_endian(&String::from("test task").into_bytes());
 
-        let task = RandomnessTask {
-            request_id: request_id.clone(),
-            subscription_id: 0,
-            group_index: 2,
-            request_type: RandomnessRequestType::Randomness,
-            params: vec![],
-            requester: PLACEHOLDER_ADDRESS,
-            seed,
-            request_confirmations: 0,
-            callback_gas_limit: 0,
-            callback_max_gas_price: 0.into(),
-            assignment_block_height: 100,
-        };
+        let task = build_task(&request_id);
 
         if let Err(e) = db.add(task.clone()).await {
             println!("{:?}", e);
@@ -1571,13 +2154,25 @@ pub mod sqlite_tests {
         assert_eq!(false, db.is_handled(&request_id).await.unwrap());
 
         let available_tasks = db
-            .check_and_get_available_tasks(100, 1, randomness_task_exclusive_window)
+            .check_and_get_available_tasks(
+                100,
+                1,
+                randomness_task_exclusive_window,
+                0,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+            )
             .await
             .unwrap();
         assert_eq!(0, available_tasks.len());
 
         let available_tasks = db
-            .check_and_get_available_tasks(100, 2, randomness_task_exclusive_window)
+            .check_and_get_available_tasks(
+                100,
+                2,
+                randomness_task_exclusive_window,
+                0,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+            )
             .await
             .unwrap();
         assert_eq!(1, available_tasks.len());
@@ -1588,10 +2183,19 @@ pub mod sqlite_tests {
 
         assert_eq!(true, db.contains(&request_id).await.unwrap());
         assert_eq!(task, db.get(&request_id).await.unwrap());
+        assert_eq!(false, db.is_handled(&request_id).await.unwrap());
+
+        db.mark_handled(&request_id).await.unwrap();
         assert_eq!(true, db.is_handled(&request_id).await.unwrap());
 
         let available_tasks = db
-            .check_and_get_available_tasks(100, 2, randomness_task_exclusive_window)
+            .check_and_get_available_tasks(
+                100,
+                2,
+                randomness_task_exclusive_window,
+                0,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+            )
             .await
             .unwrap();
         assert_eq!(0, available_tasks.len());
@@ -1613,19 +2217,7 @@ pub mod sqlite_tests {
 
         let seed = U256::from_big_endian(&String::from("test task").into_bytes());
 
-        let task = RandomnessTask {
-            request_id: request_id.clone(),
-            subscription_id: 0,
-            group_index: 2,
-            request_type: RandomnessRequestType::Randomness,
-            params: vec![],
-            requester: PLACEHOLDER_ADDRESS,
-            seed,
-            request_confirmations: 0,
-            callback_gas_limit: 0,
-            callback_max_gas_price: 0.into(),
-            assignment_block_height: 100,
-        };
+        let task = build_task(&request_id);
 
         if let Err(e) = db.add(task.clone()).await {
             println!("{:?}", e);
@@ -1640,6 +2232,8 @@ pub mod sqlite_tests {
                 100 + DEFAULT_RANDOMNESS_TASK_EXCLUSIVE_WINDOW,
                 1,
                 randomness_task_exclusive_window,
+                0,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
             )
             .await
             .unwrap();
@@ -1650,6 +2244,8 @@ pub mod sqlite_tests {
                 100 + DEFAULT_RANDOMNESS_TASK_EXCLUSIVE_WINDOW + 1,
                 1,
                 randomness_task_exclusive_window,
+                0,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
             )
             .await
             .unwrap();
@@ -1661,6 +2257,9 @@ pub mod sqlite_tests {
 
         assert_eq!(true, db.contains(&request_id).await.unwrap());
         assert_eq!(task, db.get(&request_id).await.unwrap());
+        assert_eq!(false, db.is_handled(&request_id).await.unwrap());
+
+        db.mark_handled(&request_id).await.unwrap();
         assert_eq!(true, db.is_handled(&request_id).await.unwrap());
 
         let available_tasks = db
@@ -1668,6 +2267,8 @@ pub mod sqlite_tests {
                 100 + DEFAULT_RANDOMNESS_TASK_EXCLUSIVE_WINDOW + 1,
                 1,
                 randomness_task_exclusive_window,
+                0,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
             )
             .await
             .unwrap();
@@ -1675,4 +2276,335 @@ pub mod sqlite_tests {
 
         teardown();
     }
+
+    #[tokio::test]
+    async fn test_add_and_get_urgent_randomness_task_over_exclusive_window() {
+        setup();
+
+        let randomness_task_exclusive_window = 10;
+
+        let urgent_randomness_task_exclusive_window = 2;
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut db = db.get_bls_tasks_client::<RandomnessTask>();
+
+        let request_id = vec![1];
+
+        let task = RandomnessTask {
+            priority: RandomnessTaskPriority::Urgent,
+            ..build_task(&request_id)
+        };
+
+        if let Err(e) = db.add(task.clone()).await {
+            println!("{:?}", e);
+        }
+
+        assert_eq!(task, db.get(&request_id).await.unwrap());
+
+        let available_tasks = db
+            .check_and_get_available_tasks(
+                100 + urgent_randomness_task_exclusive_window,
+                1,
+                randomness_task_exclusive_window,
+                urgent_randomness_task_exclusive_window,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+            )
+            .await
+            .unwrap();
+        assert_eq!(0, available_tasks.len());
+
+        let available_tasks = db
+            .check_and_get_available_tasks(
+                100 + urgent_randomness_task_exclusive_window + 1,
+                1,
+                randomness_task_exclusive_window,
+                urgent_randomness_task_exclusive_window,
+                DEFAULT_RANDOMNESS_TASK_CLAIM_LEASE_DURATION,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, available_tasks.len());
+        assert_eq!(request_id, available_tasks[0].request_id);
+        assert_eq!(RandomnessTaskPriority::Urgent, available_tasks[0].priority);
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_claimed_randomness_task_returns_after_lease_expiry() {
+        setup();
+
+        let claim_lease_duration = 5;
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut db = db.get_bls_tasks_client::<RandomnessTask>();
+
+        let request_id = vec![1];
+
+        db.add(build_task(&request_id)).await.unwrap();
+
+        let available_tasks = db
+            .check_and_get_available_tasks(100, 2, 10, 0, claim_lease_duration)
+            .await
+            .unwrap();
+        assert_eq!(1, available_tasks.len());
+
+        // still claimed within the lease
+        let available_tasks = db
+            .check_and_get_available_tasks(
+                100 + claim_lease_duration,
+                2,
+                10,
+                0,
+                claim_lease_duration,
+            )
+            .await
+            .unwrap();
+        assert_eq!(0, available_tasks.len());
+
+        // the lease expires without the task being handled
+        let available_tasks = db
+            .check_and_get_available_tasks(
+                100 + claim_lease_duration + 1,
+                2,
+                10,
+                0,
+                claim_lease_duration,
+            )
+            .await
+            .unwrap();
+        assert_eq!(1, available_tasks.len());
+        assert_eq!(false, db.is_handled(&request_id).await.unwrap());
+
+        db.mark_handled(&request_id).await.unwrap();
+
+        let available_tasks = db
+            .check_and_get_available_tasks(
+                100 + 3 * claim_lease_duration,
+                2,
+                10,
+                0,
+                claim_lease_duration,
+            )
+            .await
+            .unwrap();
+        assert_eq!(0, available_tasks.len());
+        assert_eq!(true, db.is_handled(&request_id).await.unwrap());
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_state() {
+        setup();
+
+        let target_path = "test_import.sqlite";
+        let archive_path = "test_state.archive";
+        for path in [target_path, archive_path] {
+            if PathBuf::from(path).exists() {
+                fs::remove_file(path).expect("could not remove file");
+            }
+        }
+
+        let source = build_sqlite_db().await.unwrap();
+
+        let mut node_cache = source.get_node_info_client::<PairingCurve>();
+        let id_address = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+        let (private_key, public_key) = G2Scheme::keypair(&mut rand::thread_rng());
+        node_cache
+            .save_node_info(id_address, "127.0.0.1".to_string(), private_key, public_key)
+            .await
+            .unwrap();
+
+        let mut tasks_cache = source.get_bls_tasks_client::<RandomnessTask>();
+        for request_id in [[1], [2]] {
+            tasks_cache.add(build_task(&request_id)).await.unwrap();
+        }
+        tasks_cache.mark_handled(&[1]).await.unwrap();
+
+        let archive = source.export_state(archive_path).await.unwrap();
+        assert_eq!(archive.randomness_tasks.len(), 1);
+        assert_eq!(archive.randomness_tasks[0].request_id, vec![2]);
+
+        let target = SqliteDB::build(target_path, CIPHER_KEY.as_bytes())
+            .await
+            .unwrap();
+        target.import_state(archive_path).await.unwrap();
+
+        let mut node_cache = target.get_node_info_client::<PairingCurve>();
+        assert!(node_cache.refresh_current_node_info().await.unwrap());
+        assert_eq!(id_address, node_cache.get_id_address().unwrap());
+        assert_eq!(&public_key, node_cache.get_dkg_public_key().unwrap());
+        assert_eq!(archive, target.build_state_archive().await.unwrap());
+
+        assert_eq!(
+            target.import_state(archive_path).await,
+            Err(crate::DBError::ExistingNodeRecord)
+        );
+
+        for path in [target_path, archive_path] {
+            fs::remove_file(path).expect("could not remove file");
+        }
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_inspect() {
+        setup();
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut node_cache = db.get_node_info_client::<PairingCurve>();
+        let (private_key, public_key) = G2Scheme::keypair(&mut rand::thread_rng());
+        node_cache
+            .save_node_info(
+                "0x0000000000000000000000000000000000000001"
+                    .parse()
+                    .unwrap(),
+                "127.0.0.1".to_string(),
+                private_key,
+                public_key,
+            )
+            .await
+            .unwrap();
+
+        let mut tasks_cache = db.get_bls_tasks_client::<RandomnessTask>();
+        for request_id in [[1], [2], [3]] {
+            tasks_cache.add(build_task(&request_id)).await.unwrap();
+        }
+        tasks_cache.mark_handled(&[1]).await.unwrap();
+
+        let report = crate::inspect::inspect::<PairingCurve>(DB_PATH, CIPHER_KEY.as_bytes())
+            .await
+            .unwrap();
+
+        assert!(report.contains("id address: 0x0000000000000000000000000000000000000001"));
+        assert!(report.contains("rpc endpoint: 127.0.0.1"));
+        assert!(report.contains("Group info:\n  none"));
+        assert!(report.contains("pending: 2"));
+        assert!(report.contains("handled: 1"));
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_evict_randomness_results_over_max_entries() {
+        setup();
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut db = db
+            .get_randomness_result_client()
+            .await
+            .unwrap()
+            .with_max_entries(3);
+
+        for request_id in 1..=3u8 {
+            db.add(2, build_task(&[request_id]), vec![request_id], 3)
+                .await
+                .unwrap();
+        }
+
+        // the oldest result is being fulfilled, and the second is used again
+        db.update_commit_result(&[1], BLSResultCacheState::Committing, None)
+            .await
+            .unwrap();
+        db.add_partial_signature(vec![2], PLACEHOLDER_ADDRESS, vec![7])
+            .await
+            .unwrap();
+
+        db.add(2, build_task(&[4]), vec![4], 3).await.unwrap();
+
+        assert!(db.contains(&[1]).await.unwrap());
+        assert!(db.contains(&[2]).await.unwrap());
+        assert!(!db.contains(&[3]).await.unwrap());
+        assert!(db.contains(&[4]).await.unwrap());
+
+        let counts = db.count_by_state().await.unwrap();
+        assert!(counts.contains(&(BLSResultCacheState::Expired, 1)));
+        assert!(counts.contains(&(BLSResultCacheState::NotCommitted, 2)));
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_expire_signatures() {
+        setup();
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut randomness_result_client = db.get_randomness_result_client().await.unwrap();
+
+        for (request_id, assignment_block_height) in [(1u8, 100), (2, 150)] {
+            randomness_result_client
+                .add(
+                    2,
+                    RandomnessTask {
+                        assignment_block_height,
+                        ..build_task(&[request_id])
+                    },
+                    vec![request_id],
+                    3,
+                )
+                .await
+                .unwrap();
+        }
+
+        let expired = randomness_result_client
+            .expire_signatures(201, 100)
+            .await
+            .unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].randomness_task.request_id, vec![1]);
+
+        // the record of the expired result is kept for inspection
+        assert_eq!(
+            randomness_result_client.get(&[1]).await.unwrap().state,
+            BLSResultCacheState::Expired
+        );
+
+        // and isn't loaded back, so it is never expired twice
+        let mut randomness_result_client = db.get_randomness_result_client().await.unwrap();
+
+        let expired = randomness_result_client
+            .expire_signatures(251, 100)
+            .await
+            .unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].randomness_task.request_id, vec![2]);
+
+        teardown();
+    }
+
+    #[tokio::test]
+    async fn test_save_randomness() {
+        setup();
+
+        let db = build_sqlite_db().await.unwrap();
+
+        let mut randomness_result_client = db.get_randomness_result_client().await.unwrap();
+
+        randomness_result_client
+            .add(2, build_task(&[1]), vec![1], 3)
+            .await
+            .unwrap();
+
+        randomness_result_client
+            .save_randomness(&[1], [7; 32])
+            .await
+            .unwrap();
+
+        // the randomness is loaded back along with the result
+        let randomness_result_client = db.get_randomness_result_client().await.unwrap();
+
+        let result = randomness_result_client.get(&[1]).await.unwrap();
+        assert_eq!(result.randomness, Some([7; 32]));
+
+        teardown();
+    }
 }
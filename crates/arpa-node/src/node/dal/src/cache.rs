@@ -1,5 +1,8 @@
 use crate::error::{DataAccessResult, GroupError, NodeInfoError};
-use crate::{BLSResultCacheState, ContextInfoUpdater};
+use crate::{
+    check_dkg_output, check_group_size, check_partial_public_keys, BLSResultCacheState,
+    ContextInfoUpdater,
+};
 
 use super::{
     BLSTasksFetcher, BLSTasksUpdater, BlockInfoFetcher, BlockInfoUpdater, GroupInfoFetcher,
@@ -7,15 +10,21 @@ use super::{
     SignatureResultCacheUpdater,
 };
 use arpa_node_core::log::encoder;
+use arpa_node_core::metrics::{
+    RESULT_CACHE_EVICTIONS, RESULT_CACHE_SIZE, RESULT_CACHE_STATE_TRANSITIONS,
+};
 use arpa_node_core::{
-    BLSTask, BLSTaskError, DKGStatus, DKGTask, Group, Member, RandomnessTask, Task,
+    select_committers, BLSTask, BLSTaskError, DKGStatus, DKGTask, Group, Member, RandomnessTask,
+    RandomnessTaskPriority, Task, DEFAULT_MAX_COMMITTER_CACHE_ENTRIES, DEFAULT_MAX_GROUP_SIZE,
 };
 use async_trait::async_trait;
 use dkg_core::primitives::DKGOutput;
-use ethers_core::types::Address;
-use log::info;
+use ethers_core::types::{Address, H256};
+use ethers_core::utils::hex;
+use log::{debug, info, warn};
 use std::collections::{BTreeMap, HashMap};
-use threshold_bls::group::{Curve, Element, PairingCurve};
+use threshold_bls::group::{Curve, Element, PairingCurve, Point};
+use threshold_bls::poly::Eval;
 use threshold_bls::serialize::point_to_hex;
 use threshold_bls::sig::Share;
 
@@ -48,6 +57,7 @@ pub struct InMemoryNodeInfoCache<C: PairingCurve> {
     pub(crate) node_rpc_endpoint: Option<String>,
     pub(crate) dkg_private_key: Option<C::Scalar>,
     pub(crate) dkg_public_key: Option<C::G2>,
+    pub(crate) last_processed_block: Option<usize>,
 }
 
 impl<C: PairingCurve> std::fmt::Debug for InMemoryNodeInfoCache<C> {
@@ -60,6 +70,7 @@ impl<C: PairingCurve> std::fmt::Debug for InMemoryNodeInfoCache<C> {
                 "dkg_public_key",
                 &(self.dkg_public_key.as_ref()).map(point_to_hex),
             )
+            .field("last_processed_block", &self.last_processed_block)
             .finish()
     }
 }
@@ -71,6 +82,7 @@ impl<C: PairingCurve> InMemoryNodeInfoCache<C> {
             node_rpc_endpoint: None,
             dkg_private_key: None,
             dkg_public_key: None,
+            last_processed_block: None,
         }
     }
 
@@ -79,12 +91,14 @@ impl<C: PairingCurve> InMemoryNodeInfoCache<C> {
         node_rpc_endpoint: String,
         dkg_private_key: C::Scalar,
         dkg_public_key: C::G2,
+        last_processed_block: Option<usize>,
     ) -> Self {
         InMemoryNodeInfoCache {
             id_address,
             node_rpc_endpoint: Some(node_rpc_endpoint),
             dkg_private_key: Some(dkg_private_key),
             dkg_public_key: Some(dkg_public_key),
+            last_processed_block,
         }
     }
 }
@@ -113,6 +127,11 @@ impl<C: PairingCurve> NodeInfoUpdater<C> for InMemoryNodeInfoCache<C> {
         self.refresh_context_entry();
         Ok(())
     }
+
+    async fn set_last_processed_block(&mut self, block_height: usize) -> DataAccessResult<()> {
+        self.last_processed_block = Some(block_height);
+        Ok(())
+    }
 }
 
 impl<C: PairingCurve> NodeInfoFetcher<C> for InMemoryNodeInfoCache<C> {
@@ -138,6 +157,10 @@ impl<C: PairingCurve> NodeInfoFetcher<C> for InMemoryNodeInfoCache<C> {
             .as_ref()
             .ok_or_else(|| NodeInfoError::NoDKGKeyPair.into())
     }
+
+    fn get_last_processed_block(&self) -> DataAccessResult<Option<usize>> {
+        Ok(self.last_processed_block)
+    }
 }
 
 #[derive(Clone)]
@@ -147,6 +170,7 @@ pub struct InMemoryGroupInfoCache<C: PairingCurve> {
     pub(crate) dkg_status: DKGStatus,
     pub(crate) self_index: usize,
     pub(crate) dkg_start_block_height: usize,
+    pub(crate) max_group_size: usize,
 }
 
 impl<C: PairingCurve> Default for InMemoryGroupInfoCache<C> {
@@ -177,6 +201,7 @@ impl<C: PairingCurve> InMemoryGroupInfoCache<C> {
             dkg_status: DKGStatus::None,
             self_index: 0,
             dkg_start_block_height: 0,
+            max_group_size: DEFAULT_MAX_GROUP_SIZE,
         }
     }
 
@@ -193,9 +218,15 @@ impl<C: PairingCurve> InMemoryGroupInfoCache<C> {
             dkg_status,
             self_index,
             dkg_start_block_height,
+            max_group_size: DEFAULT_MAX_GROUP_SIZE,
         }
     }
 
+    pub fn with_max_group_size(mut self, max_group_size: usize) -> Self {
+        self.max_group_size = max_group_size;
+        self
+    }
+
     fn only_has_group_task(&self) -> DataAccessResult<()> {
         if self.dkg_start_block_height == 0 {
             return Err(GroupError::NoGroupTask.into());
@@ -208,6 +239,11 @@ impl<C: PairingCurve> InMemoryGroupInfoCache<C> {
 impl<C: PairingCurve> ContextInfoUpdater for InMemoryGroupInfoCache<C> {
     fn refresh_context_entry(&self) {
         encoder::CONTEXT_INFO.write()[1] = format!("{:?}", &self);
+        encoder::CONTEXT_INFO.write()[2] = if self.only_has_group_task().is_ok() {
+            self.group.index.to_string()
+        } else {
+            "".to_string()
+        };
     }
 }
 
@@ -246,6 +282,8 @@ impl<PC: PairingCurve + Send + Sync> GroupInfoUpdater<PC> for InMemoryGroupInfoC
     }
 
     async fn save_task_info(&mut self, self_index: usize, task: DKGTask) -> DataAccessResult<()> {
+        check_group_size(task.size.max(task.members.len()), self.max_group_size)?;
+
         self.self_index = self_index;
 
         self.group.index = task.group_index;
@@ -304,6 +342,10 @@ impl<PC: PairingCurve + Send + Sync> GroupInfoUpdater<PC> for InMemoryGroupInfoC
             return Err(GroupError::GroupAlreadyReady.into());
         }
 
+        check_group_size(self.group.size, self.max_group_size)?;
+
+        check_dkg_output(&self.group, &output)?;
+
         // every member index is started from 0
         let qualified_node_indices = output
             .qual
@@ -323,6 +365,20 @@ impl<PC: PairingCurve + Send + Sync> GroupInfoUpdater<PC> for InMemoryGroupInfoC
         let public_key: PC::G2 =
             bincode::deserialize(&bincode::serialize(&output.public.public_key())?)?;
 
+        let partial_public_keys = qualified_node_indices
+            .iter()
+            .map(|index| {
+                Ok(Eval {
+                    value: bincode::deserialize(&bincode::serialize(
+                        &output.public.eval(*index as u32).value,
+                    )?)?,
+                    index: *index as u32,
+                })
+            })
+            .collect::<DataAccessResult<Vec<Eval<PC::G2>>>>()?;
+
+        check_partial_public_keys::<PC>(self.group.threshold, &public_key, &partial_public_keys)?;
+
         let mut partial_public_key = PC::G2::new();
 
         let share = bincode::deserialize(&bincode::serialize(&output.share)?)?;
@@ -346,10 +402,10 @@ impl<PC: PairingCurve + Send + Sync> GroupInfoUpdater<PC> for InMemoryGroupInfoC
                 }
             }
 
-            let member_partial_public_key = bincode::deserialize(&bincode::serialize(
-                &output.public.eval(member.index as u32).value,
-            )?)?;
-            member.partial_public_key = Some(member_partial_public_key);
+            member.partial_public_key = partial_public_keys
+                .iter()
+                .find(|key| key.index as usize == member.index)
+                .map(|key| key.value.clone());
 
             if self.self_index == member.index {
                 partial_public_key = member.partial_public_key.clone().unwrap();
@@ -469,10 +525,10 @@ impl<C: PairingCurve> GroupInfoFetcher<C> for InMemoryGroupInfoCache<C> {
             .map_err(|e| e.into())
     }
 
-    fn get_committers(&self) -> DataAccessResult<Vec<Address>> {
+    fn get_committers(&self, task: Option<&RandomnessTask>) -> DataAccessResult<Vec<Address>> {
         self.only_has_group_task()?;
 
-        Ok(self.group.committers.clone())
+        Ok(select_committers(&self.group, task, self.group.threshold))
     }
 
     fn get_dkg_start_block_height(&self) -> DataAccessResult<usize> {
@@ -490,7 +546,85 @@ impl<C: PairingCurve> GroupInfoFetcher<C> for InMemoryGroupInfoCache<C> {
     fn is_committer(&self, id_address: Address) -> DataAccessResult<bool> {
         self.only_has_group_task()?;
 
-        Ok(self.group.committers.contains(&id_address))
+        Ok(select_committers(&self.group, None, self.group.threshold).contains(&id_address))
+    }
+}
+
+/// A validated public key along with the G2 side of its pairing precomputed.
+#[derive(Clone)]
+pub struct PreparedPublicKey<C: PairingCurve> {
+    pub key: C::G2,
+    pub prepared: C::G2Prepared,
+}
+
+/// Validated public keys of the current group keyed by the address of their members. A key is
+/// only checked against the prime-order subgroup and prepared for pairing the first time it is
+/// seen in an epoch, and the whole cache is dropped once the group moves to another epoch.
+#[derive(Clone)]
+pub struct PublicKeyCache<C: PairingCurve> {
+    epoch: usize,
+    keys: HashMap<Address, PreparedPublicKey<C>>,
+}
+
+impl<C: PairingCurve> Default for PublicKeyCache<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: PairingCurve> std::fmt::Debug for PublicKeyCache<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PublicKeyCache")
+            .field("epoch", &self.epoch)
+            .field("keys", &self.keys.len())
+            .finish()
+    }
+}
+
+impl<C: PairingCurve> PublicKeyCache<C> {
+    pub fn new() -> Self {
+        PublicKeyCache {
+            epoch: 0,
+            keys: HashMap::new(),
+        }
+    }
+
+    /// Returns the prepared `key` of the member, validating and preparing it only on a miss.
+    pub fn get_or_insert(
+        &mut self,
+        epoch: usize,
+        member: Address,
+        key: &C::G2,
+    ) -> DataAccessResult<PreparedPublicKey<C>> {
+        if epoch != self.epoch {
+            self.keys.clear();
+            self.epoch = epoch;
+        }
+
+        if let Some(prepared_key) = self.keys.get(&member).filter(|k| k.key == *key) {
+            return Ok(prepared_key.clone());
+        }
+
+        if !key.is_valid() {
+            return Err(BLSTaskError::InvalidCurvePoint.into());
+        }
+
+        let prepared_key = PreparedPublicKey {
+            prepared: C::prepare_g2(key),
+            key: key.clone(),
+        };
+
+        self.keys.insert(member, prepared_key.clone());
+
+        Ok(prepared_key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
     }
 }
 
@@ -532,8 +666,14 @@ impl<T: Task + Sync + Clone> BLSTasksFetcher<T> for InMemoryBLSTasksQueue<T> {
 #[async_trait]
 impl BLSTasksUpdater<RandomnessTask> for InMemoryBLSTasksQueue<RandomnessTask> {
     async fn add(&mut self, task: RandomnessTask) -> DataAccessResult<()> {
-        self.bls_tasks
-            .insert(task.request_id().to_vec(), BLSTask { task, state: false });
+        self.bls_tasks.insert(
+            task.request_id().to_vec(),
+            BLSTask {
+                task,
+                state: false,
+                lease_expiry_block_height: None,
+            },
+        );
 
         Ok(())
     }
@@ -543,49 +683,186 @@ impl BLSTasksUpdater<RandomnessTask> for InMemoryBLSTasksQueue<RandomnessTask> {
         current_block_height: usize,
         current_group_index: usize,
         randomness_task_exclusive_window: usize,
+        urgent_randomness_task_exclusive_window: usize,
+        claim_lease_duration: usize,
     ) -> DataAccessResult<Vec<RandomnessTask>> {
         let available_tasks = self
             .bls_tasks
             .iter_mut()
             .filter(|(_, task)| !task.state)
+            .filter(|(_, task)| match task.lease_expiry_block_height {
+                Some(lease_expiry_block_height) => current_block_height > lease_expiry_block_height,
+                None => true,
+            })
             .filter(|(_, task)| {
+                let exclusive_window = match task.task.priority {
+                    RandomnessTaskPriority::Normal => randomness_task_exclusive_window,
+                    RandomnessTaskPriority::Urgent => urgent_randomness_task_exclusive_window,
+                };
+
                 task.task.group_index == current_group_index as u32
-                    || current_block_height
-                        > task.task.assignment_block_height + randomness_task_exclusive_window
+                    || current_block_height > task.task.assignment_block_height + exclusive_window
             })
             .map(|(_, task)| {
-                task.state = true;
+                task.lease_expiry_block_height = Some(current_block_height + claim_lease_duration);
                 task.task.clone()
             })
             .collect::<Vec<_>>();
 
         Ok(available_tasks)
     }
+
+    async fn mark_handled(&mut self, task_request_id: &[u8]) -> DataAccessResult<()> {
+        let task = self
+            .bls_tasks
+            .get_mut(task_request_id)
+            .ok_or(BLSTaskError::TaskNotFound)?;
+
+        task.state = true;
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 pub struct InMemorySignatureResultCache<C: ResultCache> {
     signature_result_caches: BTreeMap<Vec<u8>, BLSResultCache<C>>,
+    // the results by when they were last added or given a partial signature, by a logical clock,
+    // so that they are walked from the least recently used one
+    last_used: BTreeMap<u64, Vec<u8>>,
+    // when each result was last used, i.e. the reverse of last_used
+    used_at: HashMap<Vec<u8>, u64>,
+    clock: u64,
+    max_entries: usize,
+}
+
+impl<C: ResultCache> Default for InMemorySignatureResultCache<C> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<C: ResultCache> InMemorySignatureResultCache<C> {
     pub fn new() -> Self {
         InMemorySignatureResultCache {
             signature_result_caches: BTreeMap::new(),
+            last_used: BTreeMap::new(),
+            used_at: HashMap::new(),
+            clock: 0,
+            max_entries: DEFAULT_MAX_COMMITTER_CACHE_ENTRIES,
         }
     }
 
     pub fn rebuild(results: Vec<BLSResultCache<C>>) -> Self {
         let mut cache = InMemorySignatureResultCache::new();
 
+        // the bound is enforced from the next result added, so that nothing is dropped silently
         for result in results {
-            cache
-                .signature_result_caches
-                .insert(result.result_cache.request_id().to_vec(), result);
+            let request_id = result.result_cache.request_id().to_vec();
+            cache.touch(&request_id);
+            cache.signature_result_caches.insert(request_id, result);
         }
 
+        RESULT_CACHE_SIZE.set(cache.signature_result_caches.len() as i64);
+
         cache
     }
+
+    /// Bounds the number of cached results, which is enforced as results are added.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    /// Adds the result unless it is already cached, then evicts the results over `max_entries`.
+    /// Returns whether the result was added, along with the evicted results.
+    pub fn insert(&mut self, result: BLSResultCache<C>) -> (bool, Vec<BLSResultCache<C>>) {
+        let request_id = result.result_cache.request_id().to_vec();
+
+        if self.signature_result_caches.contains_key(&request_id) {
+            return (false, vec![]);
+        }
+
+        self.touch(&request_id);
+        self.signature_result_caches
+            .insert(request_id.clone(), result);
+
+        (true, self.evict(&request_id))
+    }
+
+    fn touch(&mut self, task_request_id: &[u8]) {
+        self.clock += 1;
+
+        if let Some(used_at) = self.used_at.insert(task_request_id.to_vec(), self.clock) {
+            self.last_used.remove(&used_at);
+        }
+        self.last_used.insert(self.clock, task_request_id.to_vec());
+    }
+
+    fn forget(&mut self, task_request_id: &[u8]) {
+        if let Some(used_at) = self.used_at.remove(task_request_id) {
+            self.last_used.remove(&used_at);
+        }
+    }
+
+    /// Evicts the settled results first, then the least recently used of `NotCommitted`, which
+    /// are given up as `Expired`. A result in `Committing` is never evicted, as its fulfillment
+    /// may still land, nor is the result just inserted.
+    fn evict(&mut self, inserted_request_id: &[u8]) -> Vec<BLSResultCache<C>> {
+        let mut evicted = vec![];
+
+        while self.signature_result_caches.len() > self.max_entries {
+            let state_of = |request_id: &Vec<u8>| {
+                self.signature_result_caches
+                    .get(request_id)
+                    .map(|v| v.state)
+            };
+
+            let candidates = self
+                .last_used
+                .values()
+                .filter(|request_id| request_id.as_slice() != inserted_request_id);
+
+            let Some(request_id) = candidates
+                .clone()
+                .find(|request_id| {
+                    !matches!(
+                        state_of(request_id),
+                        Some(BLSResultCacheState::NotCommitted | BLSResultCacheState::Committing)
+                    )
+                })
+                .or_else(|| {
+                    candidates.clone().find(|request_id| {
+                        state_of(request_id) == Some(BLSResultCacheState::NotCommitted)
+                    })
+                })
+                .cloned()
+            else {
+                break;
+            };
+
+            self.forget(&request_id);
+
+            if let Some(v) = self.signature_result_caches.remove(&request_id) {
+                if v.state == BLSResultCacheState::NotCommitted {
+                    warn!(
+                        "evict randomness result cache of task request id: {} as the cache exceeds {} entries",
+                        hex::encode(&request_id),
+                        self.max_entries
+                    );
+
+                    record_state_transition(&request_id, v.state, BLSResultCacheState::Expired);
+                }
+
+                RESULT_CACHE_EVICTIONS.inc();
+                evicted.push(v);
+            }
+        }
+
+        RESULT_CACHE_SIZE.set(self.signature_result_caches.len() as i64);
+
+        evicted
+    }
 }
 
 impl Task for RandomnessResultCache {
@@ -603,6 +880,10 @@ impl ResultCache for RandomnessResultCache {
 pub struct BLSResultCache<C: ResultCache> {
     pub result_cache: C,
     pub state: BLSResultCacheState,
+    // the fulfillment transaction last sent for the result
+    pub tx_hash: Option<H256>,
+    // derived from the signature the node fulfilled the result with
+    pub randomness: Option<[u8; 32]>,
 }
 
 #[derive(Clone, Debug)]
@@ -628,6 +909,60 @@ impl<C: ResultCache + Send + Sync> SignatureResultCacheFetcher<C>
             .cloned()
             .ok_or_else(|| BLSTaskError::CommitterCacheNotExisted.into())
     }
+
+    async fn count_by_state(&self) -> DataAccessResult<Vec<(BLSResultCacheState, usize)>> {
+        Ok(BLSResultCacheState::ALL
+            .into_iter()
+            .map(|state| {
+                let count = self
+                    .signature_result_caches
+                    .values()
+                    .filter(|v| v.state == state)
+                    .count();
+                (state, count)
+            })
+            .collect())
+    }
+
+    async fn get_committing_signatures(&self) -> DataAccessResult<Vec<C>> {
+        Ok(self
+            .signature_result_caches
+            .values()
+            .filter(|v| v.state == BLSResultCacheState::Committing)
+            .map(|v| v.result_cache.clone())
+            .collect())
+    }
+
+    async fn get_inflight_tx(&self, task_request_id: &[u8]) -> DataAccessResult<Option<H256>> {
+        let signature_result_cache = self
+            .signature_result_caches
+            .get(task_request_id)
+            .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
+
+        Ok(signature_result_cache
+            .tx_hash
+            .filter(|_| signature_result_cache.state == BLSResultCacheState::Committing))
+    }
+}
+
+fn record_state_transition(
+    task_request_id: &[u8],
+    from: BLSResultCacheState,
+    to: BLSResultCacheState,
+) {
+    // e.g. a retried fulfillment only updates the transaction of a result in `Committing`
+    if from != to {
+        RESULT_CACHE_STATE_TRANSITIONS
+            .with_label_values(&[&from.to_string(), &to.to_string()])
+            .inc();
+    }
+
+    debug!(
+        "randomness result cache of task request id: {} transitions from {} to {}",
+        hex::encode(task_request_id),
+        from,
+        to
+    );
 }
 
 #[async_trait]
@@ -649,22 +984,14 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
             partial_signatures: BTreeMap::new(),
         };
 
-        if self
-            .signature_result_caches
-            .contains_key(&signature_result_cache.randomness_task.request_id)
-        {
-            return Ok(false);
-        }
-
-        self.signature_result_caches.insert(
-            signature_result_cache.randomness_task.request_id.clone(),
-            BLSResultCache {
-                result_cache: signature_result_cache,
-                state: BLSResultCacheState::NotCommitted,
-            },
-        );
+        let (added, _) = self.insert(BLSResultCache {
+            result_cache: signature_result_cache,
+            state: BLSResultCacheState::NotCommitted,
+            tx_hash: None,
+            randomness: None,
+        });
 
-        Ok(true)
+        Ok(added)
     }
 
     async fn add_partial_signature(
@@ -691,6 +1018,8 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
             .partial_signatures
             .insert(member_address, partial_signature);
 
+        self.touch(&task_request_id);
+
         Ok(true)
     }
 
@@ -709,6 +1038,11 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
                     && v.result_cache.partial_signatures.len() >= v.result_cache.threshold
             })
             .map(|v| {
+                record_state_transition(
+                    &v.result_cache.randomness_task.request_id,
+                    v.state,
+                    BLSResultCacheState::Committing,
+                );
                 v.state = BLSResultCacheState::Committing;
                 v.result_cache.clone()
             })
@@ -721,14 +1055,288 @@ impl SignatureResultCacheUpdater<RandomnessResultCache>
         &mut self,
         task_request_id: &[u8],
         status: BLSResultCacheState,
+        tx_hash: Option<H256>,
     ) -> DataAccessResult<()> {
         let signature_result_cache = self
             .signature_result_caches
             .get_mut(task_request_id)
             .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
 
+        record_state_transition(task_request_id, signature_result_cache.state, status);
+
         signature_result_cache.state = status;
 
+        signature_result_cache.tx_hash = tx_hash;
+
         Ok(())
     }
+
+    async fn save_randomness(
+        &mut self,
+        task_request_id: &[u8],
+        randomness: [u8; 32],
+    ) -> DataAccessResult<()> {
+        let signature_result_cache = self
+            .signature_result_caches
+            .get_mut(task_request_id)
+            .ok_or(BLSTaskError::CommitterCacheNotExisted)?;
+
+        signature_result_cache.randomness = Some(randomness);
+
+        Ok(())
+    }
+
+    async fn expire_signatures(
+        &mut self,
+        current_block_height: usize,
+        timeout_blocks: usize,
+    ) -> DataAccessResult<Vec<RandomnessResultCache>> {
+        let expired_request_ids = self
+            .signature_result_caches
+            .iter()
+            .filter(|(_, v)| {
+                v.state == BLSResultCacheState::NotCommitted
+                    && current_block_height
+                        > v.result_cache.randomness_task.assignment_block_height
+                            + v.result_cache.randomness_task.request_confirmations as usize
+                            + timeout_blocks
+            })
+            .map(|(request_id, _)| request_id.clone())
+            .collect::<Vec<_>>();
+
+        let expired_signatures = expired_request_ids
+            .iter()
+            .filter_map(|request_id| {
+                self.forget(request_id);
+                self.signature_result_caches.remove(request_id)
+            })
+            .map(|v| {
+                record_state_transition(
+                    &v.result_cache.randomness_task.request_id,
+                    v.state,
+                    BLSResultCacheState::Expired,
+                );
+                v.result_cache
+            })
+            .collect::<Vec<_>>();
+
+        RESULT_CACHE_SIZE.set(self.signature_result_caches.len() as i64);
+
+        Ok(expired_signatures)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{InMemorySignatureResultCache, PublicKeyCache, RandomnessResultCache};
+    use crate::{BLSResultCacheState, SignatureResultCacheFetcher, SignatureResultCacheUpdater};
+    use arpa_node_core::{
+        metrics::RESULT_CACHE_STATE_TRANSITIONS, RandomnessRequestType, RandomnessTask,
+        RandomnessTaskPriority,
+    };
+    use ethers_core::types::{Address, H256, U256};
+    use threshold_bls::{
+        curve::bn254::{PairingCurve as BN254, G2},
+        group::Element,
+    };
+
+    fn build_task(request_id: Vec<u8>, assignment_block_height: usize) -> RandomnessTask {
+        RandomnessTask {
+            request_id,
+            subscription_id: 1,
+            group_index: 0,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 3,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    #[test]
+    fn test_public_key_cache() {
+        let (a, b) = (Address::random(), Address::random());
+        let key = G2::one();
+        let mut other_key = G2::one();
+        other_key.add(&key);
+
+        let mut cache = PublicKeyCache::<BN254>::new();
+        cache.get_or_insert(1, a, &key).unwrap();
+        cache.get_or_insert(1, a, &key).unwrap();
+        assert_eq!(cache.len(), 1);
+
+        cache.get_or_insert(1, b, &other_key).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // the key of a member is looked up by its address, and prepared again once it changes
+        assert_eq!(
+            cache.get_or_insert(1, a, &other_key).unwrap().key,
+            other_key
+        );
+        assert_eq!(cache.len(), 2);
+
+        // the keys of the previous epoch are dropped
+        cache.get_or_insert(2, a, &key).unwrap();
+        assert_eq!(cache.len(), 1);
+    }
+
+    fn count_transitions(from: BLSResultCacheState, to: BLSResultCacheState) -> u64 {
+        RESULT_CACHE_STATE_TRANSITIONS
+            .with_label_values(&[&from.to_string(), &to.to_string()])
+            .get()
+    }
+
+    #[tokio::test]
+    async fn test_record_state_transitions() {
+        use BLSResultCacheState::*;
+
+        // the counters are shared by the tests running in parallel, none of which but this one
+        // makes the transitions counted exactly
+        let ready = count_transitions(NotCommitted, Committing);
+        let committed = count_transitions(Committing, Committed);
+        let committed_by_others = count_transitions(NotCommitted, CommittedByOthers);
+
+        let mut cache = InMemorySignatureResultCache::<RandomnessResultCache>::new();
+        for request_id in [1u8, 2] {
+            cache
+                .add(0, build_task(vec![request_id], 100), vec![], 1)
+                .await
+                .unwrap();
+        }
+        cache
+            .add_partial_signature(vec![1], Address::random(), vec![])
+            .await
+            .unwrap();
+
+        let ready_signatures = cache.get_ready_to_commit_signatures(103).await.unwrap();
+        assert_eq!(ready_signatures.len(), 1);
+        assert!(count_transitions(NotCommitted, Committing) > ready);
+
+        // a retried fulfillment only updates the transaction, which is no transition
+        cache
+            .update_commit_result(&[1], Committing, Some(H256::random()))
+            .await
+            .unwrap();
+        assert_eq!(count_transitions(Committing, Committing), 0);
+
+        cache
+            .update_commit_result(&[1], Committed, Some(H256::random()))
+            .await
+            .unwrap();
+        assert_eq!(count_transitions(Committing, Committed), committed + 1);
+
+        cache
+            .update_commit_result(&[2], CommittedByOthers, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            count_transitions(NotCommitted, CommittedByOthers),
+            committed_by_others + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evict_least_recently_used() {
+        use BLSResultCacheState::*;
+
+        let expired = count_transitions(NotCommitted, Expired);
+
+        let mut cache =
+            InMemorySignatureResultCache::<RandomnessResultCache>::new().with_max_entries(2);
+        for request_id in 1..=2u8 {
+            cache
+                .add(0, build_task(vec![request_id], 100), vec![], 3)
+                .await
+                .unwrap();
+        }
+
+        // the first result is used again after the second one
+        cache
+            .add_partial_signature(vec![1], Address::random(), vec![])
+            .await
+            .unwrap();
+        cache
+            .add(0, build_task(vec![3], 100), vec![], 3)
+            .await
+            .unwrap();
+
+        assert!(cache.contains(&[1]).await.unwrap());
+        assert!(!cache.contains(&[2]).await.unwrap());
+        assert!(cache.contains(&[3]).await.unwrap());
+        assert!(count_transitions(NotCommitted, Expired) > expired);
+
+        // neither the results being fulfilled nor the one just inserted are evicted
+        for request_id in [1u8, 3] {
+            cache
+                .update_commit_result(&[request_id], Committing, None)
+                .await
+                .unwrap();
+        }
+        cache
+            .add(0, build_task(vec![4], 100), vec![], 3)
+            .await
+            .unwrap();
+
+        for request_id in [1u8, 3, 4] {
+            assert!(cache.contains(&[request_id]).await.unwrap());
+        }
+
+        // the settled results go first, even if used more recently
+        cache
+            .update_commit_result(&[1], NotCommitted, None)
+            .await
+            .unwrap();
+        cache
+            .update_commit_result(&[3], CommittedByOthers, None)
+            .await
+            .unwrap();
+        cache
+            .add(0, build_task(vec![5], 100), vec![], 3)
+            .await
+            .unwrap();
+
+        assert!(!cache.contains(&[1]).await.unwrap());
+        assert!(!cache.contains(&[3]).await.unwrap());
+        assert!(cache.contains(&[4]).await.unwrap());
+        assert!(cache.contains(&[5]).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expire_signatures() {
+        let mut cache = InMemorySignatureResultCache::<RandomnessResultCache>::new();
+
+        for (request_id, assignment_block_height) in [(1u8, 100), (2, 100), (3, 150)] {
+            cache
+                .add(
+                    0,
+                    build_task(vec![request_id], assignment_block_height),
+                    vec![],
+                    3,
+                )
+                .await
+                .unwrap();
+        }
+
+        // the second result is being fulfilled, which is never given up
+        cache
+            .update_commit_result(&[2], BLSResultCacheState::Committing, None)
+            .await
+            .unwrap();
+
+        // the timeout counts from the block the task is ready to be signed at
+        assert!(cache.expire_signatures(113, 10).await.unwrap().is_empty());
+
+        let expired = cache.expire_signatures(114, 10).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].randomness_task.request_id, vec![1]);
+
+        assert!(!cache.contains(&[1]).await.unwrap());
+        assert!(cache.contains(&[2]).await.unwrap());
+        assert!(cache.contains(&[3]).await.unwrap());
+    }
 }
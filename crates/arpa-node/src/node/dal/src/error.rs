@@ -22,6 +22,15 @@ pub enum DataAccessError {
 
     #[error("could not deserialize: {0}")]
     DeserializationError(#[from] bincode::Error),
+
+    #[error("the DKG output is inconsistent with the group: {0}")]
+    InconsistentDkgOutput(String),
+
+    #[error("the partial public keys are inconsistent with the group public key: {0}")]
+    InconsistentPublicKeys(String),
+
+    #[error("unsupported serialization version {0} of the persisted data, please check the node version")]
+    UnsupportedSerializationVersion(u8),
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -49,6 +58,9 @@ pub enum GroupError {
 
     #[error("the group is still waiting for other's DKGOutput to commit")]
     GroupWaitingForConsensus,
+
+    #[error("the group of {size} members exceeds max_group_size of {max_group_size}")]
+    GroupTooLarge { size: usize, max_group_size: usize },
 }
 
 #[derive(Debug, Error, PartialEq)]
@@ -1,16 +1,17 @@
 pub mod cache;
 pub mod error;
 
-use arpa_node_core::{DKGStatus, DKGTask, Group, Member, Task};
+use arpa_node_core::{DKGStatus, DKGTask, Group, Member, RandomnessTask, Task};
 use async_trait::async_trait;
 use cache::BLSResultCache;
 pub use dkg_core::primitives::DKGOutput;
-use error::DataAccessResult;
-use ethers_core::types::Address;
+use error::{DataAccessError, DataAccessResult, GroupError};
+use ethers_core::types::{Address, H256};
 use std::collections::BTreeMap;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use threshold_bls::{
     group::{Curve, PairingCurve},
+    poly::{Eval, Poly},
     sig::Share,
 };
 
@@ -35,6 +36,10 @@ pub trait NodeInfoUpdater<C: PairingCurve> {
         dkg_private_key: C::Scalar,
         dkg_public_key: C::G2,
     ) -> DataAccessResult<()>;
+
+    /// Records the block up to which the randomness tasks have been processed, so that a
+    /// restarted node resumes from it rather than from the latest block.
+    async fn set_last_processed_block(&mut self, block_height: usize) -> DataAccessResult<()>;
 }
 
 pub trait NodeInfoFetcher<C: PairingCurve> {
@@ -45,6 +50,91 @@ pub trait NodeInfoFetcher<C: PairingCurve> {
     fn get_dkg_private_key(&self) -> DataAccessResult<&C::Scalar>;
 
     fn get_dkg_public_key(&self) -> DataAccessResult<&C::G2>;
+
+    fn get_last_processed_block(&self) -> DataAccessResult<Option<usize>>;
+}
+
+/// Rejects a group larger than `max_group_size`, so that a pathological group assigned by the
+/// controller never gets to the verification and aggregation paths.
+pub fn check_group_size(size: usize, max_group_size: usize) -> DataAccessResult<()> {
+    if size > max_group_size {
+        return Err(GroupError::GroupTooLarge {
+            size,
+            max_group_size,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Cross-checks a DKG output against the group it was run for, so that a corrupt group is never persisted.
+pub fn check_dkg_output<PC: PairingCurve, C: Curve>(
+    group: &Group<PC>,
+    output: &DKGOutput<C>,
+) -> DataAccessResult<()> {
+    let qualified_size = output.qual.nodes.len();
+
+    if qualified_size < group.threshold || qualified_size > group.size {
+        return Err(DataAccessError::InconsistentDkgOutput(format!(
+            "{} qualified nodes with group size {} and threshold {}",
+            qualified_size, group.size, group.threshold
+        )));
+    }
+
+    // a threshold of t is shared by a polynomial of degree t - 1
+    let output_threshold = output.public.degree() + 1;
+
+    if output_threshold != group.threshold {
+        return Err(DataAccessError::InconsistentDkgOutput(format!(
+            "threshold {} of the public polynomial is different from group threshold {}",
+            output_threshold, group.threshold
+        )));
+    }
+
+    if let Some(node) = output.qual.nodes.iter().find(|node| {
+        !group
+            .members
+            .values()
+            .any(|member| member.index == node.id() as usize)
+    }) {
+        return Err(DataAccessError::InconsistentDkgOutput(format!(
+            "qualified node {} is not a member of the group",
+            node.id()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that the partial public keys of the qualified members interpolate to the group public
+/// key, so that a corrupt DKG output is caught before any signature is verified with it.
+pub fn check_partial_public_keys<PC: PairingCurve>(
+    threshold: usize,
+    public_key: &PC::G2,
+    partial_public_keys: &[Eval<PC::G2>],
+) -> DataAccessResult<()> {
+    let public_polynomial = Poly::<PC::G2>::full_recover(threshold, partial_public_keys.to_vec())
+        .map_err(|e| DataAccessError::InconsistentPublicKeys(e.to_string()))?;
+
+    if public_polynomial.public_key() != public_key {
+        return Err(DataAccessError::InconsistentPublicKeys(
+            "the partial public keys interpolate to another group public key".to_string(),
+        ));
+    }
+
+    // the keys beyond the threshold must lie on the same polynomial as well
+    if let Some(partial_public_key) = partial_public_keys
+        .iter()
+        .find(|key| public_polynomial.eval(key.index).value != key.value)
+    {
+        return Err(DataAccessError::InconsistentPublicKeys(format!(
+            "the partial public key of member {} is off the public polynomial",
+            partial_public_key.index
+        )));
+    }
+
+    Ok(())
 }
 
 #[async_trait]
@@ -96,7 +186,9 @@ pub trait GroupInfoFetcher<C: PairingCurve> {
 
     fn get_member(&self, id_address: Address) -> DataAccessResult<&Member<C>>;
 
-    fn get_committers(&self) -> DataAccessResult<Vec<Address>>;
+    /// Returns the committers in the order decided by the coordinator, or starting from the
+    /// primary committer of the task if given, see `select_committers`.
+    fn get_committers(&self, task: Option<&RandomnessTask>) -> DataAccessResult<Vec<Address>>;
 
     fn get_dkg_start_block_height(&self) -> DataAccessResult<usize>;
 
@@ -118,12 +210,18 @@ pub trait BLSTasksFetcher<T> {
 pub trait BLSTasksUpdater<T: Task> {
     async fn add(&mut self, task: T) -> DataAccessResult<()>;
 
+    /// Claims the available tasks for `claim_lease_duration` blocks. A claimed task that is not
+    /// marked handled before the lease expires becomes available again.
     async fn check_and_get_available_tasks(
         &mut self,
         current_block_height: usize,
         current_group_index: usize,
         randomness_task_exclusive_window: usize,
+        urgent_randomness_task_exclusive_window: usize,
+        claim_lease_duration: usize,
     ) -> DataAccessResult<Vec<T>>;
+
+    async fn mark_handled(&mut self, task_request_id: &[u8]) -> DataAccessResult<()>;
 }
 
 #[async_trait]
@@ -131,6 +229,16 @@ pub trait SignatureResultCacheFetcher<T: ResultCache> {
     async fn contains(&self, task_request_id: &[u8]) -> DataAccessResult<bool>;
 
     async fn get(&self, task_request_id: &[u8]) -> DataAccessResult<BLSResultCache<T>>;
+
+    /// Counts the results in each state, so that e.g. the results stuck in `Committing` can be
+    /// told apart.
+    async fn count_by_state(&self) -> DataAccessResult<Vec<(BLSResultCacheState, usize)>>;
+
+    /// Returns the results whose fulfillment was being sent, e.g. when the node stopped.
+    async fn get_committing_signatures(&self) -> DataAccessResult<Vec<T>>;
+
+    /// Returns the fulfillment transaction sent for the result still in `Committing`, if any.
+    async fn get_inflight_tx(&self, task_request_id: &[u8]) -> DataAccessResult<Option<H256>>;
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -139,15 +247,26 @@ pub enum BLSResultCacheState {
     Committing,
     Committed,
     CommittedByOthers,
+    // the threshold was not reached before the partial collection timed out
+    Expired,
 }
 
 impl BLSResultCacheState {
+    pub const ALL: [BLSResultCacheState; 5] = [
+        BLSResultCacheState::NotCommitted,
+        BLSResultCacheState::Committing,
+        BLSResultCacheState::Committed,
+        BLSResultCacheState::CommittedByOthers,
+        BLSResultCacheState::Expired,
+    ];
+
     pub fn to_i32(&self) -> i32 {
         match self {
             BLSResultCacheState::NotCommitted => 0,
             BLSResultCacheState::Committing => 1,
             BLSResultCacheState::Committed => 2,
             BLSResultCacheState::CommittedByOthers => 3,
+            BLSResultCacheState::Expired => 4,
         }
     }
 }
@@ -159,11 +278,18 @@ impl From<i32> for BLSResultCacheState {
             1 => BLSResultCacheState::Committing,
             2 => BLSResultCacheState::Committed,
             3 => BLSResultCacheState::CommittedByOthers,
+            4 => BLSResultCacheState::Expired,
             _ => panic!("Invalid BLSResultCacheState"),
         }
     }
 }
 
+impl fmt::Display for BLSResultCacheState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 #[async_trait]
 pub trait SignatureResultCacheUpdater<T: ResultCache> {
     async fn get_ready_to_commit_signatures(
@@ -186,11 +312,31 @@ pub trait SignatureResultCacheUpdater<T: ResultCache> {
         partial_signature: Vec<u8>,
     ) -> DataAccessResult<bool>;
 
+    /// Updates the state of the result along with the fulfillment transaction sent for it, if
+    /// any, so that a node restarted in `Committing` can look up whether it already landed.
     async fn update_commit_result(
         &mut self,
         task_request_id: &[u8],
         status: BLSResultCacheState,
+        tx_hash: Option<H256>,
+    ) -> DataAccessResult<()>;
+
+    /// Records the randomness derived from the signature the node fulfilled the result with, so
+    /// that it can be cross-checked against the one the Adapter emits.
+    async fn save_randomness(
+        &mut self,
+        task_request_id: &[u8],
+        randomness: [u8; 32],
     ) -> DataAccessResult<()>;
+
+    /// Gives up the results still not committed `timeout_blocks` blocks after their partial
+    /// signatures could first be collected, marking them `Expired` and dropping them from the
+    /// cache so that a task whose threshold is never reached doesn't linger. Returns them.
+    async fn expire_signatures(
+        &mut self,
+        current_block_height: usize,
+        timeout_blocks: usize,
+    ) -> DataAccessResult<Vec<T>>;
 }
 
 pub trait ResultCache: Task + Clone {
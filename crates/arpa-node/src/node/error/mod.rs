@@ -3,7 +3,7 @@ use arpa_node_core::SchedulerError;
 use arpa_node_dal::error::DataAccessError;
 use arpa_node_sqlite_db::DBError;
 use dkg_core::{primitives::DKGError, NodeError as DKGNodeError};
-use ethers::providers::ProviderError;
+use ethers::{providers::ProviderError, types::Address};
 use rustc_hex::FromHexError;
 use thiserror::Error;
 use threshold_bls::sig::BLSError;
@@ -36,6 +36,9 @@ pub enum NodeError {
     #[error("the message of the task is different from the committer")]
     InvalidTaskMessage,
 
+    #[error("the message of the task is not derived from the seed of the request")]
+    InvalidTaskSeed,
+
     #[error("not supported task type")]
     InvalidTaskType,
 
@@ -81,9 +84,68 @@ pub enum NodeError {
     #[error("you are not contained in the group")]
     MemberNotExisted,
 
+    #[error("the member has no partial public key to verify its partial signature")]
+    PartialPublicKeyNotExisted,
+
+    #[error("the group epoch of the partial signature: {0} is different from the current group epoch: {1}")]
+    GroupEpochMismatch(usize, usize),
+
+    #[error(
+        "the threshold: {0} of the signature cache is different from the group threshold: {1}"
+    )]
+    ThresholdMismatch(usize, usize),
+
+    #[error("the group threshold: {0} is below the minimum threshold: {1}")]
+    ThresholdBelowMinimum(usize, usize),
+
+    #[error("the committer didn't respond in {0} milliseconds")]
+    CommitterTimeout(u64),
+
+    #[error("can't resolve the endpoint: {0}, expected host:port with a bracketed IPv6 address, an IPv4 address or a hostname")]
+    UnresolvableEndpoint(String),
+
+    #[error("the node {id_address:?} is not registered to the controller, run `send register` in node-shell, or call nodeRegister(0x{id_public_key}) on the controller from the node account")]
+    NotRegistered {
+        id_address: Address,
+        id_public_key: String,
+    },
+
     #[error("DKG has not started yet")]
     DKGNotStarted,
 
     #[error("DKG has ended")]
     DKGEnded,
+
+    #[error("can't read randomness tasks from file {0}")]
+    TaskFileError(String),
+}
+
+impl NodeError {
+    /// Whether the committer couldn't be reached, rather than it answered with a rejection.
+    pub fn is_transport_error(&self) -> bool {
+        match self {
+            NodeError::RpcClientError(_) | NodeError::CommitterTimeout(_) => true,
+            NodeError::RpcResponseError(status) => matches!(
+                status.code(),
+                tonic::Code::Unavailable | tonic::Code::DeadlineExceeded
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::NodeError;
+
+    #[test]
+    fn test_is_transport_error() {
+        assert!(NodeError::CommitterTimeout(1000).is_transport_error());
+        assert!(NodeError::RpcResponseError(tonic::Status::unavailable("")).is_transport_error());
+        assert!(
+            !NodeError::RpcResponseError(tonic::Status::failed_precondition(""))
+                .is_transport_error()
+        );
+        assert!(!NodeError::GroupNotReady.is_transport_error());
+    }
 }
@@ -1,14 +1,24 @@
 use super::{types::Topic, Event};
 use crate::node::subscriber::DebuggableEvent;
+use arpa_node_core::DKGStatus;
 
 #[derive(Clone, Debug)]
 pub struct DKGPhase {
-    pub phase: usize,
+    pub group_index: usize,
+    pub group_epoch: usize,
+    // the value of `in_phase()` on the coordinator
+    pub phase: i8,
+    pub dkg_status: DKGStatus,
 }
 
 impl DKGPhase {
-    pub fn new(phase: usize) -> Self {
-        DKGPhase { phase }
+    pub fn new(group_index: usize, group_epoch: usize, phase: i8, dkg_status: DKGStatus) -> Self {
+        DKGPhase {
+            group_index,
+            group_epoch,
+            phase,
+            dkg_status,
+        }
     }
 }
 
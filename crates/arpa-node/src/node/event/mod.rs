@@ -7,6 +7,7 @@ pub mod new_randomness_task;
 pub mod ready_to_fulfill_randomness_task;
 pub mod ready_to_handle_randomness_task;
 pub mod run_dkg;
+pub mod sequenced;
 pub mod types;
 
 use std::any::Any;
@@ -16,4 +17,10 @@ pub trait Event {
     fn topic(&self) -> Topic;
 
     fn as_any(&self) -> &dyn Any;
+
+    /// Sequence number of the event within its topic, attached by the event queue on publishing.
+    /// It is 0 for an event which has not gone through the event queue.
+    fn sequence(&self) -> u64 {
+        0
+    }
 }
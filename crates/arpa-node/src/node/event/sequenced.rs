@@ -0,0 +1,35 @@
+use super::{types::Topic, Event};
+use crate::node::subscriber::DebuggableEvent;
+
+/// An event along with the sequence number attached by the event queue on publishing.
+///
+/// Sequence numbers increase monotonically per topic, so that subscribers which handle
+/// events asynchronously can detect reordering and drop stale events.
+#[derive(Debug)]
+pub struct SequencedEvent {
+    sequence: u64,
+    event: Box<dyn DebuggableEvent>,
+}
+
+impl SequencedEvent {
+    pub fn new(sequence: u64, event: Box<dyn DebuggableEvent>) -> Self {
+        SequencedEvent { sequence, event }
+    }
+}
+
+impl Event for SequencedEvent {
+    fn topic(&self) -> Topic {
+        self.event.topic()
+    }
+
+    // exposes the wrapped event so that subscribers downcast to it as usual
+    fn as_any(&self) -> &dyn std::any::Any {
+        self.event.as_any()
+    }
+
+    fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+impl DebuggableEvent for SequencedEvent {}
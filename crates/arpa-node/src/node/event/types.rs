@@ -18,3 +18,30 @@ pub enum Topic {
     ReadyToFulfillGroupRelayTask,
     ReadyToFulfillGroupRelayConfirmationTask(usize),
 }
+
+impl Topic {
+    /// Name of the topic regardless of the chain, as used in the event queue config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topic::NewBlock(_) => "NewBlock",
+            Topic::NewDKGTask => "NewDKGTask",
+            Topic::RunDKG => "RunDKG",
+            Topic::DKGPhase => "DKGPhase",
+            Topic::DKGSuccess => "DKGSuccess",
+            Topic::DKGPostProcess => "DKGPostProcess",
+            Topic::NewRandomnessTask(_) => "NewRandomnessTask",
+            Topic::NewGroupRelayTask => "NewGroupRelayTask",
+            Topic::NewGroupRelayConfirmationTask(_) => "NewGroupRelayConfirmationTask",
+            Topic::ReadyToHandleRandomnessTask(_) => "ReadyToHandleRandomnessTask",
+            Topic::ReadyToHandleGroupRelayTask => "ReadyToHandleGroupRelayTask",
+            Topic::ReadyToHandleGroupRelayConfirmationTask(_) => {
+                "ReadyToHandleGroupRelayConfirmationTask"
+            }
+            Topic::ReadyToFulfillRandomnessTask(_) => "ReadyToFulfillRandomnessTask",
+            Topic::ReadyToFulfillGroupRelayTask => "ReadyToFulfillGroupRelayTask",
+            Topic::ReadyToFulfillGroupRelayConfirmationTask(_) => {
+                "ReadyToFulfillGroupRelayConfirmationTask"
+            }
+        }
+    }
+}
@@ -4,16 +4,20 @@ use crate::node::{
     event::new_block::NewBlock,
     queue::{event_queue::EventQueue, EventPublisher},
 };
+#[cfg(any(test, feature = "testing"))]
+use arpa_node_contract_client::mock::MockChainProvider;
 use arpa_node_contract_client::provider::{BlockFetcher, ChainProviderBuilder};
-use arpa_node_core::ChainIdentity;
+use arpa_node_core::{metrics::BLOCK_HEIGHT, ChainIdentity};
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 pub struct BlockListener<I: ChainIdentity + ChainProviderBuilder> {
     chain_id: usize,
     chain_identity: Arc<RwLock<I>>,
     eq: Arc<RwLock<EventQueue>>,
+    cancellation_token: CancellationToken,
 }
 
 impl<I: ChainIdentity + ChainProviderBuilder> BlockListener<I> {
@@ -21,11 +25,13 @@ impl<I: ChainIdentity + ChainProviderBuilder> BlockListener<I> {
         chain_id: usize,
         chain_identity: Arc<RwLock<I>>,
         eq: Arc<RwLock<EventQueue>>,
+        cancellation_token: CancellationToken,
     ) -> Self {
         BlockListener {
             chain_id,
             chain_identity,
             eq,
+            cancellation_token,
         }
     }
 }
@@ -49,22 +55,73 @@ impl<I: ChainIdentity + ChainProviderBuilder + Sync + Send + 'static> Listener
         let eq = self.eq.clone();
 
         client
-            .subscribe_new_block_height(move |block_height: usize| {
-                let eq = eq.clone();
-                async move {
-                    eq.read()
-                        .await
-                        .publish(NewBlock {
-                            chain_id,
-                            block_height,
-                        })
-                        .await;
+            .subscribe_new_block_height(
+                self.cancellation_token.clone(),
+                move |block_height: usize| {
+                    let eq = eq.clone();
+                    async move {
+                        BLOCK_HEIGHT.set(block_height as i64);
 
-                    Ok(())
-                }
-            })
+                        eq.read()
+                            .await
+                            .publish(NewBlock {
+                                chain_id,
+                                block_height,
+                            })
+                            .await;
+
+                        Ok(())
+                    }
+                },
+            )
             .await?;
 
         Ok(())
     }
 }
+
+/// Advances a `MockChainProvider` in tests and publishes the `NewBlock` of every block it mines,
+/// in place of the `BlockListener`.
+///
+/// Each `NewBlock` is delivered to the subscribers before `advance_blocks` returns, so the caches
+/// are up to date with the mock chain right after the call.
+#[cfg(any(test, feature = "testing"))]
+pub struct MockChainHandle {
+    chain_id: usize,
+    chain: MockChainProvider,
+    eq: Arc<RwLock<EventQueue>>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl MockChainHandle {
+    pub fn new(chain_id: usize, chain: MockChainProvider, eq: Arc<RwLock<EventQueue>>) -> Self {
+        MockChainHandle {
+            chain_id,
+            chain,
+            eq,
+        }
+    }
+
+    pub fn get_chain(&self) -> &MockChainProvider {
+        &self.chain
+    }
+
+    /// Mines `n` blocks on the mock chain, returns the new block height.
+    pub async fn advance_blocks(&self, n: usize) -> usize {
+        let from = self.chain.get_block_height();
+        let to = self.chain.advance_blocks(n);
+
+        for block_height in from + 1..=to {
+            self.eq
+                .read()
+                .await
+                .publish(NewBlock {
+                    chain_id: self.chain_id,
+                    block_height,
+                })
+                .await;
+        }
+
+        to
+    }
+}
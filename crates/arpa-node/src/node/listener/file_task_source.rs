@@ -0,0 +1,158 @@
+use super::Listener;
+use crate::node::{
+    error::{NodeError, NodeResult},
+    event::new_randomness_task::NewRandomnessTask,
+    queue::{event_queue::EventQueue, EventPublisher},
+};
+use arpa_node_core::{RandomnessTask, RandomnessTaskFilter};
+use arpa_node_dal::{BLSTasksFetcher, BLSTasksUpdater};
+use async_trait::async_trait;
+use log::info;
+use std::{fs, path::PathBuf, sync::Arc};
+use tokio::sync::RwLock;
+
+/// Feeds the randomness tasks listed in a JSON file to the node in place of the adapter logs,
+/// so that the fulfillment pipeline can be exercised without a live chain.
+///
+/// The file holds an array of tasks in the format of `RandomnessTask::to_json`. It is read again
+/// on every round, so tasks appended to it are picked up, while the tasks already in the cache
+/// are skipped.
+pub struct FileTaskSource<T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>> {
+    chain_id: usize,
+    path: PathBuf,
+    randomness_tasks_cache: Arc<RwLock<T>>,
+    eq: Arc<RwLock<EventQueue>>,
+    randomness_task_filter: RandomnessTaskFilter,
+}
+
+impl<T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>> FileTaskSource<T> {
+    pub fn new(
+        chain_id: usize,
+        path: PathBuf,
+        randomness_tasks_cache: Arc<RwLock<T>>,
+        eq: Arc<RwLock<EventQueue>>,
+        randomness_task_filter: RandomnessTaskFilter,
+    ) -> Self {
+        FileTaskSource {
+            chain_id,
+            path,
+            randomness_tasks_cache,
+            eq,
+            randomness_task_filter,
+        }
+    }
+
+    fn read_tasks(&self) -> NodeResult<Vec<RandomnessTask>> {
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| NodeError::TaskFileError(format!("{:?}: {}", self.path, e)))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| NodeError::TaskFileError(format!("{:?}: {}", self.path, e)))
+    }
+}
+
+#[async_trait]
+impl<T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask> + Sync + Send>
+    EventPublisher<NewRandomnessTask> for FileTaskSource<T>
+{
+    async fn publish(&self, event: NewRandomnessTask) {
+        self.eq.read().await.publish(event).await;
+    }
+}
+
+#[async_trait]
+impl<T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask> + Sync + Send> Listener
+    for FileTaskSource<T>
+{
+    async fn listen(&self) -> NodeResult<()> {
+        for randomness_task in self.read_tasks()? {
+            if !self.randomness_task_filter.matches(&randomness_task) {
+                continue;
+            }
+
+            if self
+                .randomness_tasks_cache
+                .read()
+                .await
+                .contains(&randomness_task.request_id)
+                .await?
+            {
+                continue;
+            }
+
+            info!("read new randomness task from file. {:?}", randomness_task);
+
+            self.randomness_tasks_cache
+                .write()
+                .await
+                .add(randomness_task.clone())
+                .await?;
+
+            self.publish(NewRandomnessTask::new(self.chain_id, randomness_task))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::FileTaskSource;
+    use crate::node::{listener::Listener, queue::event_queue::EventQueue};
+    use arpa_node_core::{
+        RandomnessRequestType, RandomnessTask, RandomnessTaskFilter, RandomnessTaskPriority,
+    };
+    use arpa_node_dal::{cache::InMemoryBLSTasksQueue, BLSTasksFetcher};
+    use ethers::types::{Address, U256};
+    use std::{fs, sync::Arc};
+    use tokio::sync::RwLock;
+
+    fn build_task(request_id: Vec<u8>, group_index: u32) -> RandomnessTask {
+        RandomnessTask {
+            request_id,
+            subscription_id: 1,
+            group_index,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 100,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_task_source() {
+        let path = std::env::temp_dir().join("arpa_node_test_randomness_tasks.json");
+        let tasks = vec![build_task(vec![1], 0), build_task(vec![2], 1)];
+        fs::write(&path, serde_json::to_string(&tasks).unwrap()).unwrap();
+
+        let randomness_tasks_cache = Arc::new(RwLock::new(InMemoryBLSTasksQueue::new()));
+
+        let source = FileTaskSource::new(
+            1,
+            path.clone(),
+            randomness_tasks_cache.clone(),
+            Arc::new(RwLock::new(EventQueue::new())),
+            RandomnessTaskFilter {
+                group_index: Some(0),
+                subscription_id: None,
+            },
+        );
+
+        source.listen().await.unwrap();
+        // tasks already in the cache are not fed again
+        source.listen().await.unwrap();
+
+        let cache = randomness_tasks_cache.read().await;
+        assert!(cache.contains(&[1]).await.unwrap());
+        assert!(!cache.contains(&[2]).await.unwrap());
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,30 +1,54 @@
 pub mod block;
+pub mod file_task_source;
 pub mod new_randomness_task;
 pub mod post_commit_grouping;
 pub mod post_grouping;
 pub mod pre_grouping;
+pub mod proxy_upgrade;
 pub mod randomness_signature_aggregation;
 pub mod ready_to_handle_randomness_task;
+pub mod share_consistency;
 
-use crate::node::error::NodeResult;
+use crate::node::{error::NodeResult, scheduler::registry::TaskReporter};
 use arpa_node_core::jitter;
 use async_trait::async_trait;
 use log::error;
 use tokio::time::sleep;
 use tokio_retry::strategy::FixedInterval;
+use tokio_util::sync::CancellationToken;
 
 #[async_trait]
 pub trait Listener {
-    async fn start(&self, interval_millis: u64, use_jitter: bool) -> NodeResult<()> {
-        let mut retry_strategy =
-            FixedInterval::from_millis(interval_millis)
-                .map(|e| if use_jitter { jitter(e) } else { e });
+    async fn start(
+        &self,
+        interval_millis: u64,
+        use_jitter: bool,
+        jitter_ratio: f64,
+        cancellation_token: CancellationToken,
+        task_reporter: TaskReporter,
+    ) -> NodeResult<()> {
+        let mut retry_strategy = FixedInterval::from_millis(interval_millis).map(|e| {
+            if use_jitter {
+                jitter(e, jitter_ratio)
+            } else {
+                e
+            }
+        });
 
         loop {
-            if let Err(err) = self.listen().await {
-                error!("listener is interrupted. Retry... Error: {:?}, ", err);
+            match self.listen().await {
+                Ok(()) => task_reporter.report_running(),
+                Err(err) => {
+                    error!("listener is interrupted. Retry... Error: {:?}, ", err);
+                    task_reporter.report_restart(format!("{:?}", err));
+                }
+            }
+
+            // stops between two rounds once the task is cancelled
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return Ok(()),
+                _ = sleep(retry_strategy.next().unwrap()) => {}
             }
-            sleep(retry_strategy.next().unwrap()).await;
         }
     }
 
@@ -3,54 +3,78 @@ use crate::node::{
     error::NodeResult,
     event::new_randomness_task::NewRandomnessTask,
     queue::{event_queue::EventQueue, EventPublisher},
+    telemetry::in_request_span,
 };
 use arpa_node_contract_client::adapter::{AdapterClientBuilder, AdapterLogs};
-use arpa_node_core::{ChainIdentity, RandomnessTask};
-use arpa_node_dal::{BLSTasksFetcher, BLSTasksUpdater};
+use arpa_node_core::{ChainIdentity, RandomnessTask, RandomnessTaskFilter, RandomnessTaskPriority};
+use arpa_node_dal::{BLSTasksFetcher, BLSTasksUpdater, NodeInfoFetcher, NodeInfoUpdater};
 use async_trait::async_trait;
 use ethers::types::Address;
 use log::info;
-use std::sync::Arc;
+use std::{marker::PhantomData, sync::Arc};
+use threshold_bls::group::PairingCurve;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 pub struct NewRandomnessTaskListener<
+    N: NodeInfoFetcher<PC> + NodeInfoUpdater<PC>,
     T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>,
     I: ChainIdentity + AdapterClientBuilder,
+    PC: PairingCurve,
 > {
     chain_id: usize,
     id_address: Address,
     chain_identity: Arc<RwLock<I>>,
+    node_cache: Arc<RwLock<N>>,
     randomness_tasks_cache: Arc<RwLock<T>>,
     eq: Arc<RwLock<EventQueue>>,
+    randomness_task_filter: RandomnessTaskFilter,
+    urgent_request_confirmations: Option<u16>,
+    cancellation_token: CancellationToken,
+    pc: PhantomData<PC>,
 }
 
 impl<
+        N: NodeInfoFetcher<PC> + NodeInfoUpdater<PC>,
         T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>,
         I: ChainIdentity + AdapterClientBuilder,
-    > NewRandomnessTaskListener<T, I>
+        PC: PairingCurve,
+    > NewRandomnessTaskListener<N, T, I, PC>
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         chain_id: usize,
         id_address: Address,
         chain_identity: Arc<RwLock<I>>,
+        node_cache: Arc<RwLock<N>>,
         randomness_tasks_cache: Arc<RwLock<T>>,
         eq: Arc<RwLock<EventQueue>>,
+        randomness_task_filter: RandomnessTaskFilter,
+        urgent_request_confirmations: Option<u16>,
+        cancellation_token: CancellationToken,
     ) -> Self {
         NewRandomnessTaskListener {
             chain_id,
             id_address,
             chain_identity,
+            node_cache,
             randomness_tasks_cache,
             eq,
+            randomness_task_filter,
+            urgent_request_confirmations,
+            cancellation_token,
+            pc: PhantomData,
         }
     }
 }
 
 #[async_trait]
 impl<
+        N: NodeInfoFetcher<PC> + NodeInfoUpdater<PC> + Sync + Send,
         T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask> + Sync + Send,
         I: ChainIdentity + AdapterClientBuilder + Sync + Send,
-    > EventPublisher<NewRandomnessTask> for NewRandomnessTaskListener<T, I>
+        PC: PairingCurve + Sync + Send,
+    > EventPublisher<NewRandomnessTask> for NewRandomnessTaskListener<N, T, I, PC>
 {
     async fn publish(&self, event: NewRandomnessTask) {
         self.eq.read().await.publish(event).await;
@@ -59,9 +83,11 @@ impl<
 
 #[async_trait]
 impl<
+        N: NodeInfoFetcher<PC> + NodeInfoUpdater<PC> + Sync + Send + 'static,
         T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask> + Sync + Send + 'static,
         I: ChainIdentity + AdapterClientBuilder + Sync + Send,
-    > Listener for NewRandomnessTaskListener<T, I>
+        PC: PairingCurve + Sync + Send,
+    > Listener for NewRandomnessTaskListener<N, T, I, PC>
 {
     async fn listen(&self) -> NodeResult<()> {
         let client = self
@@ -70,36 +96,60 @@ impl<
             .await
             .build_adapter_client(self.id_address);
         let chain_id = self.chain_id;
+        let urgent_request_confirmations = self.urgent_request_confirmations;
 
-        client
-            .subscribe_randomness_task(move |randomness_task| {
-                let randomness_tasks_cache = self.randomness_tasks_cache.clone();
-                let eq = self.eq.clone();
+        // resumes from the last processed block itself rather than the next one, as the block
+        // may carry more tasks than were processed before the node stopped
+        let from_block = self.node_cache.read().await.get_last_processed_block()?;
 
-                async move {
-                    let contained_res = randomness_tasks_cache
-                        .read()
-                        .await
-                        .contains(&randomness_task.request_id)
-                        .await;
-                    if let Ok(false) = contained_res {
-                        info!("received new randomness task. {:?}", randomness_task);
+        client
+            .subscribe_randomness_task(
+                self.randomness_task_filter,
+                from_block,
+                self.cancellation_token.clone(),
+                move |mut randomness_task| {
+                    randomness_task.priority = RandomnessTaskPriority::of_request(
+                        randomness_task.request_confirmations,
+                        urgent_request_confirmations,
+                    );
 
-                        randomness_tasks_cache
-                            .write()
-                            .await
-                            .add(randomness_task.clone())
-                            .await
-                            .map_err(anyhow::Error::from)?;
+                    let node_cache = self.node_cache.clone();
+                    let randomness_tasks_cache = self.randomness_tasks_cache.clone();
+                    let eq = self.eq.clone();
+                    let request_id = randomness_task.request_id.clone();
 
-                        eq.read()
+                    in_request_span("new_randomness_task", &request_id, async move {
+                        let contained_res = randomness_tasks_cache
+                            .read()
                             .await
-                            .publish(NewRandomnessTask::new(chain_id, randomness_task))
+                            .contains(&randomness_task.request_id)
                             .await;
-                    }
-                    Ok(())
-                }
-            })
+                        if let Ok(false) = contained_res {
+                            info!("received new randomness task. {:?}", randomness_task);
+
+                            randomness_tasks_cache
+                                .write()
+                                .await
+                                .add(randomness_task.clone())
+                                .await
+                                .map_err(anyhow::Error::from)?;
+
+                            node_cache
+                                .write()
+                                .await
+                                .set_last_processed_block(randomness_task.assignment_block_height)
+                                .await
+                                .map_err(anyhow::Error::from)?;
+
+                            eq.read()
+                                .await
+                                .publish(NewRandomnessTask::new(chain_id, randomness_task))
+                                .await;
+                        }
+                        Ok(())
+                    })
+                },
+            )
             .await?;
 
         Ok(())
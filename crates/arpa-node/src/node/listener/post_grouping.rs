@@ -63,7 +63,7 @@ impl<
 
         if let Ok(dkg_status) = dkg_status {
             match dkg_status {
-                DKGStatus::None => {}
+                DKGStatus::None | DKGStatus::Timeout | DKGStatus::ShareInconsistent => {}
                 DKGStatus::InPhase | DKGStatus::CommitSuccess | DKGStatus::WaitForPostProcess => {
                     let dkg_start_block_height =
                         self.group_cache.read().await.get_dkg_start_block_height()?;
@@ -11,6 +11,7 @@ use async_trait::async_trait;
 use std::{marker::PhantomData, sync::Arc};
 use threshold_bls::group::PairingCurve;
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 
 pub struct PreGroupingListener<
     G: GroupInfoFetcher<PC>,
@@ -20,6 +21,7 @@ pub struct PreGroupingListener<
     main_chain_identity: Arc<RwLock<I>>,
     group_cache: Arc<RwLock<G>>,
     eq: Arc<RwLock<EventQueue>>,
+    cancellation_token: CancellationToken,
     pc: PhantomData<PC>,
 }
 
@@ -30,11 +32,13 @@ impl<G: GroupInfoFetcher<PC>, I: ChainIdentity + ControllerClientBuilder<PC>, PC
         main_chain_identity: Arc<RwLock<I>>,
         group_cache: Arc<RwLock<G>>,
         eq: Arc<RwLock<EventQueue>>,
+        cancellation_token: CancellationToken,
     ) -> Self {
         PreGroupingListener {
             main_chain_identity,
             group_cache,
             eq,
+            cancellation_token,
             pc: PhantomData,
         }
     }
@@ -68,7 +72,7 @@ impl<
         let self_id_address = self.main_chain_identity.read().await.get_id_address();
 
         client
-            .subscribe_dkg_task(move |dkg_task| {
+            .subscribe_dkg_task(self.cancellation_token.clone(), move |dkg_task| {
                 let group_cache = self.group_cache.clone();
                 let eq = self.eq.clone();
 
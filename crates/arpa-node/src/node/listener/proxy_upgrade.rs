@@ -0,0 +1,115 @@
+use super::Listener;
+use crate::node::error::NodeResult;
+use arpa_node_core::ChainIdentity;
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Re-resolves the implementation of every proxy contract, so that an upgrade is picked up
+/// without reconfiguring the node.
+pub struct ProxyUpgradeListener<I: ChainIdentity> {
+    chain_identity: Arc<RwLock<I>>,
+}
+
+impl<I: ChainIdentity> ProxyUpgradeListener<I> {
+    pub fn new(chain_identity: Arc<RwLock<I>>) -> Self {
+        ProxyUpgradeListener { chain_identity }
+    }
+}
+
+#[async_trait]
+impl<I: ChainIdentity + Sync + Send> Listener for ProxyUpgradeListener<I> {
+    async fn listen(&self) -> NodeResult<()> {
+        let chain_identity = self.chain_identity.read().await;
+
+        for proxy_address in chain_identity.get_proxy_addresses() {
+            chain_identity
+                .resolve_implementation_address(proxy_address)
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::ProxyUpgradeListener;
+    use crate::node::listener::Listener;
+    use arpa_node_core::{
+        ChainIdentity, Config, GeneralChainIdentity, EIP1967_IMPLEMENTATION_SLOT,
+    };
+    use ethers::{prelude::*, utils::Anvil};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    async fn upgrade_proxy(
+        provider: &Provider<Http>,
+        proxy_address: Address,
+        implementation: Address,
+    ) {
+        provider
+            .request::<_, bool>(
+                "anvil_setStorageAt",
+                (
+                    proxy_address,
+                    U256::from_big_endian(EIP1967_IMPLEMENTATION_SLOT.as_bytes()),
+                    H256::from(implementation),
+                ),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_follow_proxy_upgrades() {
+        let anvil = Anvil::new().spawn();
+        let config = Config::default().initialize();
+        let time_limits = config.time_limits.unwrap();
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let proxy_address = Address::random();
+
+        let identity = GeneralChainIdentity::new(
+            anvil.chain_id() as usize,
+            wallet,
+            anvil.endpoint(),
+            100,
+            Address::random(),
+            Address::random(),
+            time_limits.contract_transaction_retry_descriptor,
+            time_limits.contract_view_retry_descriptor,
+            time_limits.get_transaction_confirmation_descriptor(),
+            time_limits.circuit_breaker_descriptor,
+            config.fulfill_randomness_gas_limit_multiplier.unwrap(),
+        )
+        .with_proxies(vec![proxy_address]);
+        let provider = identity.get_provider();
+
+        let chain_identity = Arc::new(RwLock::new(identity));
+        assert_eq!(
+            chain_identity.read().await.get_proxy_addresses(),
+            vec![proxy_address]
+        );
+        assert!(chain_identity
+            .read()
+            .await
+            .get_implementation_address(proxy_address)
+            .is_none());
+
+        let listener = ProxyUpgradeListener::new(chain_identity.clone());
+
+        for implementation in [Address::random(), Address::random()] {
+            upgrade_proxy(&provider, proxy_address, implementation).await;
+
+            listener.listen().await.unwrap();
+
+            assert_eq!(
+                chain_identity
+                    .read()
+                    .await
+                    .get_implementation_address(proxy_address),
+                Some(implementation)
+            );
+        }
+    }
+}
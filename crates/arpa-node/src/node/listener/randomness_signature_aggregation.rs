@@ -1,14 +1,19 @@
 use super::Listener;
 use crate::node::{
-    error::NodeResult,
+    error::{NodeError, NodeResult},
     event::ready_to_fulfill_randomness_task::ReadyToFulfillRandomnessTask,
     queue::{event_queue::EventQueue, EventPublisher},
+    subscriber::randomness_signature_aggregation::settle_fulfilled_task,
 };
+use arpa_node_contract_client::adapter::{AdapterClientBuilder, AdapterViews};
+use arpa_node_core::ChainIdentity;
 use arpa_node_dal::{
-    cache::RandomnessResultCache, BlockInfoFetcher, GroupInfoFetcher, SignatureResultCacheUpdater,
+    cache::RandomnessResultCache, BLSResultCacheState, BlockInfoFetcher, GroupInfoFetcher,
+    SignatureResultCacheUpdater,
 };
 use async_trait::async_trait;
-use ethers::types::Address;
+use ethers::{types::Address, utils::hex};
+use log::{error, info, warn};
 use std::{marker::PhantomData, sync::Arc};
 use threshold_bls::group::PairingCurve;
 use tokio::sync::RwLock;
@@ -17,14 +22,18 @@ pub struct RandomnessSignatureAggregationListener<
     B: BlockInfoFetcher,
     G: GroupInfoFetcher<PC>,
     C: SignatureResultCacheUpdater<RandomnessResultCache>,
+    I: ChainIdentity + AdapterClientBuilder,
     PC: PairingCurve,
 > {
     chain_id: usize,
     id_address: Address,
+    chain_identity: Arc<RwLock<I>>,
     block_cache: Arc<RwLock<B>>,
     group_cache: Arc<RwLock<G>>,
     randomness_signature_cache: Arc<RwLock<C>>,
     eq: Arc<RwLock<EventQueue>>,
+    minimum_threshold: usize,
+    partial_collection_timeout_blocks: usize,
     pc: PhantomData<PC>,
 }
 
@@ -32,24 +41,32 @@ impl<
         B: BlockInfoFetcher,
         G: GroupInfoFetcher<PC>,
         C: SignatureResultCacheUpdater<RandomnessResultCache>,
+        I: ChainIdentity + AdapterClientBuilder,
         PC: PairingCurve,
-    > RandomnessSignatureAggregationListener<B, G, C, PC>
+    > RandomnessSignatureAggregationListener<B, G, C, I, PC>
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         chain_id: usize,
         id_address: Address,
+        chain_identity: Arc<RwLock<I>>,
         block_cache: Arc<RwLock<B>>,
         group_cache: Arc<RwLock<G>>,
         randomness_signature_cache: Arc<RwLock<C>>,
         eq: Arc<RwLock<EventQueue>>,
+        minimum_threshold: usize,
+        partial_collection_timeout_blocks: usize,
     ) -> Self {
         RandomnessSignatureAggregationListener {
             chain_id,
             id_address,
+            chain_identity,
             block_cache,
             group_cache,
             randomness_signature_cache,
             eq,
+            minimum_threshold,
+            partial_collection_timeout_blocks,
             pc: PhantomData,
         }
     }
@@ -60,9 +77,10 @@ impl<
         B: BlockInfoFetcher + Sync + Send,
         G: GroupInfoFetcher<PC> + Sync + Send,
         C: SignatureResultCacheUpdater<RandomnessResultCache> + Sync + Send,
+        I: ChainIdentity + AdapterClientBuilder + Sync + Send,
         PC: PairingCurve + Sync + Send,
     > EventPublisher<ReadyToFulfillRandomnessTask>
-    for RandomnessSignatureAggregationListener<B, G, C, PC>
+    for RandomnessSignatureAggregationListener<B, G, C, I, PC>
 {
     async fn publish(&self, event: ReadyToFulfillRandomnessTask) {
         self.eq.read().await.publish(event).await;
@@ -74,26 +92,113 @@ impl<
         B: BlockInfoFetcher + Sync + Send,
         G: GroupInfoFetcher<PC> + Sync + Send,
         C: SignatureResultCacheUpdater<RandomnessResultCache> + Sync + Send,
+        I: ChainIdentity + AdapterClientBuilder + Sync + Send,
         PC: PairingCurve + Sync + Send,
-    > Listener for RandomnessSignatureAggregationListener<B, G, C, PC>
+    > Listener for RandomnessSignatureAggregationListener<B, G, C, I, PC>
 {
     async fn listen(&self) -> NodeResult<()> {
         let is_committer = self.group_cache.read().await.is_committer(self.id_address);
 
         if let Ok(true) = is_committer {
+            let group_threshold = self.group_cache.read().await.get_threshold()?;
+
+            if group_threshold < self.minimum_threshold {
+                return Err(NodeError::ThresholdBelowMinimum(
+                    group_threshold,
+                    self.minimum_threshold,
+                ));
+            }
+
             let current_block_height = self.block_cache.read().await.get_block_height();
 
-            let ready_signatures = self
+            let expired_signatures = self
                 .randomness_signature_cache
                 .write()
                 .await
-                .get_ready_to_commit_signatures(current_block_height)
+                .expire_signatures(current_block_height, self.partial_collection_timeout_blocks)
                 .await?;
 
-            if !ready_signatures.is_empty() {
+            for signature in expired_signatures.iter() {
+                warn!(
+                    "give up collecting partial signatures of task {}: threshold {} not reached within {} blocks",
+                    hex::encode(&signature.randomness_task.request_id),
+                    signature.threshold,
+                    self.partial_collection_timeout_blocks
+                );
+            }
+
+            let (ready_signatures, stale_signatures): (Vec<_>, Vec<_>) = self
+                .randomness_signature_cache
+                .write()
+                .await
+                .get_ready_to_commit_signatures(current_block_height)
+                .await?
+                .into_iter()
+                .partition(|signature| signature.threshold == group_threshold);
+
+            // a cache built before re-grouping may carry a stale threshold, which must not be
+            // aggregated with. It's marked expired so that it's neither picked up again nor left
+            // in committing state.
+            for signature in stale_signatures.iter() {
+                let request_id = &signature.randomness_task.request_id;
+
+                error!(
+                    "give up aggregating task {}: {}",
+                    hex::encode(request_id),
+                    NodeError::ThresholdMismatch(signature.threshold, group_threshold)
+                );
+
+                self.randomness_signature_cache
+                    .write()
+                    .await
+                    .update_commit_result(request_id, BLSResultCacheState::Expired, None)
+                    .await?;
+            }
+
+            if ready_signatures.is_empty() {
+                return Ok(());
+            }
+
+            let client = self
+                .chain_identity
+                .read()
+                .await
+                .build_adapter_client(self.id_address);
+
+            let mut signatures_to_fulfill = vec![];
+
+            // a peer may have fulfilled the task while the node was collecting partial signatures,
+            // racing it would only waste gas. The task is left to the fulfillment if its state
+            // can't be told, which checks it once more before sending the transaction.
+            for signature in ready_signatures {
+                let request_id = &signature.randomness_task.request_id;
+
+                if let Ok(false) = client.is_task_pending(request_id).await {
+                    if let Err(e) = settle_fulfilled_task(
+                        &client,
+                        &self.randomness_signature_cache,
+                        self.id_address,
+                        request_id,
+                        signature.randomness_task.assignment_block_height,
+                    )
+                    .await
+                    {
+                        error!("{:?}", e);
+                    }
+
+                    info!(
+                        "stop collecting partial signatures of task {} as it is no longer pending",
+                        hex::encode(request_id)
+                    );
+                } else {
+                    signatures_to_fulfill.push(signature);
+                }
+            }
+
+            if !signatures_to_fulfill.is_empty() {
                 self.publish(ReadyToFulfillRandomnessTask {
                     chain_id: self.chain_id,
-                    tasks: ready_signatures,
+                    tasks: signatures_to_fulfill,
                 })
                 .await;
             }
@@ -102,3 +207,252 @@ impl<
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::RandomnessSignatureAggregationListener;
+    use crate::node::{
+        event::types::Topic, listener::Listener, queue::event_queue::EventQueue,
+        subscriber::event_recorder::RecordedEvents,
+    };
+    use arpa_node_contract_client::{
+        adapter::AdapterTransactions,
+        mock::{MockChainIdentity, MockChainProvider},
+    };
+    use arpa_node_core::{
+        Config, DKGStatus, GeneralChainIdentity, Group, Member, RandomnessRequestType,
+        RandomnessTask, RandomnessTaskPriority,
+    };
+    use arpa_node_dal::{
+        cache::{
+            InMemoryBlockInfoCache, InMemoryGroupInfoCache, InMemorySignatureResultCache,
+            RandomnessResultCache,
+        },
+        BLSResultCacheState, SignatureResultCacheFetcher, SignatureResultCacheUpdater,
+    };
+    use ethers::{
+        signers::LocalWallet,
+        types::{Address, U256},
+    };
+    use std::{collections::HashMap, sync::Arc};
+    use threshold_bls::curve::bn254::PairingCurve as BN254;
+    use tokio::sync::RwLock;
+
+    fn build_identity(chain_id: usize) -> GeneralChainIdentity {
+        let config = Config::default().initialize();
+        let time_limits = config.time_limits.unwrap();
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+
+        GeneralChainIdentity::new(
+            chain_id,
+            wallet,
+            "localhost:8545".to_string(),
+            3000,
+            Address::random(),
+            Address::random(),
+            time_limits.contract_transaction_retry_descriptor,
+            time_limits.contract_view_retry_descriptor,
+            time_limits.get_transaction_confirmation_descriptor(),
+            time_limits.circuit_breaker_descriptor,
+            config.fulfill_randomness_gas_limit_multiplier.unwrap(),
+        )
+    }
+
+    fn build_task(request_id: Vec<u8>) -> RandomnessTask {
+        RandomnessTask {
+            request_id,
+            subscription_id: 1,
+            group_index: 0,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 0,
+            priority: RandomnessTaskPriority::Normal,
+        }
+    }
+
+    fn build_group_cache(members: &[Address]) -> InMemoryGroupInfoCache<BN254> {
+        let mut group = Group::<BN254>::new();
+        group.size = members.len();
+        group.threshold = 2;
+        group.state = true;
+        group.members = members
+            .iter()
+            .enumerate()
+            .map(|(index, id_address)| {
+                (
+                    *id_address,
+                    Member {
+                        index,
+                        id_address: *id_address,
+                        rpc_endpoint: None,
+                        partial_public_key: None,
+                    },
+                )
+            })
+            .collect();
+        group.committers = members.to_vec();
+
+        InMemoryGroupInfoCache::rebuild(None, group, DKGStatus::WaitForPostProcess, 0, 1)
+    }
+
+    #[tokio::test]
+    async fn test_expire_signatures_of_stale_threshold() {
+        let chain_id = 1;
+        let eq = Arc::new(RwLock::new(EventQueue::new()));
+
+        let recorded_events = RecordedEvents::new();
+        recorded_events
+            .record(eq.clone(), &[Topic::ReadyToFulfillRandomnessTask(chain_id)])
+            .await;
+
+        let chain_identity =
+            MockChainIdentity::new(build_identity(chain_id), MockChainProvider::default());
+        let id_address = chain_identity.get_chain().get_id_address();
+        let members = vec![id_address, Address::random()];
+
+        // the first result was cached with the threshold of the group before re-grouping
+        let randomness_signature_cache = Arc::new(RwLock::new(InMemorySignatureResultCache::<
+            RandomnessResultCache,
+        >::new()));
+        for (request_id, threshold) in [(1u8, 1), (2, 2)] {
+            chain_identity
+                .get_chain()
+                .emit_randomness_task(build_task(vec![request_id]));
+
+            let mut cache = randomness_signature_cache.write().await;
+            cache
+                .add(0, build_task(vec![request_id]), vec![], threshold)
+                .await
+                .unwrap();
+            for member in members.iter() {
+                cache
+                    .add_partial_signature(vec![request_id], *member, vec![])
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let listener = RandomnessSignatureAggregationListener::new(
+            chain_id,
+            id_address,
+            Arc::new(RwLock::new(chain_identity)),
+            Arc::new(RwLock::new(InMemoryBlockInfoCache::new())),
+            Arc::new(RwLock::new(build_group_cache(&members))),
+            randomness_signature_cache.clone(),
+            eq,
+            1,
+            10,
+        );
+
+        listener.listen().await.unwrap();
+
+        let events = recorded_events.get_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].request_ids, vec![vec![2]]);
+
+        for (request_id, state) in [
+            (1u8, BLSResultCacheState::Expired),
+            (2, BLSResultCacheState::Committing),
+        ] {
+            assert_eq!(
+                randomness_signature_cache
+                    .read()
+                    .await
+                    .get(&[request_id])
+                    .await
+                    .unwrap()
+                    .state,
+                state
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_task_fulfilled_by_peer() {
+        let chain_id = 1;
+        let eq = Arc::new(RwLock::new(EventQueue::new()));
+
+        let recorded_events = RecordedEvents::new();
+        recorded_events
+            .record(eq.clone(), &[Topic::ReadyToFulfillRandomnessTask(chain_id)])
+            .await;
+
+        let chain_identity =
+            MockChainIdentity::new(build_identity(chain_id), MockChainProvider::default());
+        let id_address = chain_identity.get_chain().get_id_address();
+        let peer = Address::random();
+        let members = vec![id_address, peer];
+
+        let randomness_signature_cache = Arc::new(RwLock::new(InMemorySignatureResultCache::<
+            RandomnessResultCache,
+        >::new()));
+        for request_id in [1u8, 2] {
+            chain_identity
+                .get_chain()
+                .emit_randomness_task(build_task(vec![request_id]));
+
+            let mut cache = randomness_signature_cache.write().await;
+            cache
+                .add(0, build_task(vec![request_id]), vec![], 2)
+                .await
+                .unwrap();
+            for member in members.iter() {
+                cache
+                    .add_partial_signature(vec![request_id], *member, vec![])
+                    .await
+                    .unwrap();
+            }
+        }
+
+        // the peer fulfilled the first task while the node was collecting partial signatures
+        chain_identity
+            .get_chain()
+            .with_id_address(peer)
+            .fulfill_randomness(0, 2, build_task(vec![1]), vec![7; 64], HashMap::new(), None)
+            .await
+            .unwrap();
+
+        let listener = RandomnessSignatureAggregationListener::new(
+            chain_id,
+            id_address,
+            Arc::new(RwLock::new(chain_identity)),
+            Arc::new(RwLock::new(InMemoryBlockInfoCache::new())),
+            Arc::new(RwLock::new(build_group_cache(&members))),
+            randomness_signature_cache.clone(),
+            eq,
+            1,
+            10,
+        );
+
+        listener.listen().await.unwrap();
+
+        let events = recorded_events.get_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].request_ids, vec![vec![2]]);
+
+        for (request_id, state) in [
+            (1u8, BLSResultCacheState::CommittedByOthers),
+            (2, BLSResultCacheState::Committing),
+        ] {
+            assert_eq!(
+                randomness_signature_cache
+                    .read()
+                    .await
+                    .get(&[request_id])
+                    .await
+                    .unwrap()
+                    .state,
+                state
+            );
+        }
+    }
+}
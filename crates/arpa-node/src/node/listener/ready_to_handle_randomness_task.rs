@@ -5,7 +5,7 @@ use crate::node::{
     queue::{event_queue::EventQueue, EventPublisher},
 };
 use arpa_node_contract_client::adapter::{AdapterClientBuilder, AdapterViews};
-use arpa_node_core::{ChainIdentity, RandomnessTask};
+use arpa_node_core::{ChainIdentity, DKGStatus, RandomnessTask};
 use arpa_node_dal::{BLSTasksUpdater, BlockInfoFetcher, GroupInfoFetcher};
 use async_trait::async_trait;
 use ethers::types::Address;
@@ -29,6 +29,8 @@ pub struct ReadyToHandleRandomnessTaskListener<
     eq: Arc<RwLock<EventQueue>>,
     pc: PhantomData<PC>,
     randomness_task_exclusive_window: usize,
+    urgent_randomness_task_exclusive_window: usize,
+    randomness_task_claim_lease_duration: usize,
 }
 
 impl<
@@ -49,6 +51,8 @@ impl<
         randomness_tasks_cache: Arc<RwLock<T>>,
         eq: Arc<RwLock<EventQueue>>,
         randomness_task_exclusive_window: usize,
+        urgent_randomness_task_exclusive_window: usize,
+        randomness_task_claim_lease_duration: usize,
     ) -> Self {
         ReadyToHandleRandomnessTaskListener {
             chain_id,
@@ -60,6 +64,8 @@ impl<
             eq,
             pc: PhantomData,
             randomness_task_exclusive_window,
+            urgent_randomness_task_exclusive_window,
+            randomness_task_claim_lease_duration,
         }
     }
 }
@@ -91,7 +97,12 @@ impl<
     async fn listen(&self) -> NodeResult<()> {
         let is_bls_ready = self.group_cache.read().await.get_state();
 
-        if let Ok(true) = is_bls_ready {
+        let is_share_inconsistent = matches!(
+            self.group_cache.read().await.get_dkg_status(),
+            Ok(DKGStatus::ShareInconsistent)
+        );
+
+        if let (Ok(true), false) = (is_bls_ready, is_share_inconsistent) {
             let current_group_index = self.group_cache.read().await.get_index()?;
 
             let current_block_height = self.block_cache.read().await.get_block_height();
@@ -104,6 +115,8 @@ impl<
                     current_block_height,
                     current_group_index,
                     self.randomness_task_exclusive_window,
+                    self.urgent_randomness_task_exclusive_window,
+                    self.randomness_task_claim_lease_duration,
                 )
                 .await?;
 
@@ -137,3 +150,244 @@ impl<
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::ReadyToHandleRandomnessTaskListener;
+    use crate::node::{
+        event::types::Topic,
+        listener::{
+            block::MockChainHandle,
+            randomness_signature_aggregation::RandomnessSignatureAggregationListener, Listener,
+        },
+        queue::event_queue::EventQueue,
+        scheduler::dynamic::SimpleDynamicTaskScheduler,
+        subscriber::{
+            block::BlockSubscriber, event_recorder::RecordedEvents,
+            randomness_signature_aggregation::RandomnessSignatureAggregationSubscriber,
+            ready_to_handle_randomness_task::ReadyToHandleRandomnessTaskSubscriber, Subscriber,
+        },
+    };
+    use arpa_node_contract_client::{
+        adapter::AdapterViews,
+        mock::{MockChainIdentity, MockChainProvider},
+    };
+    use arpa_node_core::{
+        Config, DKGStatus, GeneralChainIdentity, Group, Member, RandomnessRequestType,
+        RandomnessTask, RandomnessTaskPriority,
+    };
+    use arpa_node_dal::{
+        cache::{
+            InMemoryBLSTasksQueue, InMemoryBlockInfoCache, InMemoryGroupInfoCache,
+            InMemorySignatureResultCache, RandomnessResultCache,
+        },
+        BLSTasksUpdater,
+    };
+    use ethers::{
+        signers::LocalWallet,
+        types::{Address, U256},
+    };
+    use std::{sync::Arc, time::Duration};
+    use threshold_bls::{
+        curve::bn254::{PairingCurve as BN254, Scalar},
+        group::Element,
+        sig::Share,
+    };
+    use tokio::sync::RwLock;
+
+    fn build_identity(chain_id: usize) -> GeneralChainIdentity {
+        let config = Config::default().initialize();
+        let time_limits = config.time_limits.unwrap();
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+
+        GeneralChainIdentity::new(
+            chain_id,
+            wallet,
+            "localhost:8545".to_string(),
+            3000,
+            Address::random(),
+            Address::random(),
+            time_limits.contract_transaction_retry_descriptor,
+            time_limits.contract_view_retry_descriptor,
+            time_limits.get_transaction_confirmation_descriptor(),
+            time_limits.circuit_breaker_descriptor,
+            config.fulfill_randomness_gas_limit_multiplier.unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_exclusive_window() {
+        let chain_id = 1;
+        let time_limits = Config::default().initialize().time_limits.unwrap();
+        let eq = Arc::new(RwLock::new(EventQueue::new()));
+
+        let recorded_events = RecordedEvents::new();
+        recorded_events
+            .record(
+                eq.clone(),
+                &[
+                    Topic::NewBlock(chain_id),
+                    Topic::ReadyToHandleRandomnessTask(chain_id),
+                ],
+            )
+            .await;
+
+        let block_cache = Arc::new(RwLock::new(InMemoryBlockInfoCache::new()));
+        BlockSubscriber::new(chain_id, block_cache.clone(), eq.clone())
+            .subscribe()
+            .await;
+
+        let chain_identity =
+            MockChainIdentity::new(build_identity(chain_id), MockChainProvider::default());
+        let id_address = chain_identity.get_chain().get_id_address();
+        let handle = MockChainHandle::new(chain_id, chain_identity.get_chain().clone(), eq.clone());
+
+        // the node is in group 0 while the task is assigned to group 1 at block 0
+        let task = RandomnessTask {
+            request_id: vec![1],
+            subscription_id: 1,
+            group_index: 1,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::exp10(9),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 0,
+            priority: RandomnessTaskPriority::Normal,
+        };
+        handle.get_chain().emit_randomness_task(task.clone());
+
+        let randomness_tasks_cache = Arc::new(RwLock::new(InMemoryBLSTasksQueue::new()));
+        randomness_tasks_cache
+            .write()
+            .await
+            .add(task.clone())
+            .await
+            .unwrap();
+
+        // a group of the node alone, so that it is the only committer and aggregates its own
+        // partial signature
+        let mut group = Group::<BN254>::new();
+        group.size = 1;
+        group.threshold = 1;
+        group.state = true;
+        group.members.insert(
+            id_address,
+            Member {
+                index: 0,
+                id_address,
+                rpc_endpoint: None,
+                partial_public_key: None,
+            },
+        );
+        group.committers = vec![id_address];
+        let group_cache = Arc::new(RwLock::new(InMemoryGroupInfoCache::rebuild(
+            Some(Share {
+                index: 0,
+                private: Scalar::one(),
+            }),
+            group,
+            DKGStatus::WaitForPostProcess,
+            0,
+            1,
+        )));
+
+        let chain_identity = Arc::new(RwLock::new(chain_identity));
+        let randomness_signature_cache = Arc::new(RwLock::new(InMemorySignatureResultCache::<
+            RandomnessResultCache,
+        >::new()));
+        let ts = Arc::new(RwLock::new(SimpleDynamicTaskScheduler::new()));
+
+        ReadyToHandleRandomnessTaskSubscriber::new(
+            chain_id,
+            id_address,
+            group_cache.clone(),
+            randomness_tasks_cache.clone(),
+            randomness_signature_cache.clone(),
+            eq.clone(),
+            ts.clone(),
+            time_limits.commit_partial_signature_retry_descriptor,
+        )
+        .subscribe()
+        .await;
+
+        RandomnessSignatureAggregationSubscriber::<_, _, BN254>::new(
+            chain_id,
+            id_address,
+            chain_identity.clone(),
+            randomness_signature_cache.clone(),
+            eq.clone(),
+            ts,
+            None,
+            None,
+        )
+        .subscribe()
+        .await;
+
+        let aggregation_listener = RandomnessSignatureAggregationListener::new(
+            chain_id,
+            id_address,
+            chain_identity.clone(),
+            block_cache.clone(),
+            group_cache.clone(),
+            randomness_signature_cache,
+            eq.clone(),
+            1,
+            10,
+        );
+
+        let listener = ReadyToHandleRandomnessTaskListener::new(
+            chain_id,
+            id_address,
+            chain_identity,
+            block_cache,
+            group_cache,
+            randomness_tasks_cache,
+            eq,
+            5,
+            2,
+            10,
+        );
+
+        for _ in 0..6 {
+            handle.advance_blocks(1).await;
+            listener.listen().await.unwrap();
+        }
+
+        // other groups can only take over the task after its exclusive window of 5 blocks
+        assert_eq!(
+            recorded_events.find_block_height(Topic::ReadyToHandleRandomnessTask(chain_id), &[1]),
+            Some(6)
+        );
+        assert_eq!(
+            recorded_events
+                .get_topics()
+                .iter()
+                .filter(|topic| matches!(topic, Topic::NewBlock(_)))
+                .count(),
+            6
+        );
+
+        // the partial signature is cached and the task is fulfilled by the tasks the subscribers
+        // spawn, which the aggregation listener is polled for in between
+        let client = handle.get_chain().with_id_address(id_address);
+        let mut fulfillment = None;
+        for _ in 0..100 {
+            aggregation_listener.listen().await.unwrap();
+            fulfillment = client.get_fulfillment(&[1], 0).await.unwrap();
+            if fulfillment.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let (_, committer, block_height) = fulfillment.unwrap();
+        assert_eq!(committer, id_address);
+        assert_eq!(block_height, 6);
+    }
+}
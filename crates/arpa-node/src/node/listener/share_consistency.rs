@@ -0,0 +1,61 @@
+use super::Listener;
+use crate::node::{context::sync::check_share_consistency, error::NodeResult};
+use arpa_node_contract_client::controller::ControllerClientBuilder;
+use arpa_node_core::ChainIdentity;
+use arpa_node_dal::{GroupInfoFetcher, GroupInfoUpdater};
+use async_trait::async_trait;
+use ethers::types::Address;
+use std::{marker::PhantomData, sync::Arc};
+use threshold_bls::group::PairingCurve;
+use tokio::sync::RwLock;
+
+pub struct ShareConsistencyListener<
+    G: GroupInfoFetcher<PC> + GroupInfoUpdater<PC>,
+    I: ChainIdentity + ControllerClientBuilder<PC>,
+    PC: PairingCurve,
+> {
+    id_address: Address,
+    main_chain_identity: Arc<RwLock<I>>,
+    group_cache: Arc<RwLock<G>>,
+    pc: PhantomData<PC>,
+}
+
+impl<
+        G: GroupInfoFetcher<PC> + GroupInfoUpdater<PC>,
+        I: ChainIdentity + ControllerClientBuilder<PC>,
+        PC: PairingCurve,
+    > ShareConsistencyListener<G, I, PC>
+{
+    pub fn new(
+        id_address: Address,
+        main_chain_identity: Arc<RwLock<I>>,
+        group_cache: Arc<RwLock<G>>,
+    ) -> Self {
+        ShareConsistencyListener {
+            id_address,
+            main_chain_identity,
+            group_cache,
+            pc: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<
+        G: GroupInfoFetcher<PC> + GroupInfoUpdater<PC> + Sync + Send,
+        I: ChainIdentity + ControllerClientBuilder<PC> + Sync + Send,
+        PC: PairingCurve + Sync + Send + 'static,
+    > Listener for ShareConsistencyListener<G, I, PC>
+{
+    async fn listen(&self) -> NodeResult<()> {
+        let client = self
+            .main_chain_identity
+            .read()
+            .await
+            .build_controller_client();
+
+        check_share_consistency(&self.group_cache, &client, self.id_address).await?;
+
+        Ok(())
+    }
+}
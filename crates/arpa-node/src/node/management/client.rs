@@ -1,6 +1,9 @@
 use crate::node::error::{NodeError, NodeResult};
 use crate::rpc_stub::management::management_service_client::ManagementServiceClient;
-use crate::rpc_stub::management::ListFixedTasksRequest;
+use crate::rpc_stub::management::{
+    CountRandomnessResultsRequest, ListFixedTasksRequest, ListTaskStatusesRequest,
+    RandomnessResultStateCount, TaskStatus,
+};
 use tonic::codegen::InterceptedService;
 use tonic::service::Interceptor;
 use tonic::transport::Channel;
@@ -55,4 +58,26 @@ impl GeneralManagementClient {
             .map(|r| r.into_inner().fixed_tasks)
             .map_err(|status| status.into())
     }
+
+    pub async fn list_task_statuses(&self) -> NodeResult<Vec<TaskStatus>> {
+        let mut management_client = self.prepare_service_client().await?;
+
+        let request = Request::new(ListTaskStatusesRequest {});
+        management_client
+            .list_task_statuses(request)
+            .await
+            .map(|r| r.into_inner().task_statuses)
+            .map_err(|status| status.into())
+    }
+
+    pub async fn count_randomness_results(&self) -> NodeResult<Vec<RandomnessResultStateCount>> {
+        let mut management_client = self.prepare_service_client().await?;
+
+        let request = Request::new(CountRandomnessResultsRequest {});
+        management_client
+            .count_randomness_results(request)
+            .await
+            .map(|r| r.into_inner().state_counts)
+            .map_err(|status| status.into())
+    }
 }
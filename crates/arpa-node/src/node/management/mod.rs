@@ -1,36 +1,44 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 use super::{
     algorithm::bls::{BLSCore, SimpleBLSCore},
-    committer::{client::GeneralCommitterClient, CommitterClient, CommitterService},
+    committer::{
+        client::GeneralCommitterClient, pool::CommitterChannelPool, CommitterClient,
+        CommitterService,
+    },
     context::{
         chain::{Chain, ChainFetcher, MainChainFetcher},
         types::GeneralContext,
         ContextFetcher,
     },
     error::{NodeError, NodeResult},
-    scheduler::FixedTaskScheduler,
+    scheduler::{registry::TaskStatus, FixedTaskScheduler},
 };
 use anyhow::Result;
 use arpa_node_contract_client::{
     adapter::{AdapterClientBuilder, AdapterTransactions},
     controller::{ControllerClientBuilder, ControllerTransactions},
     coordinator::CoordinatorClientBuilder,
-    provider::ChainProviderBuilder,
+    provider::{ChainProviderBuilder, TransactionReplacer},
 };
 use arpa_node_core::{
     BLSTaskType, ChainIdentity, DKGStatus, ExponentialBackoffRetryDescriptor, Group,
     ListenerDescriptor, ListenerType, PartialSignature, RandomnessTask, SchedulerResult, TaskType,
+    DEFAULT_COMMIT_PARTIAL_SIGNATURE_PER_ATTEMPT_TIMEOUT_MILLIS,
     DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_BASE, DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_FACTOR,
     DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_MAX_ATTEMPTS,
-    DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_USE_JITTER, DEFAULT_LISTENER_INTERVAL_MILLIS,
+    DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_USE_JITTER, DEFAULT_JITTER_RATIO,
+    DEFAULT_LISTENER_INTERVAL_MILLIS,
 };
 use arpa_node_dal::{
-    cache::RandomnessResultCache, error::DataAccessResult, BLSTasksFetcher, BLSTasksUpdater,
-    ContextInfoUpdater, GroupInfoFetcher, GroupInfoUpdater, NodeInfoFetcher, NodeInfoUpdater,
-    SignatureResultCacheFetcher, SignatureResultCacheUpdater,
+    cache::RandomnessResultCache, error::DataAccessResult, BLSResultCacheState, BLSTasksFetcher,
+    BLSTasksUpdater, ContextInfoUpdater, GroupInfoFetcher, GroupInfoUpdater, NodeInfoFetcher,
+    NodeInfoUpdater, SignatureResultCacheFetcher, SignatureResultCacheUpdater,
 };
-use ethers::types::Address;
+use ethers::types::{Address, H256, U256};
 use threshold_bls::{group::PairingCurve, poly::Eval, sig::Share};
 
 pub mod server;
@@ -52,6 +60,11 @@ pub struct GroupInfo<PC: PairingCurve> {
     pub dkg_start_block_height: usize,
 }
 
+pub struct GroupKeys<PC: PairingCurve> {
+    pub public_key: PC::G2,
+    pub partial_public_keys: BTreeMap<Address, PC::G2>,
+}
+
 pub trait ServiceClient<C> {
     async fn prepare_service_client(&self) -> NodeResult<C>;
 }
@@ -64,11 +77,15 @@ pub trait NodeService {
     async fn node_quit(&self) -> NodeResult<()>;
 
     async fn shutdown_node(&self) -> NodeResult<()>;
+
+    async fn replace_stuck_transaction(&self, nonce: U256, new_fee_gwei: u64) -> NodeResult<H256>;
 }
 
 pub trait ComponentService {
     async fn list_fixed_tasks(&self) -> SchedulerResult<Vec<TaskType>>;
 
+    async fn list_task_statuses(&self) -> SchedulerResult<Vec<(TaskType, TaskStatus)>>;
+
     async fn start_listener(&self, task_type: ListenerType) -> SchedulerResult<()>;
 
     async fn shutdown_listener(&self, task_type: ListenerType) -> SchedulerResult<()>;
@@ -77,6 +94,11 @@ pub trait DBService<PC: PairingCurve> {
     async fn get_node_info(&self) -> DataAccessResult<NodeInfo<PC>>;
 
     async fn get_group_info(&self) -> DataAccessResult<GroupInfo<PC>>;
+
+    async fn get_group_keys(&self) -> DataAccessResult<GroupKeys<PC>>;
+
+    async fn count_randomness_results(&self)
+        -> DataAccessResult<Vec<(BLSResultCacheState, usize)>>;
 }
 
 pub trait DKGService {
@@ -199,6 +221,21 @@ impl<
         // TODO shutdown gracefully
         std::process::exit(1);
     }
+
+    async fn replace_stuck_transaction(&self, nonce: U256, new_fee_gwei: u64) -> NodeResult<H256> {
+        let provider = self
+            .get_main_chain()
+            .get_chain_identity()
+            .read()
+            .await
+            .build_chain_provider();
+
+        let tx_hash = provider
+            .replace_stuck_transaction(nonce, new_fee_gwei)
+            .await?;
+
+        Ok(tx_hash)
+    }
 }
 
 impl<
@@ -256,6 +293,14 @@ impl<
             .collect())
     }
 
+    async fn list_task_statuses(&self) -> SchedulerResult<Vec<(TaskType, TaskStatus)>> {
+        Ok(self
+            .get_fixed_task_handler()
+            .read()
+            .await
+            .get_task_statuses())
+    }
+
     async fn start_listener(&self, task_type: ListenerType) -> SchedulerResult<()> {
         self.get_main_chain()
             .init_listener(
@@ -267,11 +312,16 @@ impl<
     }
 
     async fn shutdown_listener(&self, task_type: ListenerType) -> SchedulerResult<()> {
-        self.get_fixed_task_handler()
+        let aborted_task = self
+            .get_fixed_task_handler()
             .write()
             .await
-            .abort(&TaskType::Listener(task_type))
-            .await
+            .abort(&TaskType::Listener(task_type))?;
+
+        // the other tasks can still be managed while the listener is given its grace period
+        aborted_task.stop().await;
+
+        Ok(())
     }
 }
 
@@ -400,6 +450,39 @@ impl<
             dkg_start_block_height,
         })
     }
+
+    async fn get_group_keys(&self) -> DataAccessResult<GroupKeys<PC>> {
+        let group_cache = self.get_main_chain().get_group_cache();
+        let group_cache = group_cache.read().await;
+
+        let public_key = group_cache.get_public_key()?.to_owned();
+        let partial_public_keys = group_cache
+            .get_members()?
+            .iter()
+            .filter_map(|(id_address, member)| {
+                member
+                    .partial_public_key
+                    .as_ref()
+                    .map(|k| (*id_address, k.to_owned()))
+            })
+            .collect();
+
+        Ok(GroupKeys {
+            public_key,
+            partial_public_keys,
+        })
+    }
+
+    async fn count_randomness_results(
+        &self,
+    ) -> DataAccessResult<Vec<(BLSResultCacheState, usize)>> {
+        self.get_main_chain()
+            .get_randomness_result_cache()
+            .read()
+            .await
+            .count_by_state()
+            .await
+    }
 }
 
 impl<
@@ -648,11 +731,23 @@ impl<
             .unwrap()
             .to_string();
 
+        let group_epoch = self
+            .get_main_chain()
+            .get_group_cache()
+            .read()
+            .await
+            .get_epoch()?;
+
         let commit_partial_signature_retry_descriptor = ExponentialBackoffRetryDescriptor {
             base: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_BASE,
             factor: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_FACTOR,
             max_attempts: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_MAX_ATTEMPTS,
             use_jitter: DEFAULT_COMMIT_PARTIAL_SIGNATURE_RETRY_USE_JITTER,
+            jitter_ratio: DEFAULT_JITTER_RATIO,
+            per_attempt_timeout_millis: Some(
+                DEFAULT_COMMIT_PARTIAL_SIGNATURE_PER_ATTEMPT_TIMEOUT_MILLIS,
+            ),
+            max_interval_millis: None,
         };
 
         let committer_client = GeneralCommitterClient::build(
@@ -660,6 +755,7 @@ impl<
             committer_id_address,
             endpoint,
             commit_partial_signature_retry_descriptor,
+            Arc::new(CommitterChannelPool::default()),
         );
 
         let chain_id = self
@@ -676,6 +772,7 @@ impl<
                 randomness_task_request_id,
                 msg,
                 partial,
+                group_epoch,
             )
             .await?;
 
@@ -723,8 +820,22 @@ impl<
             .get(&randomness_task_request_id)
             .await?;
 
+        let threshold = self
+            .get_main_chain()
+            .get_group_cache()
+            .read()
+            .await
+            .get_threshold()?;
+
         client
-            .fulfill_randomness(group_index, randomness_task, sig, partial_signatures)
+            .fulfill_randomness(
+                group_index,
+                threshold,
+                randomness_task,
+                sig,
+                partial_signatures,
+                None,
+            )
             .await?;
 
         Ok(())
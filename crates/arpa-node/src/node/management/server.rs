@@ -6,19 +6,22 @@ use crate::rpc_stub::management::management_service_server::{
     ManagementService, ManagementServiceServer,
 };
 use crate::rpc_stub::management::{
-    AggregatePartialSigsReply, AggregatePartialSigsRequest, FulfillRandomnessReply,
-    FulfillRandomnessRequest, GetGroupInfoReply, GetGroupInfoRequest, GetNodeInfoReply,
-    GetNodeInfoRequest, Group, ListFixedTasksReply, ListFixedTasksRequest, Member,
-    NodeActivateReply, NodeActivateRequest, NodeQuitReply, NodeQuitRequest, NodeRegisterReply,
-    NodeRegisterRequest, PartialSignReply, PartialSignRequest, PostProcessDkgReply,
-    PostProcessDkgRequest, SendPartialSigReply, SendPartialSigRequest, ShutdownListenerReply,
-    ShutdownListenerRequest, ShutdownNodeReply, ShutdownNodeRequest, StartListenerReply,
-    StartListenerRequest, VerifyPartialSigsReply, VerifyPartialSigsRequest, VerifySigReply,
-    VerifySigRequest,
+    AggregatePartialSigsReply, AggregatePartialSigsRequest, CountRandomnessResultsReply,
+    CountRandomnessResultsRequest, FulfillRandomnessReply, FulfillRandomnessRequest,
+    GetGroupInfoReply, GetGroupInfoRequest, GetGroupKeysReply, GetGroupKeysRequest,
+    GetNodeInfoReply, GetNodeInfoRequest, Group, ListFixedTasksReply, ListFixedTasksRequest,
+    ListTaskStatusesReply, ListTaskStatusesRequest, Member, NodeActivateReply, NodeActivateRequest,
+    NodeQuitReply, NodeQuitRequest, NodeRegisterReply, NodeRegisterRequest, PartialSignReply,
+    PartialSignRequest, PostProcessDkgReply, PostProcessDkgRequest, RandomnessResultStateCount,
+    ReplaceStuckTransactionReply, ReplaceStuckTransactionRequest, SendPartialSigReply,
+    SendPartialSigRequest, ShutdownListenerReply, ShutdownListenerRequest, ShutdownNodeReply,
+    ShutdownNodeRequest, StartListenerReply, StartListenerRequest, TaskStatus,
+    VerifyPartialSigsReply, VerifyPartialSigsRequest, VerifySigReply, VerifySigRequest,
 };
 use arpa_node_contract_client::{
     adapter::AdapterClientBuilder, controller::ControllerClientBuilder,
-    coordinator::CoordinatorClientBuilder, provider::ChainProviderBuilder,
+    coordinator::CoordinatorClientBuilder, error::ContractClientError,
+    provider::ChainProviderBuilder,
 };
 use arpa_node_core::{
     address_to_string, ChainIdentity, Group as ModelGroup, ListenerType, Member as ModelMember,
@@ -39,14 +42,16 @@ use std::{
     task::{Context, Poll},
     time::Duration,
 };
-use threshold_bls::group::PairingCurve;
+use threshold_bls::{group::PairingCurve, serialize::point_to_hex};
 use tokio::sync::RwLock;
 use tonic::transport::Body;
 use tonic::{body::BoxBody, transport::Server, Request, Response, Status};
 use tower::{Layer, Service};
 use uuid::Uuid;
 
-use super::{BLSRandomnessService, DBService, DKGService, GroupInfo, NodeInfo, NodeService};
+use super::{
+    BLSRandomnessService, DBService, DKGService, GroupInfo, GroupKeys, NodeInfo, NodeService,
+};
 
 type NodeContext<N, G, T, C, I, PC> = Arc<RwLock<GeneralContext<N, G, T, C, I, PC>>>;
 
@@ -148,6 +153,33 @@ impl<
         return Ok(Response::new(ListFixedTasksReply { fixed_tasks }));
     }
 
+    async fn list_task_statuses(
+        &self,
+        request: Request<ListTaskStatusesRequest>,
+    ) -> Result<Response<ListTaskStatusesReply>, Status> {
+        let _req = request.into_inner();
+
+        let task_statuses = self
+            .context
+            .read()
+            .await
+            .list_task_statuses()
+            .await
+            .map(|ts| {
+                ts.into_iter()
+                    .map(|(task_type, status)| TaskStatus {
+                        task_type: task_type.to_string(),
+                        state: status.state.to_string(),
+                        restarts: status.restarts as u64,
+                        last_error: status.last_error.unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .map_err(|e: SchedulerError| Status::internal(e.to_string()))?;
+
+        return Ok(Response::new(ListTaskStatusesReply { task_statuses }));
+    }
+
     async fn start_listener(
         &self,
         request: Request<StartListenerRequest>,
@@ -236,6 +268,28 @@ impl<
         return Ok(Response::new(ShutdownNodeReply { res: true }));
     }
 
+    async fn replace_stuck_transaction(
+        &self,
+        request: Request<ReplaceStuckTransactionRequest>,
+    ) -> Result<tonic::Response<ReplaceStuckTransactionReply>, tonic::Status> {
+        let req = request.into_inner();
+        let tx_hash = self
+            .context
+            .read()
+            .await
+            .replace_stuck_transaction(req.nonce.into(), req.new_fee_gwei)
+            .await
+            .map_err(|e: NodeError| match e {
+                NodeError::ContractClientError(ContractClientError::NoPendingTransaction(_)) => {
+                    Status::failed_precondition(e.to_string())
+                }
+                _ => Status::internal(e.to_string()),
+            })?;
+        return Ok(Response::new(ReplaceStuckTransactionReply {
+            tx_hash: format!("{:?}", tx_hash),
+        }));
+    }
+
     async fn get_node_info(
         &self,
         request: Request<GetNodeInfoRequest>,
@@ -266,6 +320,42 @@ impl<
         return Ok(Response::new(group_info.into()));
     }
 
+    async fn get_group_keys(
+        &self,
+        request: Request<GetGroupKeysRequest>,
+    ) -> Result<tonic::Response<GetGroupKeysReply>, tonic::Status> {
+        let _req = request.into_inner();
+        let group_keys = self
+            .context
+            .read()
+            .await
+            .get_group_keys()
+            .await
+            .map_err(|e: DataAccessError| Status::unavailable(e.to_string()))?;
+        return Ok(Response::new(group_keys.into()));
+    }
+
+    async fn count_randomness_results(
+        &self,
+        request: Request<CountRandomnessResultsRequest>,
+    ) -> Result<tonic::Response<CountRandomnessResultsReply>, tonic::Status> {
+        let _req = request.into_inner();
+        let state_counts = self
+            .context
+            .read()
+            .await
+            .count_randomness_results()
+            .await
+            .map_err(|e: DataAccessError| Status::unavailable(e.to_string()))?
+            .into_iter()
+            .map(|(state, count)| RandomnessResultStateCount {
+                state: state.to_string(),
+                count: count as u64,
+            })
+            .collect();
+        return Ok(Response::new(CountRandomnessResultsReply { state_counts }));
+    }
+
     async fn post_process_dkg(
         &self,
         request: Request<PostProcessDkgRequest>,
@@ -428,6 +518,21 @@ impl<PC: PairingCurve> From<GroupInfo<PC>> for GetGroupInfoReply {
     }
 }
 
+impl<PC: PairingCurve> From<GroupKeys<PC>> for GetGroupKeysReply {
+    fn from(k: GroupKeys<PC>) -> Self {
+        let partial_public_keys = k
+            .partial_public_keys
+            .iter()
+            .map(|(id_address, key)| (address_to_string(*id_address), point_to_hex(key)))
+            .collect();
+
+        GetGroupKeysReply {
+            public_key: point_to_hex(&k.public_key),
+            partial_public_keys,
+        }
+    }
+}
+
 impl<PC: PairingCurve> From<ModelGroup<PC>> for Group {
     fn from(g: ModelGroup<PC>) -> Self {
         let public_key = if let Some(k) = g.public_key {
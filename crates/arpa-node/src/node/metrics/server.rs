@@ -0,0 +1,34 @@
+use arpa_node_core::metrics::{gather_metrics, metrics_content_type};
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use std::convert::Infallible;
+
+async fn serve_metrics(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = match (req.method(), req.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .status(StatusCode::OK)
+            .header(CONTENT_TYPE, metrics_content_type())
+            .body(Body::from(gather_metrics()))
+            .unwrap(),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap(),
+    };
+
+    Ok(response)
+}
+
+pub async fn start_metrics_server(endpoint: String) -> Result<(), Box<dyn std::error::Error>> {
+    let addr = endpoint.parse()?;
+
+    let make_service =
+        make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(serve_metrics)) });
+
+    Server::try_bind(&addr)?.serve(make_service).await?;
+
+    Ok(())
+}
@@ -17,3 +17,7 @@ pub mod algorithm;
 pub mod committer;
 
 pub mod management;
+
+pub mod metrics;
+
+pub mod telemetry;
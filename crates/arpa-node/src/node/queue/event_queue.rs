@@ -1,30 +1,260 @@
-use super::{EventPublisher, EventSubscriber};
+use super::{EventPublisher, EventSubscriber, SubscriberId};
 use crate::node::{
-    event::types::Topic,
+    error::NodeError,
+    event::{sequenced::SequencedEvent, types::Topic},
     subscriber::{DebuggableEvent, DebuggableSubscriber},
 };
+use arpa_node_core::{
+    jitter,
+    metrics::{EVENTS_DEAD_LETTERED, EVENTS_DROPPED},
+    EventQueueDescriptor, OverflowPolicy,
+};
 use async_trait::async_trait;
-use log::error;
-use std::collections::HashMap;
+use log::{error, warn};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::Notify;
+use tokio_retry::strategy::ExponentialBackoff;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct EventQueue {
-    subscribers: HashMap<Topic, Vec<Box<dyn DebuggableSubscriber>>>,
+    subscribers: HashMap<Topic, Vec<(SubscriberId, Box<dyn DebuggableSubscriber>)>>,
+    next_subscriber_id: SubscriberId,
+    descriptor: EventQueueDescriptor,
+    pending_events: Mutex<HashMap<Topic, Arc<PendingEvents>>>,
+    dead_letter_handler: Box<dyn DeadLetterHandler>,
+}
+
+impl Default for EventQueue {
+    fn default() -> Self {
+        EventQueue::new()
+    }
+}
+
+/// Receives the events which a subscriber still failed to handle after all the retries.
+pub trait DeadLetterHandler: std::fmt::Debug + Send + Sync {
+    fn handle(&self, topic: Topic, event: &dyn DebuggableEvent, error: &NodeError);
+}
+
+/// Logs the failed event, which carries the request id or the group epoch of the task,
+/// and counts it by topic.
+#[derive(Debug, Default)]
+pub struct LoggingDeadLetterHandler;
+
+impl DeadLetterHandler for LoggingDeadLetterHandler {
+    fn handle(&self, topic: Topic, event: &dyn DebuggableEvent, error: &NodeError) {
+        EVENTS_DEAD_LETTERED
+            .with_label_values(&[topic.name()])
+            .inc();
+
+        error!(
+            "subscriber of {:?} failed to handle the event, error: {:?}, event: {:?}",
+            topic, error, event
+        );
+    }
+}
+
+/// Events of a topic published but not yet delivered to the subscribers.
+#[derive(Debug)]
+struct PendingEvents {
+    capacity: usize,
+    events: Mutex<VecDeque<Box<dyn DebuggableEvent>>>,
+    // the last sequence number attached to an event of the topic, starting from 1
+    last_sequence: AtomicU64,
+    // held by the publisher which is delivering the pending events of the topic
+    delivering: tokio::sync::Mutex<()>,
+    delivered: Notify,
+}
+
+impl PendingEvents {
+    fn new(capacity: usize) -> Self {
+        PendingEvents {
+            capacity,
+            events: Mutex::new(VecDeque::new()),
+            last_sequence: AtomicU64::new(0),
+            delivering: tokio::sync::Mutex::new(()),
+            delivered: Notify::new(),
+        }
+    }
+
+    /// Returns false if the event is rejected.
+    async fn push(
+        &self,
+        topic: Topic,
+        event: Box<dyn DebuggableEvent>,
+        overflow_policy: OverflowPolicy,
+    ) -> bool {
+        loop {
+            let delivered = self.delivered.notified();
+
+            {
+                let mut events = self.events.lock().unwrap();
+
+                if events.len() < self.capacity {
+                    events.push_back(self.sequenced(event));
+                    return true;
+                }
+
+                match overflow_policy {
+                    OverflowPolicy::DropOldest => {
+                        let dropped = events.pop_front();
+                        events.push_back(self.sequenced(event));
+
+                        EVENTS_DROPPED.with_label_values(&[topic.name()]).inc();
+                        warn!(
+                            "event queue of {:?} is full with capacity {}, dropped the oldest event: {:?}",
+                            topic, self.capacity, dropped
+                        );
+
+                        return true;
+                    }
+                    OverflowPolicy::Error => {
+                        EVENTS_DROPPED.with_label_values(&[topic.name()]).inc();
+                        error!(
+                            "event queue of {:?} is full with capacity {}, rejected the event: {:?}",
+                            topic, self.capacity, event
+                        );
+
+                        return false;
+                    }
+                    OverflowPolicy::Block => {}
+                }
+            }
+
+            delivered.await;
+        }
+    }
+
+    // called with the events locked so that sequence numbers follow the order of the queue
+    fn sequenced(&self, event: Box<dyn DebuggableEvent>) -> Box<dyn DebuggableEvent> {
+        let sequence = self.last_sequence.fetch_add(1, Ordering::SeqCst) + 1;
+
+        Box::new(SequencedEvent::new(sequence, event))
+    }
+
+    fn pop(&self) -> Option<Box<dyn DebuggableEvent>> {
+        let event = self.events.lock().unwrap().pop_front();
+
+        if event.is_some() {
+            self.delivered.notify_waiters();
+        }
+
+        event
+    }
+
+    fn is_empty(&self) -> bool {
+        self.events.lock().unwrap().is_empty()
+    }
 }
 
 impl EventQueue {
     pub fn new() -> Self {
+        EventQueue::with_descriptor(EventQueueDescriptor::default())
+    }
+
+    pub fn with_descriptor(descriptor: EventQueueDescriptor) -> Self {
         EventQueue {
             subscribers: HashMap::new(),
+            next_subscriber_id: 0,
+            descriptor,
+            pending_events: Mutex::new(HashMap::new()),
+            dead_letter_handler: Box::new(LoggingDeadLetterHandler),
+        }
+    }
+
+    pub fn set_dead_letter_handler(&mut self, dead_letter_handler: Box<dyn DeadLetterHandler>) {
+        self.dead_letter_handler = dead_letter_handler;
+    }
+
+    fn get_pending_events(&self, topic: Topic) -> Arc<PendingEvents> {
+        self.pending_events
+            .lock()
+            .unwrap()
+            .entry(topic)
+            .or_insert_with(|| {
+                Arc::new(PendingEvents::new(
+                    self.descriptor.get_capacity(topic.name()),
+                ))
+            })
+            .clone()
+    }
+
+    async fn deliver(&self, topic: Topic, event: &dyn DebuggableEvent) {
+        if let Some(subscribers) = self.subscribers.get(&topic) {
+            let retry_descriptor = self.descriptor.notify_retry_descriptor;
+
+            for (_, subscriber) in subscribers {
+                let mut retry_strategy = ExponentialBackoff::from_millis(retry_descriptor.base)
+                    .factor(retry_descriptor.factor)
+                    .max_delay(retry_descriptor.get_max_interval())
+                    .map(|e| {
+                        if retry_descriptor.use_jitter {
+                            jitter(e, retry_descriptor.jitter_ratio)
+                        } else {
+                            e
+                        }
+                    })
+                    .take(retry_descriptor.max_attempts);
+
+                let mut attempts = 0;
+
+                while let Err(e) = subscriber.notify(topic, event).await {
+                    let interval = match retry_strategy.next() {
+                        Some(interval) => interval,
+                        None => {
+                            self.dead_letter_handler.handle(topic, event, &e);
+                            break;
+                        }
+                    };
+
+                    attempts += 1;
+
+                    warn!(
+                        "subscriber of {:?} failed to handle the event, retrying({}/{}) in {:?}, error: {:?}",
+                        topic, attempts, retry_descriptor.max_attempts, interval, e
+                    );
+
+                    tokio::time::sleep(interval).await;
+                }
+            }
         }
     }
 }
 
 impl EventSubscriber for EventQueue {
-    fn subscribe(&mut self, topic: Topic, subscriber: Box<dyn DebuggableSubscriber>) {
-        self.subscribers.entry(topic).or_insert_with(Vec::new);
+    fn subscribe(
+        &mut self,
+        topic: Topic,
+        subscriber: Box<dyn DebuggableSubscriber>,
+    ) -> SubscriberId {
+        let subscriber_id = self.next_subscriber_id;
+
+        self.next_subscriber_id += 1;
+
+        self.subscribers
+            .entry(topic)
+            .or_insert_with(Vec::new)
+            .push((subscriber_id, subscriber));
+
+        subscriber_id
+    }
 
-        self.subscribers.get_mut(&topic).unwrap().push(subscriber);
+    fn unsubscribe(&mut self, topic: Topic, subscriber_id: SubscriberId) -> bool {
+        match self.subscribers.get_mut(&topic) {
+            Some(subscribers) => {
+                let len = subscribers.len();
+
+                subscribers.retain(|(id, _)| *id != subscriber_id);
+
+                subscribers.len() != len
+            }
+            None => false,
+        }
     }
 }
 
@@ -33,11 +263,34 @@ impl<E: DebuggableEvent + Clone + Send + Sync + 'static> EventPublisher<E> for E
     async fn publish(&self, event: E) {
         let topic = event.topic();
 
-        if let Some(subscribers) = self.subscribers.get(&topic) {
-            for subscriber in subscribers {
-                if let Err(e) = subscriber.notify(topic, &event).await {
-                    error!("{:?}", e);
+        if !self.subscribers.contains_key(&topic) {
+            return;
+        }
+
+        let pending_events = self.get_pending_events(topic);
+
+        if !pending_events
+            .push(topic, Box::new(event), self.descriptor.overflow_policy)
+            .await
+        {
+            return;
+        }
+
+        // Events are delivered in order by whichever publisher gets the delivering lock,
+        // the others return once their events are queued.
+        loop {
+            match pending_events.delivering.try_lock() {
+                Ok(_delivering) => {
+                    while let Some(event) = pending_events.pop() {
+                        self.deliver(topic, event.as_ref()).await;
+                    }
                 }
+                Err(_) => break,
+            }
+
+            // events queued right before the lock was released are picked up here
+            if pending_events.is_empty() {
+                break;
             }
         }
     }
@@ -45,19 +298,25 @@ impl<E: DebuggableEvent + Clone + Send + Sync + 'static> EventPublisher<E> for E
 
 #[cfg(test)]
 pub mod tests {
-    use super::EventPublisher;
+    use super::{DeadLetterHandler, EventPublisher, EventSubscriber, PendingEvents};
     use crate::node::{
-        event::new_block::NewBlock,
+        error::{NodeError, NodeResult},
+        event::{new_block::NewBlock, types::Topic, Event},
         listener::block::BlockListener,
-        queue::event_queue::EventQueue,
-        subscriber::{block::BlockSubscriber, Subscriber},
+        queue::{event_queue::EventQueue, SubscriberId},
+        subscriber::{block::BlockSubscriber, DebuggableEvent, DebuggableSubscriber, Subscriber},
     };
-    use arpa_node_core::{Config, GeneralChainIdentity};
+    use arpa_node_core::{Config, EventQueueDescriptor, GeneralChainIdentity, OverflowPolicy};
     use arpa_node_dal::cache::InMemoryBlockInfoCache;
     use arpa_node_dal::BlockInfoFetcher;
-    use ethers::types::Address;
-    use std::sync::Arc;
+    use async_trait::async_trait;
+    use ethers::{signers::LocalWallet, types::Address};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
     use tokio::sync::RwLock;
+    use tokio_util::sync::CancellationToken;
 
     #[tokio::test]
     async fn test() {
@@ -75,9 +334,10 @@ pub mod tests {
 
         s.subscribe().await;
 
-        let fake_wallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
-            .parse()
-            .unwrap();
+        let fake_wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
 
         let contract_transaction_retry_descriptor = config
             .time_limits
@@ -96,11 +356,17 @@ pub mod tests {
             Address::random(),
             contract_transaction_retry_descriptor,
             contract_view_retry_descriptor,
+            config
+                .time_limits
+                .unwrap()
+                .get_transaction_confirmation_descriptor(),
+            config.time_limits.unwrap().circuit_breaker_descriptor,
+            config.fulfill_randomness_gas_limit_multiplier.unwrap(),
         );
 
         let chain_identity = Arc::new(RwLock::new(chain_identity));
 
-        let p = BlockListener::new(chain_id, chain_identity, eq);
+        let p = BlockListener::new(chain_id, chain_identity, eq, CancellationToken::new());
 
         p.publish(NewBlock {
             chain_id,
@@ -126,4 +392,164 @@ pub mod tests {
 
         assert_eq!(10, block_cache.clone().read().await.get_block_height());
     }
+
+    #[tokio::test]
+    async fn test_unsubscribe() {
+        let eq = Arc::new(RwLock::new(EventQueue::new()));
+
+        let chain_id = 1;
+
+        let block_cache = Arc::new(RwLock::new(InMemoryBlockInfoCache::new()));
+
+        let s = BlockSubscriber::new(chain_id, block_cache.clone(), eq.clone());
+
+        let subscriber_id = s.subscribe().await;
+
+        eq.read()
+            .await
+            .publish(NewBlock {
+                chain_id,
+                block_height: 1,
+            })
+            .await;
+
+        assert_eq!(1, block_cache.clone().read().await.get_block_height());
+
+        assert!(!eq
+            .write()
+            .await
+            .unsubscribe(Topic::NewBlock(999), subscriber_id));
+
+        assert!(eq
+            .write()
+            .await
+            .unsubscribe(Topic::NewBlock(chain_id), subscriber_id));
+
+        assert!(!eq
+            .write()
+            .await
+            .unsubscribe(Topic::NewBlock(chain_id), subscriber_id));
+
+        eq.read()
+            .await
+            .publish(NewBlock {
+                chain_id,
+                block_height: 10,
+            })
+            .await;
+
+        assert_eq!(1, block_cache.clone().read().await.get_block_height());
+    }
+
+    #[tokio::test]
+    async fn test_overflow_policy() {
+        let topic = Topic::NewBlock(1);
+
+        let new_block = |block_height| Box::new(NewBlock::new(1, block_height));
+
+        let pending_events = PendingEvents::new(1);
+        assert!(
+            pending_events
+                .push(topic, new_block(1), OverflowPolicy::DropOldest)
+                .await
+        );
+        assert!(
+            pending_events
+                .push(topic, new_block(2), OverflowPolicy::DropOldest)
+                .await
+        );
+        let event = pending_events.pop().unwrap();
+        assert_eq!(
+            2,
+            event
+                .as_any()
+                .downcast_ref::<NewBlock>()
+                .unwrap()
+                .block_height
+        );
+        assert_eq!(2, event.sequence());
+        assert!(pending_events.is_empty());
+
+        let pending_events = PendingEvents::new(1);
+        assert!(
+            pending_events
+                .push(topic, new_block(1), OverflowPolicy::Error)
+                .await
+        );
+        assert!(
+            !pending_events
+                .push(topic, new_block(2), OverflowPolicy::Error)
+                .await
+        );
+        let event = pending_events.pop().unwrap();
+        assert_eq!(
+            1,
+            event
+                .as_any()
+                .downcast_ref::<NewBlock>()
+                .unwrap()
+                .block_height
+        );
+        assert_eq!(1, event.sequence());
+        assert!(pending_events.is_empty());
+    }
+
+    #[derive(Debug, Default)]
+    struct FailingSubscriber {
+        notified: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Subscriber for FailingSubscriber {
+        async fn notify(&self, _topic: Topic, _payload: &dyn DebuggableEvent) -> NodeResult<()> {
+            self.notified.fetch_add(1, Ordering::SeqCst);
+            Err(NodeError::InvalidTaskType)
+        }
+
+        async fn subscribe(self) -> SubscriberId {
+            unreachable!()
+        }
+    }
+
+    impl DebuggableSubscriber for FailingSubscriber {}
+
+    #[derive(Debug, Default)]
+    struct CountingDeadLetterHandler {
+        handled: Arc<AtomicUsize>,
+    }
+
+    impl DeadLetterHandler for CountingDeadLetterHandler {
+        fn handle(&self, _topic: Topic, _event: &dyn DebuggableEvent, error: &NodeError) {
+            assert!(matches!(error, NodeError::InvalidTaskType));
+            self.handled.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter() {
+        let topic = Topic::NewBlock(1);
+
+        let notified = Arc::new(AtomicUsize::new(0));
+        let handled = Arc::new(AtomicUsize::new(0));
+
+        let mut descriptor = EventQueueDescriptor::default();
+        descriptor.notify_retry_descriptor.max_attempts = 2;
+        descriptor.notify_retry_descriptor.factor = 1;
+
+        let mut eq = EventQueue::with_descriptor(descriptor);
+        eq.set_dead_letter_handler(Box::new(CountingDeadLetterHandler {
+            handled: handled.clone(),
+        }));
+        eq.subscribe(
+            topic,
+            Box::new(FailingSubscriber {
+                notified: notified.clone(),
+            }),
+        );
+
+        eq.deliver(topic, &NewBlock::new(1, 1)).await;
+
+        assert_eq!(3, notified.load(Ordering::SeqCst));
+        assert_eq!(1, handled.load(Ordering::SeqCst));
+    }
 }
@@ -7,8 +7,18 @@ use super::{
 
 pub mod event_queue;
 
+/// Identifies a subscriber in the event queue so that it can be removed later.
+pub type SubscriberId = usize;
+
 pub trait EventSubscriber {
-    fn subscribe(&mut self, topic: Topic, subscriber: Box<dyn DebuggableSubscriber>);
+    fn subscribe(
+        &mut self,
+        topic: Topic,
+        subscriber: Box<dyn DebuggableSubscriber>,
+    ) -> SubscriberId;
+
+    /// Removes the subscriber from the topic, returns false if it was not subscribed to it.
+    fn unsubscribe(&mut self, topic: Topic, subscriber_id: SubscriberId) -> bool;
 }
 
 #[async_trait]
@@ -1,19 +1,61 @@
-use super::{FixedTaskScheduler, TaskScheduler, TaskType};
+use super::{
+    registry::{TaskRegistry, TaskStatus},
+    FixedTaskScheduler, TaskScheduler, TaskType,
+};
 use arpa_node_core::{SchedulerError, SchedulerResult};
 use async_trait::async_trait;
-use futures::Future;
-use std::collections::HashMap;
+use futures::{Future, FutureExt};
+use log::error;
+use std::{collections::HashMap, panic::AssertUnwindSafe, time::Duration};
 use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// How long an aborted task is given to stop on its cancellation token before it's aborted.
+const TASK_CANCELLATION_GRACE_PERIOD_MILLIS: u64 = 5000;
+
+/// A task taken out of the scheduler, which still has to be stopped.
+#[derive(Debug)]
+pub struct AbortedTask {
+    handle: JoinHandle<()>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl AbortedTask {
+    /// Cancels the token of the task if it has one, giving the task a grace period to stop
+    /// by itself before it's aborted.
+    pub async fn stop(mut self) {
+        if let Some(cancellation_token) = self.cancellation_token {
+            cancellation_token.cancel();
+
+            // let the task finish the callback in flight and stop by itself
+            if tokio::time::timeout(
+                Duration::from_millis(TASK_CANCELLATION_GRACE_PERIOD_MILLIS),
+                &mut self.handle,
+            )
+            .await
+            .is_ok()
+            {
+                return;
+            }
+        }
+
+        self.handle.abort();
+    }
+}
 
 #[derive(Debug, Default)]
 pub struct SimpleFixedTaskScheduler {
     fixed_tasks: HashMap<TaskType, JoinHandle<()>>,
+    cancellation_tokens: HashMap<TaskType, CancellationToken>,
+    task_registry: TaskRegistry,
 }
 
 impl SimpleFixedTaskScheduler {
     pub fn new() -> Self {
         SimpleFixedTaskScheduler {
             fixed_tasks: HashMap::new(),
+            cancellation_tokens: HashMap::new(),
+            task_registry: TaskRegistry::new(),
         }
     }
 }
@@ -31,9 +73,19 @@ impl TaskScheduler for SimpleFixedTaskScheduler {
         let mut mdc = vec![];
         log_mdc::iter(|k, v| mdc.push((k.to_owned(), v.to_owned())));
 
+        self.task_registry.mark_started(task_type);
+        let task_registry = self.task_registry.clone();
+
         let handle = tokio::spawn(async move {
             log_mdc::extend(mdc);
-            future.await;
+            match AssertUnwindSafe(future).catch_unwind().await {
+                Ok(()) => task_registry.mark_stopped(task_type),
+                Err(panic) => {
+                    let reason = panic_message(&panic);
+                    error!("task {} panicked: {}", task_type, reason);
+                    task_registry.mark_failed(task_type, reason);
+                }
+            }
         });
         self.fixed_tasks.insert(task_type, handle);
         Ok(())
@@ -48,23 +100,53 @@ impl FixedTaskScheduler for SimpleFixedTaskScheduler {
         }
     }
 
-    async fn abort(&mut self, task_type: &TaskType) -> SchedulerResult<()> {
+    fn abort(&mut self, task_type: &TaskType) -> SchedulerResult<AbortedTask> {
         if !self.fixed_tasks.contains_key(task_type) {
             return Err(SchedulerError::TaskNotFound);
         }
         let handle = self.fixed_tasks.remove(task_type).unwrap();
-        handle.abort();
-        Ok(())
+        self.task_registry.mark_stopped(*task_type);
+
+        Ok(AbortedTask {
+            handle,
+            cancellation_token: self.cancellation_tokens.remove(task_type),
+        })
+    }
+
+    fn get_cancellation_token(&mut self, task_type: &TaskType) -> CancellationToken {
+        self.cancellation_tokens
+            .entry(*task_type)
+            .or_default()
+            .clone()
     }
 
     fn get_tasks(&self) -> Vec<&TaskType> {
         self.fixed_tasks.keys().collect::<Vec<&TaskType>>()
     }
+
+    fn get_task_registry(&self) -> TaskRegistry {
+        self.task_registry.clone()
+    }
+
+    fn get_task_statuses(&self) -> Vec<(TaskType, TaskStatus)> {
+        self.task_registry.get_statuses()
+    }
+}
+
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_owned()
+    }
 }
 
 #[cfg(test)]
 pub mod tests {
 
+    use crate::node::scheduler::registry::TaskState;
     use tokio::time;
 
     #[tokio::test]
@@ -88,4 +170,101 @@ pub mod tests {
 
         println!("main finished");
     }
+
+    #[tokio::test]
+    async fn test_abort_cancels_the_task_gracefully() {
+        use super::SimpleFixedTaskScheduler;
+        use crate::node::scheduler::{FixedTaskScheduler, TaskScheduler};
+        use arpa_node_core::{ListenerType, TaskType};
+        use std::sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        };
+
+        let mut scheduler = SimpleFixedTaskScheduler::new();
+        let task_type = TaskType::Listener(ListenerType::Block);
+
+        let cancellation_token = scheduler.get_cancellation_token(&task_type);
+        let stopped = Arc::new(AtomicBool::new(false));
+        let task_stopped = stopped.clone();
+
+        scheduler
+            .add_task(task_type, async move {
+                cancellation_token.cancelled().await;
+                task_stopped.store(true, Ordering::SeqCst);
+            })
+            .unwrap();
+
+        scheduler.abort(&task_type).unwrap().stop().await;
+
+        assert!(stopped.load(Ordering::SeqCst));
+        assert!(scheduler.get_tasks().is_empty());
+        assert_eq!(
+            scheduler
+                .get_task_registry()
+                .get_status(&task_type)
+                .unwrap()
+                .state,
+            TaskState::Stopped
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_is_released_while_the_aborted_task_stops() {
+        use super::SimpleFixedTaskScheduler;
+        use crate::node::scheduler::{FixedTaskScheduler, TaskScheduler};
+        use arpa_node_core::{ListenerType, TaskType};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+
+        let scheduler = Arc::new(RwLock::new(SimpleFixedTaskScheduler::new()));
+        let task_type = TaskType::Listener(ListenerType::Block);
+
+        let cancellation_token = scheduler.write().await.get_cancellation_token(&task_type);
+        scheduler
+            .write()
+            .await
+            .add_task(task_type, async move {
+                cancellation_token.cancelled().await;
+                // the callback in flight takes a while to finish
+                time::sleep(time::Duration::from_millis(500)).await;
+            })
+            .unwrap();
+
+        let aborted_task = scheduler.write().await.abort(&task_type).unwrap();
+        let stopping = tokio::spawn(aborted_task.stop());
+
+        // the scheduler can still be managed while the aborted task is given its grace period
+        scheduler
+            .write()
+            .await
+            .add_task(TaskType::Listener(ListenerType::PreGrouping), async {})
+            .unwrap();
+        assert!(!stopping.is_finished());
+
+        stopping.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_panicked_task_is_marked_failed() {
+        use super::SimpleFixedTaskScheduler;
+        use crate::node::scheduler::{FixedTaskScheduler, TaskScheduler};
+        use arpa_node_core::{RpcServerType, TaskType};
+
+        let mut scheduler = SimpleFixedTaskScheduler::new();
+        let task_type = TaskType::RpcServer(RpcServerType::Metrics);
+
+        scheduler
+            .add_task(task_type, async move {
+                panic!("address in use");
+            })
+            .unwrap();
+
+        time::sleep(time::Duration::from_millis(100)).await;
+
+        let statuses = scheduler.get_task_statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].1.state, TaskState::Failed);
+        assert_eq!(statuses[0].1.last_error.as_deref(), Some("address in use"));
+    }
 }
@@ -1,8 +1,12 @@
 pub mod dynamic;
 pub mod fixed;
+pub mod registry;
 use arpa_node_core::{SchedulerResult, TaskType};
 use async_trait::async_trait;
+use fixed::AbortedTask;
 use futures::Future;
+use registry::{TaskRegistry, TaskStatus};
+use tokio_util::sync::CancellationToken;
 
 pub trait TaskScheduler {
     fn add_task<T>(&mut self, task_type: TaskType, future: T) -> SchedulerResult<()>
@@ -15,9 +19,19 @@ pub trait TaskScheduler {
 pub trait FixedTaskScheduler: TaskScheduler {
     async fn join(mut self);
 
-    async fn abort(&mut self, task_type: &TaskType) -> SchedulerResult<()>;
+    /// Takes the task out of the scheduler. The returned task is to be stopped without
+    /// holding the scheduler, as stopping it can take the whole grace period.
+    fn abort(&mut self, task_type: &TaskType) -> SchedulerResult<AbortedTask>;
+
+    /// Returns the cancellation token of the task, which is cancelled when the task is aborted.
+    fn get_cancellation_token(&mut self, task_type: &TaskType) -> CancellationToken;
 
     fn get_tasks(&self) -> Vec<&TaskType>;
+
+    /// Returns the registry the tasks report their state to.
+    fn get_task_registry(&self) -> TaskRegistry;
+
+    fn get_task_statuses(&self) -> Vec<(TaskType, TaskStatus)>;
 }
 
 pub trait DynamicTaskScheduler: TaskScheduler {
@@ -0,0 +1,173 @@
+use arpa_node_core::TaskType;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    Restarting,
+    Failed,
+    Stopped,
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskStatus {
+    pub state: TaskState,
+    pub restarts: usize,
+    pub last_error: Option<String>,
+}
+
+impl TaskStatus {
+    fn running() -> Self {
+        TaskStatus {
+            state: TaskState::Running,
+            restarts: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// Keeps the state of every fixed task, shared between the scheduler and the tasks themselves.
+#[derive(Debug, Clone, Default)]
+pub struct TaskRegistry {
+    statuses: Arc<Mutex<HashMap<TaskType, TaskStatus>>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        TaskRegistry::default()
+    }
+
+    /// Starts tracking the task from scratch.
+    pub fn mark_started(&self, task_type: TaskType) {
+        self.statuses
+            .lock()
+            .unwrap()
+            .insert(task_type, TaskStatus::running());
+    }
+
+    /// Marks the task as running again, keeping its restart count and last error.
+    pub fn mark_running(&self, task_type: TaskType) {
+        self.update(task_type, |status| status.state = TaskState::Running);
+    }
+
+    pub fn record_restart(&self, task_type: TaskType, error: String) {
+        self.update(task_type, |status| {
+            status.state = TaskState::Restarting;
+            status.restarts += 1;
+            status.last_error = Some(error);
+        });
+    }
+
+    pub fn mark_failed(&self, task_type: TaskType, error: String) {
+        self.update(task_type, |status| {
+            status.state = TaskState::Failed;
+            status.last_error = Some(error);
+        });
+    }
+
+    /// A failed task stays failed so that the reason it stopped is still reported.
+    pub fn mark_stopped(&self, task_type: TaskType) {
+        self.update(task_type, |status| {
+            if status.state != TaskState::Failed {
+                status.state = TaskState::Stopped;
+            }
+        });
+    }
+
+    pub fn get_status(&self, task_type: &TaskType) -> Option<TaskStatus> {
+        self.statuses.lock().unwrap().get(task_type).cloned()
+    }
+
+    pub fn get_statuses(&self) -> Vec<(TaskType, TaskStatus)> {
+        self.statuses
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(task_type, status)| (*task_type, status.clone()))
+            .collect()
+    }
+
+    /// Returns a handle that reports on behalf of a single task.
+    pub fn reporter(&self, task_type: TaskType) -> TaskReporter {
+        TaskReporter {
+            task_type,
+            registry: self.clone(),
+        }
+    }
+
+    fn update<F: FnOnce(&mut TaskStatus)>(&self, task_type: TaskType, f: F) {
+        f(self
+            .statuses
+            .lock()
+            .unwrap()
+            .entry(task_type)
+            .or_insert_with(TaskStatus::running));
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaskReporter {
+    task_type: TaskType,
+    registry: TaskRegistry,
+}
+
+impl TaskReporter {
+    pub fn report_running(&self) {
+        self.registry.mark_running(self.task_type);
+    }
+
+    pub fn report_restart(&self, error: String) {
+        self.registry.record_restart(self.task_type, error);
+    }
+
+    pub fn report_failure(&self, error: String) {
+        self.registry.mark_failed(self.task_type, error);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::{TaskRegistry, TaskState};
+    use arpa_node_core::{ListenerType, TaskType};
+
+    #[test]
+    fn test_task_registry() {
+        let registry = TaskRegistry::new();
+        let task_type = TaskType::Listener(ListenerType::Block);
+        let reporter = registry.reporter(task_type);
+
+        registry.mark_started(task_type);
+        reporter.report_restart("connection reset".to_owned());
+        reporter.report_restart("connection refused".to_owned());
+
+        let status = registry.get_status(&task_type).unwrap();
+        assert_eq!(status.state, TaskState::Restarting);
+        assert_eq!(status.restarts, 2);
+        assert_eq!(status.last_error.as_deref(), Some("connection refused"));
+
+        reporter.report_running();
+        assert_eq!(
+            registry.get_status(&task_type).unwrap().state,
+            TaskState::Running
+        );
+
+        reporter.report_failure("address in use".to_owned());
+        registry.mark_stopped(task_type);
+        let status = registry.get_status(&task_type).unwrap();
+        assert_eq!(status.state, TaskState::Failed);
+        assert_eq!(status.last_error.as_deref(), Some("address in use"));
+
+        registry.mark_started(task_type);
+        assert_eq!(registry.get_status(&task_type).unwrap().restarts, 0);
+    }
+}
@@ -1,20 +1,24 @@
 use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
 use crate::node::{
     error::NodeResult,
-    event::{new_block::NewBlock, types::Topic},
-    queue::{event_queue::EventQueue, EventSubscriber},
+    event::{new_block::NewBlock, types::Topic, Event},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
 };
 use arpa_node_dal::BlockInfoUpdater;
 use async_trait::async_trait;
 use log::debug;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tokio::sync::{watch, RwLock};
 
 #[derive(Debug)]
 pub struct BlockSubscriber<B: BlockInfoUpdater> {
     pub chain_id: usize,
     block_cache: Arc<RwLock<B>>,
     eq: Arc<RwLock<EventQueue>>,
+    last_sequence: AtomicU64,
 }
 
 impl<B: BlockInfoUpdater> BlockSubscriber<B> {
@@ -23,10 +27,23 @@ impl<B: BlockInfoUpdater> BlockSubscriber<B> {
             chain_id,
             block_cache,
             eq,
+            last_sequence: AtomicU64::new(0),
         }
     }
 }
 
+/// Records the sequence of the event, returns false if a later event has been handled already.
+fn is_latest(last_sequence: &AtomicU64, payload: &dyn DebuggableEvent) -> bool {
+    let sequence = payload.sequence();
+
+    // events published without the event queue carry no sequence
+    if sequence == 0 {
+        return true;
+    }
+
+    last_sequence.fetch_max(sequence, Ordering::SeqCst) < sequence
+}
+
 #[async_trait]
 impl<B: BlockInfoUpdater + std::fmt::Debug + Sync + Send + 'static> Subscriber
     for BlockSubscriber<B>
@@ -36,6 +53,15 @@ impl<B: BlockInfoUpdater + std::fmt::Debug + Sync + Send + 'static> Subscriber
 
         let &NewBlock { block_height, .. } = payload.as_any().downcast_ref::<NewBlock>().unwrap();
 
+        if !is_latest(&self.last_sequence, payload) {
+            debug!(
+                "drop stale NewBlock event of block height {} with sequence {}",
+                block_height,
+                payload.sequence()
+            );
+            return Ok(());
+        }
+
         self.block_cache
             .write()
             .await
@@ -44,7 +70,7 @@ impl<B: BlockInfoUpdater + std::fmt::Debug + Sync + Send + 'static> Subscriber
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let chain_id = self.chain_id;
@@ -53,7 +79,7 @@ impl<B: BlockInfoUpdater + std::fmt::Debug + Sync + Send + 'static> Subscriber
 
         eq.write()
             .await
-            .subscribe(Topic::NewBlock(chain_id), subscriber);
+            .subscribe(Topic::NewBlock(chain_id), subscriber)
     }
 }
 
@@ -61,3 +87,59 @@ impl<B: BlockInfoUpdater + std::fmt::Debug + Sync + Send + 'static> DebuggableSu
     for BlockSubscriber<B>
 {
 }
+
+/// Forwards the latest block height to the DKG tasks which advance phases on new blocks.
+#[derive(Debug)]
+pub struct BlockHeightSubscriber {
+    pub chain_id: usize,
+    block_height_sender: watch::Sender<usize>,
+    eq: Arc<RwLock<EventQueue>>,
+    last_sequence: AtomicU64,
+}
+
+impl BlockHeightSubscriber {
+    pub fn new(
+        chain_id: usize,
+        block_height_sender: watch::Sender<usize>,
+        eq: Arc<RwLock<EventQueue>>,
+    ) -> Self {
+        BlockHeightSubscriber {
+            chain_id,
+            block_height_sender,
+            eq,
+            last_sequence: AtomicU64::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl Subscriber for BlockHeightSubscriber {
+    async fn notify(&self, topic: Topic, payload: &(dyn DebuggableEvent)) -> NodeResult<()> {
+        debug!("{:?}", topic);
+
+        let &NewBlock { block_height, .. } = payload.as_any().downcast_ref::<NewBlock>().unwrap();
+
+        if !is_latest(&self.last_sequence, payload) {
+            return Ok(());
+        }
+
+        // there may be no DKG task waiting for now, so keep the value regardless of receivers
+        self.block_height_sender.send_replace(block_height);
+
+        Ok(())
+    }
+
+    async fn subscribe(self) -> SubscriberId {
+        let eq = self.eq.clone();
+
+        let chain_id = self.chain_id;
+
+        let subscriber = Box::new(self);
+
+        eq.write()
+            .await
+            .subscribe(Topic::NewBlock(chain_id), subscriber)
+    }
+}
+
+impl DebuggableSubscriber for BlockHeightSubscriber {}
@@ -0,0 +1,59 @@
+use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
+use crate::node::{
+    error::NodeResult,
+    event::{dkg_phase::DKGPhase, types::Topic},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
+};
+use arpa_node_core::metrics::{DKG_PHASE, DKG_STATUS};
+use async_trait::async_trait;
+use log::{debug, info};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Exposes DKG phase and status changes through logs and metrics,
+/// so that an orchestrator can tell when to expect `commit_dkg` on-chain.
+#[derive(Debug)]
+pub struct DKGPhaseSubscriber {
+    eq: Arc<RwLock<EventQueue>>,
+}
+
+impl DKGPhaseSubscriber {
+    pub fn new(eq: Arc<RwLock<EventQueue>>) -> Self {
+        DKGPhaseSubscriber { eq }
+    }
+}
+
+#[async_trait]
+impl Subscriber for DKGPhaseSubscriber {
+    async fn notify(&self, topic: Topic, payload: &(dyn DebuggableEvent)) -> NodeResult<()> {
+        debug!("{:?}", topic);
+
+        let &DKGPhase {
+            group_index,
+            group_epoch,
+            phase,
+            dkg_status,
+        } = payload.as_any().downcast_ref::<DKGPhase>().unwrap();
+
+        info!(
+            "DKG of group {} epoch {} is in phase {} with status {:?}",
+            group_index, group_epoch, phase, dkg_status
+        );
+
+        DKG_PHASE.set(phase as i64);
+
+        DKG_STATUS.set(dkg_status.to_usize() as i64);
+
+        Ok(())
+    }
+
+    async fn subscribe(self) -> SubscriberId {
+        let eq = self.eq.clone();
+
+        let subscriber = Box::new(self);
+
+        eq.write().await.subscribe(Topic::DKGPhase, subscriber)
+    }
+}
+
+impl DebuggableSubscriber for DKGPhaseSubscriber {}
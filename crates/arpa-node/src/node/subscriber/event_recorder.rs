@@ -0,0 +1,152 @@
+use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
+use crate::node::{
+    error::NodeResult,
+    event::{
+        new_block::NewBlock, new_randomness_task::NewRandomnessTask,
+        ready_to_fulfill_randomness_task::ReadyToFulfillRandomnessTask,
+        ready_to_handle_randomness_task::ReadyToHandleRandomnessTask, types::Topic,
+    },
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
+};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+
+/// An event captured by an `EventRecorder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedEvent {
+    pub topic: Topic,
+    // the height of the last NewBlock recorded before the event, 0 if there is none
+    pub block_height: usize,
+    // the request ids of the randomness tasks carried by the event
+    pub request_ids: Vec<Vec<u8>>,
+}
+
+/// The events captured by the recorders sharing it, in the order they were delivered.
+#[derive(Debug, Clone, Default)]
+pub struct RecordedEvents {
+    events: Arc<Mutex<Vec<RecordedEvent>>>,
+}
+
+impl RecordedEvents {
+    pub fn new() -> Self {
+        RecordedEvents::default()
+    }
+
+    /// Subscribes a recorder to each of the topics, so that the events published on them from now
+    /// on are captured.
+    pub async fn record(&self, eq: Arc<RwLock<EventQueue>>, topics: &[Topic]) {
+        for &topic in topics {
+            EventRecorder::new(topic, self.clone(), eq.clone())
+                .subscribe()
+                .await;
+        }
+    }
+
+    pub fn get_events(&self) -> Vec<RecordedEvent> {
+        self.events.lock().unwrap().clone()
+    }
+
+    pub fn get_topics(&self) -> Vec<Topic> {
+        self.get_events().iter().map(|event| event.topic).collect()
+    }
+
+    /// Returns the block height at which an event of the topic first carried the request,
+    /// or None if it never did.
+    pub fn find_block_height(&self, topic: Topic, request_id: &[u8]) -> Option<usize> {
+        self.get_events()
+            .into_iter()
+            .find(|event| {
+                event.topic == topic && event.request_ids.iter().any(|id| id == request_id)
+            })
+            .map(|event| event.block_height)
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+
+    fn push(&self, topic: Topic, payload: &dyn DebuggableEvent) {
+        let mut events = self.events.lock().unwrap();
+
+        let last_block_height = events
+            .iter()
+            .rev()
+            .find(|event| matches!(event.topic, Topic::NewBlock(_)))
+            .map_or(0, |event| event.block_height);
+
+        let payload = payload.as_any();
+
+        let (block_height, request_ids) =
+            if let Some(new_block) = payload.downcast_ref::<NewBlock>() {
+                (new_block.block_height, vec![])
+            } else if let Some(event) = payload.downcast_ref::<NewRandomnessTask>() {
+                (
+                    last_block_height,
+                    vec![event.randomness_task.request_id.clone()],
+                )
+            } else if let Some(event) = payload.downcast_ref::<ReadyToHandleRandomnessTask>() {
+                (
+                    last_block_height,
+                    event
+                        .tasks
+                        .iter()
+                        .map(|task| task.request_id.clone())
+                        .collect(),
+                )
+            } else if let Some(event) = payload.downcast_ref::<ReadyToFulfillRandomnessTask>() {
+                (
+                    last_block_height,
+                    event
+                        .tasks
+                        .iter()
+                        .map(|task| task.randomness_task.request_id.clone())
+                        .collect(),
+                )
+            } else {
+                (last_block_height, vec![])
+            };
+
+        events.push(RecordedEvent {
+            topic,
+            block_height,
+            request_ids,
+        });
+    }
+}
+
+/// Captures every event published on its topic, so that a test can assert which events fired
+/// and at which block, e.g. that a task became ready only once its exclusive window had passed.
+#[derive(Debug)]
+pub struct EventRecorder {
+    topic: Topic,
+    events: RecordedEvents,
+    eq: Arc<RwLock<EventQueue>>,
+}
+
+impl EventRecorder {
+    pub fn new(topic: Topic, events: RecordedEvents, eq: Arc<RwLock<EventQueue>>) -> Self {
+        EventRecorder { topic, events, eq }
+    }
+}
+
+#[async_trait]
+impl Subscriber for EventRecorder {
+    async fn notify(&self, topic: Topic, payload: &(dyn DebuggableEvent)) -> NodeResult<()> {
+        self.events.push(topic, payload);
+
+        Ok(())
+    }
+
+    async fn subscribe(self) -> SubscriberId {
+        let eq = self.eq.clone();
+
+        let topic = self.topic;
+
+        let subscriber = Box::new(self);
+
+        eq.write().await.subscribe(topic, subscriber)
+    }
+}
+
+impl DebuggableSubscriber for EventRecorder {}
@@ -1,14 +1,17 @@
 use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
 use crate::node::{
-    algorithm::dkg::{AllPhasesDKGCore, DKGCore},
+    algorithm::dkg::{
+        fetch_expected_disqualified_indices, AllPhasesDKGCore, DKGCore, DKGPhasePublisher,
+    },
     error::NodeResult,
     event::{run_dkg::RunDKG, types::Topic},
-    queue::{event_queue::EventQueue, EventSubscriber},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
     scheduler::{dynamic::SimpleDynamicTaskScheduler, DynamicTaskScheduler},
 };
 use arpa_node_contract_client::{
     controller::{ControllerClientBuilder, ControllerTransactions},
     coordinator::CoordinatorClientBuilder,
+    ethers::abi_version::check_coordinator_abi,
 };
 use arpa_node_core::{ChainIdentity, DKGStatus, DKGTask};
 use arpa_node_dal::{
@@ -16,11 +19,11 @@ use arpa_node_dal::{
 };
 use async_trait::async_trait;
 use core::fmt::Debug;
-use log::{debug, error};
+use log::{debug, error, warn};
 use rand::{prelude::ThreadRng, RngCore};
-use std::{marker::PhantomData, sync::Arc};
-use threshold_bls::{curve::bn254::Scalar, group::PairingCurve};
-use tokio::sync::RwLock;
+use std::{collections::BTreeSet, marker::PhantomData, sync::Arc};
+use threshold_bls::group::{CurveFrom, PairingCurve};
+use tokio::sync::{watch, RwLock};
 
 #[derive(Debug)]
 pub struct InGroupingSubscriber<
@@ -36,6 +39,7 @@ pub struct InGroupingSubscriber<
     ts: Arc<RwLock<SimpleDynamicTaskScheduler>>,
     c: PhantomData<C>,
     dkg_wait_for_phase_interval_millis: u64,
+    block_height_receiver: Option<watch::Receiver<usize>>,
 }
 
 impl<
@@ -52,6 +56,7 @@ impl<
         eq: Arc<RwLock<EventQueue>>,
         ts: Arc<RwLock<SimpleDynamicTaskScheduler>>,
         dkg_wait_for_phase_interval_millis: u64,
+        block_height_receiver: Option<watch::Receiver<usize>>,
     ) -> Self {
         InGroupingSubscriber {
             main_chain_identity,
@@ -61,6 +66,7 @@ impl<
             ts,
             c: PhantomData,
             dkg_wait_for_phase_interval_millis,
+            block_height_receiver,
         }
     }
 }
@@ -79,6 +85,8 @@ pub struct AllInOneDKGHandler<
     group_cache: Arc<RwLock<G>>,
     c: PhantomData<C>,
     dkg_wait_for_phase_interval_millis: u64,
+    block_height_receiver: Option<watch::Receiver<usize>>,
+    phase_publisher: DKGPhasePublisher,
 }
 
 impl<
@@ -96,6 +104,8 @@ impl<
         node_cache: Arc<RwLock<N>>,
         group_cache: Arc<RwLock<G>>,
         dkg_wait_for_phase_interval_millis: u64,
+        block_height_receiver: Option<watch::Receiver<usize>>,
+        phase_publisher: DKGPhasePublisher,
     ) -> Self {
         AllInOneDKGHandler {
             rng,
@@ -104,6 +114,8 @@ impl<
             group_cache,
             c: PhantomData,
             dkg_wait_for_phase_interval_millis,
+            block_height_receiver,
+            phase_publisher,
         }
     }
 }
@@ -146,20 +158,29 @@ impl<
 
         let dkg_private_key = self.node_cache.read().await.get_dkg_private_key()?.clone();
 
-        let dkg_private_key: Scalar = bincode::deserialize(&bincode::serialize(&dkg_private_key)?)?;
-
         let task_group_index = task.group_index;
 
         let task_epoch = task.epoch;
 
+        // a coordinator incompatible with the node would fail the DKG halfway
+        check_coordinator_abi(
+            &*self.main_chain_identity.read().await,
+            task.coordinator_address,
+        )
+        .await?;
+
         let coordinator_client = self
             .main_chain_identity
             .read()
             .await
-            .build_coordinator_client(task.coordinator_address);
+            .build_coordinator_client::<CurveFrom<C::Scalar, C::G2>>(task.coordinator_address);
 
-        let mut dkg_core =
-            AllPhasesDKGCore::new(coordinator_client, self.dkg_wait_for_phase_interval_millis);
+        let mut dkg_core = AllPhasesDKGCore::new(
+            coordinator_client,
+            self.dkg_wait_for_phase_interval_millis,
+            self.block_height_receiver.clone(),
+            self.phase_publisher.clone(),
+        );
 
         let output = dkg_core
             .run_dkg(dkg_private_key, node_rpc_endpoint, self.rng)
@@ -172,6 +193,50 @@ impl<
             .save_output(task_group_index, task_epoch, output)
             .await?;
 
+        // the cross-check is advisory, a failure to fetch the board should not block the commit
+        let coordinator_client = self
+            .main_chain_identity
+            .read()
+            .await
+            .build_coordinator_client::<CurveFrom<C::Scalar, C::G2>>(task.coordinator_address);
+
+        match fetch_expected_disqualified_indices::<CurveFrom<C::Scalar, C::G2>>(
+            &coordinator_client,
+            task.members.len(),
+        )
+        .await
+        {
+            Ok(expected_indices) => {
+                // every member index is started from 0
+                let expected_disqualified_nodes = expected_indices
+                    .iter()
+                    .filter_map(|idx| task.members.get(*idx as usize).copied())
+                    .collect::<BTreeSet<_>>();
+                let computed_disqualified_nodes =
+                    disqualified_nodes.iter().copied().collect::<BTreeSet<_>>();
+
+                if computed_disqualified_nodes != expected_disqualified_nodes {
+                    warn!(
+                        "disqualified nodes of group {} epoch {} diverge from the coordinator, computed only: {:?}, expected only: {:?}",
+                        task_group_index,
+                        task_epoch,
+                        computed_disqualified_nodes
+                            .difference(&expected_disqualified_nodes)
+                            .collect::<Vec<_>>(),
+                        expected_disqualified_nodes
+                            .difference(&computed_disqualified_nodes)
+                            .collect::<Vec<_>>()
+                    );
+                }
+            }
+            Err(e) => {
+                warn!(
+                    "failed to cross-check disqualified nodes of group {} epoch {}: {:?}",
+                    task_group_index, task_epoch, e
+                );
+            }
+        }
+
         controller_client
             .commit_dkg(
                 task_group_index,
@@ -230,35 +295,58 @@ impl<
 
         let task_epoch = task.epoch;
 
+        let phase_publisher = DKGPhasePublisher::new(task_group_index, task_epoch, self.eq.clone());
+
+        let phase_publisher_for_shutdown_signal = phase_publisher.clone();
+
         let mut handler = AllInOneDKGHandler::new(
             RNG_FN,
             chain_identity,
             self.node_cache.clone(),
             self.group_cache.clone(),
             self.dkg_wait_for_phase_interval_millis,
+            self.block_height_receiver.clone(),
+            phase_publisher.clone(),
         );
 
         self.ts.write().await.add_task_with_shutdown_signal(
             async move {
                 if let Err(e) = handler.handle(task).await {
                     error!("{:?}", e);
-                } else if let Err(e) = group_cache_for_handler
-                    .write()
-                    .await
-                    .update_dkg_status(task_group_index, task_epoch, DKGStatus::CommitSuccess)
-                    .await
-                {
-                    error!("{:?}", e);
+                } else {
+                    match group_cache_for_handler
+                        .write()
+                        .await
+                        .update_dkg_status(task_group_index, task_epoch, DKGStatus::CommitSuccess)
+                        .await
+                    {
+                        Ok(_) => {
+                            phase_publisher
+                                .update_status(DKGStatus::CommitSuccess)
+                                .await
+                        }
+                        Err(e) => error!("{:?}", e),
+                    }
                 }
             },
             move || {
                 let group_cache = group_cache_for_handler_shutdown_signal.clone();
+                let phase_publisher = phase_publisher_for_shutdown_signal.clone();
                 async move {
                     let cache_index = group_cache.clone().read().await.get_index().unwrap_or(0);
 
                     let cache_epoch = group_cache.clone().read().await.get_epoch().unwrap_or(0);
 
-                    cache_index != task_group_index || cache_epoch != task_epoch
+                    let timeout = matches!(
+                        group_cache.clone().read().await.get_dkg_status(),
+                        Ok(DKGStatus::Timeout)
+                    );
+
+                    if timeout {
+                        phase_publisher.update_status(DKGStatus::Timeout).await;
+                    }
+
+                    cache_index != task_group_index || cache_epoch != task_epoch || timeout
                     //NodeError::GroupIndexObsolete(cache_index)
                     //NodeError::GroupEpochObsolete(cache_epoch)
                 }
@@ -269,12 +357,12 @@ impl<
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let subscriber = Box::new(self);
 
-        eq.write().await.subscribe(Topic::RunDKG, subscriber);
+        eq.write().await.subscribe(Topic::RunDKG, subscriber)
     }
 }
 
@@ -1,4 +1,7 @@
 pub mod block;
+pub mod dkg_phase;
+#[cfg(any(test, feature = "testing"))]
+pub mod event_recorder;
 pub mod in_grouping;
 pub mod post_grouping;
 pub mod post_success_grouping;
@@ -9,6 +12,7 @@ pub mod ready_to_handle_randomness_task;
 use crate::node::{
     error::NodeResult,
     event::{types::Topic, Event},
+    queue::SubscriberId,
 };
 use async_trait::async_trait;
 
@@ -20,5 +24,5 @@ pub trait DebuggableSubscriber: Subscriber + std::fmt::Debug + Send + Sync {}
 pub trait Subscriber {
     async fn notify(&self, topic: Topic, payload: &dyn DebuggableEvent) -> NodeResult<()>;
 
-    async fn subscribe(self);
+    async fn subscribe(self) -> SubscriberId;
 }
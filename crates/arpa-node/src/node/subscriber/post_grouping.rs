@@ -2,14 +2,12 @@ use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
 use crate::node::{
     error::NodeResult,
     event::{dkg_post_process::DKGPostProcess, types::Topic},
-    queue::{event_queue::EventQueue, EventSubscriber},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
     scheduler::{dynamic::SimpleDynamicTaskScheduler, TaskScheduler},
 };
-use arpa_node_contract_client::controller::{
-    ControllerClientBuilder, ControllerTransactions, ControllerViews,
-};
-use arpa_node_core::{ChainIdentity, DKGStatus, SubscriberType, TaskType, PLACEHOLDER_ADDRESS};
-use arpa_node_dal::GroupInfoUpdater;
+use arpa_node_contract_client::controller::{ControllerClientBuilder, ControllerTransactions};
+use arpa_node_core::{ChainIdentity, DKGStatus, SubscriberType, TaskType};
+use arpa_node_dal::{GroupInfoFetcher, GroupInfoUpdater};
 use arpa_node_log::*;
 use async_trait::async_trait;
 use log::{debug, error, info};
@@ -20,7 +18,7 @@ use tokio::sync::RwLock;
 #[derive(Debug)]
 pub struct PostGroupingSubscriber<
     I: ChainIdentity + ControllerClientBuilder<C>,
-    G: GroupInfoUpdater<C>,
+    G: GroupInfoFetcher<C> + GroupInfoUpdater<C>,
     C: PairingCurve,
 > {
     main_chain_identity: Arc<RwLock<I>>,
@@ -30,8 +28,11 @@ pub struct PostGroupingSubscriber<
     c: PhantomData<C>,
 }
 
-impl<I: ChainIdentity + ControllerClientBuilder<C>, G: GroupInfoUpdater<C>, C: PairingCurve>
-    PostGroupingSubscriber<I, G, C>
+impl<
+        I: ChainIdentity + ControllerClientBuilder<C>,
+        G: GroupInfoFetcher<C> + GroupInfoUpdater<C>,
+        C: PairingCurve,
+    > PostGroupingSubscriber<I, G, C>
 {
     pub fn new(
         main_chain_identity: Arc<RwLock<I>>,
@@ -56,7 +57,7 @@ pub trait DKGPostProcessHandler {
 
 pub struct GeneralDKGPostProcessHandler<
     I: ChainIdentity + ControllerClientBuilder<C>,
-    G: GroupInfoUpdater<C>,
+    G: GroupInfoFetcher<C> + GroupInfoUpdater<C>,
     C: PairingCurve,
 > {
     main_chain_identity: Arc<RwLock<I>>,
@@ -67,22 +68,28 @@ pub struct GeneralDKGPostProcessHandler<
 #[async_trait]
 impl<
         I: ChainIdentity + ControllerClientBuilder<C> + Sync + Send,
-        G: GroupInfoUpdater<C> + Sync + Send,
+        G: GroupInfoFetcher<C> + GroupInfoUpdater<C> + Sync + Send,
         C: PairingCurve + Sync + Send,
     > DKGPostProcessHandler for GeneralDKGPostProcessHandler<I, G, C>
 {
     #[log_function]
     async fn handle(&self, group_index: usize, group_epoch: usize) -> NodeResult<()> {
+        // a DKG still in phase by now won't finish, so stop it and wait for the next grouping
+        let dkg_status = match self.group_cache.read().await.get_dkg_status()? {
+            DKGStatus::InPhase => DKGStatus::Timeout,
+            _ => DKGStatus::None,
+        };
+
         if self
             .group_cache
             .write()
             .await
-            .update_dkg_status(group_index, group_epoch, DKGStatus::None)
+            .update_dkg_status(group_index, group_epoch, dkg_status)
             .await?
         {
             info!(
-                "DKG status updated to None for group {} epoch {}",
-                group_index, group_epoch
+                "DKG status updated to {:?} for group {} epoch {}",
+                dkg_status, group_index, group_epoch
             );
 
             let client = self
@@ -91,9 +98,7 @@ impl<
                 .await
                 .build_controller_client();
 
-            if PLACEHOLDER_ADDRESS != client.get_coordinator(group_index).await? {
-                client.post_process_dkg(group_index, group_epoch).await?;
-            };
+            client.post_process_dkg(group_index, group_epoch).await?;
         }
 
         Ok(())
@@ -103,7 +108,7 @@ impl<
 #[async_trait]
 impl<
         I: ChainIdentity + ControllerClientBuilder<C> + std::fmt::Debug + Sync + Send + 'static,
-        G: GroupInfoUpdater<C> + std::fmt::Debug + Sync + Send + 'static,
+        G: GroupInfoFetcher<C> + GroupInfoUpdater<C> + std::fmt::Debug + Sync + Send + 'static,
         C: PairingCurve + std::fmt::Debug + Sync + Send + 'static,
     > Subscriber for PostGroupingSubscriber<I, G, C>
 {
@@ -136,20 +141,20 @@ impl<
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let subscriber = Box::new(self);
 
         eq.write()
             .await
-            .subscribe(Topic::DKGPostProcess, subscriber);
+            .subscribe(Topic::DKGPostProcess, subscriber)
     }
 }
 
 impl<
         I: ChainIdentity + ControllerClientBuilder<C> + std::fmt::Debug + Sync + Send + 'static,
-        G: GroupInfoUpdater<C> + std::fmt::Debug + Sync + Send + 'static,
+        G: GroupInfoFetcher<C> + GroupInfoUpdater<C> + std::fmt::Debug + Sync + Send + 'static,
         C: PairingCurve + std::fmt::Debug + Sync + Send + 'static,
     > DebuggableSubscriber for PostGroupingSubscriber<I, G, C>
 {
@@ -2,9 +2,9 @@ use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
 use crate::node::{
     error::NodeResult,
     event::{dkg_success::DKGSuccess, types::Topic},
-    queue::{event_queue::EventQueue, EventSubscriber},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
 };
-use arpa_node_core::DKGStatus;
+use arpa_node_core::{metrics::GROUP_EPOCH, DKGStatus};
 use arpa_node_dal::GroupInfoUpdater;
 use async_trait::async_trait;
 use log::{debug, info};
@@ -62,6 +62,8 @@ impl<
                 .save_committers(group.index, group.epoch, group.committers)
                 .await?;
 
+            GROUP_EPOCH.set(group.epoch as i64);
+
             info!(
                 "Group index:{} epoch:{} is available, committers saved.",
                 group.index, group.epoch
@@ -71,12 +73,12 @@ impl<
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let subscriber = Box::new(self);
 
-        eq.write().await.subscribe(Topic::DKGSuccess, subscriber);
+        eq.write().await.subscribe(Topic::DKGSuccess, subscriber)
     }
 }
 
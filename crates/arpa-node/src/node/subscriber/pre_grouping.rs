@@ -2,7 +2,7 @@ use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
 use crate::node::{
     error::NodeResult,
     event::{new_dkg_task::NewDKGTask, run_dkg::RunDKG, types::Topic},
-    queue::{event_queue::EventQueue, EventPublisher, EventSubscriber},
+    queue::{event_queue::EventQueue, EventPublisher, EventSubscriber, SubscriberId},
 };
 use arpa_node_core::DKGStatus;
 use arpa_node_dal::{ContextInfoUpdater, GroupInfoFetcher, GroupInfoUpdater};
@@ -116,12 +116,12 @@ impl<
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let subscriber = Box::new(self);
 
-        eq.write().await.subscribe(Topic::NewDKGTask, subscriber);
+        eq.write().await.subscribe(Topic::NewDKGTask, subscriber)
     }
 }
 
@@ -3,17 +3,26 @@ use crate::node::{
     algorithm::bls::{BLSCore, SimpleBLSCore},
     error::{NodeError, NodeResult},
     event::{ready_to_fulfill_randomness_task::ReadyToFulfillRandomnessTask, types::Topic},
-    queue::{event_queue::EventQueue, EventSubscriber},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
     scheduler::{dynamic::SimpleDynamicTaskScheduler, TaskScheduler},
+    telemetry::in_request_span,
+};
+use arpa_node_contract_client::{
+    adapter::{AdapterClientBuilder, AdapterTransactions, AdapterViews},
+    TransactionSentHook,
+};
+use arpa_node_core::{
+    derive_randomness, estimate_fulfill_randomness_gas,
+    metrics::{FULFILLMENTS_FAILED, FULFILLMENTS_SUBMITTED, FULFILLMENTS_SUCCEEDED},
+    min_fulfillment_payment, ChainIdentity, PartialSignature, RandomnessTask, SubscriberType,
+    TaskType,
 };
-use arpa_node_contract_client::adapter::{AdapterClientBuilder, AdapterTransactions, AdapterViews};
-use arpa_node_core::{ChainIdentity, PartialSignature, RandomnessTask, SubscriberType, TaskType};
 use arpa_node_dal::{
     cache::RandomnessResultCache, BLSResultCacheState, SignatureResultCacheUpdater,
 };
 use async_trait::async_trait;
-use ethers::types::Address;
-use log::{debug, error, info};
+use ethers::types::{Address, U256};
+use log::{debug, error, info, warn};
 use std::{collections::HashMap, marker::PhantomData, sync::Arc};
 use threshold_bls::{group::PairingCurve, poly::Eval};
 use tokio::sync::RwLock;
@@ -30,6 +39,8 @@ pub struct RandomnessSignatureAggregationSubscriber<
     randomness_signature_cache: Arc<RwLock<C>>,
     eq: Arc<RwLock<EventQueue>>,
     ts: Arc<RwLock<SimpleDynamicTaskScheduler>>,
+    max_gas_price_gwei: Option<u64>,
+    min_fulfillment_profit_margin: Option<f64>,
     c: PhantomData<PC>,
 }
 
@@ -46,6 +57,8 @@ impl<
         randomness_signature_cache: Arc<RwLock<C>>,
         eq: Arc<RwLock<EventQueue>>,
         ts: Arc<RwLock<SimpleDynamicTaskScheduler>>,
+        max_gas_price_gwei: Option<u64>,
+        min_fulfillment_profit_margin: Option<f64>,
     ) -> Self {
         RandomnessSignatureAggregationSubscriber {
             chain_id,
@@ -54,16 +67,109 @@ impl<
             randomness_signature_cache,
             eq,
             ts,
+            max_gas_price_gwei,
+            min_fulfillment_profit_margin,
             c: PhantomData,
         }
     }
 }
 
+/// Looks up the fulfillment of the task on chain, treating a failed lookup as not fulfilled by
+/// the node.
+pub async fn is_fulfilled_by<A: AdapterViews + Sync>(
+    client: &A,
+    id_address: Address,
+    request_id: &[u8],
+    assignment_block_height: usize,
+) -> bool {
+    match client
+        .get_fulfillment(request_id, assignment_block_height)
+        .await
+    {
+        Ok(Some((randomness, fulfiller, block_number))) if fulfiller == id_address => {
+            info!(
+                "randomness of task request id: {} has been fulfilled by the node at block {}, randomness: {}",
+                hex::encode(request_id),
+                block_number,
+                randomness
+            );
+            true
+        }
+        Ok(_) => false,
+        Err(e) => {
+            warn!(
+                "failed to look up the fulfillment of task request id: {}, caused by: {:?}",
+                hex::encode(request_id),
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Settles the cache of a task which is no longer pending on chain, so that the node stops
+/// working on it.
+pub async fn settle_fulfilled_task<
+    A: AdapterViews + Sync,
+    C: SignatureResultCacheUpdater<RandomnessResultCache>,
+>(
+    client: &A,
+    randomness_signature_cache: &RwLock<C>,
+    id_address: Address,
+    request_id: &[u8],
+    assignment_block_height: usize,
+) -> NodeResult<()> {
+    // the task may have been fulfilled by the node itself before it restarted
+    let state = if is_fulfilled_by(client, id_address, request_id, assignment_block_height).await {
+        BLSResultCacheState::Committed
+    } else {
+        BLSResultCacheState::CommittedByOthers
+    };
+
+    randomness_signature_cache
+        .write()
+        .await
+        .update_commit_result(request_id, state, None)
+        .await?;
+
+    Ok(())
+}
+
+/// Persists every attempt of the fulfillment as soon as it is sent, so that a restarted node can
+/// tell whether the fulfillment has landed.
+pub fn build_inflight_tx_hook<
+    C: SignatureResultCacheUpdater<RandomnessResultCache> + Sync + Send + 'static,
+>(
+    randomness_signature_cache: Arc<RwLock<C>>,
+    request_id: Vec<u8>,
+) -> TransactionSentHook {
+    Box::new(move |tx_hash| {
+        let randomness_signature_cache = randomness_signature_cache.clone();
+        let request_id = request_id.clone();
+        Box::pin(async move {
+            if let Err(e) = randomness_signature_cache
+                .write()
+                .await
+                .update_commit_result(&request_id, BLSResultCacheState::Committing, Some(tx_hash))
+                .await
+            {
+                error!(
+                    "failed to save the fulfillment transaction {:?} of task request id: {}, caused by: {:?}",
+                    tx_hash,
+                    hex::encode(&request_id),
+                    e
+                );
+            }
+        })
+    })
+}
+
 #[async_trait]
 pub trait FulfillRandomnessHandler {
     async fn handle(
         &self,
         group_index: usize,
+        threshold: usize,
         randomness_task: RandomnessTask,
         signature: Vec<u8>,
         partial_signatures: HashMap<Address, PartialSignature>,
@@ -77,17 +183,47 @@ pub struct GeneralFulfillRandomnessHandler<
     id_address: Address,
     chain_identity: Arc<RwLock<I>>,
     randomness_signature_cache: Arc<RwLock<C>>,
+    max_gas_price_gwei: Option<u64>,
+    min_fulfillment_profit_margin: Option<f64>,
+}
+
+impl<
+        I: ChainIdentity + AdapterClientBuilder,
+        C: SignatureResultCacheUpdater<RandomnessResultCache>,
+    > GeneralFulfillRandomnessHandler<I, C>
+{
+    /// Persists the randomness derived from the signature the node fulfilled the task with, so
+    /// that it can be cross-checked against the one the Adapter emits. The fulfillment has
+    /// already landed by then, so a failure here is only logged.
+    async fn save_randomness(&self, request_id: &[u8], signature: &[u8]) {
+        let randomness = derive_randomness(signature);
+
+        if let Err(e) = self
+            .randomness_signature_cache
+            .write()
+            .await
+            .save_randomness(request_id, randomness)
+            .await
+        {
+            error!(
+                "failed to save the randomness of task request id: {}, caused by: {:?}",
+                hex::encode(request_id),
+                e
+            );
+        }
+    }
 }
 
 #[async_trait]
 impl<
         I: ChainIdentity + AdapterClientBuilder + Sync + Send,
-        C: SignatureResultCacheUpdater<RandomnessResultCache> + Sync + Send,
+        C: SignatureResultCacheUpdater<RandomnessResultCache> + Sync + Send + 'static,
     > FulfillRandomnessHandler for GeneralFulfillRandomnessHandler<I, C>
 {
     async fn handle(
         &self,
         group_index: usize,
+        threshold: usize,
         randomness_task: RandomnessTask,
         signature: Vec<u8>,
         partial_signatures: HashMap<Address, PartialSignature>,
@@ -100,6 +236,8 @@ impl<
 
         let randomness_task_request_id = randomness_task.request_id.clone();
 
+        let assignment_block_height = randomness_task.assignment_block_height;
+
         if client.is_task_pending(&randomness_task_request_id).await? {
             let wei_per_gas = self
                 .chain_identity
@@ -115,6 +253,7 @@ impl<
                     .update_commit_result(
                         &randomness_task_request_id,
                         BLSResultCacheState::NotCommitted,
+                        None,
                     )
                     .await?;
 
@@ -124,49 +263,137 @@ impl<
                 return Ok(());
             }
 
+            if let Some(max_gas_price_gwei) = self.max_gas_price_gwei {
+                let max_gas_price = U256::from(max_gas_price_gwei) * U256::exp10(9);
+
+                if wei_per_gas > max_gas_price {
+                    self.randomness_signature_cache
+                        .write()
+                        .await
+                        .update_commit_result(
+                            &randomness_task_request_id,
+                            BLSResultCacheState::NotCommitted,
+                            None,
+                        )
+                        .await?;
+
+                    info!("defer fulfilling randomness as gas price exceeds the cap of the node! task request id: {}, current_gas_price:{:?}, max_gas_price_gwei: {:?}",
+                        format!("{:?}",hex::encode(randomness_task_request_id)), wei_per_gas, max_gas_price_gwei);
+
+                    return Ok(());
+                }
+            }
+
+            // a zero payment means the task was saved before the payment was tracked
+            if let Some(margin) = self
+                .min_fulfillment_profit_margin
+                .filter(|_| !randomness_task.estimated_payment.is_zero())
+            {
+                let estimated_gas = estimate_fulfill_randomness_gas(
+                    randomness_task.callback_gas_limit,
+                    partial_signatures.len() as u32,
+                    threshold as u32,
+                );
+                let estimated_cost = U256::from(estimated_gas) * wei_per_gas;
+                let min_payment = min_fulfillment_payment(estimated_cost, margin);
+
+                if randomness_task.estimated_payment < min_payment {
+                    self.randomness_signature_cache
+                        .write()
+                        .await
+                        .update_commit_result(
+                            &randomness_task_request_id,
+                            BLSResultCacheState::NotCommitted,
+                            None,
+                        )
+                        .await?;
+
+                    info!("defer fulfilling randomness as it is not profitable! task request id: {}, estimated_payment: {:?}, estimated_gas: {}, current_gas_price: {:?}, estimated_cost: {:?}, min_fulfillment_profit_margin: {}",
+                        format!("{:?}",hex::encode(randomness_task_request_id)), randomness_task.estimated_payment, estimated_gas, wei_per_gas, estimated_cost, margin);
+
+                    return Ok(());
+                }
+
+                info!("fulfilling randomness is profitable. task request id: {}, estimated_payment: {:?}, estimated_gas: {}, current_gas_price: {:?}, estimated_cost: {:?}, min_fulfillment_profit_margin: {}",
+                    format!("{:?}",hex::encode(&randomness_task_request_id)), randomness_task.estimated_payment, estimated_gas, wei_per_gas, estimated_cost, margin);
+            }
+
+            FULFILLMENTS_SUBMITTED.inc();
+
+            let on_sent = build_inflight_tx_hook(
+                self.randomness_signature_cache.clone(),
+                randomness_task_request_id.clone(),
+            );
+
             match client
                 .fulfill_randomness(
                     group_index,
+                    threshold,
                     randomness_task,
                     signature.clone(),
                     partial_signatures,
+                    Some(on_sent),
                 )
                 .await
             {
                 Ok(tx_hash) => {
+                    FULFILLMENTS_SUCCEEDED.inc();
+
                     self.randomness_signature_cache
                         .write()
                         .await
                         .update_commit_result(
                             &randomness_task_request_id,
                             BLSResultCacheState::Committed,
+                            Some(tx_hash),
                         )
                         .await?;
 
-                    info!("fulfill randomness successfully! tx_hash:{:?}, task request id: {}, group_index: {}, signature: {}",
-                    tx_hash, format!("{:?}",hex::encode(randomness_task_request_id)), group_index, hex::encode(signature));
+                    self.save_randomness(&randomness_task_request_id, &signature)
+                        .await;
+
+                    info!("fulfill randomness successfully! tx_hash:{:?}, task request id: {}, group_index: {}, signature: {}, randomness: {}",
+                    tx_hash, format!("{:?}",hex::encode(&randomness_task_request_id)), group_index, hex::encode(&signature), hex::encode(derive_randomness(&signature)));
                 }
                 Err(e) => {
+                    // an earlier attempt of the retried transaction may have landed anyway
+                    let state = if is_fulfilled_by(
+                        &client,
+                        self.id_address,
+                        &randomness_task_request_id,
+                        assignment_block_height,
+                    )
+                    .await
+                    {
+                        FULFILLMENTS_SUCCEEDED.inc();
+                        BLSResultCacheState::Committed
+                    } else {
+                        FULFILLMENTS_FAILED.inc();
+                        BLSResultCacheState::NotCommitted
+                    };
+
                     self.randomness_signature_cache
                         .write()
                         .await
-                        .update_commit_result(
-                            &randomness_task_request_id,
-                            BLSResultCacheState::NotCommitted,
-                        )
+                        .update_commit_result(&randomness_task_request_id, state, None)
                         .await?;
+
+                    if state == BLSResultCacheState::Committed {
+                        self.save_randomness(&randomness_task_request_id, &signature)
+                            .await;
+                    }
                     error!("{:?}", e);
                 }
             }
         } else {
-            self.randomness_signature_cache
-                .write()
-                .await
-                .update_commit_result(
-                    &randomness_task_request_id,
-                    BLSResultCacheState::CommittedByOthers,
-                )
-                .await?;
+            settle_fulfilled_task(
+                &client,
+                &self.randomness_signature_cache,
+                self.id_address,
+                &randomness_task_request_id,
+                assignment_block_height,
+            )
+            .await?;
         }
 
         Ok(())
@@ -204,6 +431,14 @@ impl<
                 partial_signatures,
             } = signature.clone();
 
+            let handler = GeneralFulfillRandomnessHandler {
+                id_address: self.id_address,
+                chain_identity: self.chain_identity.clone(),
+                randomness_signature_cache: self.randomness_signature_cache.clone(),
+                max_gas_price_gwei: self.max_gas_price_gwei,
+                min_fulfillment_profit_margin: self.min_fulfillment_profit_margin,
+            };
+
             let partials = partial_signatures
                 .values()
                 .cloned()
@@ -223,24 +458,15 @@ impl<
                 })
                 .collect::<Result<_, NodeError>>()?;
 
-            let id_address = self.id_address;
-
-            let chain_identity = self.chain_identity.clone();
-
-            let randomness_signature_cache = self.randomness_signature_cache.clone();
+            let request_id = randomness_task.request_id.clone();
 
             self.ts.write().await.add_task(
                 TaskType::Subscriber(SubscriberType::RandomnessSignatureAggregation),
-                async move {
-                    let handler = GeneralFulfillRandomnessHandler {
-                        id_address,
-                        chain_identity,
-                        randomness_signature_cache,
-                    };
-
+                in_request_span("fulfill_randomness", &request_id, async move {
                     if let Err(e) = handler
                         .handle(
                             group_index,
+                            threshold,
                             randomness_task,
                             signature.clone(),
                             partial_signatures,
@@ -249,14 +475,14 @@ impl<
                     {
                         error!("{:?}", e);
                     }
-                },
+                }),
             )?;
         }
 
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let chain_id = self.chain_id;
@@ -265,7 +491,7 @@ impl<
 
         eq.write()
             .await
-            .subscribe(Topic::ReadyToFulfillRandomnessTask(chain_id), subscriber);
+            .subscribe(Topic::ReadyToFulfillRandomnessTask(chain_id), subscriber)
     }
 }
 
@@ -280,3 +506,78 @@ impl<
     > DebuggableSubscriber for RandomnessSignatureAggregationSubscriber<I, C, PC>
 {
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::build_inflight_tx_hook;
+    use arpa_node_contract_client::{adapter::AdapterTransactions, mock::MockChainProvider};
+    use arpa_node_core::{RandomnessRequestType, RandomnessTask, RandomnessTaskPriority};
+    use arpa_node_dal::{
+        cache::{InMemorySignatureResultCache, RandomnessResultCache},
+        BLSResultCacheState, SignatureResultCacheFetcher, SignatureResultCacheUpdater,
+    };
+    use ethers::types::{Address, U256};
+    use std::{collections::HashMap, sync::Arc};
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_inflight_tx_hook() {
+        let task = RandomnessTask {
+            request_id: vec![1],
+            subscription_id: 1,
+            group_index: 0,
+            request_type: RandomnessRequestType::Randomness,
+            params: vec![],
+            requester: Address::zero(),
+            seed: U256::from(42),
+            request_confirmations: 0,
+            callback_gas_limit: 0,
+            callback_max_gas_price: U256::zero(),
+            estimated_payment: U256::zero(),
+            assignment_block_height: 0,
+            priority: RandomnessTaskPriority::Normal,
+        };
+
+        let chain = MockChainProvider::new(Address::random());
+        chain.emit_randomness_task(task.clone());
+
+        let randomness_signature_cache = Arc::new(RwLock::new(InMemorySignatureResultCache::<
+            RandomnessResultCache,
+        >::new()));
+        randomness_signature_cache
+            .write()
+            .await
+            .add(0, task.clone(), vec![], 1)
+            .await
+            .unwrap();
+
+        // the transaction is saved by the hook before the fulfillment returns
+        let tx_hash = chain
+            .fulfill_randomness(
+                0,
+                1,
+                task,
+                vec![7; 64],
+                HashMap::new(),
+                Some(build_inflight_tx_hook(
+                    randomness_signature_cache.clone(),
+                    vec![1],
+                )),
+            )
+            .await
+            .unwrap();
+
+        let randomness_signature_cache = randomness_signature_cache.read().await;
+        assert_eq!(
+            randomness_signature_cache
+                .get_inflight_tx(&[1])
+                .await
+                .unwrap(),
+            Some(tx_hash)
+        );
+        assert_eq!(
+            randomness_signature_cache.get(&[1]).await.unwrap().state,
+            BLSResultCacheState::Committing
+        );
+    }
+}
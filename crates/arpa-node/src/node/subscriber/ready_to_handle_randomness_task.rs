@@ -1,24 +1,26 @@
 use crate::node::{
     algorithm::bls::{BLSCore, SimpleBLSCore},
     committer::{
-        client::GeneralCommitterClient, CommitterClient, CommitterClientHandler, CommitterService,
+        client::GeneralCommitterClient, pool::CommitterChannelPool, CommitterClient,
+        CommitterClientHandler, CommitterService,
     },
     error::NodeResult,
     event::{ready_to_handle_randomness_task::ReadyToHandleRandomnessTask, types::Topic},
-    queue::{event_queue::EventQueue, EventSubscriber},
+    queue::{event_queue::EventQueue, EventSubscriber, SubscriberId},
     scheduler::{dynamic::SimpleDynamicTaskScheduler, TaskScheduler},
+    telemetry::in_request_span,
 };
 use arpa_node_core::{
-    u256_to_vec, BLSTaskType, ExponentialBackoffRetryDescriptor, RandomnessTask, SubscriberType,
-    TaskType,
+    committer_selection_seed, compute_seed, BLSTaskType, ExponentialBackoffRetryDescriptor,
+    RandomnessTask, SubscriberType, TaskType,
 };
 use arpa_node_dal::{
-    cache::RandomnessResultCache, BLSTasksFetcher, GroupInfoFetcher, SignatureResultCacheFetcher,
-    SignatureResultCacheUpdater,
+    cache::RandomnessResultCache, BLSTasksFetcher, BLSTasksUpdater, GroupInfoFetcher,
+    SignatureResultCacheFetcher, SignatureResultCacheUpdater,
 };
 use async_trait::async_trait;
-use ethers::types::{Address, U256};
-use log::{debug, error, info};
+use ethers::{types::Address, utils::hex};
+use log::{debug, error, info, warn};
 use std::{marker::PhantomData, sync::Arc};
 use threshold_bls::group::PairingCurve;
 use tokio::sync::RwLock;
@@ -28,7 +30,7 @@ use super::{DebuggableEvent, DebuggableSubscriber, Subscriber};
 #[derive(Debug)]
 pub struct ReadyToHandleRandomnessTaskSubscriber<
     G: GroupInfoFetcher<PC>,
-    T: BLSTasksFetcher<RandomnessTask>,
+    T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>,
     C: SignatureResultCacheUpdater<RandomnessResultCache>
         + SignatureResultCacheFetcher<RandomnessResultCache>,
     PC: PairingCurve,
@@ -42,11 +44,12 @@ pub struct ReadyToHandleRandomnessTaskSubscriber<
     ts: Arc<RwLock<SimpleDynamicTaskScheduler>>,
     c: PhantomData<PC>,
     commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    committer_channel_pool: Arc<CommitterChannelPool>,
 }
 
 impl<
         G: GroupInfoFetcher<PC>,
-        T: BLSTasksFetcher<RandomnessTask>,
+        T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>,
         C: SignatureResultCacheUpdater<RandomnessResultCache>
             + SignatureResultCacheFetcher<RandomnessResultCache>,
         PC: PairingCurve,
@@ -73,6 +76,7 @@ impl<
             ts,
             c: PhantomData,
             commit_partial_signature_retry_descriptor,
+            committer_channel_pool: Arc::new(CommitterChannelPool::default()),
         }
     }
 }
@@ -84,7 +88,7 @@ pub trait RandomnessHandler {
 
 pub struct GeneralRandomnessHandler<
     G: GroupInfoFetcher<PC>,
-    T: BLSTasksFetcher<RandomnessTask>,
+    T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask>,
     C: SignatureResultCacheUpdater<RandomnessResultCache>
         + SignatureResultCacheFetcher<RandomnessResultCache>,
     PC: PairingCurve,
@@ -98,12 +102,13 @@ pub struct GeneralRandomnessHandler<
     ts: Arc<RwLock<SimpleDynamicTaskScheduler>>,
     c: PhantomData<PC>,
     commit_partial_signature_retry_descriptor: ExponentialBackoffRetryDescriptor,
+    committer_channel_pool: Arc<CommitterChannelPool>,
 }
 
 #[async_trait]
 impl<
         G: GroupInfoFetcher<PC> + Sync + Send,
-        T: BLSTasksFetcher<RandomnessTask> + Sync + Send,
+        T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask> + Sync + Send,
         C: SignatureResultCacheUpdater<RandomnessResultCache>
             + SignatureResultCacheFetcher<RandomnessResultCache>
             + Sync
@@ -123,12 +128,16 @@ impl<
     fn get_commit_partial_signature_retry_descriptor(&self) -> ExponentialBackoffRetryDescriptor {
         self.commit_partial_signature_retry_descriptor
     }
+
+    fn get_committer_channel_pool(&self) -> Arc<CommitterChannelPool> {
+        self.committer_channel_pool.clone()
+    }
 }
 
 #[async_trait]
 impl<
         G: GroupInfoFetcher<PC> + Sync + Send + 'static,
-        T: BLSTasksFetcher<RandomnessTask> + Sync + Send + 'static,
+        T: BLSTasksFetcher<RandomnessTask> + BLSTasksUpdater<RandomnessTask> + Sync + Send + 'static,
         C: SignatureResultCacheUpdater<RandomnessResultCache>
             + SignatureResultCacheFetcher<RandomnessResultCache>
             + Sync
@@ -139,11 +148,7 @@ impl<
 {
     async fn handle(self) -> NodeResult<()> {
         for task in self.tasks.iter() {
-            let actual_seed = [
-                &u256_to_vec(&task.seed)[..],
-                &u256_to_vec(&U256::from(task.assignment_block_height))[..],
-            ]
-            .concat();
+            let actual_seed = compute_seed(task);
 
             let partial_signature = SimpleBLSCore::<PC>::partial_sign(
                 self.group_cache.read().await.get_secret_share()?,
@@ -154,6 +159,8 @@ impl<
 
             let current_group_index = self.group_cache.read().await.get_index()?;
 
+            let current_group_epoch = self.group_cache.read().await.get_epoch()?;
+
             if self
                 .group_cache
                 .read()
@@ -192,26 +199,46 @@ impl<
                     .await?;
             }
 
-            let committers = self.prepare_committer_clients().await?;
+            debug!(
+                "committer selection seed of task {}: {}",
+                hex::encode(&task.request_id),
+                committer_selection_seed(task)
+            );
+
+            // tries the committers in the deterministic order of the task, so that the partial
+            // signature escalates to the next committer once the previous one is unreachable
+            // after all the retries of commit_partial_signature_retry_descriptor, or has not
+            // accepted it.
+            let committers = self.prepare_committer_clients_for_task(task).await?;
+
+            // the node is the first committer, its partial signature has been cached above
+            if committers.is_empty() {
+                self.randomness_tasks_cache
+                    .write()
+                    .await
+                    .mark_handled(&task.request_id)
+                    .await?;
+                continue;
+            }
 
-            for committer in committers.into_iter() {
-                let chain_id = self.chain_id;
-                let request_id = task.request_id.clone();
-                let actual_seed = actual_seed.clone();
-                let partial_signature = partial_signature.clone();
+            let chain_id = self.chain_id;
+            let request_id = task.request_id.clone();
+            let randomness_tasks_cache = self.randomness_tasks_cache.clone();
 
-                self.ts.write().await.add_task(
-                    TaskType::Subscriber(SubscriberType::SendingPartialSignature),
-                    async move {
+            self.ts.write().await.add_task(
+                TaskType::Subscriber(SubscriberType::SendingPartialSignature),
+                in_request_span("send_partial_signature", &task.request_id, async move {
+                    for committer in committers.into_iter() {
                         let committer_id = committer.get_committer_id_address();
 
                         match committer
                             .commit_partial_signature(
                                 chain_id,
                                 BLSTaskType::Randomness,
-                                request_id,
-                                actual_seed,
-                                partial_signature,
+                                request_id.clone(),
+                                actual_seed.clone(),
+                                partial_signature.clone(),
+                                current_group_epoch,
                             )
                             .await
                         {
@@ -220,23 +247,45 @@ impl<
                                     "Partial signature sent and accepted by committer: {:?}",
                                     committer_id
                                 );
+
+                                if let Err(e) = randomness_tasks_cache
+                                    .write()
+                                    .await
+                                    .mark_handled(&request_id)
+                                    .await
+                                {
+                                    error!("Failed to mark the task handled: {:?}", e);
+                                }
+                                return;
                             }
                             Ok(false) => {
                                 info!(
-                                    "Partial signature is not accepted by committer: {:?}",
+                                    "Partial signature is not accepted by committer: {:?}, sending to the next committer",
                                     committer_id
                                 );
                             }
+                            Err(e) if e.is_transport_error() => {
+                                warn!(
+                                    "Error while sending partial signature to committer: {:?}, escalating to the next committer, caused by: {:?}",
+                                    committer_id, e
+                                );
+                            }
                             Err(e) => {
+                                // the committer is reachable but refused the partial
+                                // signature, which the next one would do as well
                                 error!(
-                                    "Error while sending partial signature to committer: {:?}, caused by: {:?}",
+                                    "Partial signature is rejected by committer: {:?}, caused by: {:?}",
                                     committer_id, e
                                 );
+                                return;
                             }
                         }
-                    },
-                )?;
-            }
+                    }
+
+                    // the task is left claimed, so that it is handled again once the lease expires
+                    error!("None of the committers accepted the partial signature.");
+                }),
+            )?;
         }
 
         Ok(())
@@ -246,7 +295,12 @@ impl<
 #[async_trait]
 impl<
         G: GroupInfoFetcher<PC> + std::fmt::Debug + Sync + Send + 'static,
-        T: BLSTasksFetcher<RandomnessTask> + std::fmt::Debug + Sync + Send + 'static,
+        T: BLSTasksFetcher<RandomnessTask>
+            + BLSTasksUpdater<RandomnessTask>
+            + std::fmt::Debug
+            + Sync
+            + Send
+            + 'static,
         C: SignatureResultCacheUpdater<RandomnessResultCache>
             + SignatureResultCacheFetcher<RandomnessResultCache>
             + std::fmt::Debug
@@ -280,6 +334,8 @@ impl<
         let commit_partial_signature_retry_descriptor =
             self.commit_partial_signature_retry_descriptor;
 
+        let committer_channel_pool = self.committer_channel_pool.clone();
+
         self.ts.write().await.add_task(
             TaskType::Subscriber(SubscriberType::ReadyToHandleRandomnessTask),
             async move {
@@ -293,6 +349,7 @@ impl<
                     ts: task_scheduler_for_handler,
                     c: PhantomData::<PC>,
                     commit_partial_signature_retry_descriptor,
+                    committer_channel_pool,
                 };
 
                 if let Err(e) = handler.handle().await {
@@ -304,7 +361,7 @@ impl<
         Ok(())
     }
 
-    async fn subscribe(self) {
+    async fn subscribe(self) -> SubscriberId {
         let eq = self.eq.clone();
 
         let chain_id = self.chain_id;
@@ -313,13 +370,18 @@ impl<
 
         eq.write()
             .await
-            .subscribe(Topic::ReadyToHandleRandomnessTask(chain_id), subscriber);
+            .subscribe(Topic::ReadyToHandleRandomnessTask(chain_id), subscriber)
     }
 }
 
 impl<
         G: GroupInfoFetcher<PC> + std::fmt::Debug + Sync + Send + 'static,
-        T: BLSTasksFetcher<RandomnessTask> + std::fmt::Debug + Sync + Send + 'static,
+        T: BLSTasksFetcher<RandomnessTask>
+            + BLSTasksUpdater<RandomnessTask>
+            + std::fmt::Debug
+            + Sync
+            + Send
+            + 'static,
         C: SignatureResultCacheUpdater<RandomnessResultCache>
             + SignatureResultCacheFetcher<RandomnessResultCache>
             + std::fmt::Debug
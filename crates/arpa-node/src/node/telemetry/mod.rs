@@ -0,0 +1,62 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// Runs the future inside a `randomness_request` span which carries the request id,
+/// so that a single randomness request can be followed across async tasks.
+///
+/// The request id is also put into the log mdc whenever the future is polled,
+/// so that it shows up in structured log records.
+#[cfg(feature = "telemetry")]
+pub(crate) fn in_request_span<F: Future>(
+    stage: &'static str,
+    request_id: &[u8],
+    future: F,
+) -> impl Future<Output = F::Output> {
+    use tracing::Instrument;
+
+    let request_id = hex::encode(request_id);
+
+    let span = tracing::info_span!("randomness_request", stage, request_id = %request_id);
+
+    WithRequestId::new(request_id, future).instrument(span)
+}
+
+#[cfg(not(feature = "telemetry"))]
+pub(crate) fn in_request_span<F: Future>(
+    _stage: &'static str,
+    request_id: &[u8],
+    future: F,
+) -> impl Future<Output = F::Output> {
+    WithRequestId::new(hex::encode(request_id), future)
+}
+
+struct WithRequestId<F: Future> {
+    request_id: String,
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> WithRequestId<F> {
+    fn new(request_id: String, inner: F) -> Self {
+        WithRequestId {
+            request_id,
+            inner: Box::pin(inner),
+        }
+    }
+}
+
+impl<F: Future> Future for WithRequestId<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        log_mdc::insert("request_id", self.request_id.clone());
+
+        let res = self.inner.as_mut().poll(cx);
+
+        log_mdc::remove("request_id");
+
+        res
+    }
+}
@@ -1,35 +1,50 @@
 use arpa_node::load_config;
+use arpa_node::node::algorithm::dkg_key::generate_dkg_keypair;
 use arpa_node::node::context::chain::types::GeneralMainChain;
+use arpa_node::node::context::sync::{
+    check_share_consistency, diagnose_group, ensure_node_registered, fast_sync_group,
+    reconcile_committing_results,
+};
 use arpa_node::node::context::types::GeneralContext;
 use arpa_node::node::context::{Context, TaskWaiter};
-use arpa_node_contract_client::controller::{ControllerClientBuilder, ControllerTransactions};
+use arpa_node_contract_client::adapter::AdapterClientBuilder;
+use arpa_node_contract_client::controller::ControllerClientBuilder;
+use arpa_node_contract_client::ethers::abi_version::check_main_contract_abis;
+use arpa_node_contract_client::provider::ChainProviderBuilder;
 use arpa_node_core::format_now_date;
+use arpa_node_core::log::appender::{RollingFileAppender, RollingTrigger};
 use arpa_node_core::log::encoder::JsonEncoder;
-use arpa_node_core::GeneralChainIdentity;
-use arpa_node_core::{build_wallet_from_config, RandomnessTask};
+use arpa_node_core::log::policy::TimeRollingPeriod;
+use arpa_node_core::{
+    build_db_cipher_key, build_db_cipher_key_from_account, build_main_chain_identity,
+    build_signer_from_config, verify_chain_id, NodeSigner, RandomnessTask,
+};
+use arpa_node_core::{
+    ChainIdentity, Config, ConfigError, CurveType, GeneralChainIdentity, LogFormat,
+    LoggerDescriptor, NodeRole, RollingPolicy,
+};
 use arpa_node_dal::cache::RandomnessResultCache;
 use arpa_node_dal::{NodeInfoFetcher, NodeInfoUpdater};
+use arpa_node_sqlite_db::inspect::inspect;
 use arpa_node_sqlite_db::GroupInfoDBClient;
 use arpa_node_sqlite_db::NodeInfoDBClient;
 use arpa_node_sqlite_db::SqliteDB;
 use arpa_node_sqlite_db::{BLSTasksDBClient, SignatureResultDBClient};
-use ethers::signers::Signer;
-use log::{info, LevelFilter};
+use log::{info, warn, LevelFilter};
 use log4rs::append::console::ConsoleAppender;
-use log4rs::append::rolling_file::policy::compound::roll::delete::DeleteRoller;
-use log4rs::append::rolling_file::policy::compound::trigger::size::SizeTrigger;
-use log4rs::append::rolling_file::policy::compound::CompoundPolicy;
-use log4rs::append::rolling_file::RollingFileAppender;
-use log4rs::config::{Appender, Root};
+use log4rs::config::{Appender, Logger, Root};
+use log4rs::encode::pattern::PatternEncoder;
+use log4rs::encode::Encode;
 use log4rs::filter::threshold::ThresholdFilter;
 use log4rs::Config as LogConfig;
 use std::fs::{self};
 use std::path::PathBuf;
 use structopt::StructOpt;
+use threshold_bls::curve::bls12381::PairingCurve as BLS12381;
 use threshold_bls::curve::bn254::PairingCurve as BN254;
-use threshold_bls::schemes::bn254::G2Scheme;
+use threshold_bls::group::PairingCurve;
 use threshold_bls::serialize::point_to_hex;
-use threshold_bls::sig::Scheme;
+use tokio::sync::RwLock;
 
 #[derive(StructOpt, Debug)]
 #[structopt(name = "Arpa Node")]
@@ -37,7 +52,9 @@ pub struct Opt {
     /// Mode to run.
     /// 1) new-run: First run on Randcast client. Loading data from config.yml settings.
     /// 2) re-run: Continue to run Randcast client from some kind of breakdown. Config in existing database data.sqlite will be used.
-    #[structopt(short = "m", long, possible_values = &["new-run", "re-run"])]
+    /// 3) inspect: Print the node info, group info and task counts in the existing database read-only and exit, without binding ports or connecting to the chain. A KMS or Ledger account only needs its db_cipher_key, while a local account is still needed to derive the database key.
+    /// 4) diagnose: Compare the group in the existing database with the controller, print the differences and exit non-zero on mismatch.
+    #[structopt(short = "m", long, possible_values = &["new-run", "re-run", "inspect", "diagnose"])]
     mode: String,
 
     /// Set the config path
@@ -48,47 +65,61 @@ pub struct Opt {
         default_value = "conf/config.yml"
     )]
     config_path: PathBuf,
+
+    /// Set the path of a partial config merged over the config, e.g. per-node overrides
+    #[structopt(short = "o", long, parse(from_os_str))]
+    config_overlay_path: Option<PathBuf>,
+}
+
+fn build_log_encoder(logger: &LoggerDescriptor) -> Box<dyn Encode> {
+    match logger.format {
+        LogFormat::Json => Box::new(
+            JsonEncoder::new(logger.node_id.clone()).context_logging(logger.context_logging),
+        ),
+        LogFormat::Plain => Box::new(PatternEncoder::new(&format!(
+            "{{d(%Y-%m-%dT%H:%M:%S%.6f%:z)}} {{l}} [{}] {{T}} {{M}}:{{L}} {{X(request_id)}} - {{m}}{{n}}",
+            logger.node_id
+        ))),
+    }
 }
 
-fn init_logger(node_id: &str, context_logging: bool, log_file_path: &str, rolling_file_size: u64) {
+fn build_rolling_trigger(logger: &LoggerDescriptor) -> RollingTrigger {
+    match logger.rolling_policy {
+        RollingPolicy::Size => RollingTrigger::Size(logger.rolling_file_size),
+        RollingPolicy::Daily => RollingTrigger::Time(TimeRollingPeriod::Daily),
+        RollingPolicy::Hourly => RollingTrigger::Time(TimeRollingPeriod::Hourly),
+    }
+}
+
+fn init_logger(logger: &LoggerDescriptor) -> Result<(), ConfigError> {
+    let log_file_path = logger.log_file_path.as_str();
+
     let stdout = ConsoleAppender::builder()
-        .encoder(Box::new(
-            JsonEncoder::new(node_id.to_string()).context_logging(context_logging),
-        ))
+        .encoder(build_log_encoder(logger))
         .build();
 
-    let rolling_file = RollingFileAppender::builder()
-        .encoder(Box::new(
-            JsonEncoder::new(node_id.to_string()).context_logging(context_logging),
-        ))
-        .build(
-            format!(
-                "{}/node.log",
-                if let Some(path_without_slash) = log_file_path.strip_suffix('/') {
-                    path_without_slash
-                } else {
-                    log_file_path
-                }
-            ),
-            Box::new(CompoundPolicy::new(
-                Box::new(SizeTrigger::new(rolling_file_size)),
-                Box::new(DeleteRoller::new()),
-            )),
-        )
-        .unwrap();
+    let rolling_file = RollingFileAppender::new(
+        format!(
+            "{}/node.log",
+            if let Some(path_without_slash) = log_file_path.strip_suffix('/') {
+                path_without_slash
+            } else {
+                log_file_path
+            }
+        ),
+        build_log_encoder(logger),
+        build_rolling_trigger(logger),
+    )
+    .unwrap();
 
-    let rolling_err_file = RollingFileAppender::builder()
-        .encoder(Box::new(
-            JsonEncoder::new(node_id.to_string()).context_logging(context_logging),
-        ))
-        .build(
-            format!("{}/node_err.log", log_file_path),
-            Box::new(CompoundPolicy::new(
-                Box::new(SizeTrigger::new(rolling_file_size)),
-                Box::new(DeleteRoller::new()),
-            )),
-        )
-        .unwrap();
+    let rolling_err_file = RollingFileAppender::new(
+        format!("{}/node_err.log", log_file_path),
+        build_log_encoder(logger),
+        build_rolling_trigger(logger),
+    )
+    .unwrap();
+
+    let level_filters = logger.get_level_filters()?;
 
     let log_config = LogConfig::builder()
         .appender(Appender::builder().build("stdout", Box::new(stdout)))
@@ -98,16 +129,23 @@ fn init_logger(node_id: &str, context_logging: bool, log_file_path: &str, rollin
                 .filter(Box::new(ThresholdFilter::new(LevelFilter::Error)))
                 .build("err_file", Box::new(rolling_err_file)),
         )
+        .loggers(
+            level_filters
+                .into_iter()
+                .map(|(module, level)| Logger::builder().build(module, level)),
+        )
         .build(
             Root::builder()
                 .appender("stdout")
                 .appender("file")
                 .appender("err_file")
-                .build(LevelFilter::Info),
+                .build(logger.get_root_level_filter()),
         )
         .unwrap();
 
     log4rs::init_config(log_config).unwrap();
+
+    Ok(())
 }
 
 #[tokio::main]
@@ -115,24 +153,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let opt = Opt::from_args();
     println!("{:#?}", opt);
 
-    let config = load_config(opt.config_path);
+    let mut config = load_config(opt.config_path, opt.config_overlay_path);
+
+    init_logger(config.logger.as_ref().unwrap())?;
+
+    // inspecting only reads the database, so neither the signer nor the chain is reached
+    if opt.mode == "inspect" {
+        let data_path = config.data_path.clone().unwrap();
+        let cipher_key = build_db_cipher_key_from_account(&config.account)?;
 
-    init_logger(
-        &config.logger.as_ref().unwrap().node_id,
-        config.logger.as_ref().unwrap().context_logging,
-        &config.logger.as_ref().unwrap().log_file_path,
-        config.logger.as_ref().unwrap().rolling_file_size,
-    );
+        let report = match config.curve {
+            CurveType::Bn254 => inspect::<BN254>(&data_path, &cipher_key).await?,
+            CurveType::Bls12_381 => inspect::<BLS12381>(&data_path, &cipher_key).await?,
+        };
 
-    info!("{:?}", config);
+        println!("{}", report);
 
+        return Ok(());
+    }
+
+    // the account is only decrypted or reached here, so a wrong keystore password fails the start
+    let wallet = build_signer_from_config(&config.account, config.chain_id).await?;
+
+    config.set_id_address(&wallet)?;
+
+    info!("{:?}", config.to_redacted());
+
+    match config.curve {
+        CurveType::Bn254 => run_node::<BN254>(&opt.mode, config, wallet).await,
+        CurveType::Bls12_381 => run_node::<BLS12381>(&opt.mode, config, wallet).await,
+    }
+}
+
+async fn run_node<PC: PairingCurve + std::fmt::Debug + Clone + Sync + Send + 'static>(
+    mode: &str,
+    config: Config,
+    wallet: NodeSigner,
+) -> Result<(), Box<dyn std::error::Error>> {
     let data_path = PathBuf::from(config.data_path.clone().unwrap());
 
-    match opt.mode.as_str() {
+    match mode {
         "new-run" => {
-            let wallet = build_wallet_from_config(&config.account)?;
-
-            let id_address = wallet.address();
+            let id_address = config.id_address;
 
             if data_path.exists() {
                 fs::rename(
@@ -148,13 +210,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             let db = SqliteDB::build(
                 data_path.as_os_str().to_str().unwrap(),
-                &wallet.signer().to_bytes(),
+                &build_db_cipher_key(&config.account, &wallet)?,
             )
             .await?;
 
-            let rng = &mut rand::thread_rng();
-
-            let (dkg_private_key, dkg_public_key) = G2Scheme::keypair(rng);
+            let (dkg_private_key, dkg_public_key) =
+                generate_dkg_keypair::<PC>(&wallet, config.derive_dkg_key_from_account);
 
             info!("dkg public_key: {}", point_to_hex(&dkg_public_key));
 
@@ -172,39 +233,43 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 )
                 .await?;
 
-            let group_cache = db.get_group_info_client();
+            // the randomness tasks requested before start_block are never processed
+            if let Some(start_block) = config.start_block {
+                node_cache.set_last_processed_block(start_block).await?;
+            }
+
+            let group_cache = db
+                .get_group_info_client()
+                .with_max_group_size(config.max_group_size.unwrap());
 
             let randomness_tasks_cache = db.get_bls_tasks_client::<RandomnessTask>();
 
-            let randomness_result_cache = db.get_randomness_result_client().await?;
-
-            let main_chain_identity = GeneralChainIdentity::new(
-                config.chain_id,
-                wallet,
-                config.provider_endpoint.clone(),
-                config.time_limits.unwrap().provider_polling_interval_millis,
-                config
-                    .controller_address
-                    .parse()
-                    .expect("bad format of controller_address"),
-                config
-                    .adapter_address
-                    .parse()
-                    .expect("bad format of adapter_address"),
-                config
-                    .time_limits
-                    .unwrap()
-                    .contract_transaction_retry_descriptor,
-                config.time_limits.unwrap().contract_view_retry_descriptor,
-            );
+            let randomness_result_cache = db
+                .get_randomness_result_client()
+                .await?
+                .with_max_entries(config.max_committer_cache_entries.unwrap());
+
+            let main_chain_identity = build_main_chain_identity(&config, wallet);
+
+            // a wrong chain_id makes every transaction fail replay protection
+            verify_chain_id(&main_chain_identity.get_provider(), config.chain_id).await?;
+
+            for proxy_address in main_chain_identity.get_proxy_addresses() {
+                main_chain_identity
+                    .resolve_implementation_address(proxy_address)
+                    .await?;
+            }
+
+            // fail fast rather than on the first call against an incompatible contract set
+            check_main_contract_abis(&main_chain_identity).await?;
 
             let main_chain = GeneralMainChain::<
-                NodeInfoDBClient<BN254>,
-                GroupInfoDBClient<BN254>,
+                NodeInfoDBClient<PC>,
+                GroupInfoDBClient<PC>,
                 BLSTasksDBClient<RandomnessTask>,
                 SignatureResultDBClient<RandomnessResultCache>,
                 GeneralChainIdentity,
-                BN254,
+                PC,
             >::new(
                 "main chain".to_string(),
                 main_chain_identity.clone(),
@@ -213,31 +278,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 randomness_tasks_cache,
                 randomness_result_cache,
                 config.time_limits.unwrap(),
+                config.minimum_threshold.unwrap(),
                 config.listeners.clone(),
-            );
+                config.randomness_task_filter.unwrap_or_default(),
+            )
+            .with_randomness_task_file(config.randomness_task_file.clone().map(PathBuf::from));
+
+            let node_role = config.node_role;
+
+            let auto_register = config.auto_register;
 
             let context = GeneralContext::new(main_chain, config);
 
             let handle = context.deploy().await?;
 
-            // TODO register node to randcast network, this should be moved to node_cmd_client(triggering manully to avoid accidental operation) in prod
             let client =
-                ControllerClientBuilder::<BN254>::build_controller_client(&main_chain_identity);
+                ControllerClientBuilder::<PC>::build_controller_client(&main_chain_identity);
 
-            client
-                .node_register(bincode::serialize(&dkg_public_key).unwrap())
-                .await?;
+            // a verifier stays out of grouping, so it is never registered
+            if node_role == NodeRole::Verifier {
+                info!("Running as a verifier, the node is not registered to the controller.");
+            } else {
+                ensure_node_registered(&client, id_address, &dkg_public_key, auto_register).await?;
+            }
 
             handle.wait_task().await;
         }
         "re-run" => {
-            let wallet = build_wallet_from_config(&config.account)?;
-
-            let id_address = wallet.address();
+            let id_address = config.id_address;
 
             let db = SqliteDB::build(
                 data_path.as_os_str().to_str().unwrap(),
-                &wallet.signer().to_bytes(),
+                &build_db_cipher_key(&config.account, &wallet)?,
             )
             .await?;
 
@@ -252,6 +324,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             assert_eq!(node_cache.get_id_address()?, id_address,"Node identity is different from the database, please check or execute in new-run mode.");
 
             node_cache.get_node_rpc_endpoint()?;
+
+            // a node to be registered automatically can start without a DKG keypair
+            if node_cache.get_dkg_public_key().is_err() && config.auto_register {
+                let (dkg_private_key, dkg_public_key) =
+                    generate_dkg_keypair::<PC>(&wallet, config.derive_dkg_key_from_account);
+
+                info!("dkg public_key: {}", point_to_hex(&dkg_public_key));
+
+                node_cache
+                    .set_dkg_key_pair(dkg_private_key, dkg_public_key)
+                    .await?;
+            }
+
             node_cache.get_dkg_public_key()?;
 
             // update committer rpc endpoint according to config
@@ -264,41 +349,91 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 )
                 .await?;
 
-            let mut group_cache = db.get_group_info_client();
+            // start_block only takes effect until the node has processed any block
+            if let Some(start_block) = config.start_block {
+                if node_cache.get_last_processed_block()?.is_none() {
+                    node_cache.set_last_processed_block(start_block).await?;
+                }
+            }
+
+            let mut group_cache = db
+                .get_group_info_client()
+                .with_max_group_size(config.max_group_size.unwrap());
 
             group_cache.refresh_current_group_info().await?;
 
             let randomness_tasks_cache = db.get_bls_tasks_client::<RandomnessTask>();
 
-            let randomness_result_cache = db.get_randomness_result_client().await?;
-
-            let main_chain_identity = GeneralChainIdentity::new(
-                config.chain_id,
-                wallet,
-                config.provider_endpoint.clone(),
-                config.time_limits.unwrap().provider_polling_interval_millis,
-                config
-                    .controller_address
-                    .parse()
-                    .expect("bad format of controller_address"),
-                config
-                    .adapter_address
-                    .parse()
-                    .expect("bad format of adapter_address"),
-                config
-                    .time_limits
-                    .unwrap()
-                    .contract_transaction_retry_descriptor,
-                config.time_limits.unwrap().contract_view_retry_descriptor,
-            );
+            let mut randomness_result_cache = db
+                .get_randomness_result_client()
+                .await?
+                .with_max_entries(config.max_committer_cache_entries.unwrap());
+
+            let main_chain_identity = build_main_chain_identity(&config, wallet);
+
+            // a wrong chain_id makes every transaction fail replay protection
+            verify_chain_id(&main_chain_identity.get_provider(), config.chain_id).await?;
+
+            for proxy_address in main_chain_identity.get_proxy_addresses() {
+                main_chain_identity
+                    .resolve_implementation_address(proxy_address)
+                    .await?;
+            }
+
+            // fail fast rather than on the first call against an incompatible contract set
+            check_main_contract_abis(&main_chain_identity).await?;
+
+            // resume signing right away if the grouping finished on chain while the node was down
+            if config.node_role != NodeRole::Verifier {
+                let client =
+                    ControllerClientBuilder::<PC>::build_controller_client(&main_chain_identity);
+
+                ensure_node_registered(
+                    &client,
+                    id_address,
+                    node_cache.get_dkg_public_key()?,
+                    config.auto_register,
+                )
+                .await?;
+
+                if let Err(e) = fast_sync_group(&mut group_cache, &client, id_address).await {
+                    warn!("Failed to fast-sync the group from chain: {:?}", e);
+                }
+
+                // don't sign with a share that diverged from the one registered on chain
+                let locked_group_cache = RwLock::new(group_cache);
+
+                if let Err(e) =
+                    check_share_consistency(&locked_group_cache, &client, id_address).await
+                {
+                    warn!("Failed to check the share consistency with chain: {:?}", e);
+                }
+
+                group_cache = locked_group_cache.into_inner();
+            }
+
+            // don't send a fulfillment which may have landed while the node was down once more
+            if let Err(e) = reconcile_committing_results(
+                &mut randomness_result_cache,
+                &main_chain_identity.build_adapter_client(id_address),
+                &main_chain_identity.build_chain_provider(),
+                id_address,
+            )
+            .await
+            {
+                warn!(
+                    "Failed to reconcile the committing randomness results with chain: {:?}",
+                    e
+                );
+            }
 
             let main_chain = GeneralMainChain::<
-                NodeInfoDBClient<BN254>,
-                GroupInfoDBClient<BN254>,
+                NodeInfoDBClient<PC>,
+                GroupInfoDBClient<PC>,
                 BLSTasksDBClient<RandomnessTask>,
                 SignatureResultDBClient<RandomnessResultCache>,
                 GeneralChainIdentity,
-                BN254,
+                PC,
             >::new(
                 "main chain".to_string(),
                 main_chain_identity,
@@ -307,8 +442,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 randomness_tasks_cache,
                 randomness_result_cache,
                 config.time_limits.unwrap(),
+                config.minimum_threshold.unwrap(),
                 config.listeners.clone(),
-            );
+                config.randomness_task_filter.unwrap_or_default(),
+            )
+            .with_randomness_task_file(config.randomness_task_file.clone().map(PathBuf::from));
 
             let context = GeneralContext::new(main_chain, config);
 
@@ -316,8 +454,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
             handle.wait_task().await;
         }
+        "diagnose" => {
+            let diff = diagnose::<PC>(&config, wallet).await?;
+
+            if !diff.is_empty() {
+                println!("The group in the database is inconsistent with the chain:");
+                for line in diff {
+                    println!("  {}", line);
+                }
+                std::process::exit(1);
+            }
+
+            println!("The group in the database is consistent with the chain.");
+        }
         _ => panic!("unimplemented mode"),
     }
 
     Ok(())
 }
+
+/// Compares the group in the database, opened read-only, with the same group on chain.
+async fn diagnose<PC: PairingCurve + std::fmt::Debug + Clone + Sync + Send + 'static>(
+    config: &Config,
+    wallet: NodeSigner,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let db = SqliteDB::build_read_only(
+        config.data_path.as_ref().unwrap(),
+        &build_db_cipher_key(&config.account, &wallet)?,
+    )
+    .await?;
+
+    let mut group_cache = db.get_group_info_client::<PC>();
+
+    if !group_cache.refresh_current_group_info().await? {
+        return Err("There is no group in the database to diagnose.".into());
+    }
+
+    let main_chain_identity = build_main_chain_identity(config, wallet);
+
+    verify_chain_id(&main_chain_identity.get_provider(), config.chain_id).await?;
+
+    let client = ControllerClientBuilder::<PC>::build_controller_client(&main_chain_identity);
+
+    Ok(diagnose_group(&group_cache, &client).await?)
+}
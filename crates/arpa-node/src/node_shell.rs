@@ -10,13 +10,16 @@ use arpa_node_contract_client::controller::{
 };
 use arpa_node_contract_client::ethers::adapter::AdapterClient;
 use arpa_node_contract_client::ethers::controller::ControllerClient;
-use arpa_node_contract_client::{ServiceClient, TransactionCaller, ViewCaller};
+use arpa_node_contract_client::{
+    with_signer_transaction_type, ServiceClient, TransactionCaller, ViewCaller,
+};
 use arpa_node_core::{
-    address_to_string, build_wallet_from_config, pad_to_bytes32, ChainIdentity, Config,
-    GeneralChainIdentity, WalletSigner,
+    address_to_string, build_db_cipher_key, build_main_chain_identity, build_signer_from_config,
+    create_keystore, derive_accounts, pad_to_bytes32, verify_chain_id, ChainIdentity, Config,
+    GeneralChainIdentity, NodeSigner, WalletSigner,
 };
 use arpa_node_dal::NodeInfoFetcher;
-use arpa_node_sqlite_db::SqliteDB;
+use arpa_node_sqlite_db::{archive::NodeStateArchive, SqliteDB};
 use ethers::prelude::k256::ecdsa::SigningKey;
 use ethers::providers::Middleware;
 use ethers::signers::coins_bip39::English;
@@ -47,6 +50,10 @@ pub struct Opt {
     )]
     config_path: PathBuf,
 
+    /// Set the path of a partial config merged over the config, e.g. per-node overrides
+    #[structopt(short = "o", long, parse(from_os_str))]
+    config_overlay_path: Option<PathBuf>,
+
     /// Set the history file path
     #[structopt(
         short = "H",
@@ -63,7 +70,7 @@ pub struct Opt {
 
 struct Context {
     config: Config,
-    wallet: LocalWallet,
+    wallet: NodeSigner,
     main_chain_identity: GeneralChainIdentity,
     db: SqliteDB,
     staking_contract_address: Address,
@@ -108,12 +115,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = ArpaClient::call_contract_transaction(
                 "approve-arpa-to-staking",
-                arpa_contract.approve(context.staking_contract_address, amount),
+                with_signer_transaction_type(
+                    arpa_contract.approve(context.staking_contract_address, amount),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(arpa_contract.address()),
                 true,
             )
             .await?;
@@ -140,6 +156,9 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
             )
             .await?;
 
@@ -162,6 +181,9 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(arpa_contract.address()),
             )
             .await?;
 
@@ -183,6 +205,9 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(arpa_contract.address()),
             )
             .await?;
 
@@ -195,12 +220,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = StakingClient::call_contract_transaction(
                 "stake",
-                staking_contract.stake(amount),
+                with_signer_transaction_type(
+                    staking_contract.stake(amount),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
                 true,
             )
             .await?;
@@ -227,6 +261,9 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
             )
             .await?;
 
@@ -239,12 +276,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = StakingClient::call_contract_transaction(
                 "unstake",
-                staking_contract.unstake(amount),
+                with_signer_transaction_type(
+                    staking_contract.unstake(amount),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
                 true,
             )
             .await?;
@@ -262,12 +308,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = StakingClient::call_contract_transaction(
                 "claim_frozen_principal",
-                staking_contract.claim_frozen_principal(),
+                with_signer_transaction_type(
+                    staking_contract.claim_frozen_principal(),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
                 true,
             )
             .await?;
@@ -326,12 +381,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = ControllerClient::call_contract_transaction(
                 "node_activate",
-                controller_contract.node_activate(),
+                with_signer_transaction_type(
+                    controller_contract.node_activate(),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
                 true,
             )
             .await?;
@@ -359,12 +423,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = ControllerClient::call_contract_transaction(
                 "node_quit",
-                controller_contract.node_quit(),
+                with_signer_transaction_type(
+                    controller_contract.node_quit(),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
                 true,
             )
             .await?;
@@ -402,13 +475,22 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = ControllerClient::call_contract_transaction(
                 "change_dkg_public_key",
-                controller_contract
-                    .change_dkg_public_key(bincode::serialize(&dkg_public_key)?.into()),
+                with_signer_transaction_type(
+                    controller_contract
+                        .change_dkg_public_key(bincode::serialize(&dkg_public_key)?.into()),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
                 true,
             )
             .await?;
@@ -444,12 +526,21 @@ async fn send(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
 
             let trx_hash = ControllerClient::call_contract_transaction(
                 "node_withdraw",
-                controller_contract.node_withdraw(recipient),
+                with_signer_transaction_type(
+                    controller_contract.node_withdraw(recipient),
+                    &context.main_chain_identity.get_signer(),
+                ),
                 context
                     .config
                     .time_limits
                     .unwrap()
                     .contract_transaction_retry_descriptor,
+                context
+                    .main_chain_identity
+                    .get_transaction_confirmation_descriptor(),
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
                 true,
             )
             .await?;
@@ -511,6 +602,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -534,6 +628,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -557,6 +654,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -584,6 +684,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -614,6 +717,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -639,6 +745,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -667,6 +776,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                         .time_limits
                         .unwrap()
                         .contract_view_retry_descriptor,
+                    &context
+                        .main_chain_identity
+                        .get_circuit_breaker(controller_contract.address()),
                 )
                 .await?;
 
@@ -704,6 +816,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(controller_contract.address()),
             )
             .await?;
 
@@ -817,6 +932,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
             )
             .await?;
 
@@ -836,6 +954,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
             )
             .await?;
 
@@ -855,6 +976,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
             )
             .await?;
 
@@ -874,6 +998,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(staking_contract.address()),
             )
             .await?;
 
@@ -908,6 +1035,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(arpa_contract.address()),
             )
             .await?;
 
@@ -940,6 +1070,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(adapter_contract.address()),
             )
             .await?;
 
@@ -975,6 +1108,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(adapter_contract.address()),
             )
             .await?;
 
@@ -1002,6 +1138,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(adapter_contract.address()),
             )
             .await?;
 
@@ -1051,6 +1190,9 @@ async fn call(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
                     .time_limits
                     .unwrap()
                     .contract_view_retry_descriptor,
+                &context
+                    .main_chain_identity
+                    .get_circuit_breaker(adapter_contract.address()),
             )
             .await?;
 
@@ -1126,6 +1268,19 @@ fn generate(args: ArgMatches, _context: &mut Context) -> anyhow::Result<Option<S
 
             Ok(Some("Mnemonic generated successfully.".to_owned()))
         }
+        Some(("keystore-from-mnemonic", sub_matches)) => {
+            let path = sub_matches.get_one::<PathBuf>("path").unwrap();
+            let mnemonic = sub_matches.get_one::<String>("mnemonic").unwrap();
+            let index = *sub_matches.get_one::<u32>("index").unwrap();
+            let password = sub_matches.get_one::<String>("password").unwrap();
+
+            let address = create_keystore(mnemonic, index, path, password)?;
+
+            Ok(Some(format!(
+                "keystore generated successfully, address: {}",
+                address_to_string(address)
+            )))
+        }
 
         _ => panic!("Unknown subcommand {:?}", args.subcommand_name()),
     }
@@ -1137,7 +1292,22 @@ async fn show(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
             context.show_address = true;
             Ok(Some(address_to_string(context.wallet.address())))
         }
-        Some(("config", _sub_matches)) => Ok(Some(format!("{:#?}", context.config))),
+        Some(("config", _sub_matches)) => Ok(Some(format!("{:#?}", context.config.to_redacted()))),
+        Some(("hd-accounts", sub_matches)) => {
+            let mnemonic = sub_matches.get_one::<String>("mnemonic").unwrap();
+            let count = *sub_matches.get_one::<u32>("count").unwrap();
+            let derivation_path = sub_matches.get_one::<String>("derivation-path");
+
+            let accounts = derive_accounts(mnemonic, derivation_path.map(|x| &**x), count)?;
+
+            Ok(Some(
+                accounts
+                    .into_iter()
+                    .map(|(index, address)| format!("{}: {}", index, address_to_string(address)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            ))
+        }
         Some(("node", sub_matches)) => {
             let display_sensitive = sub_matches.get_flag("display-sensitive");
 
@@ -1160,6 +1330,47 @@ async fn show(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<
     }
 }
 
+async fn state(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<String>> {
+    match args.subcommand() {
+        Some(("export", sub_matches)) => {
+            let path = sub_matches.get_one::<String>("path").unwrap();
+
+            let archive = context.db.export_state(path).await?;
+
+            Ok(Some(format!(
+                "Exported node {} with {} group, {} unhandled randomness tasks and {} uncommitted randomness results to {}",
+                archive.node_info.id_address,
+                if archive.group_info.is_some() { "its" } else { "no" },
+                archive.randomness_tasks.len(),
+                archive.randomness_results.len(),
+                path
+            )))
+        }
+        Some(("import", sub_matches)) => {
+            let path = sub_matches.get_one::<String>("path").unwrap();
+
+            let archive = NodeStateArchive::read(path)?;
+
+            // the database is encrypted by the node account, which has to be the archived node
+            if archive.node_info.id_address.parse::<Address>()? != context.wallet.address() {
+                return Ok(Some(format!(
+                    "The archive belongs to node {}, which is different from the node identity {}",
+                    archive.node_info.id_address,
+                    address_to_string(context.wallet.address())
+                )));
+            }
+
+            context.db.import_state_archive(&archive).await?;
+
+            Ok(Some(format!(
+                "Imported node {} from {}, start the node client in re-run mode to resume",
+                archive.node_info.id_address, path
+            )))
+        }
+        _ => panic!("Unknown subcommand {:?}", args.subcommand_name()),
+    }
+}
+
 async fn inspect(args: ArgMatches, context: &mut Context) -> anyhow::Result<Option<String>> {
     match args.subcommand() {
         Some(("list-fixed-tasks", _sub_matches)) => {
@@ -1172,6 +1383,26 @@ async fn inspect(args: ArgMatches, context: &mut Context) -> anyhow::Result<Opti
                 management_client.list_fixed_tasks().await?
             )))
         }
+        Some(("list-task-statuses", _sub_matches)) => {
+            let management_client = GeneralManagementClient::new(
+                context.config.node_management_rpc_endpoint.to_owned(),
+                context.config.node_management_rpc_token.clone(),
+            );
+            Ok(Some(format!(
+                "task-statuses: {:#?}",
+                management_client.list_task_statuses().await?
+            )))
+        }
+        Some(("count-randomness-results", _sub_matches)) => {
+            let management_client = GeneralManagementClient::new(
+                context.config.node_management_rpc_endpoint.to_owned(),
+                context.config.node_management_rpc_token.clone(),
+            );
+            Ok(Some(format!(
+                "randomness-result-counts: {:#?}",
+                management_client.count_randomness_results().await?
+            )))
+        }
         _ => panic!("Unknown subcommand {:?}", args.subcommand_name()),
     }
 }
@@ -1207,36 +1438,20 @@ fn read_file_line_by_line(filepath: PathBuf) -> anyhow::Result<String> {
 async fn main() -> anyhow::Result<()> {
     let opt = Opt::from_args();
 
-    let config = load_config(opt.config_path);
-
-    let wallet = build_wallet_from_config(&config.account)?;
-
-    let main_chain_identity = GeneralChainIdentity::new(
-        config.chain_id,
-        wallet.clone(),
-        config.provider_endpoint.clone(),
-        config.time_limits.unwrap().provider_polling_interval_millis,
-        config
-            .controller_address
-            .parse()
-            .expect("bad format of controller_address"),
-        config
-            .adapter_address
-            .parse()
-            .expect("bad format of adapter_address"),
-        config
-            .time_limits
-            .unwrap()
-            .contract_transaction_retry_descriptor,
-        config.time_limits.unwrap().contract_view_retry_descriptor,
-    );
+    let config = load_config(opt.config_path, opt.config_overlay_path);
+
+    let wallet = build_signer_from_config(&config.account, config.chain_id).await?;
+
+    let main_chain_identity = build_main_chain_identity(&config, wallet.clone());
+
+    verify_chain_id(&main_chain_identity.get_provider(), config.chain_id).await?;
 
     let db = SqliteDB::build(
         PathBuf::from(config.data_path.clone().unwrap())
             .as_os_str()
             .to_str()
             .unwrap(),
-        &wallet.signer().to_bytes(),
+        &build_db_cipher_key(&config.account, &wallet)?,
     )
     .await
     .unwrap();
@@ -1250,6 +1465,9 @@ async fn main() -> anyhow::Result<()> {
         "controller_config",
         controller_contract.get_controller_config(),
         config.time_limits.unwrap().contract_view_retry_descriptor,
+        &context
+            .main_chain_identity
+            .get_circuit_breaker(controller_contract.address()),
     )
     .await?
     .0;
@@ -1260,6 +1478,9 @@ async fn main() -> anyhow::Result<()> {
         "get_arpa_token",
         staking_contract.get_arpa_token(),
         config.time_limits.unwrap().contract_view_retry_descriptor,
+        &context
+            .main_chain_identity
+            .get_circuit_breaker(staking_contract.address()),
     )
     .await?;
 
@@ -1418,6 +1639,15 @@ async fn main() -> anyhow::Result<()> {
                     .arg(Arg::new("password").required(true).help("password to encrypt hd-wallet"))
                     .arg(Arg::new("derivation-path").required(false).help("derivation path, default is m/44'/60'/0'/0/0"))
                     .about("Generate hierarchical deterministic wallet and save the mnemonic to a file")
+                ).subcommand(
+                    Command::new("keystore-from-mnemonic").visible_alias("km")
+                    .arg(Arg::new("path").required(true).help("path to keystore file")
+                      .value_parser(clap::value_parser!(PathBuf)))
+                    .arg(Arg::new("mnemonic").required(true).help("mnemonic to derive the wallet from"))
+                    .arg(Arg::new("index").required(true).help("index of the account to derive")
+                      .value_parser(clap::value_parser!(u32)))
+                    .arg(Arg::new("password").required(true).help("password to encrypt keystore file"))
+                    .about("Generate keystore file from an account of a mnemonic and print its address")
                 )
                 .about("Generate node identity(wallet) corresponding to ARPA node format"),
             generate
@@ -1429,6 +1659,13 @@ async fn main() -> anyhow::Result<()> {
                 ).subcommand(
                     Command::new("config").visible_alias("c")
                     .about("Print node config")
+                ).subcommand(
+                    Command::new("hd-accounts").visible_alias("ha")
+                    .arg(Arg::new("mnemonic").required(true).help("mnemonic to derive accounts from"))
+                    .arg(Arg::new("count").required(true).help("number of accounts to derive")
+                      .value_parser(clap::value_parser!(u32)))
+                    .arg(Arg::new("derivation-path").required(false).help("derivation path, same as hdwallet path in config"))
+                    .about("Print addresses derived from a mnemonic by index")
                 ).subcommand(
                     Command::new("node").visible_alias("n")
                     .arg(Arg::new("display-sensitive").short('s').long("display-sensitive")
@@ -1437,12 +1674,33 @@ async fn main() -> anyhow::Result<()> {
                 )
                 .about("Show information of the config file and node database"),
                 |args, context| Box::pin(show(args, context)),
+        ).with_command_async(
+            Command::new("state")
+                .subcommand(
+                    Command::new("export").visible_alias("e")
+                    .arg(Arg::new("path").required(true).help("path of the archive to write"))
+                    .about("Export node info, group, share and pending tasks to a single archive")
+                ).subcommand(
+                    Command::new("import").visible_alias("i")
+                    .arg(Arg::new("path").required(true).help("path of the archive to read"))
+                    .about("Import an exported archive into a fresh node database")
+                )
+                .about("Migrate the node state between hosts. The archive holds the DKG private key and the share in plain"),
+                |args, context| Box::pin(state(args, context)),
         ).with_command_async(
             Command::new("inspect")
                 .subcommand(
                     Command::new("list-fixed-tasks").visible_alias("lft")
                     .about("List fixed tasks of the node")
                 )
+                .subcommand(
+                    Command::new("list-task-statuses").visible_alias("lts")
+                    .about("List the state, restart count and last error of the fixed tasks")
+                )
+                .subcommand(
+                    Command::new("count-randomness-results").visible_alias("crr")
+                    .about("Count the randomness results of the committer cache by state")
+                )
                 .about("Connect to the node client and inspect the node status"),
                 |args, context| Box::pin(inspect(args, context)),
         ).with_on_after_command_async(|context| Box::pin(update_prompt(context)));
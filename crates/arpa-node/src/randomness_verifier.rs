@@ -0,0 +1,61 @@
+use arpa_node::node::algorithm::randomness::verify_randomness;
+use arpa_node_core::{encode_seed, u256_to_vec};
+use ethers::types::U256;
+use structopt::StructOpt;
+use threshold_bls::curve::bn254::PairingCurve as BN254;
+
+/// Verifies a randomness fulfilled on chain against the group signature and the seed, without
+/// a node or a connection to the chain. Exits non-zero if the signature is invalid.
+#[derive(StructOpt, Debug)]
+#[structopt(name = "Randomness Verifier")]
+pub struct Opt {
+    /// Public key of the group the request was assigned to, as the hex of its uint256[4]
+    #[structopt(short = "p", long)]
+    group_public_key: String,
+
+    /// Seed of the request, in decimal or hex with the 0x prefix
+    #[structopt(short = "s", long, parse(try_from_str = parse_u256))]
+    seed: U256,
+
+    /// Block number the request was assigned at, which is signed along with the seed
+    #[structopt(short = "b", long)]
+    assignment_block_height: usize,
+
+    /// Signature of the group, as the hex of the uint256 passed to fulfillRandomness
+    #[structopt(short = "g", long)]
+    signature: String,
+}
+
+fn parse_u256(s: &str) -> Result<U256, String> {
+    match s.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| e.to_string()),
+        None => U256::from_dec_str(s).map_err(|e| e.to_string()),
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let opt = Opt::from_args();
+
+    let seed = encode_seed(opt.seed, opt.assignment_block_height);
+
+    let verification = verify_randomness::<BN254>(&opt.group_public_key, &seed, &opt.signature)?;
+
+    println!(
+        "{}",
+        if verification.valid {
+            "valid"
+        } else {
+            "invalid"
+        }
+    );
+    println!(
+        "randomness: 0x{}",
+        hex::encode(u256_to_vec(&verification.randomness))
+    );
+
+    if !verification.valid {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
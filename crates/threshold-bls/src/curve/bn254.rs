@@ -4,7 +4,7 @@ use crate::hash::try_and_increment::TryAndIncrement;
 use crate::hash::HashToCurve;
 use crate::serialize::ContractSerialize;
 use ark_bn254 as bn254;
-use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
 use ark_ff::PrimeField;
 use ark_ff::{Field, One, UniformRand, Zero};
 use rand_core::RngCore;
@@ -243,6 +243,11 @@ impl Point for G1 {
 
         Ok(())
     }
+
+    fn is_valid(&self) -> bool {
+        let affine = self.0.into_affine();
+        affine.is_on_curve() && affine.is_in_correct_subgroup_assuming_on_curve()
+    }
 }
 
 impl fmt::Display for G1 {
@@ -289,6 +294,11 @@ impl Point for G2 {
 
         Ok(())
     }
+
+    fn is_valid(&self) -> bool {
+        let affine = self.0.into_affine();
+        affine.is_on_curve() && affine.is_in_correct_subgroup_assuming_on_curve()
+    }
 }
 
 impl fmt::Display for G2 {
@@ -343,10 +353,23 @@ impl PC for PairingCurve {
     type G1 = G1;
     type G2 = G2;
     type GT = GT;
+    type G2Prepared = <bn254::Bn254 as PairingEngine>::G2Prepared;
 
     fn pair(a: &Self::G1, b: &Self::G2) -> Self::GT {
         GT(<bn254::Bn254 as PairingEngine>::pairing(a.0, b.0))
     }
+
+    fn prepare_g2(b: &Self::G2) -> Self::G2Prepared {
+        b.0.into_affine().into()
+    }
+
+    fn pairings_product_is_one(pairs: &[(&Self::G1, &Self::G2Prepared)]) -> bool {
+        let prepared = pairs
+            .iter()
+            .map(|(a, b)| (a.0.into_affine().into(), (*b).clone()))
+            .collect::<Vec<_>>();
+        <bn254::Bn254 as PairingEngine>::product_of_pairings(&prepared).is_one()
+    }
 }
 
 // Serde implementations (ideally, these should be upstreamed to Zexe)
@@ -465,6 +488,33 @@ mod tests {
         assert_eq!(de, sig);
     }
 
+    #[test]
+    fn pairings_product() {
+        let rng = &mut rand::thread_rng();
+        let a = Scalar::rand(rng);
+        let mut minus_one = Scalar::one();
+        minus_one.negate();
+
+        // e(a * g1, g2) * e(-g1, a * g2) == 1
+        let mut p1 = G1::one();
+        p1.mul(&a);
+        let mut p2 = G1::one();
+        p2.mul(&minus_one);
+        let mut q2 = G2::one();
+        q2.mul(&a);
+        let q1 = PairingCurve::prepare_g2(&G2::one());
+        let q2 = PairingCurve::prepare_g2(&q2);
+
+        assert!(PairingCurve::pairings_product_is_one(&[
+            (&p1, &q1),
+            (&p2, &q2)
+        ]));
+        assert!(!PairingCurve::pairings_product_is_one(&[
+            (&p1, &q1),
+            (&p1, &q2)
+        ]));
+    }
+
     #[test]
     fn gt_exp() {
         let rng = &mut rand::thread_rng();
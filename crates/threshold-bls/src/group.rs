@@ -52,6 +52,9 @@ pub trait Point: Element {
 
     /// Maps the provided data to a group element
     fn map(&mut self, data: &[u8]) -> Result<(), <Self as Point>::Error>;
+
+    /// Returns whether the point is on the curve and in the prime-order subgroup
+    fn is_valid(&self) -> bool;
 }
 
 /// A group holds functionalities to create scalar and points related; it is
@@ -84,8 +87,19 @@ pub trait PairingCurve: Clone + Debug {
 
     type GT: Element;
 
+    /// G2 element with the G2 side of the miller loop precomputed, so that pairing
+    /// against the same element repeatedly is cheaper
+    type G2Prepared: Clone + Debug + Send + Sync;
+
     /// Perfors a pairing operation between the 2 group elements
     fn pair(a: &Self::G1, b: &Self::G2) -> Self::GT;
+
+    /// Precomputes the G2 side of the miller loop of the element
+    fn prepare_g2(b: &Self::G2) -> Self::G2Prepared;
+
+    /// Returns whether the product of all the pairings equals one, computing
+    /// them with a single final exponentiation
+    fn pairings_product_is_one(pairs: &[(&Self::G1, &Self::G2Prepared)]) -> bool;
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
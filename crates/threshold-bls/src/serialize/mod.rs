@@ -60,6 +60,11 @@ impl ContractSerialize for bn254::G2 {
     }
 
     fn deserialize_from_contract_form(bytes: &[u8]) -> Result<Self, BLSError> {
+        // x and y are two field elements each, of 32 bytes apiece
+        if bytes.len() != 128 {
+            return Err(BLSError::ContractSerializationError);
+        }
+
         let mut x1 = bytes[..32].to_vec();
         let mut x2 = bytes[32..64].to_vec();
 
@@ -139,4 +144,16 @@ pub mod tests {
             assert_eq!(g2, g2_deserialized);
         }
     }
+
+    #[test]
+    fn test_deserialize_bn254_g2_element_of_wrong_length() {
+        use crate::curve::bn254::G2;
+
+        let g2_bytes = G2::rand(&mut thread_rng())
+            .serialize_to_contract_form()
+            .unwrap();
+
+        assert!(G2::deserialize_from_contract_form(&g2_bytes[..100]).is_err());
+        assert!(G2::deserialize_from_contract_form(&[]).is_err());
+    }
 }